@@ -3,10 +3,11 @@
 use axum::{Json, Router, extract::State, routing::post};
 use misskey_common::AppResult;
 use misskey_core::{
-    CreateReportInput, CreateSuspensionInput, ReportStatus, ResolveReportInput, UpdateInstanceInput,
+    CreateReportInput, CreateSuspensionInput, InspectedSummary, ReportStatus, ResolveReportInput,
+    UpdateInstanceInput,
 };
 use misskey_db::entities::{
-    abuse_report, instance, meta_settings, registration_approval, user_suspension,
+    abuse_report, instance, meta_settings, note, registration_approval, user_suspension,
 };
 use serde::{Deserialize, Serialize};
 
@@ -262,6 +263,22 @@ pub struct InstanceHostRequest {
     pub host: String,
 }
 
+/// Inspect remote object request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectRequest {
+    pub uri: String,
+}
+
+/// Inspected remote object response.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectResponse {
+    /// The JSON as served by the remote server.
+    pub raw: serde_json::Value,
+    pub parsed: InspectedSummary,
+}
+
 // ==================== Meta Settings Types ====================
 
 /// Meta settings response.
@@ -289,11 +306,20 @@ pub struct MetaSettingsResponse {
     pub max_pages_per_user: i32,
     pub default_drive_capacity_mb: i32,
     pub max_file_size_mb: i32,
+    pub federation_hosts: Vec<String>,
+    pub default_note_visibility: String,
+    pub default_is_locked: bool,
+    pub default_receive_dm_from_followers_only: bool,
+    pub default_theme: Option<String>,
 }
 
 impl From<meta_settings::Model> for MetaSettingsResponse {
     fn from(meta: meta_settings::Model) -> Self {
         let langs: Vec<String> = serde_json::from_value(meta.langs).unwrap_or_default();
+        let federation_hosts: Vec<String> = meta
+            .federation_hosts
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default();
         Self {
             name: meta.name,
             short_name: meta.short_name,
@@ -316,6 +342,11 @@ impl From<meta_settings::Model> for MetaSettingsResponse {
             max_pages_per_user: meta.max_pages_per_user,
             default_drive_capacity_mb: meta.default_drive_capacity_mb,
             max_file_size_mb: meta.max_file_size_mb,
+            federation_hosts,
+            default_note_visibility: format!("{:?}", meta.default_note_visibility).to_lowercase(),
+            default_is_locked: meta.default_is_locked,
+            default_receive_dm_from_followers_only: meta.default_receive_dm_from_followers_only,
+            default_theme: meta.default_theme,
         }
     }
 }
@@ -346,6 +377,11 @@ pub struct UpdateMetaSettingsRequest {
     pub default_drive_capacity_mb: Option<i32>,
     pub max_file_size_mb: Option<i32>,
     pub bubble_instances: Option<Vec<String>>,
+    pub federation_hosts: Option<Vec<String>>,
+    pub default_note_visibility: Option<note::Visibility>,
+    pub default_is_locked: Option<bool>,
+    pub default_receive_dm_from_followers_only: Option<bool>,
+    pub default_theme: Option<String>,
 }
 
 // ==================== Registration Approval Types ====================
@@ -763,6 +799,27 @@ async fn federation_stats(
     }))
 }
 
+/// Fetch a remote actor or note for inspection without storing it (admin only).
+async fn inspect_remote_object(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(req): Json<InspectRequest>,
+) -> AppResult<ApiResponse<InspectResponse>> {
+    // Verify admin
+    if !user.is_admin {
+        return Err(misskey_common::AppError::Forbidden(
+            "Only admins can inspect remote objects".to_string(),
+        ));
+    }
+
+    let inspected = state.ap_resolve_service.inspect(&user.id, &req.uri).await?;
+
+    Ok(ApiResponse::ok(InspectResponse {
+        raw: inspected.raw,
+        parsed: inspected.summary,
+    }))
+}
+
 // ========== Meta Settings Endpoints ==========
 
 /// Get meta settings (admin only).
@@ -817,6 +874,11 @@ async fn update_meta_settings(
         default_drive_capacity_mb: req.default_drive_capacity_mb,
         max_file_size_mb: req.max_file_size_mb,
         bubble_instances: req.bubble_instances,
+        federation_hosts: req.federation_hosts,
+        default_note_visibility: req.default_note_visibility,
+        default_is_locked: req.default_is_locked,
+        default_receive_dm_from_followers_only: req.default_receive_dm_from_followers_only,
+        default_theme: req.default_theme,
     };
 
     let meta = state.meta_settings_service.update(input).await?;
@@ -917,6 +979,7 @@ pub fn router() -> Router<AppState> {
         .route("/federation/silence-instance", post(silence_instance))
         .route("/federation/unsilence-instance", post(unsilence_instance))
         .route("/federation/stats", post(federation_stats))
+        .route("/federation/inspect", post(inspect_remote_object))
         // Stats
         .route("/queue/stats", post(admin_stats))
         // Meta settings
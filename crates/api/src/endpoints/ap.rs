@@ -0,0 +1,54 @@
+//! `ActivityPub` lookup endpoints.
+
+use axum::{Json, Router, extract::State, routing::post};
+use misskey_common::{AppError, AppResult};
+use misskey_core::ResolvedObject;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    endpoints::{notes::NoteResponse, users::UserResponse},
+    extractors::AuthUser,
+    middleware::AppState,
+    response::ApiResponse,
+};
+
+/// Show `ActivityPub` object request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApShowRequest {
+    pub uri: String,
+}
+
+/// Object a URI resolved to.
+#[derive(Serialize)]
+#[serde(tag = "type", content = "object")]
+pub enum ApShowResponse {
+    Note(NoteResponse),
+    User(UserResponse),
+}
+
+/// Resolve a local or remote URI to a note or user, fetching it if unknown.
+async fn show(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(req): Json<ApShowRequest>,
+) -> AppResult<ApiResponse<ApShowResponse>> {
+    let response = match state.ap_resolve_service.resolve(&user.id, &req.uri).await? {
+        ResolvedObject::Note(note) => {
+            let note = state
+                .note_service
+                .hydrate(vec![note])
+                .await?
+                .pop()
+                .ok_or_else(|| AppError::NotFound("Note author not found".to_string()))?;
+            ApShowResponse::Note(note.into())
+        }
+        ResolvedObject::User(user) => ApShowResponse::User(user.into()),
+    };
+
+    Ok(ApiResponse::ok(response))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/show", post(show))
+}
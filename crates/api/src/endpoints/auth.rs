@@ -264,6 +264,55 @@ async fn signout(
     Ok(ApiResponse::ok(SignoutResponse { ok: true }))
 }
 
+/// Password reset request.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetPasswordRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+/// Request a password reset link by email.
+///
+/// Always reports success for well-formed addresses so that registered
+/// emails cannot be discovered through this endpoint.
+async fn reset_password(
+    State(state): State<AppState>,
+    Json(req): Json<ResetPasswordRequest>,
+) -> AppResult<ApiResponse<()>> {
+    req.validate()?;
+
+    state.password_reset_service.request(&req.email).await?;
+
+    Ok(ApiResponse::ok(()))
+}
+
+/// Password reset confirmation request.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetPasswordConfirmRequest {
+    #[validate(length(min = 1))]
+    pub token: String,
+
+    #[validate(length(min = 8, max = 128))]
+    pub password: String,
+}
+
+/// Set a new password using a reset token.
+async fn reset_password_confirm(
+    State(state): State<AppState>,
+    Json(req): Json<ResetPasswordConfirmRequest>,
+) -> AppResult<ApiResponse<()>> {
+    req.validate()?;
+
+    state
+        .password_reset_service
+        .confirm(&req.token, &req.password)
+        .await?;
+
+    Ok(ApiResponse::ok(()))
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/signup", post(signup))
@@ -272,4 +321,6 @@ pub fn router() -> Router<AppState> {
         .route("/signin/webauthn/complete", post(signin_webauthn_complete))
         .route("/signout", post(signout))
         .route("/regenerate-token", post(regenerate_token))
+        .route("/auth/reset-password", post(reset_password))
+        .route("/auth/reset-password/confirm", post(reset_password_confirm))
 }
@@ -299,6 +299,7 @@ async fn timeline(
             req.since_id.as_deref(),
         )
         .await?;
+    let notes = state.note_service.hydrate(notes).await?;
 
     Ok(ApiResponse::ok(notes.into_iter().map(Into::into).collect()))
 }
@@ -320,6 +320,50 @@ async fn storage_usage(
     }))
 }
 
+/// Storage used by one folder.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderUsageResponse {
+    /// `None` for files in the drive root.
+    pub folder_id: Option<String>,
+    pub file_count: i64,
+    pub bytes: i64,
+}
+
+/// Storage usage broken down by folder.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageUsageBreakdownResponse {
+    pub folders: Vec<FolderUsageResponse>,
+    pub file_count: i64,
+    pub logical_bytes: i64,
+    pub physical_bytes: i64,
+    pub limit: i64,
+}
+
+/// Get storage usage per folder.
+async fn storage_usage_by_folder(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+) -> AppResult<ApiResponse<StorageUsageBreakdownResponse>> {
+    let usage = state.drive_service.usage_by_folder(&user.id).await?;
+    Ok(ApiResponse::ok(StorageUsageBreakdownResponse {
+        folders: usage
+            .folders
+            .into_iter()
+            .map(|f| FolderUsageResponse {
+                folder_id: f.folder_id,
+                file_count: f.file_count,
+                bytes: f.bytes,
+            })
+            .collect(),
+        file_count: usage.file_count,
+        logical_bytes: usage.logical_bytes,
+        physical_bytes: usage.physical_bytes,
+        limit: usage.limit,
+    }))
+}
+
 // =====================
 // Folder types
 // =====================
@@ -553,4 +597,5 @@ pub fn router() -> Router<AppState> {
         .route("/files/cleanup/execute", post(cleanup_execute))
         // Storage info
         .route("/", post(storage_usage))
+        .route("/usage/folders", post(storage_usage_by_folder))
 }
@@ -0,0 +1,47 @@
+//! Email provider webhook endpoints (bounces and complaints).
+
+use axum::{Router, extract::State, http::HeaderMap, routing::post};
+use misskey_common::{AppError, AppResult};
+
+use crate::{middleware::AppState, response::ApiResponse};
+
+/// Receive an Amazon SES notification via SNS.
+async fn ses(State(state): State<AppState>, body: String) -> AppResult<ApiResponse<()>> {
+    state.email_bounce_service.handle_ses(&body).await?;
+    Ok(ApiResponse::ok(()))
+}
+
+/// Receive a `SendGrid` signed event webhook.
+async fn sendgrid(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: String,
+) -> AppResult<ApiResponse<()>> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .ok_or(AppError::Unauthorized)
+    };
+    let signature = header("X-Twilio-Email-Event-Webhook-Signature")?;
+    let timestamp = header("X-Twilio-Email-Event-Webhook-Timestamp")?;
+
+    state
+        .email_bounce_service
+        .handle_sendgrid(signature, timestamp, &body)
+        .await?;
+    Ok(ApiResponse::ok(()))
+}
+
+/// Receive a Mailgun webhook.
+async fn mailgun(State(state): State<AppState>, body: String) -> AppResult<ApiResponse<()>> {
+    state.email_bounce_service.handle_mailgun(&body).await?;
+    Ok(ApiResponse::ok(()))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/ses", post(ses))
+        .route("/sendgrid", post(sendgrid))
+        .route("/mailgun", post(mailgun))
+}
@@ -20,7 +20,7 @@ use axum::{
     routing::{get, post},
 };
 use misskey_common::{AppError, AppResult};
-use misskey_core::note::CreateNoteInput;
+use misskey_core::{is_pure_renote, note::CreateNoteInput};
 use misskey_db::entities::{note, user};
 use serde::{Deserialize, Serialize};
 
@@ -414,6 +414,23 @@ async fn favourite_status(
     state.note_favorite_service.create(&user.id, &id).await?;
 
     let note = state.note_service.get(&id).await?;
+
+    // A favourite is also a reaction with the user's default reaction emoji;
+    // an existing reaction (BadRequest) is left as it is.
+    match state.reaction_service.like(&user.id, &id).await {
+        Ok(reaction) => {
+            if note.user_id != user.id
+                && let Err(e) = state
+                    .notification_service
+                    .create_reaction_notification(&note.user_id, &user.id, &id, &reaction.reaction)
+                    .await
+            {
+                tracing::warn!(error = %e, "Failed to create reaction notification");
+            }
+        }
+        Err(AppError::BadRequest(_)) => {}
+        Err(e) => return Err(e),
+    }
     let author = state.user_service.get(&note.user_id).await.ok();
 
     let base_url = &state.base_url;
@@ -432,6 +449,9 @@ async fn unfavourite_status(
     // Remove from favorites
     state.note_favorite_service.delete(&user.id, &id).await?;
 
+    // Remove the reaction added by favouriting, leaving other reactions alone
+    state.reaction_service.unlike(&user.id, &id).await?;
+
     let note = state.note_service.get(&id).await?;
     let author = state.user_service.get(&note.user_id).await.ok();
 
@@ -487,7 +507,7 @@ async fn unreblog_status(
     // Find the user's renote of this note and delete it
     let renotes = state.note_service.get_renotes(&id, 100).await?;
     for renote in renotes {
-        if renote.user_id == user.id && renote.text.is_none() {
+        if renote.user_id == user.id && is_pure_renote(&renote) {
             // This is a pure renote (not a quote) by this user
             if let Err(e) = state.note_service.delete(&renote.id, &user.id).await {
                 tracing::warn!(error = %e, "Failed to delete renote during unreblog");
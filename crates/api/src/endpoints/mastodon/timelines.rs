@@ -8,12 +8,11 @@ use axum::{
     routing::get,
 };
 use misskey_common::AppResult;
-use misskey_db::entities::note;
 use serde::Deserialize;
 
 use crate::{extractors::AuthUser, middleware::AppState};
 
-use super::statuses::{Account, Status};
+use super::statuses::{Status, note_to_status};
 
 /// Timeline query parameters.
 #[derive(Debug, Deserialize)]
@@ -32,69 +31,6 @@ const fn default_limit() -> i32 {
     20
 }
 
-/// Convert Misskey visibility to Mastodon visibility.
-fn misskey_to_mastodon_visibility(visibility: &note::Visibility) -> String {
-    match visibility {
-        note::Visibility::Public => "public".to_string(),
-        note::Visibility::Home => "unlisted".to_string(),
-        note::Visibility::Followers => "private".to_string(),
-        note::Visibility::Specified => "direct".to_string(),
-    }
-}
-
-/// Convert note to Mastodon status.
-fn note_to_status(note: note::Model, base_url: &str) -> Status {
-    let visibility = misskey_to_mastodon_visibility(&note.visibility);
-
-    Status {
-        id: note.id.clone(),
-        created_at: note.created_at.to_rfc3339(),
-        in_reply_to_id: note.reply_id.clone(),
-        in_reply_to_account_id: None,
-        sensitive: note.cw.is_some(),
-        spoiler_text: note.cw.clone().unwrap_or_default(),
-        visibility,
-        language: None,
-        uri: format!("{}/notes/{}", base_url, note.id),
-        url: Some(format!("{}/notes/{}", base_url, note.id)),
-        replies_count: note.replies_count,
-        reblogs_count: note.renote_count,
-        favourites_count: note.reaction_count,
-        content: note.text.clone().unwrap_or_default(),
-        reblog: None,
-        account: Account {
-            id: note.user_id.clone(),
-            username: "user".to_string(),
-            acct: "user".to_string(),
-            display_name: "User".to_string(),
-            locked: false,
-            bot: false,
-            created_at: note.created_at.to_rfc3339(),
-            note: String::new(),
-            url: format!("{}/users/{}", base_url, note.user_id),
-            avatar: String::new(),
-            avatar_static: String::new(),
-            header: String::new(),
-            header_static: String::new(),
-            followers_count: 0,
-            following_count: 0,
-            statuses_count: 0,
-            last_status_at: None,
-            emojis: vec![],
-            fields: vec![],
-        },
-        media_attachments: vec![],
-        mentions: vec![],
-        tags: vec![],
-        emojis: vec![],
-        card: None,
-        poll: None,
-        favourited: None,
-        reblogged: None,
-        bookmarked: None,
-    }
-}
-
 /// GET /api/v1/timelines/home - Get home timeline.
 async fn home_timeline(
     AuthUser(user): AuthUser,
@@ -120,9 +56,12 @@ async fn home_timeline(
         .await?;
 
     let base_url = &state.base_url;
-    let statuses: Vec<Status> = notes
+    let statuses: Vec<Status> = state
+        .note_service
+        .hydrate(notes)
+        .await?
         .into_iter()
-        .map(|n| note_to_status(n, base_url))
+        .map(|n| note_to_status(n.note, Some(&n.author), base_url))
         .collect();
 
     Ok(Json(statuses))
@@ -150,9 +89,12 @@ async fn public_timeline(
     };
 
     let base_url = &state.base_url;
-    let statuses: Vec<Status> = notes
+    let statuses: Vec<Status> = state
+        .note_service
+        .hydrate(notes)
+        .await?
         .into_iter()
-        .map(|n| note_to_status(n, base_url))
+        .map(|n| note_to_status(n.note, Some(&n.author), base_url))
         .collect();
 
     Ok(Json(statuses))
@@ -178,9 +120,12 @@ async fn bubble_timeline(
         .await?;
 
     let base_url = &state.base_url;
-    let statuses: Vec<Status> = notes
+    let statuses: Vec<Status> = state
+        .note_service
+        .hydrate(notes)
+        .await?
         .into_iter()
-        .map(|n| note_to_status(n, base_url))
+        .map(|n| note_to_status(n.note, Some(&n.author), base_url))
         .collect();
 
     Ok(Json(statuses))
@@ -53,6 +53,7 @@ pub struct DatabaseMetrics {
     pub queries_total: u64,
     pub errors_total: u64,
     pub query_avg_time_us: u64,
+    pub slow_queries_total: u64,
 }
 
 #[derive(Serialize)]
@@ -109,6 +110,7 @@ impl From<MetricsSnapshot> for MetricsResponse {
                 queries_total: s.db_queries_total,
                 errors_total: s.db_errors_total,
                 query_avg_time_us: s.db_query_avg_time_us,
+                slow_queries_total: s.db_slow_queries_total,
             },
             federation: FederationMetrics {
                 activities_received: s.federation_activities_received,
@@ -256,6 +258,7 @@ mod tests {
             db_queries_total: 50,
             db_errors_total: 2,
             db_query_avg_time_us: 500,
+            db_slow_queries_total: 1,
 
             federation_activities_received: 200,
             federation_activities_delivered: 150,
@@ -304,6 +307,7 @@ mod tests {
             db_queries_total: 0,
             db_errors_total: 0,
             db_query_avg_time_us: 0,
+            db_slow_queries_total: 0,
 
             federation_activities_received: 0,
             federation_activities_delivered: 0,
@@ -6,11 +6,13 @@ mod account;
 mod admin;
 mod announcements;
 mod antennas;
+mod ap;
 mod auth;
 mod blocking;
 mod channels;
 mod clips;
 mod drive;
+mod email_webhooks;
 mod emojis;
 mod favorites;
 mod following;
@@ -57,6 +59,7 @@ pub fn router() -> Router<AppState> {
         .nest("/blocking", blocking::router())
         .nest("/mute", muting::router())
         .nest("/drive", drive::router())
+        .nest("/email/webhooks", email_webhooks::router())
         .nest("/poll", poll::router())
         .nest("/search", search::router())
         .nest("/hashtags", hashtags::router())
@@ -66,6 +69,7 @@ pub fn router() -> Router<AppState> {
         .nest("/emojis", emojis::router())
         .nest("/announcements", announcements::router())
         .nest("/antennas", antennas::router())
+        .nest("/ap", ap::router())
         .nest("/channels", channels::router())
         .nest("/clips", clips::router())
         .nest("/messaging", messaging::router())
@@ -75,6 +79,7 @@ pub fn router() -> Router<AppState> {
         .nest("/i/security-keys", security_keys::router())
         .nest("/oauth", oauth::router())
         .nest("/i/webhooks", webhooks::router())
+        .nest("/i/recent-reactions", reactions::recent_router())
         .nest("/pages", pages::router())
         .nest("/gallery", gallery::router())
         .nest("/translate", translation::router())
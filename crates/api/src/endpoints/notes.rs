@@ -1,13 +1,17 @@
 //! Notes endpoints.
 
 use axum::{Json, Router, extract::State, routing::post};
-use misskey_common::AppResult;
-use misskey_core::{AntennaService, UpdateNoteInput, note::CreateNoteInput};
+use misskey_common::{AppError, AppResult};
+use misskey_core::{
+    AntennaService, ComposeWarnings, NoteWithAuthor, UpdateNoteInput, fetch_timeline_page,
+    note::CreateNoteInput,
+};
 use misskey_db::entities::{note, note_edit};
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 use crate::{
+    endpoints::users::UserResponse,
     extractors::{AuthUser, MaybeAuthUser},
     middleware::AppState,
     response::ApiResponse,
@@ -21,12 +25,21 @@ pub struct NoteResponse {
     pub created_at: String,
     pub updated_at: Option<String>,
     pub user_id: String,
+    /// The note author (present on timelines).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<UserResponse>,
     pub text: Option<String>,
     pub cw: Option<String>,
     pub visibility: String,
     pub reply_id: Option<String>,
     pub renote_id: Option<String>,
     pub channel_id: Option<String>,
+    /// `ActivityPub` id of the note.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    /// Human-readable URL of the note.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
     pub replies_count: i32,
     pub renote_count: i32,
     pub reaction_count: i32,
@@ -41,6 +54,9 @@ pub struct NoteResponse {
     /// The matched phrases (if filtered).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub filter_matches: Option<Vec<String>>,
+    /// Whether the viewer has muted the note's thread.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_thread_muted: bool,
 }
 
 impl From<note::Model> for NoteResponse {
@@ -51,12 +67,15 @@ impl From<note::Model> for NoteResponse {
             created_at: note.created_at.to_rfc3339(),
             updated_at: note.updated_at.map(|dt| dt.to_rfc3339()),
             user_id: note.user_id,
+            user: None,
             text: note.text,
             cw: note.cw,
             visibility: format!("{:?}", note.visibility).to_lowercase(),
             reply_id: note.reply_id,
             renote_id: note.renote_id,
             channel_id: note.channel_id,
+            uri: note.uri,
+            url: note.url,
             replies_count: note.replies_count,
             renote_count: note.renote_count,
             reaction_count: note.reaction_count,
@@ -64,10 +83,19 @@ impl From<note::Model> for NoteResponse {
             filtered: false,
             filter_action: None,
             filter_matches: None,
+            is_thread_muted: false,
         }
     }
 }
 
+impl From<NoteWithAuthor> for NoteResponse {
+    fn from(item: NoteWithAuthor) -> Self {
+        let mut response = Self::from(item.note);
+        response.user = Some(item.author.into());
+        response
+    }
+}
+
 /// Note edit history response.
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -172,6 +200,9 @@ pub struct TimelineRequest {
     #[serde(default)]
     #[allow(dead_code)]
     pub since_id: Option<String>,
+    /// Show only the latest post of consecutive self-replies.
+    #[serde(default)]
+    pub collapse_self_replies: bool,
 }
 
 const fn default_limit() -> u64 {
@@ -188,7 +219,7 @@ use misskey_db::entities::word_filter::FilterContext;
 async fn apply_word_filters(
     state: &AppState,
     user_id: &str,
-    notes: Vec<note::Model>,
+    notes: Vec<NoteWithAuthor>,
     context: FilterContext,
 ) -> AppResult<Vec<NoteResponse>> {
     // Fetch filters once for all notes
@@ -201,7 +232,7 @@ async fn apply_word_filters(
 
     for note in notes {
         // Check filter before consuming note to avoid clone
-        let filter_info = if let Some(ref text) = note.text {
+        let filter_info = if let Some(ref text) = note.note.text {
             let filter_result = state
                 .word_filter_service
                 .apply_filters_with_cache(&filters, text, context)?;
@@ -258,15 +289,21 @@ async fn timeline(
         .get_exclude_user_ids_for_timeline(&user.id)
         .await?;
 
-    let notes = state
-        .note_service
-        .home_timeline(
-            &user.id,
-            limit,
-            req.until_id.as_deref(),
-            exclude_user_ids.as_deref(),
-        )
-        .await?;
+    let note_service = &state.note_service;
+    let user_id = user.id.as_str();
+    let exclude_user_ids = exclude_user_ids.as_deref();
+    let notes = fetch_timeline_page(
+        limit,
+        req.until_id.as_deref(),
+        req.collapse_self_replies,
+        |until_id| async move {
+            note_service
+                .home_timeline(user_id, limit, until_id.as_deref(), exclude_user_ids)
+                .await
+        },
+    )
+    .await?;
+    let notes = note_service.hydrate(notes).await?;
 
     // Apply word filters
     let filtered_notes = apply_word_filters(&state, &user.id, notes, FilterContext::Home).await?;
@@ -293,10 +330,20 @@ async fn local_timeline(
         None
     };
 
-    let notes = state
-        .note_service
-        .local_timeline(limit, req.until_id.as_deref(), exclude_user_ids.as_deref())
-        .await?;
+    let note_service = &state.note_service;
+    let exclude_user_ids = exclude_user_ids.as_deref();
+    let notes = fetch_timeline_page(
+        limit,
+        req.until_id.as_deref(),
+        req.collapse_self_replies,
+        |until_id| async move {
+            note_service
+                .local_timeline(limit, until_id.as_deref(), exclude_user_ids)
+                .await
+        },
+    )
+    .await?;
+    let notes = note_service.hydrate(notes).await?;
 
     // Apply word filters if user is authenticated
     let result = if let Some(ref user) = user {
@@ -328,10 +375,20 @@ async fn global_timeline(
         None
     };
 
-    let notes = state
-        .note_service
-        .global_timeline(limit, req.until_id.as_deref(), exclude_user_ids.as_deref())
-        .await?;
+    let note_service = &state.note_service;
+    let exclude_user_ids = exclude_user_ids.as_deref();
+    let notes = fetch_timeline_page(
+        limit,
+        req.until_id.as_deref(),
+        req.collapse_self_replies,
+        |until_id| async move {
+            note_service
+                .global_timeline(limit, until_id.as_deref(), exclude_user_ids)
+                .await
+        },
+    )
+    .await?;
+    let notes = note_service.hydrate(notes).await?;
 
     // Apply word filters if user is authenticated
     let result = if let Some(ref user) = user {
@@ -355,11 +412,109 @@ pub struct ShowNoteRequest {
 
 /// Get a note by ID.
 async fn show(
+    MaybeAuthUser(user): MaybeAuthUser,
     State(state): State<AppState>,
     Json(req): Json<ShowNoteRequest>,
 ) -> AppResult<ApiResponse<NoteResponse>> {
     let note = state.note_service.get(&req.note_id).await?;
-    Ok(ApiResponse::ok(note.into()))
+    let is_thread_muted = match user {
+        Some(user) => {
+            state
+                .thread_muting_service
+                .is_muted(&user.id, &note)
+                .await?
+        }
+        None => false,
+    };
+
+    let mut response = NoteResponse::from(note);
+    response.is_thread_muted = is_thread_muted;
+    Ok(ApiResponse::ok(response))
+}
+
+/// Note state request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteStateRequest {
+    pub note_ids: Vec<String>,
+}
+
+/// Per-viewer state of a note.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteStateResponse {
+    pub note_id: String,
+    pub is_favorited: bool,
+    pub is_thread_muted: bool,
+}
+
+/// Maximum number of notes per state request.
+const MAX_STATE_NOTES: usize = 100;
+
+/// Get the viewer's state (favorited, thread muted) for a batch of notes.
+async fn note_state(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(req): Json<NoteStateRequest>,
+) -> AppResult<ApiResponse<Vec<NoteStateResponse>>> {
+    if req.note_ids.len() > MAX_STATE_NOTES {
+        return Err(AppError::BadRequest(format!(
+            "At most {MAX_STATE_NOTES} notes can be requested"
+        )));
+    }
+
+    let favorited = state
+        .note_favorite_service
+        .favorited_note_ids(&user.id, &req.note_ids)
+        .await?;
+    let thread_muted = state
+        .thread_muting_service
+        .muted_among(&user.id, &req.note_ids)
+        .await?;
+
+    Ok(ApiResponse::ok(
+        req.note_ids
+            .into_iter()
+            .map(|note_id| NoteStateResponse {
+                is_favorited: favorited.contains(&note_id),
+                is_thread_muted: thread_muted.contains(&note_id),
+                note_id,
+            })
+            .collect(),
+    ))
+}
+
+/// Thread muting request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadMutingRequest {
+    pub note_id: String,
+}
+
+/// Mute the thread containing a note.
+async fn mute_thread(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(req): Json<ThreadMutingRequest>,
+) -> AppResult<ApiResponse<()>> {
+    state
+        .thread_muting_service
+        .mute(&user.id, &req.note_id)
+        .await?;
+    Ok(ApiResponse::ok(()))
+}
+
+/// Unmute the thread containing a note.
+async fn unmute_thread(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(req): Json<ThreadMutingRequest>,
+) -> AppResult<ApiResponse<()>> {
+    state
+        .thread_muting_service
+        .unmute(&user.id, &req.note_id)
+        .await?;
+    Ok(ApiResponse::ok(()))
 }
 
 /// Delete note request.
@@ -380,6 +535,58 @@ async fn delete(
     Ok(ApiResponse::ok(()))
 }
 
+/// Content of a deleted note for repopulating the composer.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedraftResponse {
+    pub text: Option<String>,
+    pub cw: Option<String>,
+    pub visibility: String,
+    pub file_ids: Vec<String>,
+    pub reply_id: Option<String>,
+    pub renote_id: Option<String>,
+    pub channel_id: Option<String>,
+}
+
+/// Delete a note and return its content so it can be posted again.
+async fn delete_and_redraft(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(req): Json<DeleteNoteRequest>,
+) -> AppResult<ApiResponse<RedraftResponse>> {
+    let note = state.note_service.delete(&req.note_id, &user.id).await?;
+    Ok(ApiResponse::ok(RedraftResponse {
+        text: note.text,
+        cw: note.cw,
+        visibility: format!("{:?}", note.visibility).to_lowercase(),
+        file_ids: serde_json::from_value(note.file_ids).unwrap_or_default(),
+        reply_id: note.reply_id,
+        renote_id: note.renote_id,
+        channel_id: note.channel_id,
+    }))
+}
+
+/// Check compose request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckComposeRequest {
+    pub text: Option<String>,
+    pub cw: Option<String>,
+}
+
+/// Check a draft against the user's word filters before posting.
+async fn check_compose(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Json(req): Json<CheckComposeRequest>,
+) -> AppResult<ApiResponse<ComposeWarnings>> {
+    let warnings = state
+        .note_service
+        .check_compose(&user.id, req.text.as_deref(), req.cw.as_deref())
+        .await?;
+    Ok(ApiResponse::ok(warnings))
+}
+
 /// User notes request.
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -494,7 +701,12 @@ pub fn router() -> Router<AppState> {
     Router::new()
         .route("/create", post(create))
         .route("/delete", post(delete))
+        .route("/delete-and-redraft", post(delete_and_redraft))
+        .route("/check-compose", post(check_compose))
         .route("/show", post(show))
+        .route("/state", post(note_state))
+        .route("/thread-muting/create", post(mute_thread))
+        .route("/thread-muting/delete", post(unmute_thread))
         .route("/timeline", post(timeline))
         .route("/local-timeline", post(local_timeline))
         .route("/global-timeline", post(global_timeline))
@@ -535,14 +747,8 @@ async fn like_note(
     State(state): State<AppState>,
     Json(req): Json<LikeNoteRequest>,
 ) -> AppResult<ApiResponse<LikeResponse>> {
-    // Get user's default reaction
-    let default_reaction = state.user_service.get_default_reaction(&user.id).await?;
-
-    // Create the reaction
-    let reaction = state
-        .reaction_service
-        .like(&user.id, &req.note_id, default_reaction.as_deref())
-        .await?;
+    // Create the reaction with the user's default reaction
+    let reaction = state.reaction_service.like(&user.id, &req.note_id).await?;
 
     // Get the note to check author
     let note = state.note_service.get(&req.note_id).await?;
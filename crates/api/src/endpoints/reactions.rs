@@ -1,6 +1,10 @@
 //! Reactions endpoints.
 
-use axum::{Json, Router, extract::State, routing::post};
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    routing::{get, post},
+};
 use misskey_common::AppResult;
 use serde::{Deserialize, Serialize};
 
@@ -114,6 +118,55 @@ async fn reactions(
     ))
 }
 
+/// Recent reactions query.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentReactionsQuery {
+    #[serde(default = "default_recent_limit")]
+    pub limit: u64,
+}
+
+const fn default_recent_limit() -> u64 {
+    10
+}
+
+/// A reaction the user uses, with how often they used it.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentReactionResponse {
+    pub reaction: String,
+    pub count: i32,
+    pub last_used_at: String,
+}
+
+/// Get the authenticated user's most used reactions, for reaction shortcuts.
+async fn recent_reactions(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<RecentReactionsQuery>,
+) -> AppResult<ApiResponse<Vec<RecentReactionResponse>>> {
+    let usages = state
+        .reaction_service
+        .recent_reactions(&user.id, query.limit)
+        .await?;
+
+    Ok(ApiResponse::ok(
+        usages
+            .into_iter()
+            .map(|u| RecentReactionResponse {
+                reaction: u.reaction,
+                count: u.count,
+                last_used_at: u.last_used_at.to_rfc3339(),
+            })
+            .collect(),
+    ))
+}
+
+/// Router for the `/i/recent-reactions` endpoint.
+pub fn recent_router() -> Router<AppState> {
+    Router::new().route("/", get(recent_reactions))
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/create", post(create))
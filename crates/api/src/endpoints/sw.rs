@@ -45,7 +45,7 @@ async fn register(
     Json(input): Json<CreateSubscriptionInput>,
 ) -> AppResult<ApiResponse<PushSubscriptionResponse>> {
     let push_service = state.push_notification_service.as_ref().ok_or_else(|| {
-        misskey_common::AppError::BadRequest("Push notifications not configured".to_string())
+        misskey_common::AppError::NotConfigured("Push notifications not configured".to_string())
     })?;
 
     // Extract user agent from headers
@@ -66,7 +66,7 @@ async fn update_subscription(
     Json(req): Json<UpdateSubscriptionRequest>,
 ) -> AppResult<ApiResponse<PushSubscriptionResponse>> {
     let push_service = state.push_notification_service.as_ref().ok_or_else(|| {
-        misskey_common::AppError::BadRequest("Push notifications not configured".to_string())
+        misskey_common::AppError::NotConfigured("Push notifications not configured".to_string())
     })?;
 
     let subscription = push_service
@@ -83,7 +83,7 @@ async fn unregister(
     Json(req): Json<UnregisterRequest>,
 ) -> AppResult<ApiResponse<()>> {
     let push_service = state.push_notification_service.as_ref().ok_or_else(|| {
-        misskey_common::AppError::BadRequest("Push notifications not configured".to_string())
+        misskey_common::AppError::NotConfigured("Push notifications not configured".to_string())
     })?;
 
     if let Some(subscription_id) = req.subscription_id {
@@ -107,7 +107,7 @@ async fn list_subscriptions(
     State(state): State<AppState>,
 ) -> AppResult<ApiResponse<Vec<PushSubscriptionResponse>>> {
     let push_service = state.push_notification_service.as_ref().ok_or_else(|| {
-        misskey_common::AppError::BadRequest("Push notifications not configured".to_string())
+        misskey_common::AppError::NotConfigured("Push notifications not configured".to_string())
     })?;
 
     let subscriptions = push_service.list(&user.id).await?;
@@ -121,7 +121,7 @@ async fn get_subscription(
     Json(req): Json<GetSubscriptionRequest>,
 ) -> AppResult<ApiResponse<PushSubscriptionResponse>> {
     let push_service = state.push_notification_service.as_ref().ok_or_else(|| {
-        misskey_common::AppError::BadRequest("Push notifications not configured".to_string())
+        misskey_common::AppError::NotConfigured("Push notifications not configured".to_string())
     })?;
 
     let subscription = push_service.get(&user.id, &req.subscription_id).await?;
@@ -73,7 +73,7 @@ async fn translate_note(
 
     // Check if translation service is available
     let translation_service = state.translation_service.as_ref().ok_or_else(|| {
-        misskey_common::AppError::BadRequest("Translation service not configured".to_string())
+        misskey_common::AppError::NotConfigured("Translation service not configured".to_string())
     })?;
 
     let input = TranslateInput {
@@ -93,7 +93,7 @@ async fn translate_text(
 ) -> AppResult<ApiResponse<TranslationResponse>> {
     // Check if translation service is available
     let translation_service = state.translation_service.as_ref().ok_or_else(|| {
-        misskey_common::AppError::BadRequest("Translation service not configured".to_string())
+        misskey_common::AppError::NotConfigured("Translation service not configured".to_string())
     })?;
 
     let input = TranslateInput {
@@ -113,7 +113,7 @@ async fn detect_language(
 ) -> AppResult<ApiResponse<LanguageDetectionResponse>> {
     // Check if translation service is available
     let translation_service = state.translation_service.as_ref().ok_or_else(|| {
-        misskey_common::AppError::BadRequest("Translation service not configured".to_string())
+        misskey_common::AppError::NotConfigured("Translation service not configured".to_string())
     })?;
 
     let result = translation_service.detect_language(&req.text).await?;
@@ -126,7 +126,7 @@ async fn supported_languages(
 ) -> AppResult<ApiResponse<Vec<SupportedLanguage>>> {
     // Check if translation service is available
     let translation_service = state.translation_service.as_ref().ok_or_else(|| {
-        misskey_common::AppError::BadRequest("Translation service not configured".to_string())
+        misskey_common::AppError::NotConfigured("Translation service not configured".to_string())
     })?;
 
     let result = translation_service.supported_languages().await?;
@@ -1,15 +1,24 @@
 //! Users endpoints.
 
-use axum::{Json, Router, extract::State, routing::post};
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    routing::{get, post},
+};
 use misskey_common::{AppError, AppResult};
-use misskey_core::UpdateUserInput;
-use misskey_db::entities::user;
+use misskey_core::{ProfileField, UpdateUserInput};
+use misskey_db::{entities::user, repositories::UserRelation};
 use serde::{Deserialize, Serialize};
 
-use crate::{extractors::AuthUser, middleware::AppState, response::ApiResponse};
+use crate::{
+    endpoints::notes::NoteResponse,
+    extractors::{AuthUser, MaybeAuthUser},
+    middleware::AppState,
+    response::ApiResponse,
+};
 
 /// User response.
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UserResponse {
     pub id: String,
@@ -64,11 +73,85 @@ pub struct ShowUserRequest {
     pub host: Option<String>,
 }
 
+/// Profile field of a user.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFieldResponse {
+    pub name: String,
+    pub value: String,
+}
+
+impl From<ProfileField> for UserFieldResponse {
+    fn from(field: ProfileField) -> Self {
+        Self {
+            name: field.name,
+            value: field.value,
+        }
+    }
+}
+
+/// Relationship of the viewer to a user.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserRelationResponse {
+    pub is_following: bool,
+    pub is_followed: bool,
+    pub is_blocking: bool,
+    pub is_blocked: bool,
+    pub is_muted: bool,
+}
+
+impl From<UserRelation> for UserRelationResponse {
+    fn from(relation: UserRelation) -> Self {
+        Self {
+            is_following: relation.is_following,
+            is_followed: relation.is_followed,
+            is_blocking: relation.is_blocking,
+            is_blocked: relation.is_blocked,
+            is_muted: relation.is_muted,
+        }
+    }
+}
+
+/// User response with profile details.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDetailedResponse {
+    #[serde(flatten)]
+    pub user: UserResponse,
+    pub fields: Vec<UserFieldResponse>,
+    pub pinned_note_ids: Vec<String>,
+    pub pinned_notes: Vec<NoteResponse>,
+    /// Present when the viewer is signed in and is not the user.
+    #[serde(flatten)]
+    pub relation: Option<UserRelationResponse>,
+}
+
 /// Get a user by ID or username.
 async fn show(
+    MaybeAuthUser(viewer): MaybeAuthUser,
     State(state): State<AppState>,
     Json(req): Json<ShowUserRequest>,
-) -> AppResult<ApiResponse<UserResponse>> {
+) -> AppResult<ApiResponse<UserDetailedResponse>> {
+    show_user(&state, viewer.as_ref(), req).await
+}
+
+/// Get a user by ID or username given as query parameters.
+async fn show_by_query(
+    MaybeAuthUser(viewer): MaybeAuthUser,
+    State(state): State<AppState>,
+    Query(req): Query<ShowUserRequest>,
+) -> AppResult<ApiResponse<UserDetailedResponse>> {
+    show_user(&state, viewer.as_ref(), req).await
+}
+
+/// Look up a user with their profile fields, visible pinned notes and
+/// relationship to the viewer.
+async fn show_user(
+    state: &AppState,
+    viewer: Option<&user::Model>,
+    req: ShowUserRequest,
+) -> AppResult<ApiResponse<UserDetailedResponse>> {
     let user = if let Some(user_id) = req.user_id {
         state.user_service.get(&user_id).await?
     } else if let Some(username) = req.username {
@@ -82,7 +165,34 @@ async fn show(
         ));
     };
 
-    Ok(ApiResponse::ok(user.into()))
+    let viewer_id = viewer.map(|viewer| viewer.id.as_str());
+    let details = state
+        .user_service
+        .get_profile_details(&user.id, viewer_id)
+        .await?;
+
+    let relation = match viewer_id {
+        Some(viewer_id) if viewer_id != user.id => Some(
+            state
+                .user_service
+                .get_relation(viewer_id, &user.id)
+                .await?
+                .into(),
+        ),
+        _ => None,
+    };
+
+    Ok(ApiResponse::ok(UserDetailedResponse {
+        user: user.into(),
+        fields: details.fields.into_iter().map(Into::into).collect(),
+        pinned_note_ids: details
+            .pinned_notes
+            .iter()
+            .map(|note| note.id.clone())
+            .collect(),
+        pinned_notes: details.pinned_notes.into_iter().map(Into::into).collect(),
+        relation,
+    }))
 }
 
 /// Update user request.
@@ -105,6 +215,10 @@ pub struct UpdateUserRequest {
     /// Require HTTP signature verification for requests to this user's resources.
     /// When enabled (Authorized Fetch / Secure Mode), unsigned requests will be rejected.
     pub secure_fetch_only: Option<bool>,
+    /// Reject quotes of this user's notes
+    pub disallow_quotes: Option<bool>,
+    /// Hide whether this user is online from other users
+    pub hide_online_status: Option<bool>,
 }
 
 impl UpdateUserRequest {
@@ -128,6 +242,8 @@ impl UpdateUserRequest {
             hide_bots: self.hide_bots,
             default_reaction: self.default_reaction,
             secure_fetch_only: self.secure_fetch_only,
+            disallow_quotes: self.disallow_quotes,
+            hide_online_status: self.hide_online_status,
         }
     }
 }
@@ -248,13 +364,70 @@ async fn reorder_pinned_notes(
     Ok(ApiResponse::ok(()))
 }
 
+/// Maximum users per online status lookup.
+const MAX_ONLINE_LOOKUP: usize = 100;
+
+/// Online status query.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnlineStatusQuery {
+    /// Comma-separated user IDs.
+    pub user_ids: String,
+}
+
+/// Online status of a user.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnlineStatusResponse {
+    pub user_id: String,
+    /// `None` when the user hides their online status.
+    pub online: Option<bool>,
+}
+
+/// Get whether users currently have an open streaming connection.
+async fn online(
+    AuthUser(user): AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<OnlineStatusQuery>,
+) -> AppResult<ApiResponse<Vec<OnlineStatusResponse>>> {
+    let user_ids: Vec<String> = query
+        .user_ids
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(String::from)
+        .collect();
+    if user_ids.len() > MAX_ONLINE_LOOKUP {
+        return Err(AppError::BadRequest(format!(
+            "At most {MAX_ONLINE_LOOKUP} users can be looked up at once"
+        )));
+    }
+
+    let visible = state
+        .user_service
+        .online_status_visible_to(&user_ids, &user.id)
+        .await?;
+
+    let mut statuses = Vec::with_capacity(user_ids.len());
+    for user_id in user_ids {
+        let online = if visible.contains(&user_id) {
+            Some(state.streaming.presence.is_online(&user_id).await)
+        } else {
+            None
+        };
+        statuses.push(OnlineStatusResponse { user_id, online });
+    }
+    Ok(ApiResponse::ok(statuses))
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/me", post(me))
-        .route("/show", post(show))
+        .route("/show", get(show_by_query).post(show))
         .route("/update", post(update))
         .route("/pin", post(pin_note))
         .route("/unpin", post(unpin_note))
         .route("/pinned-notes", post(get_pinned_notes))
         .route("/reorder-pinned-notes", post(reorder_pinned_notes))
+        .route("/online", get(online))
 }
@@ -15,12 +15,14 @@
 pub mod endpoints;
 pub mod extractors;
 pub mod middleware;
+pub mod presence;
 pub mod rate_limit;
 pub mod response;
 pub mod sse;
 pub mod streaming;
 
 pub use endpoints::router;
+pub use presence::{PresenceChange, PresenceTracker};
 pub use rate_limit::{ApiRateLimiter, RateLimitConfig, RateLimiterState};
 pub use sse::{SseBroadcaster, SseEvent};
 pub use streaming::{StreamingState, streaming_handler};
@@ -4,13 +4,14 @@
 
 use axum::{body::Body, extract::State, http::Request, middleware::Next, response::Response};
 use misskey_core::{
-    AccountService, AnnouncementService, AntennaService, BlockingService, ChannelService,
-    ClipService, DriveService, EmojiService, FollowingService, GalleryService, GroupService,
-    HashtagService, InstanceService, MessagingService, MetaSettingsService, ModerationService,
-    MutingService, NoteFavoriteService, NoteService, NotificationService, OAuthService,
-    PageService, PollService, PushNotificationService, ReactionService,
-    RegistrationApprovalService, ScheduledNoteService, TranslationService, TwoFactorService,
-    UserListService, UserService, WebAuthnService, WebhookService, WordFilterService,
+    AccountService, AnnouncementService, AntennaService, ApResolveService, BlockingService,
+    ChannelService, ClipService, DriveService, EmailBounceService, EmojiService, FollowingService,
+    GalleryService, GroupService, HashtagService, InstanceService, MessagingService,
+    MetaSettingsService, ModerationService, MutingService, NoteFavoriteService, NoteService,
+    NotificationService, OAuthService, PageService, PasswordResetService, PollService,
+    PushNotificationService, ReactionService, RegistrationApprovalService, ScheduledNoteService,
+    ThreadMutingService, TranslationService, TwoFactorService, UserListService, UserService,
+    WebAuthnService, WebhookService, WordFilterService,
 };
 
 use crate::sse::SseBroadcaster;
@@ -22,13 +23,17 @@ pub struct AppState {
     /// Base URL of this instance (e.g., `https://example.com`).
     pub base_url: String,
     pub user_service: UserService,
+    pub password_reset_service: PasswordResetService,
+    pub email_bounce_service: EmailBounceService,
     pub note_service: NoteService,
+    pub ap_resolve_service: ApResolveService,
     pub following_service: FollowingService,
     pub reaction_service: ReactionService,
     pub notification_service: NotificationService,
     pub blocking_service: BlockingService,
     pub clip_service: ClipService,
     pub muting_service: MutingService,
+    pub thread_muting_service: ThreadMutingService,
     pub drive_service: DriveService,
     pub poll_service: PollService,
     pub hashtag_service: HashtagService,
@@ -0,0 +1,312 @@
+//! Online presence of streaming users.
+//!
+//! A user is online while they hold at least one open streaming connection.
+//! The in-memory tracker only sees connections to this process; the Redis
+//! tracker shares them between all instances.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use fred::clients::{Client as RedisClient, SubscriberClient};
+use fred::interfaces::{EventInterface, KeysInterface, PubsubInterface, SortedSetsInterface};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// How long a connection counts as open without a heartbeat.
+///
+/// Connections of a crashed instance drop out after this long.
+pub const PRESENCE_TTL_SECS: i64 = 90;
+
+/// How often open connections refresh their presence.
+pub const PRESENCE_HEARTBEAT_SECS: u64 = 30;
+
+/// A user came online or went offline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresenceChange {
+    /// The user whose presence changed.
+    pub user_id: String,
+    /// Whether they are online now.
+    pub online: bool,
+}
+
+/// Storage backend for open connections.
+#[derive(Clone)]
+enum PresenceBackend {
+    /// Open connections per user (single-instance only).
+    InMemory(Arc<Mutex<HashMap<String, usize>>>),
+    /// Sorted set of connection IDs per user, scored by expiry.
+    Redis {
+        redis: Arc<RedisClient>,
+        prefix: String,
+    },
+}
+
+/// Tracks which users have an open streaming connection.
+#[derive(Clone)]
+pub struct PresenceTracker {
+    backend: PresenceBackend,
+    /// Presence changes, relayed from Redis for the distributed backend.
+    changes: broadcast::Sender<PresenceChange>,
+}
+
+impl PresenceTracker {
+    /// Create an in-memory presence tracker (single-instance only).
+    #[must_use]
+    pub fn new() -> Self {
+        let (changes, _) = broadcast::channel(1000);
+        Self {
+            backend: PresenceBackend::InMemory(Arc::new(Mutex::new(HashMap::new()))),
+            changes,
+        }
+    }
+
+    /// Create a Redis-backed presence tracker shared by all instances.
+    ///
+    /// Presence changes are published on a Redis channel and relayed to local
+    /// subscribers through `subscriber`, so every instance sees every change.
+    pub async fn with_redis(
+        redis: Arc<RedisClient>,
+        subscriber: SubscriberClient,
+        prefix: &str,
+    ) -> Result<Self, fred::error::Error> {
+        let (changes, _) = broadcast::channel(1000);
+        let channel = format!("{prefix}:presence");
+        subscriber.subscribe(channel.clone()).await?;
+
+        let tx = changes.clone();
+        let mut messages = subscriber.message_rx();
+        tokio::spawn(async move {
+            while let Ok(message) = messages.recv().await {
+                if message.channel != channel.as_str() {
+                    continue;
+                }
+                match message
+                    .value
+                    .as_string()
+                    .map(|payload| serde_json::from_str::<PresenceChange>(&payload))
+                {
+                    Some(Ok(change)) => {
+                        let _ = tx.send(change);
+                    }
+                    Some(Err(e)) => warn!(error = %e, "Failed to parse presence change"),
+                    None => {}
+                }
+            }
+        });
+
+        Ok(Self {
+            backend: PresenceBackend::Redis {
+                redis,
+                prefix: prefix.to_string(),
+            },
+            changes,
+        })
+    }
+
+    /// Check if using Redis backend.
+    #[must_use]
+    pub const fn is_distributed(&self) -> bool {
+        matches!(self.backend, PresenceBackend::Redis { .. })
+    }
+
+    /// Subscribe to presence changes.
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<PresenceChange> {
+        self.changes.subscribe()
+    }
+
+    /// Record an open connection, publishing a change if the user came online.
+    pub async fn connect(&self, user_id: &str, connection_id: &str) {
+        let came_online = match &self.backend {
+            PresenceBackend::InMemory(connections) => {
+                let mut connections = connections
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                let count = connections.entry(user_id.to_string()).or_insert(0);
+                *count += 1;
+                *count == 1
+            }
+            PresenceBackend::Redis { redis, prefix } => {
+                let key = presence_key(prefix, user_id);
+                let result: Result<u64, _> = async {
+                    expire_connections(redis, &key).await?;
+                    refresh_connection(redis, &key, connection_id).await?;
+                    redis.zcard(&key).await
+                }
+                .await;
+                match result {
+                    Ok(count) => count == 1,
+                    Err(e) => {
+                        warn!(error = %e, user_id, "Failed to record presence");
+                        false
+                    }
+                }
+            }
+        };
+        if came_online {
+            self.publish(user_id, true).await;
+        }
+    }
+
+    /// Keep an open connection from expiring.
+    pub async fn refresh(&self, user_id: &str, connection_id: &str) {
+        if let PresenceBackend::Redis { redis, prefix } = &self.backend
+            && let Err(e) =
+                refresh_connection(redis, &presence_key(prefix, user_id), connection_id).await
+        {
+            warn!(error = %e, user_id, "Failed to refresh presence");
+        }
+    }
+
+    /// Drop a closed connection, publishing a change if the user went offline.
+    pub async fn disconnect(&self, user_id: &str, connection_id: &str) {
+        let went_offline = match &self.backend {
+            PresenceBackend::InMemory(connections) => {
+                let mut connections = connections
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                match connections.get_mut(user_id) {
+                    Some(count) if *count > 1 => {
+                        *count -= 1;
+                        false
+                    }
+                    Some(_) => {
+                        connections.remove(user_id);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            PresenceBackend::Redis { redis, prefix } => {
+                let key = presence_key(prefix, user_id);
+                let result: Result<u64, _> = async {
+                    let removed: u64 = redis.zrem(&key, connection_id).await?;
+                    if removed == 0 {
+                        // Its heartbeat already lapsed; nothing left to release
+                        return Ok(u64::MAX);
+                    }
+                    expire_connections(redis, &key).await?;
+                    redis.zcard(&key).await
+                }
+                .await;
+                match result {
+                    Ok(count) => count == 0,
+                    Err(e) => {
+                        warn!(error = %e, user_id, "Failed to release presence");
+                        false
+                    }
+                }
+            }
+        };
+        if went_offline {
+            self.publish(user_id, false).await;
+        }
+    }
+
+    /// Whether the user has an open streaming connection.
+    pub async fn is_online(&self, user_id: &str) -> bool {
+        match &self.backend {
+            PresenceBackend::InMemory(connections) => connections
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .contains_key(user_id),
+            PresenceBackend::Redis { redis, prefix } => {
+                let key = presence_key(prefix, user_id);
+                let result: Result<u64, _> = async {
+                    expire_connections(redis, &key).await?;
+                    redis.zcard(&key).await
+                }
+                .await;
+                result.unwrap_or_else(|e| {
+                    warn!(error = %e, user_id, "Failed to read presence");
+                    0
+                }) > 0
+            }
+        }
+    }
+
+    async fn publish(&self, user_id: &str, online: bool) {
+        let change = PresenceChange {
+            user_id: user_id.to_string(),
+            online,
+        };
+        match &self.backend {
+            PresenceBackend::InMemory(_) => {
+                let _ = self.changes.send(change);
+            }
+            PresenceBackend::Redis { redis, prefix } => {
+                let payload = serde_json::to_string(&change).unwrap_or_default();
+                let result: Result<(), _> =
+                    redis.publish(format!("{prefix}:presence"), payload).await;
+                if let Err(e) = result {
+                    warn!(error = %e, user_id, "Failed to publish presence change");
+                }
+            }
+        }
+    }
+}
+
+impl Default for PresenceTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn presence_key(prefix: &str, user_id: &str) -> String {
+    format!("{prefix}:presence:{user_id}")
+}
+
+/// Remove connections whose heartbeat has lapsed.
+async fn expire_connections(redis: &RedisClient, key: &str) -> Result<(), fred::error::Error> {
+    redis
+        .zremrangebyscore(key, "-inf", Utc::now().timestamp())
+        .await
+}
+
+/// Add or refresh a connection with a new expiry.
+async fn refresh_connection(
+    redis: &RedisClient,
+    key: &str,
+    connection_id: &str,
+) -> Result<(), fred::error::Error> {
+    let expires_at = Utc::now().timestamp() + PRESENCE_TTL_SECS;
+    #[allow(clippy::cast_precision_loss)]
+    let score = expires_at as f64;
+    let _: () = redis
+        .zadd(key, None, None, false, false, (score, connection_id))
+        .await?;
+    redis.expire(key, PRESENCE_TTL_SECS, None).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_connections_toggle_presence() {
+        let presence = PresenceTracker::new();
+        let mut changes = presence.subscribe();
+        assert!(!presence.is_online("user1").await);
+
+        presence.connect("user1", "conn1").await;
+        presence.connect("user1", "conn2").await;
+        assert!(presence.is_online("user1").await);
+
+        // Only the first connection and the last disconnect change presence
+        presence.disconnect("user1", "conn1").await;
+        assert!(presence.is_online("user1").await);
+        presence.disconnect("user1", "conn2").await;
+        assert!(!presence.is_online("user1").await);
+
+        let online: Vec<bool> = std::iter::from_fn(|| changes.try_recv().ok())
+            .map(|change| {
+                assert_eq!(change.user_id, "user1");
+                change.online
+            })
+            .collect();
+        assert_eq!(online, [true, false]);
+    }
+}
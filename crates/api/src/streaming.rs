@@ -10,12 +10,15 @@ use axum::{
     response::IntoResponse,
 };
 use futures::{SinkExt, StreamExt};
+use misskey_core::{TimelineChannel, timeline_channels};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
 use tracing::{error, info, warn};
 
 use crate::middleware::AppState;
+use crate::presence::{PRESENCE_HEARTBEAT_SECS, PresenceTracker};
 
 /// Streaming query parameters.
 #[derive(Debug, Deserialize)]
@@ -41,6 +44,8 @@ pub enum StreamChannel {
     User { user_id: String },
     /// Channel timeline stream.
     Channel { channel_id: String },
+    /// Presence changes of the given users.
+    Presence { user_ids: Vec<String> },
 }
 
 /// Stream event types.
@@ -59,6 +64,8 @@ pub enum StreamEvent {
     Unfollowed { id: String, user_id: String },
     /// Mention event.
     Mention(NoteEvent),
+    /// A user came online or went offline.
+    PresenceChanged { user_id: String, online: bool },
 }
 
 /// Note event data.
@@ -145,6 +152,8 @@ pub struct StreamingState {
     pub local_tx: Arc<broadcast::Sender<StreamEvent>>,
     /// Broadcast sender for channel-specific events.
     pub channel_tx: Arc<broadcast::Sender<ChannelEvent>>,
+    /// Users with an open streaming connection.
+    pub presence: PresenceTracker,
 }
 
 impl StreamingState {
@@ -159,21 +168,28 @@ impl StreamingState {
             global_tx: Arc::new(global_tx),
             local_tx: Arc::new(local_tx),
             channel_tx: Arc::new(channel_tx),
+            presence: PresenceTracker::new(),
         }
     }
 
+    /// Use `presence` to track who is online, e.g. one shared through Redis.
+    #[must_use]
+    pub fn with_presence(mut self, presence: PresenceTracker) -> Self {
+        self.presence = presence;
+        self
+    }
+
     /// Publish a note event to the appropriate channels.
     pub fn publish_note(&self, event: NoteEvent) {
-        let stream_event = StreamEvent::Note(event.clone());
-
-        // Publish to local timeline if visibility is public
-        if event.visibility == "public" {
-            let _ = self.local_tx.send(stream_event.clone());
-        }
+        let timelines = timeline_channels(&event.visibility);
+        let stream_event = StreamEvent::Note(event);
 
-        // Publish to global timeline
-        if event.visibility == "public" {
-            let _ = self.global_tx.send(stream_event);
+        for timeline in timelines {
+            let tx = match timeline {
+                TimelineChannel::Global => &self.global_tx,
+                TimelineChannel::Local => &self.local_tx,
+            };
+            let _ = tx.send(stream_event.clone());
         }
     }
 
@@ -233,10 +249,18 @@ async fn handle_socket(socket: WebSocket, query: StreamQuery, state: AppState) {
 
     info!(user_id = ?user_id, "Streaming connection established");
 
+    // Released after the loop, however it exits
+    let connection_id = uuid::Uuid::new_v4().to_string();
+    if let Some(id) = &user_id {
+        state.streaming.presence.connect(id, &connection_id).await;
+    }
+    let mut heartbeat = tokio::time::interval(Duration::from_secs(PRESENCE_HEARTBEAT_SECS));
+
     // Subscribe to broadcast channels
     let mut global_rx = state.streaming.global_tx.subscribe();
     let mut local_rx = state.streaming.local_tx.subscribe();
     let mut channel_rx = state.streaming.channel_tx.subscribe();
+    let mut presence_rx = state.streaming.presence.subscribe();
 
     // Track connected channels
     let mut connected_channels: std::collections::HashMap<String, StreamChannel> =
@@ -254,6 +278,7 @@ async fn handle_socket(socket: WebSocket, query: StreamQuery, state: AppState) {
                                     client_msg,
                                     &mut connected_channels,
                                     user_id.as_deref(),
+                                    &state,
                                 ).await {
                                     let json = serde_json::to_string(&response).unwrap_or_default();
                                     if sender.send(Message::Text(json.into())).await.is_err() {
@@ -321,9 +346,43 @@ async fn handle_socket(socket: WebSocket, query: StreamQuery, state: AppState) {
                         }
                 }
             }
+
+            // Handle presence changes of watched users
+            Ok(change) = presence_rx.recv() => {
+                let event = StreamEvent::PresenceChanged {
+                    user_id: change.user_id,
+                    online: change.online,
+                };
+                for (conn_id, stream_channel) in &connected_channels {
+                    if let StreamChannel::Presence { user_ids } = stream_channel
+                        && let StreamEvent::PresenceChanged { user_id: changed, .. } = &event
+                        && user_ids.contains(changed) {
+                            let msg = event_to_server_message(conn_id, &event);
+                            let json = serde_json::to_string(&msg).unwrap_or_default();
+                            if sender.send(Message::Text(json.into())).await.is_err() {
+                                break;
+                            }
+                        }
+                }
+            }
+
+            // Keep this connection counted as online
+            _ = heartbeat.tick() => {
+                if let Some(id) = &user_id {
+                    state.streaming.presence.refresh(id, &connection_id).await;
+                }
+            }
         }
     }
 
+    if let Some(id) = &user_id {
+        state
+            .streaming
+            .presence
+            .disconnect(id, &connection_id)
+            .await;
+    }
+
     info!("Streaming connection closed");
 }
 
@@ -331,7 +390,8 @@ async fn handle_socket(socket: WebSocket, query: StreamQuery, state: AppState) {
 async fn handle_client_message(
     msg: ClientMessage,
     connected_channels: &mut std::collections::HashMap<String, StreamChannel>,
-    _user_id: Option<&str>,
+    user_id: Option<&str>,
+    state: &AppState,
 ) -> Option<ServerMessage> {
     match msg {
         ClientMessage::Connect {
@@ -344,6 +404,30 @@ async fn handle_client_message(
                 "localTimeline" => StreamChannel::LocalTimeline,
                 "globalTimeline" => StreamChannel::GlobalTimeline,
                 "main" => StreamChannel::Main,
+                "presence" => {
+                    // Presence is only shown to signed-in users
+                    let Some(viewer_id) = user_id else {
+                        warn!("Presence connection without authentication");
+                        return None;
+                    };
+                    let user_ids: Vec<String> = params
+                        .get("userIds")
+                        .and_then(|v| serde_json::from_value(v.clone()).ok())
+                        .unwrap_or_default();
+                    // Users hiding their online status are never reported
+                    let user_ids = match state
+                        .user_service
+                        .online_status_visible_to(&user_ids, viewer_id)
+                        .await
+                    {
+                        Ok(user_ids) => user_ids,
+                        Err(e) => {
+                            warn!(error = %e, "Failed to check online status visibility");
+                            Vec::new()
+                        }
+                    };
+                    StreamChannel::Presence { user_ids }
+                }
                 "channel" => {
                     // Extract channelId from params
                     let channel_id = params
@@ -423,6 +507,10 @@ fn event_to_server_message(channel_id: &str, event: &StreamEvent) -> ServerMessa
             serde_json::json!({ "id": id, "userId": user_id }),
         ),
         StreamEvent::Mention(note) => ("mention", serde_json::to_value(note).unwrap_or_default()),
+        StreamEvent::PresenceChanged { user_id, online } => (
+            "presenceChanged",
+            serde_json::json!({ "userId": user_id, "online": online }),
+        ),
     };
 
     ServerMessage::Channel {
@@ -431,3 +519,28 @@ fn event_to_server_message(channel_id: &str, event: &StreamEvent) -> ServerMessa
         body,
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_followers_note_skips_timelines() {
+        let state = StreamingState::new();
+        let mut local_rx = state.local_tx.subscribe();
+        let mut global_rx = state.global_tx.subscribe();
+
+        state.publish_note(NoteEvent {
+            id: "note1".to_string(),
+            user_id: "user1".to_string(),
+            text: None,
+            cw: None,
+            visibility: "followers".to_string(),
+            created_at: String::new(),
+        });
+
+        assert!(local_rx.try_recv().is_err());
+        assert!(global_rx.try_recv().is_err());
+    }
+}
@@ -10,24 +10,29 @@ use axum::{
     http::{Request, StatusCode},
 };
 use misskey_api::{SseBroadcaster, StreamingState, middleware::AppState, router as api_router};
-use misskey_common::config::{Config, DatabaseConfig, FederationConfig, RedisConfig, ServerConfig};
+use misskey_common::config::{
+    Config, DatabaseConfig, FederationConfig, FederationMode, RedisConfig, ServerConfig,
+};
 use misskey_core::{
-    AnnouncementService, AntennaService, BlockingService, ChannelService, ClipService,
-    DriveService, EmojiService, FollowingService, GalleryService, GroupService, InstanceService,
-    MessagingService, MetaSettingsService, ModerationService, MutingService, NoteFavoriteService,
-    NoteService, NotificationService, OAuthService, PageService, PollService, ReactionService,
-    RegistrationApprovalService, ScheduledNoteService, TwoFactorService, UserListService,
-    UserService, WebAuthnConfig, WebAuthnService, WebhookService, WordFilterService,
+    AnnouncementService, AntennaService, ApResolveService, BlockingService, ChannelService,
+    ClipService, DriveService, EmailBounceService, EmailService, EmojiService, FollowingService,
+    GalleryService, GroupService, InstanceService, MessagingService, MetaSettingsService,
+    ModerationService, MutingService, NoteFavoriteService, NoteService, NotificationService,
+    OAuthService, PageService, PasswordResetService, PollService, ReactionService,
+    RegistrationApprovalService, ScheduledNoteService, ThreadMutingService, TwoFactorService,
+    UserListService, UserService, WebAuthnConfig, WebAuthnService, WebhookService,
+    WordFilterService,
 };
 use misskey_db::repositories::{
     AnnouncementRepository, AntennaRepository, BlockingRepository, ChannelRepository,
     ClipRepository, DriveFileRepository, DriveFolderRepository, EmojiRepository,
     FollowRequestRepository, FollowingRepository, GalleryRepository, GroupRepository,
     InstanceRepository, MessagingRepository, ModerationRepository, MutingRepository,
-    NoteFavoriteRepository, NoteRepository, NotificationRepository, OAuthRepository,
-    PageRepository, PollRepository, PollVoteRepository, ReactionRepository,
-    ScheduledNoteRepository, SecurityKeyRepository, UserKeypairRepository, UserListRepository,
-    UserProfileRepository, UserRepository, WebhookRepository, WordFilterRepository,
+    NoteFavoriteRepository, NoteRepository, NoteThreadMutingRepository, NotificationRepository,
+    OAuthRepository, PageRepository, PasswordResetRequestRepository, PollRepository,
+    PollVoteRepository, ReactionRepository, ScheduledNoteRepository, SecurityKeyRepository,
+    UserKeypairRepository, UserListRepository, UserProfileRepository, UserRepository,
+    WebhookRepository, WordFilterRepository,
 };
 use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase, MockExecResult};
 use std::sync::Arc;
@@ -46,6 +51,7 @@ fn create_test_config() -> Config {
             read_replicas: Vec::new(),
             max_connections: 10,
             min_connections: 1,
+            slow_query_threshold_ms: 500,
         },
         redis: RedisConfig {
             url: "redis://localhost".to_string(),
@@ -57,7 +63,17 @@ fn create_test_config() -> Config {
             instance_description: Some("A test instance".to_string()),
             maintainer_name: None,
             maintainer_email: None,
+            mode: FederationMode::Open,
+            min_account_age_secs: 0,
+            inbox_rate_limit_window_secs: 60,
+            shared_inbox_rate_limit: 300,
+            user_inbox_rate_limit: 100,
         },
+        storage: misskey_common::StorageConfig::default(),
+        email: misskey_common::EmailConfig::default(),
+        translation: misskey_common::TranslationConfig::default(),
+        push: misskey_common::PushConfig::default(),
+        notes: misskey_common::NoteConfig::default(),
     }
 }
 
@@ -92,6 +108,7 @@ fn create_test_state() -> AppState {
     let poll_vote_repo = PollVoteRepository::new(Arc::clone(&db));
     let hashtag_repo = misskey_db::repositories::HashtagRepository::new(Arc::clone(&db));
     let note_favorite_repo = NoteFavoriteRepository::new(Arc::clone(&db));
+    let note_thread_muting_repo = NoteThreadMutingRepository::new(Arc::clone(&db));
     let user_list_repo = UserListRepository::new(Arc::clone(&db));
     let moderation_repo = ModerationRepository::new(Arc::clone(&db));
     let emoji_repo = EmojiRepository::new(Arc::clone(&db));
@@ -119,6 +136,12 @@ fn create_test_state() -> AppState {
     );
     let note_service =
         NoteService::new(note_repo.clone(), user_repo.clone(), following_repo.clone());
+    let ap_resolve_service = ApResolveService::new(
+        note_repo.clone(),
+        user_repo.clone(),
+        following_repo.clone(),
+        "https://test.example.com".to_string(),
+    );
     let blocking_service = BlockingService::new(blocking_repo.clone(), following_repo.clone());
     let following_repo_for_messaging = following_repo.clone();
     let following_service =
@@ -126,6 +149,8 @@ fn create_test_state() -> AppState {
     let reaction_service = ReactionService::new(reaction_repo, note_repo.clone());
     let notification_service = NotificationService::new(notification_repo);
     let muting_service = MutingService::new(muting_repo);
+    let thread_muting_service =
+        ThreadMutingService::new(note_thread_muting_repo, note_repo.clone());
     let drive_service = DriveService::new(
         drive_file_repo,
         drive_folder_repo,
@@ -165,6 +190,14 @@ fn create_test_state() -> AppState {
     )
     .expect("Failed to create WebAuthn service");
 
+    let password_reset_service = PasswordResetService::new(
+        PasswordResetRequestRepository::new(Arc::clone(&db)),
+        user_profile_repo.clone(),
+        user_repo.clone(),
+        oauth_repo.clone(),
+        EmailService::new(None),
+        "https://test.example.com".to_string(),
+    );
     let oauth_service = OAuthService::new(oauth_repo);
     let webhook_service = WebhookService::new(webhook_repo);
     let page_service = PageService::new(page_repo);
@@ -179,13 +212,20 @@ fn create_test_state() -> AppState {
     AppState {
         base_url: "https://test.example.com".to_string(),
         user_service,
+        password_reset_service,
+        email_bounce_service: EmailBounceService::new(
+            user_profile_repo.clone(),
+            misskey_common::EmailWebhookConfig::default(),
+        ),
         note_service,
+        ap_resolve_service,
         following_service,
         reaction_service,
         notification_service,
         blocking_service,
         clip_service,
         muting_service,
+        thread_muting_service,
         drive_service,
         poll_service,
         hashtag_service,
@@ -361,6 +401,26 @@ async fn test_signup_with_invalid_json_returns_error() {
     );
 }
 
+#[tokio::test]
+async fn test_translate_when_disabled_returns_not_implemented() {
+    let app = create_test_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/translate/text")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(r#"{"text":"hello","targetLang":"en"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Translation is disabled in the test config
+    assert_eq!(response.status(), StatusCode::NOT_IMPLEMENTED);
+}
+
 #[tokio::test]
 async fn test_sse_global_timeline_returns_stream() {
     let app = create_test_router();
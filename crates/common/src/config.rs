@@ -3,6 +3,8 @@
 use serde::Deserialize;
 use std::path::Path;
 
+use crate::storage::StorageConfig;
+
 /// Application configuration.
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
@@ -14,6 +16,21 @@ pub struct Config {
     pub redis: RedisConfig,
     /// Federation configuration.
     pub federation: FederationConfig,
+    /// Drive file storage configuration.
+    #[serde(default)]
+    pub storage: StorageConfig,
+    /// Email configuration.
+    #[serde(default)]
+    pub email: EmailConfig,
+    /// Note configuration.
+    #[serde(default)]
+    pub notes: NoteConfig,
+    /// Note translation configuration.
+    #[serde(default)]
+    pub translation: TranslationConfig,
+    /// Web Push configuration.
+    #[serde(default)]
+    pub push: PushConfig,
 }
 
 /// Server configuration.
@@ -43,6 +60,9 @@ pub struct DatabaseConfig {
     /// Minimum number of connections in the pool.
     #[serde(default = "default_min_connections")]
     pub min_connections: u32,
+    /// Queries slower than this many milliseconds are logged at warn (0 disables).
+    #[serde(default = "default_slow_query_threshold_ms")]
+    pub slow_query_threshold_ms: u64,
 }
 
 /// Redis configuration.
@@ -72,6 +92,282 @@ pub struct FederationConfig {
     /// Instance maintainer email.
     #[serde(default)]
     pub maintainer_email: Option<String>,
+    /// Which remote hosts this instance federates with.
+    #[serde(default)]
+    pub mode: FederationMode,
+    /// Remote accounts first seen less than this many seconds ago have their
+    /// notes kept out of public timelines and their mentions dropped (0 disables).
+    #[serde(default)]
+    pub min_account_age_secs: u64,
+    /// Window of the per-host inbox rate limits, in seconds.
+    #[serde(default = "default_inbox_rate_limit_window_secs")]
+    pub inbox_rate_limit_window_secs: i64,
+    /// Activities a host may deliver to the shared inbox per window.
+    #[serde(default = "default_shared_inbox_rate_limit")]
+    pub shared_inbox_rate_limit: u64,
+    /// Activities a host may deliver to user inboxes per window.
+    #[serde(default = "default_user_inbox_rate_limit")]
+    pub user_inbox_rate_limit: u64,
+}
+
+/// Email configuration.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EmailConfig {
+    /// Send emails (password resets, notifications).
+    #[serde(default)]
+    pub enabled: bool,
+    /// Sender address.
+    #[serde(default)]
+    pub from_address: String,
+    /// Sender display name; defaults to the instance name.
+    #[serde(default)]
+    pub from_name: Option<String>,
+    /// Reply-to address.
+    #[serde(default)]
+    pub reply_to: Option<String>,
+    /// Provider used to send emails; required when enabled.
+    #[serde(default)]
+    pub transport: Option<EmailTransportConfig>,
+    /// Bounce and complaint webhook settings.
+    #[serde(default)]
+    pub webhooks: EmailWebhookConfig,
+}
+
+/// Email provider settings, selected by `provider`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum EmailTransportConfig {
+    /// SMTP server.
+    Smtp {
+        /// Server host.
+        host: String,
+        /// Server port.
+        #[serde(default = "default_smtp_port")]
+        port: u16,
+        /// Use TLS.
+        #[serde(default = "default_true")]
+        use_tls: bool,
+        /// Login username.
+        #[serde(default)]
+        username: Option<String>,
+        /// Login password.
+        #[serde(default)]
+        password: Option<String>,
+    },
+    /// Amazon SES.
+    Ses {
+        /// AWS region.
+        region: String,
+        /// AWS access key ID.
+        access_key_id: String,
+        /// AWS secret access key.
+        secret_access_key: String,
+    },
+    /// `SendGrid`.
+    #[serde(rename = "sendgrid")]
+    SendGrid {
+        /// API key.
+        api_key: String,
+    },
+    /// Mailgun.
+    Mailgun {
+        /// API key.
+        api_key: String,
+        /// Sending domain.
+        domain: String,
+        /// Use the EU endpoint.
+        #[serde(default)]
+        eu_region: bool,
+    },
+}
+
+/// Credentials for verifying email provider bounce webhooks.
+///
+/// A provider's webhook is rejected unless its credential is set.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EmailWebhookConfig {
+    /// ARN of the SNS topic that SES publishes bounces and complaints to.
+    #[serde(default)]
+    pub ses_topic_arn: Option<String>,
+    /// `SendGrid` signed event webhook verification key (base64 DER).
+    #[serde(default)]
+    pub sendgrid_verification_key: Option<String>,
+    /// Mailgun webhook signing key.
+    #[serde(default)]
+    pub mailgun_signing_key: Option<String>,
+}
+
+/// Note translation configuration.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TranslationConfig {
+    /// Enable the translation endpoints.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Provider: "deepl", "google", "libretranslate", "openai", "anthropic"
+    /// or "ollama".
+    #[serde(default = "default_translation_provider")]
+    pub provider: String,
+    /// API key of the provider.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Endpoint of self-hosted providers (`LibreTranslate`, Ollama).
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Model of LLM-based providers.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Seconds translations are cached (0 disables).
+    #[serde(default = "default_translation_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+}
+
+impl Default for TranslationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: default_translation_provider(),
+            api_key: None,
+            url: None,
+            model: None,
+            cache_ttl_secs: default_translation_cache_ttl_secs(),
+        }
+    }
+}
+
+/// Web Push configuration.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PushConfig {
+    /// Enable Web Push notifications.
+    #[serde(default)]
+    pub enabled: bool,
+    /// VAPID public key (base64 URL-safe).
+    #[serde(default)]
+    pub vapid_public_key: String,
+    /// VAPID private key (base64 URL-safe).
+    #[serde(default)]
+    pub vapid_private_key: String,
+    /// VAPID subject, a `mailto:` or `https:` URL.
+    #[serde(default)]
+    pub vapid_subject: String,
+}
+
+/// Note configuration.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NoteConfig {
+    /// Limits on the files attached to a single note.
+    #[serde(default)]
+    pub attachments: AttachmentLimits,
+    /// Audience of quote renotes of public notes.
+    #[serde(default)]
+    pub quote_reach: QuoteReach,
+    /// Seconds the first page of public timelines is cached (0 disables).
+    #[serde(default = "default_timeline_cache_ttl_secs")]
+    pub timeline_cache_ttl_secs: u64,
+    /// Path of the human-readable URL of local notes, relative to the server
+    /// URL. `{id}` and `{username}` are substituted.
+    #[serde(default = "default_note_url_template")]
+    pub url_template: String,
+    /// Phrase lists and posting-burst signal checked before local notes are created.
+    #[serde(default)]
+    pub content_policy: ContentPolicyConfig,
+}
+
+impl Default for NoteConfig {
+    fn default() -> Self {
+        Self {
+            attachments: AttachmentLimits::default(),
+            quote_reach: QuoteReach::default(),
+            timeline_cache_ttl_secs: default_timeline_cache_ttl_secs(),
+            url_template: default_note_url_template(),
+            content_policy: ContentPolicyConfig::default(),
+        }
+    }
+}
+
+/// Content policy applied to local notes.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContentPolicyConfig {
+    /// Phrases that make note creation fail.
+    #[serde(default)]
+    pub blocked_phrases: Vec<String>,
+    /// Phrases that flag the note for operators.
+    #[serde(default)]
+    pub flagged_phrases: Vec<String>,
+    /// Notes per window after which further notes are flagged (0 disables).
+    #[serde(default = "default_burst_max_notes")]
+    pub burst_max_notes: u64,
+    /// Length of the posting-burst window, in seconds.
+    #[serde(default = "default_burst_window_secs")]
+    pub burst_window_secs: i64,
+}
+
+impl Default for ContentPolicyConfig {
+    fn default() -> Self {
+        Self {
+            blocked_phrases: Vec::new(),
+            flagged_phrases: Vec::new(),
+            burst_max_notes: default_burst_max_notes(),
+            burst_window_secs: default_burst_window_secs(),
+        }
+    }
+}
+
+/// Which followers a quote renote of a public note is delivered to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteReach {
+    /// Only the quoting user's followers.
+    #[default]
+    RenoterFollowers,
+    /// The quoting user's followers plus those of the quoted note's local author.
+    IncludeAuthorFollowers,
+}
+
+/// Per-note attachment limits, applied on top of the fixed 16-file cap.
+///
+/// Unset limits are not enforced.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct AttachmentLimits {
+    /// Maximum combined size of all attached files, in bytes.
+    #[serde(default)]
+    pub max_total_bytes: Option<u64>,
+    /// Maximum number of attached videos.
+    #[serde(default)]
+    pub max_videos: Option<usize>,
+    /// Maximum number of attached images.
+    #[serde(default)]
+    pub max_images: Option<usize>,
+}
+
+/// Federation mode controlling which remote hosts are federated with.
+///
+/// The host list itself is an instance setting so that admins can edit it
+/// at runtime; the mode decides how that list is interpreted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FederationMode {
+    /// Federate with every host.
+    #[default]
+    Open,
+    /// Federate only with hosts in the list.
+    Allowlist,
+    /// Federate with every host except those in the list.
+    Blocklist,
+}
+
+impl FederationMode {
+    /// Check whether `host` may be federated with, given the configured host list.
+    ///
+    /// Hosts are compared case-insensitively.
+    #[must_use]
+    pub fn allows(self, host: &str, hosts: &[String]) -> bool {
+        let listed = || hosts.iter().any(|h| h.eq_ignore_ascii_case(host));
+        match self {
+            Self::Open => true,
+            Self::Allowlist => listed(),
+            Self::Blocklist => !listed(),
+        }
+    }
 }
 
 fn default_host() -> String {
@@ -90,6 +386,50 @@ const fn default_min_connections() -> u32 {
     5
 }
 
+const fn default_slow_query_threshold_ms() -> u64 {
+    500
+}
+
+const fn default_timeline_cache_ttl_secs() -> u64 {
+    5
+}
+
+const fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_translation_provider() -> String {
+    "libretranslate".to_string()
+}
+
+const fn default_translation_cache_ttl_secs() -> u64 {
+    3600
+}
+
+const fn default_burst_max_notes() -> u64 {
+    20
+}
+
+const fn default_burst_window_secs() -> i64 {
+    60
+}
+
+const fn default_inbox_rate_limit_window_secs() -> i64 {
+    60
+}
+
+const fn default_shared_inbox_rate_limit() -> u64 {
+    300
+}
+
+const fn default_user_inbox_rate_limit() -> u64 {
+    100
+}
+
+fn default_note_url_template() -> String {
+    "/notes/{id}".to_string()
+}
+
 fn default_redis_prefix() -> String {
     "misskey".to_string()
 }
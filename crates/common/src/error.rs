@@ -100,6 +100,10 @@ pub enum AppError {
     #[error("External service error: {0}")]
     ExternalService(String),
 
+    /// An optional feature is not configured on this server.
+    #[error("Not configured: {0}")]
+    NotConfigured(String),
+
     /// Internal server error.
     #[error("Internal error: {0}")]
     Internal(String),
@@ -130,6 +134,7 @@ impl AppError {
             | Self::Config(_)
             | Self::ExternalService(_)
             | Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::NotConfigured(_) => StatusCode::NOT_IMPLEMENTED,
         }
     }
 
@@ -152,6 +157,7 @@ impl AppError {
             Self::Queue(_) => "QUEUE_ERROR",
             Self::Config(_) => "CONFIG_ERROR",
             Self::ExternalService(_) => "EXTERNAL_SERVICE_ERROR",
+            Self::NotConfigured(_) => "NOT_CONFIGURED",
             Self::Internal(_) => "INTERNAL_ERROR",
         }
     }
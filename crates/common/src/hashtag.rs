@@ -0,0 +1,26 @@
+//! Hashtag normalization shared by notes, search and federation.
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Normalize a hashtag for storage and lookup.
+///
+/// Strips a leading `#`, lowercases and composes to NFC, so that tags typed
+/// with different case or with precomposed vs. combining characters (e.g.
+/// `é` vs. `e` + U+0301) are stored and searched as the same tag.
+#[must_use]
+pub fn normalize_hashtag(tag: &str) -> String {
+    tag.trim_start_matches('#').to_lowercase().nfc().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equivalent_forms_normalize_equally() {
+        let precomposed = normalize_hashtag("#Caf\u{e9}");
+        let combining = normalize_hashtag("cafe\u{301}");
+        assert_eq!(precomposed, "caf\u{e9}");
+        assert_eq!(precomposed, combining);
+    }
+}
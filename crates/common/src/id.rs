@@ -1,5 +1,8 @@
 //! ID generation utilities.
 
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
 use ulid::Ulid;
 use uuid::Uuid;
 
@@ -27,6 +30,17 @@ impl IdGenerator {
         Ulid::new().to_string().to_lowercase()
     }
 
+    /// Generate a ULID-based ID whose time component is `time`.
+    ///
+    /// Used for objects created elsewhere, so that they sort by their
+    /// original creation time rather than when they were received.
+    #[must_use]
+    pub fn generate_at(&self, time: DateTime<Utc>) -> String {
+        Ulid::from_datetime(SystemTime::from(time))
+            .to_string()
+            .to_lowercase()
+    }
+
     /// Generate a new UUID v7-based ID.
     ///
     /// UUID v7 is time-ordered and suitable for database primary keys.
@@ -50,6 +64,7 @@ impl IdGenerator {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -66,6 +81,22 @@ mod tests {
         // may not be strictly ordered due to the random component
     }
 
+    #[test]
+    fn test_generate_at_uses_given_time() {
+        let id_gen = IdGenerator::new();
+        let time = DateTime::parse_from_rfc3339("2024-06-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let id = id_gen.generate_at(time);
+
+        let ulid = Ulid::from_string(&id).unwrap();
+        assert_eq!(
+            ulid.timestamp_ms(),
+            u64::try_from(time.timestamp_millis()).unwrap()
+        );
+        assert!(id < id_gen.generate());
+    }
+
     #[test]
     fn test_generate_uuid_v7() {
         let id_gen = IdGenerator::new();
@@ -5,9 +5,11 @@
 //! - **Configuration**: Application settings via [`Config`]
 //! - **Error handling**: Unified error types via [`AppError`] and [`AppResult`]
 //! - **Cryptography**: RSA key generation for `ActivityPub` signatures
+//! - **Hashtags**: Hashtag normalization shared by notes, search and federation
 //! - **HTTP Signatures**: Implementation of HTTP Signatures for federation
 //! - **ID Generation**: ULID-based unique identifiers via [`IdGenerator`]
 //! - **Metrics**: Performance monitoring via [`Metrics`]
+//! - **Reactions**: Reaction syntax validation shared by API and federation
 //! - **Storage**: File storage backends (local, S3-compatible)
 //! - **URL Preview**: Link preview fetching for rich embeds
 //! - **URL Preview Cache**: Redis-backed caching for URL previews
@@ -29,23 +31,29 @@
 pub mod config;
 pub mod crypto;
 pub mod error;
+pub mod hashtag;
 pub mod http_signature;
 pub mod id;
 pub mod metrics;
+pub mod reaction;
 pub mod storage;
 pub mod url_preview;
 pub mod url_preview_cache;
 
-pub use config::Config;
+pub use config::{
+    AttachmentLimits, Config, ContentPolicyConfig, EmailConfig, EmailTransportConfig,
+    EmailWebhookConfig, FederationMode, NoteConfig, PushConfig, QuoteReach, TranslationConfig,
+};
 pub use crypto::{RsaKeypair, generate_rsa_keypair};
 pub use error::{AppError, AppResult};
+pub use hashtag::normalize_hashtag;
 pub use http_signature::{
     HttpSignature, build_signature_string, calculate_digest, sign_request, verify_signature,
 };
 pub use id::IdGenerator;
 pub use metrics::{Metrics, MetricsSnapshot, Timer, get_metrics};
 pub use storage::{
-    LocalStorage, StorageBackend, StorageConfig, UploadedFile, generate_storage_key,
+    LocalStorage, StorageBackend, StorageConfig, UploadedFile, generate_storage_key, s3_object_url,
 };
 pub use url_preview::{UrlPreview, UrlPreviewConfig, fetch_preview};
 pub use url_preview_cache::{UrlPreviewCache, UrlPreviewCacheError};
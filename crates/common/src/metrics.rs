@@ -48,6 +48,8 @@ pub struct Metrics {
     pub db_query_time_us_total: AtomicU64,
     /// Database query count for average calculation
     pub db_query_count: AtomicU64,
+    /// Database queries exceeding the slow-query threshold
+    pub db_slow_queries_total: AtomicU64,
 
     // === Federation Metrics ===
     /// Activities received from remote instances
@@ -117,6 +119,7 @@ impl Metrics {
             db_errors_total: AtomicU64::new(0),
             db_query_time_us_total: AtomicU64::new(0),
             db_query_count: AtomicU64::new(0),
+            db_slow_queries_total: AtomicU64::new(0),
 
             federation_activities_received: AtomicU64::new(0),
             federation_activities_delivered: AtomicU64::new(0),
@@ -184,6 +187,11 @@ impl Metrics {
         }
     }
 
+    /// Record a query that exceeded the slow-query threshold.
+    pub fn record_slow_query(&self) {
+        self.db_slow_queries_total.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Record a federation activity received.
     pub fn record_activity_received(&self) {
         self.federation_activities_received
@@ -242,6 +250,7 @@ impl Metrics {
             db_queries_total: self.db_queries_total.load(Ordering::Relaxed),
             db_errors_total: self.db_errors_total.load(Ordering::Relaxed),
             db_query_avg_time_us: self.average_db_query_time_us(),
+            db_slow_queries_total: self.db_slow_queries_total.load(Ordering::Relaxed),
 
             federation_activities_received: self
                 .federation_activities_received
@@ -366,6 +375,13 @@ impl Metrics {
             snapshot.db_errors_total
         ));
 
+        output.push_str("# HELP misskey_db_slow_queries_total Slow database queries\n");
+        output.push_str("# TYPE misskey_db_slow_queries_total counter\n");
+        output.push_str(&format!(
+            "misskey_db_slow_queries_total {}\n",
+            snapshot.db_slow_queries_total
+        ));
+
         // Federation metrics
         output.push_str(
             "# HELP misskey_federation_activities_received Activities received from remote\n",
@@ -475,6 +491,8 @@ pub struct MetricsSnapshot {
     pub db_errors_total: u64,
     /// Average database query time in microseconds.
     pub db_query_avg_time_us: u64,
+    /// Total queries exceeding the slow-query threshold.
+    pub db_slow_queries_total: u64,
 
     /// Activities received from remote instances.
     pub federation_activities_received: u64,
@@ -589,6 +607,20 @@ mod tests {
         assert_eq!(metrics.db_errors_total.load(Ordering::Relaxed), 1);
     }
 
+    #[test]
+    fn test_record_slow_query() {
+        let metrics = Metrics::new();
+
+        metrics.record_slow_query();
+
+        assert_eq!(metrics.snapshot().db_slow_queries_total, 1);
+        assert!(
+            metrics
+                .to_prometheus()
+                .contains("misskey_db_slow_queries_total 1")
+        );
+    }
+
     #[test]
     fn test_cache_hit_rate() {
         let metrics = Metrics::new();
@@ -0,0 +1,70 @@
+//! Reaction syntax shared by the API and federation layers.
+
+/// Reaction used when none is specified (bare Like, favourite, one-button like).
+pub const DEFAULT_REACTION: &str = "👍";
+
+/// Maximum number of characters in a Unicode emoji reaction.
+///
+/// Long enough for ZWJ sequences such as family emojis.
+const MAX_UNICODE_REACTION_CHARS: usize = 16;
+
+/// Check whether a string is a legal reaction.
+///
+/// Accepts custom emoji shortcodes (`:name:` or `:name@host:`) and Unicode
+/// emoji sequences. Plain ASCII text and whitespace are rejected.
+#[must_use]
+pub fn is_valid_reaction(reaction: &str) -> bool {
+    if let Some(inner) = reaction.strip_prefix(':').and_then(|r| r.strip_suffix(':')) {
+        let (name, host) = inner
+            .split_once('@')
+            .map_or((inner, None), |(n, h)| (n, Some(h)));
+        let name_ok = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-'));
+        let host_ok = host.is_none_or(|h| {
+            !h.is_empty()
+                && h.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':'))
+        });
+        return name_ok && host_ok;
+    }
+
+    // Keycap sequences (e.g. "1️⃣", "#️⃣") are the only emojis containing ASCII
+    !reaction.is_empty()
+        && reaction.chars().count() <= MAX_UNICODE_REACTION_CHARS
+        && !reaction.is_ascii()
+        && reaction
+            .chars()
+            .all(|c| !c.is_whitespace() && (!c.is_ascii() || matches!(c, '0'..='9' | '#' | '*')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unicode_emojis_are_valid() {
+        assert!(is_valid_reaction(DEFAULT_REACTION));
+        assert!(is_valid_reaction("❤️"));
+        assert!(is_valid_reaction("👨‍👩‍👧‍👦"));
+        assert!(is_valid_reaction("1️⃣"));
+    }
+
+    #[test]
+    fn test_custom_emojis_are_valid() {
+        assert!(is_valid_reaction(":blobcat:"));
+        assert!(is_valid_reaction(":blob_cat@misskey.example:"));
+    }
+
+    #[test]
+    fn test_invalid_reactions() {
+        assert!(!is_valid_reaction(""));
+        assert!(!is_valid_reaction("like"));
+        assert!(!is_valid_reaction("::"));
+        assert!(!is_valid_reaction(":has space:"));
+        assert!(!is_valid_reaction(":cat@:"));
+        assert!(!is_valid_reaction("👍 👍"));
+        assert!(!is_valid_reaction(&"👍".repeat(20)));
+    }
+}
@@ -4,17 +4,23 @@
 
 use std::path::PathBuf;
 
+use serde::Deserialize;
+
 use crate::{AppError, AppResult};
 
 /// Storage configuration.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "backend", rename_all = "lowercase")]
 pub enum StorageConfig {
     /// Local filesystem storage.
     Local {
         /// Base path for stored files.
         base_path: PathBuf,
-        /// Base URL for serving files.
+        /// Base URL for serving files, relative to the server URL.
         base_url: String,
+        /// Public base URL (e.g. a CDN) used in file URLs instead of the server URL.
+        #[serde(default)]
+        public_base_url: Option<String>,
     },
     /// S3-compatible object storage.
     S3 {
@@ -28,22 +34,97 @@ pub enum StorageConfig {
         access_key_id: String,
         /// Secret access key.
         secret_access_key: String,
-        /// Public URL prefix for serving files.
-        public_url: Option<String>,
+        /// Public base URL (e.g. a CDN) used in file URLs instead of the bucket URL.
+        #[serde(default)]
+        public_base_url: Option<String>,
         /// Path prefix within the bucket.
+        #[serde(default)]
         prefix: Option<String>,
+        /// Address the bucket as `{endpoint}/{bucket}` rather than `{bucket}.{endpoint host}`.
+        #[serde(default = "default_path_style")]
+        path_style: bool,
     },
 }
 
+const fn default_path_style() -> bool {
+    true
+}
+
 impl Default for StorageConfig {
     fn default() -> Self {
         Self::Local {
             base_path: PathBuf::from("./files"),
             base_url: "/files".to_string(),
+            public_base_url: None,
         }
     }
 }
 
+impl StorageConfig {
+    /// Get the public URL clients should use to fetch `key`.
+    ///
+    /// A configured `public_base_url` takes precedence; otherwise local files
+    /// are served from `server_url` and S3 objects from the bucket itself.
+    #[must_use]
+    pub fn url_for(&self, server_url: &str, key: &str) -> String {
+        match self {
+            Self::Local {
+                base_url,
+                public_base_url,
+                ..
+            } => match public_base_url {
+                Some(base) => join_url(base, key),
+                None if base_url.starts_with('/') => join_url(
+                    &format!("{}{base_url}", server_url.trim_end_matches('/')),
+                    key,
+                ),
+                None => join_url(base_url, key),
+            },
+            Self::S3 {
+                endpoint,
+                bucket,
+                public_base_url,
+                prefix,
+                path_style,
+                ..
+            } => {
+                let key = prefixed_key(prefix.as_deref(), key);
+                match public_base_url {
+                    Some(base) => join_url(base, &key),
+                    None => s3_object_url(endpoint, bucket, &key, *path_style),
+                }
+            }
+        }
+    }
+}
+
+fn join_url(base: &str, key: &str) -> String {
+    format!("{}/{}", base.trim_end_matches('/'), key)
+}
+
+fn prefixed_key(prefix: Option<&str>, key: &str) -> String {
+    match prefix {
+        Some(prefix) => format!("{}/{}", prefix.trim_end_matches('/'), key),
+        None => key.to_string(),
+    }
+}
+
+/// Build the direct URL of an S3 object.
+///
+/// Path-style puts the bucket in the path (`https://s3.example.com/bucket/key`),
+/// virtual-host style puts it in the hostname (`https://bucket.s3.example.com/key`).
+#[must_use]
+pub fn s3_object_url(endpoint: &str, bucket: &str, key: &str, path_style: bool) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    if path_style {
+        return format!("{endpoint}/{bucket}/{key}");
+    }
+    match endpoint.split_once("://") {
+        Some((scheme, host)) => format!("{scheme}://{bucket}.{host}/{key}"),
+        None => format!("https://{bucket}.{endpoint}/{key}"),
+    }
+}
+
 /// Uploaded file metadata.
 #[derive(Debug, Clone)]
 pub struct UploadedFile {
@@ -145,22 +226,26 @@ impl StorageBackend for LocalStorage {
 #[cfg(feature = "s3")]
 pub struct S3Storage {
     client: aws_sdk_s3::Client,
+    endpoint: String,
     bucket: String,
-    public_url: Option<String>,
+    public_base_url: Option<String>,
     prefix: Option<String>,
+    path_style: bool,
 }
 
 #[cfg(feature = "s3")]
 impl S3Storage {
     /// Create a new S3 storage backend.
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         endpoint: &str,
         bucket: String,
         region: &str,
         access_key_id: &str,
         secret_access_key: &str,
-        public_url: Option<String>,
+        public_base_url: Option<String>,
         prefix: Option<String>,
+        path_style: bool,
     ) -> AppResult<Self> {
         use aws_config::Region;
         use aws_sdk_s3::config::Credentials;
@@ -172,24 +257,23 @@ impl S3Storage {
             .endpoint_url(endpoint)
             .region(Region::new(region.to_string()))
             .credentials_provider(credentials)
-            .force_path_style(true)
+            .force_path_style(path_style)
             .build();
 
         let client = aws_sdk_s3::Client::from_conf(config);
 
         Ok(Self {
             client,
+            endpoint: endpoint.to_string(),
             bucket,
-            public_url,
+            public_base_url,
             prefix,
+            path_style,
         })
     }
 
     fn full_key(&self, key: &str) -> String {
-        match &self.prefix {
-            Some(prefix) => format!("{}/{}", prefix.trim_end_matches('/'), key),
-            None => key.to_string(),
-        }
+        prefixed_key(self.prefix.as_deref(), key)
     }
 }
 
@@ -237,9 +321,9 @@ impl StorageBackend for S3Storage {
 
     fn public_url(&self, key: &str) -> String {
         let full_key = self.full_key(key);
-        match &self.public_url {
-            Some(base) => format!("{}/{}", base.trim_end_matches('/'), full_key),
-            None => format!("https://{}.s3.amazonaws.com/{}", self.bucket, full_key),
+        match &self.public_base_url {
+            Some(base) => join_url(base, &full_key),
+            None => s3_object_url(&self.endpoint, &self.bucket, &full_key, self.path_style),
         }
     }
 
@@ -305,6 +389,47 @@ mod tests {
         assert!(key.contains('/'));
     }
 
+    #[test]
+    fn test_url_for_uses_public_base_url() {
+        let config = StorageConfig::Local {
+            base_path: PathBuf::from("./files"),
+            base_url: "/files".to_string(),
+            public_base_url: Some("https://cdn.example.com/".to_string()),
+        };
+        assert_eq!(
+            config.url_for("https://example.com", "2025/01/01/a.png"),
+            "https://cdn.example.com/2025/01/01/a.png"
+        );
+
+        let config = StorageConfig::default();
+        assert_eq!(
+            config.url_for("https://example.com", "a.png"),
+            "https://example.com/files/a.png"
+        );
+    }
+
+    #[test]
+    fn test_s3_url_styles() {
+        let config = StorageConfig::S3 {
+            endpoint: "https://s3.example.com".to_string(),
+            bucket: "media".to_string(),
+            region: "us-east-1".to_string(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            public_base_url: None,
+            prefix: Some("misskey".to_string()),
+            path_style: false,
+        };
+        assert_eq!(
+            config.url_for("https://example.com", "a.png"),
+            "https://media.s3.example.com/misskey/a.png"
+        );
+        assert_eq!(
+            s3_object_url("https://s3.example.com/", "media", "a.png", true),
+            "https://s3.example.com/media/a.png"
+        );
+    }
+
     #[test]
     fn test_generate_storage_key_no_extension() {
         let key = generate_storage_key("user123", "file");
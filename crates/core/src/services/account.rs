@@ -4,7 +4,8 @@ use chrono::{DateTime, Utc};
 use misskey_common::{AppError, AppResult, Config};
 use misskey_db::{
     entities::{
-        account_deletion, export_job, follow_request, following, import_job, user, user_profile,
+        account_deletion, export_job, follow_request, following, import_job, note, user,
+        user_profile,
     },
     repositories::{
         AccountDeletionRepository, ExportJobRepository, FollowRequestRepository,
@@ -15,7 +16,7 @@ use misskey_db::{
 use sea_orm::Set;
 use serde::{Deserialize, Serialize};
 
-use crate::DeliveryService;
+use crate::{DeliveryService, follower_inboxes};
 
 /// Account migration status.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -473,6 +474,7 @@ impl AccountService {
                 password: Set(None),
                 email: Set(None),
                 email_verified: Set(false),
+                email_undeliverable: Set(false),
                 two_factor_secret: Set(None),
                 two_factor_enabled: Set(false),
                 two_factor_pending: Set(None),
@@ -494,6 +496,10 @@ impl AccountService {
                 default_reaction: Set(None),
                 receive_dm_from_followers_only: Set(false),
                 secure_fetch_only: Set(false),
+                disallow_quotes: Set(false),
+                hide_online_status: Set(false),
+                default_note_visibility: Set(note::Visibility::Public),
+                theme: Set(None),
                 created_at: Set(Utc::now().into()),
                 updated_at: Set(None),
             };
@@ -516,27 +522,8 @@ impl AccountService {
             "cc": [followers_url]
         });
 
-        // Get follower inboxes to deliver to
-        let followers = self.following_repo.get_followers(user_id, 10000, 0).await?;
-        let mut inboxes = Vec::new();
-
-        for follow in followers {
-            if let Ok(follower) = self.user_repo.get_by_id(&follow.follower_id).await {
-                // Only deliver to remote users
-                if follower.host.is_some() {
-                    // Prefer shared inbox if available
-                    if let Some(ref shared_inbox) = follower.shared_inbox {
-                        if !inboxes.contains(shared_inbox) {
-                            inboxes.push(shared_inbox.clone());
-                        }
-                    } else if let Some(ref inbox) = follower.inbox
-                        && !inboxes.contains(inbox)
-                    {
-                        inboxes.push(inbox.clone());
-                    }
-                }
-            }
-        }
+        // Deliver to the instances of remote followers only
+        let inboxes = follower_inboxes(&self.following_repo, &self.user_repo, user_id).await?;
 
         tracing::info!(
             user_id = user_id,
@@ -867,27 +854,8 @@ impl AccountService {
                 "to": ["https://www.w3.org/ns/activitystreams#Public"]
             });
 
-            // Get follower inboxes to deliver to
-            let followers = self.following_repo.get_followers(user_id, 10000, 0).await?;
-            let mut inboxes = Vec::new();
-
-            for follow in followers {
-                if let Ok(follower) = self.user_repo.find_by_id(&follow.follower_id).await
-                    && let Some(follower) = follower
-                    && follower.host.is_some()
-                {
-                    // Prefer shared inbox if available
-                    if let Some(ref shared_inbox) = follower.shared_inbox {
-                        if !inboxes.contains(shared_inbox) {
-                            inboxes.push(shared_inbox.clone());
-                        }
-                    } else if let Some(ref inbox) = follower.inbox
-                        && !inboxes.contains(inbox)
-                    {
-                        inboxes.push(inbox.clone());
-                    }
-                }
-            }
+            // Deliver to the instances of remote followers only
+            let inboxes = follower_inboxes(&self.following_repo, &self.user_repo, user_id).await?;
 
             if !inboxes.is_empty()
                 && let Err(e) = self
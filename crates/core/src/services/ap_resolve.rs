@@ -0,0 +1,583 @@
+//! Resolution of `ActivityPub` URIs to local notes and users.
+//!
+//! Backs `ap/show`: a pasted URL is looked up locally first, and only unknown
+//! remote objects are fetched and stored through a [`RemoteObjectFetcher`].
+//! Admins can also inspect a remote object without storing it.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use misskey_common::{AppError, AppResult};
+use misskey_db::{
+    entities::{note, user},
+    repositories::{FollowingRepository, NoteRepository, UserRepository},
+};
+use misskey_federation::{FederationRateLimiter, RateLimitError};
+use serde::Serialize;
+use serde_json::Value;
+use url::Url;
+
+use crate::services::user::is_visible_to;
+
+/// Maximum resolutions per user within [`RESOLVE_WINDOW_SECS`].
+pub const MAX_RESOLVES_PER_WINDOW: u64 = 30;
+
+/// Window for counting resolutions, in seconds.
+pub const RESOLVE_WINDOW_SECS: i64 = 60 * 60;
+
+/// Rate limiter bucket for resolutions.
+const RESOLVE_RATE_LIMIT_PREFIX: &str = "ap_resolve";
+
+/// A note or user an `ActivityPub` URI resolved to.
+#[derive(Debug, Clone)]
+pub enum ResolvedObject {
+    /// The URI identifies a note.
+    Note(note::Model),
+    /// The URI identifies an actor.
+    User(user::Model),
+}
+
+/// Fetches a remote object and stores it locally.
+#[async_trait]
+pub trait RemoteObjectFetcher: Send + Sync {
+    /// Fetch the object at `uri` and store it as a note or user.
+    async fn fetch(&self, uri: &Url) -> AppResult<ResolvedObject>;
+
+    /// Fetch the raw JSON of the object at `uri` without storing it.
+    async fn fetch_raw(&self, uri: &Url) -> AppResult<Value>;
+}
+
+/// A remote object fetched for inspection.
+#[derive(Debug, Clone)]
+pub struct InspectedObject {
+    /// The JSON as served by the remote server.
+    pub raw: Value,
+    /// Fields parsed from the JSON.
+    pub summary: InspectedSummary,
+}
+
+/// Fields parsed from an inspected object.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "object")]
+pub enum InspectedSummary {
+    /// An actor (Person, Service, ...).
+    Actor(ActorSummary),
+    /// A note-like object (Note, Question, Article, ...).
+    Note(NoteSummary),
+    /// Any other object type.
+    Other { kind: Option<String> },
+}
+
+/// Parsed fields of an inspected actor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActorSummary {
+    pub id: Option<String>,
+    pub kind: String,
+    pub username: Option<String>,
+    pub host: Option<String>,
+    pub name: Option<String>,
+    pub inbox: Option<String>,
+    pub shared_inbox: Option<String>,
+    pub public_key_id: Option<String>,
+}
+
+/// Parsed fields of an inspected note.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSummary {
+    pub id: Option<String>,
+    pub kind: String,
+    pub attributed_to: Option<String>,
+    pub content: Option<String>,
+    pub summary: Option<String>,
+    pub published: Option<String>,
+    pub in_reply_to: Option<String>,
+}
+
+/// Actor types recognized by inspection.
+const ACTOR_TYPES: &[&str] = &["Person", "Service", "Application", "Group", "Organization"];
+
+/// Note-like types recognized by inspection.
+const NOTE_TYPES: &[&str] = &[
+    "Note", "Question", "Article", "Page", "Video", "Audio", "Event",
+];
+
+impl InspectedSummary {
+    /// Parse the summary of a raw `ActivityPub` object.
+    #[must_use]
+    pub fn parse(object: &Value) -> Self {
+        let text = |key: &str| object.get(key).and_then(Value::as_str).map(str::to_string);
+        // `attributedTo` and `inReplyTo` may be embedded objects
+        let link = |key: &str| {
+            object.get(key).and_then(|value| {
+                value
+                    .as_str()
+                    .or_else(|| value.get("id").and_then(Value::as_str))
+                    .map(str::to_string)
+            })
+        };
+
+        let id = text("id");
+        match text("type") {
+            Some(kind) if ACTOR_TYPES.contains(&kind.as_str()) => Self::Actor(ActorSummary {
+                host: id
+                    .as_deref()
+                    .and_then(|id| Url::parse(id).ok())
+                    .and_then(|url| url.host_str().map(str::to_string)),
+                id,
+                kind,
+                username: text("preferredUsername"),
+                name: text("name"),
+                inbox: text("inbox"),
+                shared_inbox: object
+                    .get("endpoints")
+                    .and_then(|endpoints| endpoints.get("sharedInbox"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                public_key_id: object
+                    .get("publicKey")
+                    .and_then(|key| key.get("id"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+            }),
+            Some(kind) if NOTE_TYPES.contains(&kind.as_str()) => Self::Note(NoteSummary {
+                id,
+                kind,
+                attributed_to: link("attributedTo"),
+                content: text("content"),
+                summary: text("summary"),
+                published: text("published"),
+                in_reply_to: link("inReplyTo"),
+            }),
+            kind => Self::Other { kind },
+        }
+    }
+}
+
+/// Type alias for the remote object fetcher.
+pub type RemoteObjectFetcherService = Arc<dyn RemoteObjectFetcher>;
+
+/// Service resolving `ActivityPub` URIs for the `ap/show` endpoint.
+#[derive(Clone)]
+pub struct ApResolveService {
+    note_repo: NoteRepository,
+    user_repo: UserRepository,
+    following_repo: FollowingRepository,
+    server_url: String,
+    fetcher: Option<RemoteObjectFetcherService>,
+    rate_limiter: FederationRateLimiter,
+}
+
+impl ApResolveService {
+    /// Create a new resolve service.
+    ///
+    /// Resolutions are rate limited per process until [`Self::set_rate_limiter`]
+    /// provides a shared limiter.
+    #[must_use]
+    pub fn new(
+        note_repo: NoteRepository,
+        user_repo: UserRepository,
+        following_repo: FollowingRepository,
+        server_url: String,
+    ) -> Self {
+        Self {
+            note_repo,
+            user_repo,
+            following_repo,
+            server_url,
+            fetcher: None,
+            rate_limiter: FederationRateLimiter::in_memory(
+                RESOLVE_WINDOW_SECS,
+                MAX_RESOLVES_PER_WINDOW,
+            )
+            .with_prefix(RESOLVE_RATE_LIMIT_PREFIX),
+        }
+    }
+
+    /// Set the fetcher used for objects not yet known locally.
+    pub fn set_fetcher(&mut self, fetcher: RemoteObjectFetcherService) {
+        self.fetcher = Some(fetcher);
+    }
+
+    /// Count resolutions with `rate_limiter`, e.g. a Redis-backed one shared
+    /// by all processes.
+    pub fn set_rate_limiter(&mut self, rate_limiter: FederationRateLimiter) {
+        self.rate_limiter = rate_limiter.with_prefix(RESOLVE_RATE_LIMIT_PREFIX);
+    }
+
+    /// Resolve a URI on behalf of `user_id`.
+    ///
+    /// Local URLs and already known remote objects are answered from the
+    /// database; anything else is fetched from the remote server. Notes the
+    /// user may not see are reported as not found.
+    pub async fn resolve(&self, user_id: &str, uri: &str) -> AppResult<ResolvedObject> {
+        self.check_rate_limit(user_id).await?;
+
+        let url = Url::parse(uri.trim())
+            .ok()
+            .filter(|u| matches!(u.scheme(), "https" | "http"))
+            .ok_or_else(|| AppError::BadRequest("Invalid URI".to_string()))?;
+
+        let resolved = self.resolve_url(&url).await?;
+        if let ResolvedObject::Note(note) = &resolved
+            && !self.can_see(user_id, note).await?
+        {
+            return Err(AppError::NotFound(format!("No object at {url}")));
+        }
+        Ok(resolved)
+    }
+
+    /// Look up or fetch the object at `url`.
+    async fn resolve_url(&self, url: &Url) -> AppResult<ResolvedObject> {
+        if let Some(resolved) = self.resolve_local(url).await? {
+            return Ok(resolved);
+        }
+
+        if let Some(note) = self.note_repo.find_by_uri(url.as_str()).await? {
+            return Ok(ResolvedObject::Note(note));
+        }
+        if let Some(user) = self.user_repo.find_by_uri(url.as_str()).await? {
+            return Ok(ResolvedObject::User(user));
+        }
+
+        let fetcher = self
+            .fetcher
+            .as_ref()
+            .ok_or_else(|| AppError::Federation("Federation is disabled".to_string()))?;
+        fetcher.fetch(url).await
+    }
+
+    /// Whether `user_id` may see `note`.
+    async fn can_see(&self, user_id: &str, note: &note::Model) -> AppResult<bool> {
+        let follows = note.visibility == note::Visibility::Followers
+            && note.user_id != user_id
+            && self
+                .following_repo
+                .is_following(user_id, &note.user_id)
+                .await?;
+        Ok(is_visible_to(note, Some(user_id), follows))
+    }
+
+    /// Fetch a remote object on behalf of admin `user_id` for inspection.
+    ///
+    /// Always asks the remote server, even for known objects, and stores
+    /// nothing; counts against the same limit as [`Self::resolve`].
+    pub async fn inspect(&self, user_id: &str, uri: &str) -> AppResult<InspectedObject> {
+        self.check_rate_limit(user_id).await?;
+
+        let url = Url::parse(uri.trim())
+            .ok()
+            .filter(|u| matches!(u.scheme(), "https" | "http"))
+            .ok_or_else(|| AppError::BadRequest("Invalid URI".to_string()))?;
+
+        let fetcher = self
+            .fetcher
+            .as_ref()
+            .ok_or_else(|| AppError::Federation("Federation is disabled".to_string()))?;
+        let raw = fetcher.fetch_raw(&url).await?;
+        let summary = InspectedSummary::parse(&raw);
+
+        Ok(InspectedObject { raw, summary })
+    }
+
+    /// Resolve `/notes/{id}`, `/users/{id or username}` and `/@{username}`
+    /// URLs on this server.
+    async fn resolve_local(&self, url: &Url) -> AppResult<Option<ResolvedObject>> {
+        let Ok(server_url) = Url::parse(&self.server_url) else {
+            return Ok(None);
+        };
+        if url.origin() != server_url.origin() {
+            return Ok(None);
+        }
+
+        let not_found = || AppError::NotFound(format!("No local object at {url}"));
+        let segments: Vec<&str> = url.path_segments().into_iter().flatten().collect();
+        match segments.as_slice() {
+            ["notes", id] => Ok(Some(ResolvedObject::Note(
+                self.note_repo.find_by_id(id).await?.ok_or_else(not_found)?,
+            ))),
+            ["users", id_or_username] => {
+                let user = match self.user_repo.find_by_id(id_or_username).await? {
+                    Some(user) => user,
+                    None => self
+                        .user_repo
+                        .find_by_username_and_host(id_or_username, None)
+                        .await?
+                        .ok_or_else(not_found)?,
+                };
+                Ok(Some(ResolvedObject::User(user)))
+            }
+            [handle] if handle.starts_with('@') => {
+                let user = self
+                    .user_repo
+                    .find_by_username_and_host(&handle[1..], None)
+                    .await?
+                    .ok_or_else(not_found)?;
+                Ok(Some(ResolvedObject::User(user)))
+            }
+            _ => Err(not_found()),
+        }
+    }
+
+    /// Count a resolution against the user's window.
+    async fn check_rate_limit(&self, user_id: &str) -> AppResult<()> {
+        match self.rate_limiter.check(user_id).await {
+            Ok(_) => Ok(()),
+            Err(RateLimitError::Exceeded { .. }) => Err(AppError::RateLimited),
+            Err(e) => {
+                tracing::warn!(error = %e, "Resolve rate limit check failed, allowing request");
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use misskey_db::entities::{following, note::Visibility};
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    fn remote_note() -> note::Model {
+        note::Model {
+            id: "note1".to_string(),
+            user_id: "remote1".to_string(),
+            user_host: Some("remote.example".to_string()),
+            text: Some("Hello from afar".to_string()),
+            cw: None,
+            visibility: Visibility::Public,
+            reply_id: None,
+            renote_id: None,
+            thread_id: None,
+            mentions: json!([]),
+            visible_user_ids: json!([]),
+            file_ids: json!([]),
+            tags: json!([]),
+            reactions: json!({}),
+            replies_count: 0,
+            renote_count: 0,
+            reaction_count: 0,
+            is_local: false,
+            uri: Some("https://remote.example/notes/abc".to_string()),
+            url: None,
+            channel_id: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    struct MockFetcher {
+        fetched: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl RemoteObjectFetcher for MockFetcher {
+        async fn fetch(&self, uri: &Url) -> AppResult<ResolvedObject> {
+            self.fetched.lock().unwrap().push(uri.to_string());
+            Ok(ResolvedObject::Note(remote_note()))
+        }
+
+        async fn fetch_raw(&self, uri: &Url) -> AppResult<Value> {
+            self.fetched.lock().unwrap().push(uri.to_string());
+            Ok(json!({
+                "@context": "https://www.w3.org/ns/activitystreams",
+                "type": "Person",
+                "id": uri.as_str(),
+                "preferredUsername": "bob",
+                "name": "Bob",
+                "inbox": "https://remote.example/users/bob/inbox",
+                "endpoints": { "sharedInbox": "https://remote.example/inbox" },
+                "publicKey": {
+                    "id": "https://remote.example/users/bob#main-key",
+                    "owner": uri.as_str(),
+                    "publicKeyPem": "-----BEGIN PUBLIC KEY-----"
+                }
+            }))
+        }
+    }
+
+    fn create_service(
+        note_db: MockDatabase,
+        user_db: MockDatabase,
+        fetcher: Option<Arc<MockFetcher>>,
+    ) -> ApResolveService {
+        create_service_with_following(
+            note_db,
+            user_db,
+            MockDatabase::new(DatabaseBackend::Postgres),
+            fetcher,
+        )
+    }
+
+    fn create_service_with_following(
+        note_db: MockDatabase,
+        user_db: MockDatabase,
+        following_db: MockDatabase,
+        fetcher: Option<Arc<MockFetcher>>,
+    ) -> ApResolveService {
+        let mut service = ApResolveService::new(
+            NoteRepository::new(Arc::new(note_db.into_connection())),
+            UserRepository::new(Arc::new(user_db.into_connection())),
+            FollowingRepository::new(Arc::new(following_db.into_connection())),
+            "https://local.example".to_string(),
+        );
+        if let Some(fetcher) = fetcher {
+            service.set_fetcher(fetcher);
+        }
+        service
+    }
+
+    #[tokio::test]
+    async fn test_resolve_unknown_remote_note_fetches_it() {
+        let note_db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([Vec::<note::Model>::new()]);
+        let user_db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([Vec::<user::Model>::new()]);
+        let fetcher = Arc::new(MockFetcher {
+            fetched: Mutex::new(Vec::new()),
+        });
+        let service = create_service(note_db, user_db, Some(Arc::clone(&fetcher)));
+
+        let resolved = service
+            .resolve("user1", "https://remote.example/notes/abc")
+            .await
+            .unwrap();
+
+        assert!(matches!(resolved, ResolvedObject::Note(ref note) if note.id == "note1"));
+        assert_eq!(
+            *fetcher.fetched.lock().unwrap(),
+            ["https://remote.example/notes/abc"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_hides_notes_the_user_cannot_see() {
+        let mut followers_only = remote_note();
+        followers_only.id = "local1".to_string();
+        followers_only.user_id = "author1".to_string();
+        followers_only.user_host = None;
+        followers_only.visibility = Visibility::Followers;
+        followers_only.uri = None;
+
+        let note_db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[followers_only.clone()], [followers_only]]);
+        let following_db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([Vec::<following::Model>::new()]);
+        let service = create_service_with_following(
+            note_db,
+            MockDatabase::new(DatabaseBackend::Postgres),
+            following_db,
+            None,
+        );
+
+        // A stranger gets the same answer as for a missing note
+        let result = service
+            .resolve("stranger", "https://local.example/notes/local1")
+            .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        // The author still sees their own note
+        let resolved = service
+            .resolve("author1", "https://local.example/notes/local1")
+            .await
+            .unwrap();
+        assert!(matches!(resolved, ResolvedObject::Note(ref note) if note.id == "local1"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_is_rate_limited() {
+        let note_db = MockDatabase::new(DatabaseBackend::Postgres);
+        let user_db = MockDatabase::new(DatabaseBackend::Postgres);
+        let service = create_service(note_db, user_db, None);
+
+        // Invalid URIs still count, so no database access is needed here
+        for _ in 0..MAX_RESOLVES_PER_WINDOW {
+            let result = service.resolve("user1", "not a url").await;
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+        }
+        let result = service.resolve("user1", "not a url").await;
+        assert!(matches!(result, Err(AppError::RateLimited)));
+
+        let result = service.resolve("user2", "not a url").await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_inspect_actor_returns_parsed_fields_without_storing() {
+        // Neither repository is queried: inspection never touches the database
+        let note_db = MockDatabase::new(DatabaseBackend::Postgres);
+        let user_db = MockDatabase::new(DatabaseBackend::Postgres);
+        let fetcher = Arc::new(MockFetcher {
+            fetched: Mutex::new(Vec::new()),
+        });
+        let service = create_service(note_db, user_db, Some(Arc::clone(&fetcher)));
+
+        let inspected = service
+            .inspect("admin1", "https://remote.example/users/bob")
+            .await
+            .unwrap();
+
+        assert_eq!(inspected.raw["preferredUsername"], "bob");
+        let InspectedSummary::Actor(actor) = inspected.summary else {
+            panic!("expected an actor summary");
+        };
+        assert_eq!(actor.username.as_deref(), Some("bob"));
+        assert_eq!(actor.host.as_deref(), Some("remote.example"));
+        assert_eq!(
+            actor.shared_inbox.as_deref(),
+            Some("https://remote.example/inbox")
+        );
+        assert_eq!(
+            *fetcher.fetched.lock().unwrap(),
+            ["https://remote.example/users/bob"]
+        );
+    }
+
+    #[test]
+    fn test_parse_note_summary() {
+        let summary = InspectedSummary::parse(&json!({
+            "type": "Note",
+            "id": "https://remote.example/notes/abc",
+            "attributedTo": { "id": "https://remote.example/users/bob", "type": "Person" },
+            "content": "<p>Hello</p>",
+            "published": "2025-01-01T00:00:00Z"
+        }));
+
+        let InspectedSummary::Note(note) = summary else {
+            panic!("expected a note summary");
+        };
+        assert_eq!(
+            note.attributed_to.as_deref(),
+            Some("https://remote.example/users/bob")
+        );
+        assert_eq!(note.content.as_deref(), Some("<p>Hello</p>"));
+        assert_eq!(
+            InspectedSummary::parse(&json!({ "type": "Tombstone" })),
+            InspectedSummary::Other {
+                kind: Some("Tombstone".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn test_summary_serializes_tagged_camel_case() {
+        let summary = InspectedSummary::parse(&json!({
+            "type": "Note",
+            "id": "https://remote.example/notes/abc",
+            "inReplyTo": "https://remote.example/notes/parent"
+        }));
+
+        let value = serde_json::to_value(&summary).unwrap();
+        assert_eq!(value["type"], "Note");
+        assert_eq!(value["object"]["kind"], "Note");
+        assert_eq!(
+            value["object"]["inReplyTo"],
+            "https://remote.example/notes/parent"
+        );
+    }
+}
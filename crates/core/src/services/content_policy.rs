@@ -0,0 +1,246 @@
+//! Content policy hook for note creation.
+//!
+//! Operators can plug spam or profanity heuristics into [`NoteService::create`]
+//! by implementing [`ContentPolicy`]. The bundled [`WordFilterContentPolicy`]
+//! combines word-filter phrase matching with a posting-burst signal.
+//!
+//! [`NoteService::create`]: crate::services::note::NoteService::create
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use misskey_db::{
+    entities::{
+        note::Visibility,
+        word_filter::{self, FilterAction, FilterContext},
+    },
+    repositories::NoteRepository,
+};
+use tracing::warn;
+
+use crate::services::word_filter::WordFilterService;
+
+/// The note being created, as seen by a content policy.
+#[derive(Debug, Clone, Copy)]
+pub struct CreateNoteContext<'a> {
+    pub user_id: &'a str,
+    pub text: Option<&'a str>,
+    pub cw: Option<&'a str>,
+    pub visibility: &'a Visibility,
+    pub reply_id: Option<&'a str>,
+    pub renote_id: Option<&'a str>,
+    pub file_count: usize,
+}
+
+impl CreateNoteContext<'_> {
+    /// The text a policy should inspect (content warning and body).
+    #[must_use]
+    pub fn content(&self) -> Option<String> {
+        match (self.cw, self.text) {
+            (Some(cw), Some(text)) => Some(format!("{cw} {text}")),
+            (Some(cw), None) => Some(cw.to_string()),
+            (None, Some(text)) => Some(text.to_string()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Outcome of evaluating a note against a content policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyDecision {
+    /// Create the note.
+    Allow,
+    /// Create the note, logging a warning for operators.
+    Flag,
+    /// Refuse to create the note.
+    Reject(String),
+}
+
+/// Extension point evaluated before a note is created.
+#[async_trait]
+pub trait ContentPolicy: Send + Sync {
+    /// Decide whether the note described by `ctx` may be created.
+    async fn evaluate(&self, ctx: &CreateNoteContext<'_>) -> PolicyDecision;
+}
+
+/// Type alias for a shared content policy.
+pub type ContentPolicyService = Arc<dyn ContentPolicy>;
+
+/// Default content policy backed by word filters and posting rate.
+///
+/// Blocked phrases reject the note, flagged phrases and posting bursts flag
+/// it. Phrases use the same matching as user word filters (case-insensitive,
+/// whole word).
+#[derive(Clone)]
+pub struct WordFilterContentPolicy {
+    word_filter_service: WordFilterService,
+    filters: Vec<word_filter::Model>,
+    burst_limit: Option<BurstLimit>,
+}
+
+#[derive(Clone)]
+struct BurstLimit {
+    note_repo: NoteRepository,
+    max_notes: u64,
+    window: Duration,
+}
+
+impl WordFilterContentPolicy {
+    /// Create a policy with no phrases and no burst limit.
+    #[must_use]
+    pub const fn new(word_filter_service: WordFilterService) -> Self {
+        Self {
+            word_filter_service,
+            filters: Vec::new(),
+            burst_limit: None,
+        }
+    }
+
+    /// Reject notes containing any of `phrases`.
+    #[must_use]
+    pub fn with_blocked_phrases(mut self, phrases: impl IntoIterator<Item = String>) -> Self {
+        self.add_phrases(phrases, FilterAction::Hide);
+        self
+    }
+
+    /// Flag notes containing any of `phrases`.
+    #[must_use]
+    pub fn with_flagged_phrases(mut self, phrases: impl IntoIterator<Item = String>) -> Self {
+        self.add_phrases(phrases, FilterAction::Warn);
+        self
+    }
+
+    /// Flag notes from users who already posted `max_notes` in the last `window_secs`.
+    #[must_use]
+    pub fn with_burst_limit(
+        mut self,
+        note_repo: NoteRepository,
+        max_notes: u64,
+        window_secs: i64,
+    ) -> Self {
+        self.burst_limit = Some(BurstLimit {
+            note_repo,
+            max_notes,
+            window: Duration::seconds(window_secs),
+        });
+        self
+    }
+
+    fn add_phrases(&mut self, phrases: impl IntoIterator<Item = String>, action: FilterAction) {
+        let now = Utc::now();
+        let offset = self.filters.len();
+        self.filters.extend(
+            phrases
+                .into_iter()
+                .filter(|p| !p.trim().is_empty())
+                .enumerate()
+                .map(|(i, phrase)| word_filter::Model {
+                    id: format!("content-policy-{}", offset + i),
+                    user_id: String::new(),
+                    phrase,
+                    is_regex: false,
+                    case_sensitive: false,
+                    whole_word: true,
+                    action,
+                    context: FilterContext::All,
+                    expires_at: None,
+                    match_count: 0,
+                    created_at: now.into(),
+                    updated_at: None,
+                    group_id: None,
+                }),
+        );
+    }
+
+    async fn is_bursting(&self, user_id: &str) -> bool {
+        let Some(ref limit) = self.burst_limit else {
+            return false;
+        };
+
+        match limit
+            .note_repo
+            .count_by_user_since(user_id, Utc::now() - limit.window)
+            .await
+        {
+            Ok(count) => count >= limit.max_notes,
+            Err(e) => {
+                warn!(error = %e, user_id = %user_id, "Failed to count recent notes for content policy");
+                false
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ContentPolicy for WordFilterContentPolicy {
+    async fn evaluate(&self, ctx: &CreateNoteContext<'_>) -> PolicyDecision {
+        if let Some(content) = ctx.content() {
+            match self.word_filter_service.apply_filters_with_cache(
+                &self.filters,
+                &content,
+                FilterContext::All,
+            ) {
+                Ok(result) if result.action == Some(FilterAction::Hide) => {
+                    return PolicyDecision::Reject(
+                        "Note contains content not allowed on this instance".to_string(),
+                    );
+                }
+                Ok(result) if result.matched => return PolicyDecision::Flag,
+                Ok(_) => {}
+                Err(e) => warn!(error = %e, "Failed to apply content policy filters"),
+            }
+        }
+
+        if self.is_bursting(ctx.user_id).await {
+            return PolicyDecision::Flag;
+        }
+
+        PolicyDecision::Allow
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use misskey_db::repositories::WordFilterRepository;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    fn policy() -> WordFilterContentPolicy {
+        let db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        WordFilterContentPolicy::new(WordFilterService::new(WordFilterRepository::new(db)))
+            .with_blocked_phrases(["casino".to_string()])
+            .with_flagged_phrases(["giveaway".to_string()])
+    }
+
+    fn context(text: &str) -> CreateNoteContext<'_> {
+        CreateNoteContext {
+            user_id: "user1",
+            text: Some(text),
+            cw: None,
+            visibility: &Visibility::Public,
+            reply_id: None,
+            renote_id: None,
+            file_count: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blocked_phrase_rejects() {
+        let decision = policy().evaluate(&context("Visit my Casino now")).await;
+        assert!(matches!(decision, PolicyDecision::Reject(_)));
+    }
+
+    #[tokio::test]
+    async fn test_flagged_phrase_flags() {
+        let decision = policy().evaluate(&context("Huge giveaway today")).await;
+        assert_eq!(decision, PolicyDecision::Flag);
+    }
+
+    #[tokio::test]
+    async fn test_clean_note_allowed() {
+        let decision = policy().evaluate(&context("Good morning")).await;
+        assert_eq!(decision, PolicyDecision::Allow);
+    }
+}
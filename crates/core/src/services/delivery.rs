@@ -5,7 +5,9 @@
 
 use async_trait::async_trait;
 use misskey_common::AppResult;
+use misskey_db::repositories::{FollowingRepository, UserRepository};
 use serde_json::Value;
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
 /// Trait for `ActivityPub` delivery.
@@ -146,6 +148,19 @@ pub trait ActivityDelivery: Send + Sync {
         inboxes: Vec<String>,
     ) -> AppResult<()>;
 
+    /// Queue an Update activity for an actor (profile update).
+    ///
+    /// # Arguments
+    /// * `user_id` - The ID of the user whose profile changed
+    /// * `activity` - The serialized Update activity
+    /// * `inboxes` - List of inbox URLs to deliver to (followers)
+    async fn queue_update_actor(
+        &self,
+        user_id: &str,
+        activity: Value,
+        inboxes: Vec<String>,
+    ) -> AppResult<()>;
+
     /// Queue a Delete activity for an actor (account deletion).
     ///
     /// # Arguments
@@ -259,6 +274,15 @@ impl ActivityDelivery for NoOpDelivery {
         Ok(())
     }
 
+    async fn queue_update_actor(
+        &self,
+        _user_id: &str,
+        _activity: Value,
+        _inboxes: Vec<String>,
+    ) -> AppResult<()> {
+        Ok(())
+    }
+
     async fn queue_delete_actor(
         &self,
         _user_id: &str,
@@ -271,3 +295,35 @@ impl ActivityDelivery for NoOpDelivery {
 
 /// Wrapper for boxed `ActivityDelivery` trait object.
 pub type DeliveryService = Arc<dyn ActivityDelivery>;
+
+/// Collect the inboxes of a user's remote followers.
+///
+/// Actor-level activities (`Update`/`Delete`/`Move` of the actor) go only to
+/// instances that follow the user. Shared inboxes are preferred so that each
+/// instance receives the activity once.
+pub async fn follower_inboxes(
+    following_repo: &FollowingRepository,
+    user_repo: &UserRepository,
+    user_id: &str,
+) -> AppResult<Vec<String>> {
+    let follower_ids: Vec<String> = following_repo
+        .get_followers(user_id, 10000, 0)
+        .await?
+        .into_iter()
+        .filter(|f| f.follower_host.is_some())
+        .map(|f| f.follower_id)
+        .collect();
+    if follower_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let inboxes: BTreeSet<String> = user_repo
+        .find_by_ids(&follower_ids)
+        .await?
+        .into_iter()
+        .filter(|u| u.host.is_some())
+        .filter_map(|u| u.shared_inbox.or(u.inbox))
+        .collect();
+
+    Ok(inboxes.into_iter().collect())
+}
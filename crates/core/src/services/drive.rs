@@ -1,10 +1,10 @@
 //! Drive service for file management.
 
 use crate::services::storage::StorageService;
-use misskey_common::{AppError, AppResult, IdGenerator};
+use misskey_common::{AppError, AppResult, IdGenerator, StorageConfig};
 use misskey_db::{
     entities::{drive_file, drive_folder},
-    repositories::{DriveFileRepository, DriveFolderRepository},
+    repositories::{DriveFileRepository, DriveFolderRepository, FolderUsage},
 };
 use sea_orm::Set;
 
@@ -20,6 +20,7 @@ pub struct DriveService {
     file_repo: DriveFileRepository,
     folder_repo: DriveFolderRepository,
     storage: Option<StorageService>,
+    storage_config: Option<StorageConfig>,
     id_gen: IdGenerator,
     base_url: String,
 }
@@ -47,6 +48,7 @@ impl DriveService {
             file_repo,
             folder_repo,
             storage: None,
+            storage_config: None,
             id_gen: IdGenerator::new(),
             base_url,
         }
@@ -64,6 +66,7 @@ impl DriveService {
             file_repo,
             folder_repo,
             storage: Some(storage),
+            storage_config: None,
             id_gen: IdGenerator::new(),
             base_url,
         }
@@ -74,6 +77,26 @@ impl DriveService {
         self.storage = Some(storage);
     }
 
+    /// Set the storage configuration used to build public file URLs.
+    ///
+    /// Files are still written through the storage backend; only the URL
+    /// handed out to clients honours `public_base_url`.
+    pub fn set_storage_config(&mut self, config: StorageConfig) {
+        self.storage_config = Some(config);
+    }
+
+    /// Get the public URL for a storage key.
+    fn public_url(&self, storage_key: &str) -> String {
+        if let Some(ref config) = self.storage_config {
+            config.url_for(&self.base_url, storage_key)
+        } else if let Some(ref storage) = self.storage {
+            storage.get_url(storage_key)
+        } else {
+            // Fallback URL if no storage backend is configured
+            format!("{}/files/{}", self.base_url, storage_key)
+        }
+    }
+
     /// Upload a new file.
     pub async fn upload_file(
         &self,
@@ -127,13 +150,10 @@ impl DriveService {
         let storage_key = generate_storage_key(&file_id, &input.name);
 
         // Save file to storage if backend is configured
-        let url = if let Some(ref storage) = self.storage {
+        if let Some(ref storage) = self.storage {
             storage.save(&storage_key, &input.data).await?;
-            storage.get_url(&storage_key)
-        } else {
-            // Fallback URL if no storage backend is configured
-            format!("{}/files/{}", self.base_url, storage_key)
-        };
+        }
+        let url = self.public_url(&storage_key);
 
         // Get image dimensions if applicable
         let (width, height) = if input.content_type.starts_with("image/") {
@@ -274,6 +294,23 @@ impl DriveService {
         })
     }
 
+    /// Get storage usage broken down by folder.
+    ///
+    /// Deduplicated uploads share storage, so the physical total may be lower
+    /// than the sum of the logical folder sizes.
+    pub async fn usage_by_folder(&self, user_id: &str) -> AppResult<FolderUsageBreakdown> {
+        let folders = self.file_repo.usage_by_folder(user_id).await?;
+        let physical_bytes = self.file_repo.get_physical_storage_used(user_id).await?;
+
+        Ok(FolderUsageBreakdown {
+            file_count: folders.iter().map(|f| f.file_count).sum(),
+            logical_bytes: folders.iter().map(|f| f.bytes).sum(),
+            physical_bytes,
+            limit: DEFAULT_STORAGE_LIMIT,
+            folders,
+        })
+    }
+
     /// Get unattached files (files not used in notes, pages, etc.)
     pub async fn get_unattached_files(
         &self,
@@ -356,6 +393,17 @@ pub struct StorageUsage {
     pub limit: i64,
 }
 
+/// Storage usage per folder with totals.
+pub struct FolderUsageBreakdown {
+    pub folders: Vec<FolderUsage>,
+    pub file_count: i64,
+    /// Sum of all file sizes.
+    pub logical_bytes: i64,
+    /// Storage actually occupied, counting identical content once.
+    pub physical_bytes: i64,
+    pub limit: i64,
+}
+
 /// Result of cleaning up unattached files.
 pub struct CleanupResult {
     pub deleted_count: u64,
@@ -567,6 +615,8 @@ fn get_image_dimensions(data: &[u8]) -> (Option<i32>, Option<i32>) {
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
+    use sea_orm::{DatabaseBackend, MockDatabase, Value};
+    use std::sync::Arc;
 
     #[test]
     fn test_generate_storage_key() {
@@ -593,4 +643,83 @@ mod tests {
         assert_eq!(width, Some(100));
         assert_eq!(height, Some(50));
     }
+
+    fn create_test_service() -> DriveService {
+        use sea_orm::{DatabaseBackend, MockDatabase};
+        use std::sync::Arc;
+
+        let db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        DriveService::new(
+            DriveFileRepository::new(db.clone()),
+            DriveFolderRepository::new(db),
+            "https://example.com".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_public_url_defaults_to_server_url() {
+        let service = create_test_service();
+        assert_eq!(
+            service.public_url("abc123.png"),
+            "https://example.com/files/abc123.png"
+        );
+    }
+
+    #[test]
+    fn test_public_url_uses_cdn_base() {
+        let mut service = create_test_service();
+        service.set_storage_config(StorageConfig::Local {
+            base_path: "./files".into(),
+            base_url: "/files".to_string(),
+            public_base_url: Some("https://cdn.example.net/media".to_string()),
+        });
+        assert_eq!(
+            service.public_url("abc123.png"),
+            "https://cdn.example.net/media/abc123.png"
+        );
+    }
+
+    fn folder_usage_row(
+        folder_id: Option<&str>,
+        file_count: i64,
+        bytes: i64,
+    ) -> std::collections::BTreeMap<&'static str, Value> {
+        maplit::btreemap! {
+            "folder_id" => Value::String(folder_id.map(|id| Box::new(id.to_string()))),
+            "file_count" => Value::BigInt(Some(file_count)),
+            "bytes" => Value::BigInt(Some(bytes)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_usage_by_folder_aggregates_across_folders() {
+        let file_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![
+                    folder_usage_row(Some("folder1"), 2, 300),
+                    folder_usage_row(Some("folder2"), 3, 700),
+                ]])
+                .append_query_results([[maplit::btreemap! {
+                    "total" => Value::BigInt(Some(800))
+                }]])
+                .into_connection(),
+        );
+        let folder_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let service = DriveService::new(
+            DriveFileRepository::new(file_db),
+            DriveFolderRepository::new(folder_db),
+            "https://example.com".to_string(),
+        );
+
+        let usage = service.usage_by_folder("user1").await.unwrap();
+
+        assert_eq!(usage.folders.len(), 2);
+        assert_eq!(usage.folders[0].folder_id.as_deref(), Some("folder1"));
+        assert_eq!(usage.folders[0].file_count, 2);
+        assert_eq!(usage.folders[1].bytes, 700);
+        assert_eq!(usage.file_count, 5);
+        assert_eq!(usage.logical_bytes, 1000);
+        // A duplicated upload is only stored once
+        assert_eq!(usage.physical_bytes, 800);
+    }
 }
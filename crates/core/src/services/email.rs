@@ -9,6 +9,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use misskey_common::{AppError, AppResult};
+use misskey_db::entities::user_profile;
+
+use crate::services::email_bounce::should_send_email;
 
 /// Email provider configuration.
 #[derive(Debug, Clone)]
@@ -84,6 +87,75 @@ pub struct EmailConfig {
     pub instance_url: String,
 }
 
+impl EmailConfig {
+    /// Build the service configuration from the `[email]` config section.
+    pub fn from_config(
+        config: &misskey_common::EmailConfig,
+        instance_name: &str,
+        instance_url: &str,
+    ) -> AppResult<Self> {
+        use misskey_common::EmailTransportConfig;
+
+        let transport = config.transport.clone().ok_or_else(|| {
+            AppError::Validation("email.transport is required when email is enabled".to_string())
+        })?;
+        if config.from_address.is_empty() {
+            return Err(AppError::Validation(
+                "email.from_address is required when email is enabled".to_string(),
+            ));
+        }
+
+        let provider = match transport {
+            EmailTransportConfig::Smtp {
+                host,
+                port,
+                use_tls,
+                username,
+                password,
+            } => EmailProvider::Smtp(SmtpConfig {
+                host,
+                port,
+                use_tls,
+                username,
+                password,
+            }),
+            EmailTransportConfig::Ses {
+                region,
+                access_key_id,
+                secret_access_key,
+            } => EmailProvider::Ses(SesConfig {
+                region,
+                access_key_id,
+                secret_access_key,
+            }),
+            EmailTransportConfig::SendGrid { api_key } => {
+                EmailProvider::SendGrid(SendGridConfig { api_key })
+            }
+            EmailTransportConfig::Mailgun {
+                api_key,
+                domain,
+                eu_region,
+            } => EmailProvider::Mailgun(MailgunConfig {
+                api_key,
+                domain,
+                eu_region,
+            }),
+        };
+
+        Ok(Self {
+            provider,
+            from_address: config.from_address.clone(),
+            from_name: config
+                .from_name
+                .clone()
+                .unwrap_or_else(|| instance_name.to_string()),
+            reply_to: config.reply_to.clone(),
+            instance_name: instance_name.to_string(),
+            instance_url: instance_url.to_string(),
+        })
+    }
+}
+
 /// Email notification types.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -207,7 +279,7 @@ impl EmailService {
         let config = self
             .config
             .as_ref()
-            .ok_or_else(|| AppError::BadRequest("Email service not configured".to_string()))?;
+            .ok_or_else(|| AppError::NotConfigured("Email service not configured".to_string()))?;
 
         match &config.provider {
             EmailProvider::Smtp(smtp) => self.send_smtp(smtp, config, message).await,
@@ -227,7 +299,7 @@ impl EmailService {
         let config = self
             .config
             .as_ref()
-            .ok_or_else(|| AppError::BadRequest("Email service not configured".to_string()))?;
+            .ok_or_else(|| AppError::NotConfigured("Email service not configured".to_string()))?;
 
         let (subject, text_body, html_body) =
             self.render_template(notification_type, &vars, config)?;
@@ -244,6 +316,33 @@ impl EmailService {
         self.send(message).await
     }
 
+    /// Send a notification email to the address on a user's profile.
+    ///
+    /// Returns `None` without sending when the profile has no address, or when
+    /// the address is undeliverable and the mail is not essential.
+    pub async fn send_to_profile(
+        &self,
+        profile: &user_profile::Model,
+        notification_type: EmailNotificationType,
+        vars: EmailTemplateVars,
+    ) -> AppResult<Option<EmailDeliveryResult>> {
+        let Some(email) = profile.email.as_deref() else {
+            return Ok(None);
+        };
+        if !should_send_email(profile, notification_type) {
+            tracing::debug!(
+                user_id = %profile.user_id,
+                kind = %notification_type,
+                "Not emailing undeliverable address"
+            );
+            return Ok(None);
+        }
+
+        self.send_notification(notification_type, email, vars)
+            .await
+            .map(Some)
+    }
+
     /// Render an email template.
     fn render_template(
         &self,
@@ -0,0 +1,630 @@
+//! Email bounce and complaint handling.
+//!
+//! Mail providers report hard bounces and spam complaints through webhooks.
+//! Each webhook is verified with the provider's own scheme before the
+//! reported addresses are marked undeliverable, after which only essential
+//! mail (see [`EmailNotificationType::is_essential`]) is sent to them.
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use misskey_common::{AppError, AppResult, EmailWebhookConfig};
+use misskey_db::{entities::user_profile, repositories::UserProfileRepository};
+use openssl::{hash::MessageDigest, pkey::PKey, sign::Verifier, x509::X509};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::Sha256;
+use url::Url;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+use crate::services::email::EmailNotificationType;
+
+/// Largest difference from the current time accepted in a signed webhook
+/// timestamp, so captured payloads cannot be replayed later.
+pub const MAX_WEBHOOK_AGE_SECS: i64 = 5 * 60;
+
+/// Kind of delivery failure reported by a provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BounceKind {
+    /// The address permanently rejected mail.
+    HardBounce,
+    /// The recipient reported the mail as spam.
+    Complaint,
+}
+
+/// A delivery failure for one address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BounceEvent {
+    /// Affected email address.
+    pub email: String,
+    /// What the provider reported.
+    pub kind: BounceKind,
+}
+
+/// Amazon SNS message wrapping SES notifications.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SnsMessage {
+    #[serde(rename = "Type")]
+    kind: String,
+    message_id: String,
+    topic_arn: String,
+    message: String,
+    timestamp: String,
+    #[serde(default)]
+    subject: Option<String>,
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(rename = "SubscribeURL", default)]
+    subscribe_url: Option<String>,
+    signature_version: String,
+    signature: String,
+    #[serde(rename = "SigningCertURL")]
+    signing_cert_url: String,
+}
+
+impl SnsMessage {
+    /// The string SNS signs for this message type.
+    fn string_to_sign(&self) -> String {
+        let mut fields = vec![("Message", self.message.as_str())];
+        fields.push(("MessageId", &self.message_id));
+        if self.kind == "Notification" {
+            if let Some(ref subject) = self.subject {
+                fields.push(("Subject", subject));
+            }
+        } else {
+            fields.push(("SubscribeURL", self.subscribe_url.as_deref().unwrap_or("")));
+        }
+        fields.push(("Timestamp", &self.timestamp));
+        if self.kind != "Notification" {
+            fields.push(("Token", self.token.as_deref().unwrap_or("")));
+        }
+        fields.push(("TopicArn", &self.topic_arn));
+        fields.push(("Type", &self.kind));
+
+        let mut out = String::new();
+        for (key, value) in fields {
+            out.push_str(key);
+            out.push('\n');
+            out.push_str(value);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Service receiving bounce and complaint webhooks.
+#[derive(Clone)]
+pub struct EmailBounceService {
+    profile_repo: UserProfileRepository,
+    config: EmailWebhookConfig,
+    http_client: reqwest::Client,
+}
+
+impl EmailBounceService {
+    /// Create a new bounce service.
+    #[must_use]
+    pub fn new(profile_repo: UserProfileRepository, config: EmailWebhookConfig) -> Self {
+        Self {
+            profile_repo,
+            config,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    /// Handle an Amazon SES notification delivered through SNS.
+    ///
+    /// Subscription confirmations for the configured topic are confirmed
+    /// automatically.
+    pub async fn handle_ses(&self, body: &str) -> AppResult<()> {
+        let topic_arn = self
+            .config
+            .ses_topic_arn
+            .as_deref()
+            .ok_or_else(|| AppError::Forbidden("SES webhook not configured".to_string()))?;
+
+        let message: SnsMessage = serde_json::from_str(body)
+            .map_err(|e| AppError::BadRequest(format!("Invalid SNS message: {e}")))?;
+        if message.topic_arn != topic_arn {
+            return Err(AppError::Forbidden("Unexpected SNS topic".to_string()));
+        }
+        self.verify_sns_signature(&message).await?;
+
+        match message.kind.as_str() {
+            "SubscriptionConfirmation" => {
+                let subscribe_url = message
+                    .subscribe_url
+                    .as_deref()
+                    .and_then(|u| amazonaws_url(u, "sns."))
+                    .ok_or_else(|| AppError::BadRequest("Invalid SubscribeURL".to_string()))?;
+                self.http_client
+                    .get(subscribe_url)
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        AppError::ExternalService(format!("SNS confirmation failed: {e}"))
+                    })?;
+                tracing::info!(topic_arn = %topic_arn, "Confirmed SES bounce subscription");
+                Ok(())
+            }
+            "Notification" => self.apply(&parse_ses(&message.message)?).await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle a `SendGrid` signed event webhook.
+    pub async fn handle_sendgrid(
+        &self,
+        signature: &str,
+        timestamp: &str,
+        body: &str,
+    ) -> AppResult<()> {
+        let key = self
+            .config
+            .sendgrid_verification_key
+            .as_deref()
+            .ok_or_else(|| AppError::Forbidden("SendGrid webhook not configured".to_string()))?;
+        check_webhook_timestamp(timestamp)?;
+
+        let payload = format!("{timestamp}{body}");
+        let valid = verify_signature(
+            MessageDigest::sha256(),
+            &decode_public_key(key)?,
+            payload.as_bytes(),
+            signature,
+        )?;
+        if !valid {
+            return Err(AppError::Unauthorized);
+        }
+
+        self.apply(&parse_sendgrid(body)?).await
+    }
+
+    /// Handle a Mailgun webhook.
+    pub async fn handle_mailgun(&self, body: &str) -> AppResult<()> {
+        let key = self
+            .config
+            .mailgun_signing_key
+            .as_deref()
+            .ok_or_else(|| AppError::Forbidden("Mailgun webhook not configured".to_string()))?;
+
+        let payload: Value = serde_json::from_str(body)
+            .map_err(|e| AppError::BadRequest(format!("Invalid Mailgun payload: {e}")))?;
+        let field = |name: &str| {
+            payload
+                .pointer(&format!("/signature/{name}"))
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+        };
+        check_webhook_timestamp(field("timestamp"))?;
+        if !verify_mailgun_signature(key, field("timestamp"), field("token"), field("signature")) {
+            return Err(AppError::Unauthorized);
+        }
+
+        self.apply(&parse_mailgun(&payload)).await
+    }
+
+    /// Mark every reported address undeliverable.
+    pub async fn apply(&self, events: &[BounceEvent]) -> AppResult<()> {
+        for event in events {
+            let affected = self
+                .profile_repo
+                .mark_email_undeliverable(&event.email)
+                .await?;
+            tracing::info!(
+                kind = ?event.kind,
+                profiles = affected,
+                "Marked email address undeliverable"
+            );
+        }
+        Ok(())
+    }
+
+    /// Verify an SNS message against its signing certificate.
+    async fn verify_sns_signature(&self, message: &SnsMessage) -> AppResult<()> {
+        let digest = match message.signature_version.as_str() {
+            "1" => MessageDigest::sha1(),
+            "2" => MessageDigest::sha256(),
+            other => {
+                return Err(AppError::BadRequest(format!(
+                    "Unsupported SNS signature version: {other}"
+                )));
+            }
+        };
+
+        let cert_url = amazonaws_url(&message.signing_cert_url, "sns.")
+            .ok_or_else(|| AppError::Forbidden("Untrusted SNS certificate URL".to_string()))?;
+        let pem = self
+            .http_client
+            .get(cert_url)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| AppError::ExternalService(format!("SNS certificate fetch failed: {e}")))?
+            .bytes()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("SNS certificate fetch failed: {e}")))?;
+        let key = X509::from_pem(&pem)
+            .and_then(|cert| cert.public_key())
+            .map_err(|e| AppError::ExternalService(format!("Invalid SNS certificate: {e}")))?;
+
+        if verify_signature(
+            digest,
+            &key,
+            message.string_to_sign().as_bytes(),
+            &message.signature,
+        )? {
+            Ok(())
+        } else {
+            Err(AppError::Unauthorized)
+        }
+    }
+}
+
+impl EmailNotificationType {
+    /// Whether this mail is still sent to addresses marked undeliverable.
+    ///
+    /// Account recovery and security mail is always attempted; everything
+    /// else is suppressed after a bounce or complaint.
+    #[must_use]
+    pub const fn is_essential(self) -> bool {
+        matches!(
+            self,
+            Self::PasswordReset | Self::EmailVerification | Self::SecurityAlert
+        )
+    }
+}
+
+/// Whether mail of `kind` should be sent to the profile's address.
+#[must_use]
+pub const fn should_send_email(profile: &user_profile::Model, kind: EmailNotificationType) -> bool {
+    !profile.email_undeliverable || kind.is_essential()
+}
+
+/// Parse the `Message` of an SES notification.
+///
+/// Only permanent bounces and complaints are reported; transient bounces
+/// are left for the provider to retry.
+pub fn parse_ses(message: &str) -> AppResult<Vec<BounceEvent>> {
+    let message: Value = serde_json::from_str(message)
+        .map_err(|e| AppError::BadRequest(format!("Invalid SES notification: {e}")))?;
+
+    let (kind, recipients) = match message.get("notificationType").and_then(Value::as_str) {
+        Some("Bounce")
+            if message
+                .pointer("/bounce/bounceType")
+                .and_then(Value::as_str)
+                == Some("Permanent") =>
+        {
+            (BounceKind::HardBounce, "/bounce/bouncedRecipients")
+        }
+        Some("Complaint") => (BounceKind::Complaint, "/complaint/complainedRecipients"),
+        _ => return Ok(Vec::new()),
+    };
+
+    Ok(message
+        .pointer(recipients)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|r| r.get("emailAddress").and_then(Value::as_str))
+        .map(|email| BounceEvent {
+            email: email.to_string(),
+            kind,
+        })
+        .collect())
+}
+
+/// Parse a `SendGrid` event webhook batch.
+pub fn parse_sendgrid(body: &str) -> AppResult<Vec<BounceEvent>> {
+    let events: Vec<Value> = serde_json::from_str(body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid SendGrid payload: {e}")))?;
+
+    Ok(events
+        .iter()
+        .filter_map(|event| {
+            let kind = match event.get("event").and_then(Value::as_str)? {
+                // "blocked" bounces are temporary rejections by the receiver
+                "bounce" if event.get("type").and_then(Value::as_str) != Some("blocked") => {
+                    BounceKind::HardBounce
+                }
+                "spamreport" => BounceKind::Complaint,
+                _ => return None,
+            };
+            Some(BounceEvent {
+                email: event.get("email").and_then(Value::as_str)?.to_string(),
+                kind,
+            })
+        })
+        .collect())
+}
+
+/// Parse a Mailgun webhook payload.
+#[must_use]
+pub fn parse_mailgun(payload: &Value) -> Vec<BounceEvent> {
+    let Some(data) = payload.get("event-data") else {
+        return Vec::new();
+    };
+
+    let kind = match data.get("event").and_then(Value::as_str) {
+        Some("failed") if data.get("severity").and_then(Value::as_str) == Some("permanent") => {
+            BounceKind::HardBounce
+        }
+        Some("complained") => BounceKind::Complaint,
+        _ => return Vec::new(),
+    };
+
+    data.get("recipient")
+        .and_then(Value::as_str)
+        .map(|email| BounceEvent {
+            email: email.to_string(),
+            kind,
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Verify a Mailgun webhook signature (HMAC-SHA256 of timestamp and token).
+#[must_use]
+pub fn verify_mailgun_signature(
+    signing_key: &str,
+    timestamp: &str,
+    token: &str,
+    signature: &str,
+) -> bool {
+    let Ok(expected) = hex::decode(signature) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(signing_key.as_bytes()) else {
+        return false;
+    };
+    mac.update(timestamp.as_bytes());
+    mac.update(token.as_bytes());
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Reject webhook timestamps (Unix seconds) outside [`MAX_WEBHOOK_AGE_SECS`].
+fn check_webhook_timestamp(timestamp: &str) -> AppResult<()> {
+    let timestamp: i64 = timestamp
+        .trim()
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid webhook timestamp".to_string()))?;
+    if (Utc::now().timestamp() - timestamp).abs() > MAX_WEBHOOK_AGE_SECS {
+        return Err(AppError::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Parse an `https` URL on an `amazonaws.com` host starting with `prefix`.
+fn amazonaws_url(url: &str, prefix: &str) -> Option<Url> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    (url.scheme() == "https" && host.starts_with(prefix) && host.ends_with(".amazonaws.com"))
+        .then_some(url)
+}
+
+/// Decode a base64 DER public key.
+fn decode_public_key(key: &str) -> AppResult<PKey<openssl::pkey::Public>> {
+    BASE64
+        .decode(key.trim())
+        .ok()
+        .and_then(|der| PKey::public_key_from_der(&der).ok())
+        .ok_or_else(|| AppError::Internal("Invalid webhook verification key".to_string()))
+}
+
+/// Verify a base64 signature over `data`.
+fn verify_signature(
+    digest: MessageDigest,
+    key: &PKey<openssl::pkey::Public>,
+    data: &[u8],
+    signature: &str,
+) -> AppResult<bool> {
+    let Ok(signature) = BASE64.decode(signature.trim()) else {
+        return Ok(false);
+    };
+    let mut verifier = Verifier::new(digest, key)
+        .map_err(|e| AppError::Internal(format!("Signature verifier error: {e}")))?;
+    Ok(verifier.verify_oneshot(&signature, data).unwrap_or(false))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use misskey_db::entities::note::Visibility;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn test_profile() -> user_profile::Model {
+        user_profile::Model {
+            user_id: "user1".to_string(),
+            password: None,
+            email: Some("alice@example.com".to_string()),
+            email_verified: true,
+            email_undeliverable: false,
+            two_factor_secret: None,
+            two_factor_enabled: false,
+            two_factor_pending: None,
+            two_factor_backup_codes: None,
+            auto_accept_followed: false,
+            always_mark_nsfw: false,
+            pinned_page_ids: serde_json::json!([]),
+            pinned_note_ids: serde_json::json!([]),
+            fields: serde_json::json!([]),
+            muted_words: serde_json::json!([]),
+            user_css: None,
+            birthday: None,
+            location: None,
+            lang: None,
+            pronouns: None,
+            also_known_as: None,
+            moved_to_uri: None,
+            hide_bots: false,
+            default_reaction: None,
+            receive_dm_from_followers_only: false,
+            secure_fetch_only: false,
+            disallow_quotes: false,
+            hide_online_status: false,
+            default_note_visibility: Visibility::Public,
+            theme: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn mailgun_signature(key: &str, timestamp: &str, token: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).unwrap();
+        mac.update(timestamp.as_bytes());
+        mac.update(token.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[test]
+    fn test_parse_ses_ignores_transient_bounces() {
+        let transient = json!({
+            "notificationType": "Bounce",
+            "bounce": {
+                "bounceType": "Transient",
+                "bouncedRecipients": [{"emailAddress": "alice@example.com"}]
+            }
+        });
+        assert!(parse_ses(&transient.to_string()).unwrap().is_empty());
+
+        let complaint = json!({
+            "notificationType": "Complaint",
+            "complaint": {"complainedRecipients": [{"emailAddress": "bob@example.com"}]}
+        });
+        assert_eq!(
+            parse_ses(&complaint.to_string()).unwrap(),
+            [BounceEvent {
+                email: "bob@example.com".to_string(),
+                kind: BounceKind::Complaint,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mailgun_hard_bounce_marks_email_undeliverable() {
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_exec_results([MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                }])
+                .into_connection(),
+        );
+        let service = EmailBounceService::new(
+            UserProfileRepository::new(Arc::clone(&db)),
+            EmailWebhookConfig {
+                mailgun_signing_key: Some("mailgun-key".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let timestamp = Utc::now().timestamp().to_string();
+        let body = json!({
+            "signature": {
+                "timestamp": timestamp,
+                "token": "abc123",
+                "signature": mailgun_signature("mailgun-key", &timestamp, "abc123")
+            },
+            "event-data": {
+                "event": "failed",
+                "severity": "permanent",
+                "recipient": "alice@example.com"
+            }
+        });
+        service.handle_mailgun(&body.to_string()).await.unwrap();
+        drop(service);
+
+        let log = Arc::try_unwrap(db).ok().unwrap().into_transaction_log();
+        let statements: Vec<_> = log
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .collect();
+        assert_eq!(statements.len(), 1);
+        assert!(
+            statements[0]
+                .sql
+                .starts_with(r#"UPDATE "user_profile" SET "email_undeliverable""#)
+        );
+        assert!(
+            statements[0]
+                .values
+                .as_ref()
+                .is_some_and(|v| format!("{v:?}").contains("alice@example.com"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mailgun_rejects_bad_signature() {
+        // No queries are expected: verification fails before any update
+        let db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let service = EmailBounceService::new(
+            UserProfileRepository::new(db),
+            EmailWebhookConfig {
+                mailgun_signing_key: Some("mailgun-key".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let body = json!({
+            "signature": {
+                "timestamp": Utc::now().timestamp().to_string(),
+                "token": "abc123",
+                "signature": "00"
+            },
+            "event-data": {"event": "complained", "recipient": "alice@example.com"}
+        });
+        let result = service.handle_mailgun(&body.to_string()).await;
+        assert!(matches!(result, Err(AppError::Unauthorized)));
+    }
+
+    #[tokio::test]
+    async fn test_mailgun_rejects_replayed_payload() {
+        // Correctly signed, but too old: no update may happen
+        let db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let service = EmailBounceService::new(
+            UserProfileRepository::new(db),
+            EmailWebhookConfig {
+                mailgun_signing_key: Some("mailgun-key".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let timestamp = (Utc::now().timestamp() - MAX_WEBHOOK_AGE_SECS - 60).to_string();
+        let body = json!({
+            "signature": {
+                "timestamp": timestamp,
+                "token": "abc123",
+                "signature": mailgun_signature("mailgun-key", &timestamp, "abc123")
+            },
+            "event-data": {"event": "complained", "recipient": "alice@example.com"}
+        });
+        let result = service.handle_mailgun(&body.to_string()).await;
+        assert!(matches!(result, Err(AppError::Unauthorized)));
+    }
+
+    #[test]
+    fn test_undeliverable_address_only_gets_essential_mail() {
+        let profile = |email_undeliverable| user_profile::Model {
+            email_undeliverable,
+            ..test_profile()
+        };
+
+        assert!(should_send_email(
+            &profile(false),
+            EmailNotificationType::Follow
+        ));
+        assert!(!should_send_email(
+            &profile(true),
+            EmailNotificationType::Follow
+        ));
+        assert!(should_send_email(
+            &profile(true),
+            EmailNotificationType::PasswordReset
+        ));
+    }
+}
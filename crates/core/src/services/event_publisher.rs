@@ -21,6 +21,13 @@ pub enum StreamEvent {
     NoteDeleted { id: String, user_id: String },
     /// A note was updated.
     NoteUpdated { id: String },
+    /// A note's visibility was narrowed and some subscribers lost access.
+    NoteUnavailable {
+        id: String,
+        user_id: String,
+        old_visibility: String,
+        new_visibility: String,
+    },
     /// A user followed another user.
     Followed {
         follower_id: String,
@@ -62,6 +69,38 @@ pub enum StreamEvent {
     },
 }
 
+/// Timeline a note event is fanned out to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineChannel {
+    /// The global (federated) timeline.
+    Global,
+    /// The local timeline.
+    Local,
+}
+
+/// Timelines whose subscribers may see a note of the given visibility.
+///
+/// Timeline subscribers are not filtered per viewer, so only public notes
+/// are fanned out to them.
+#[must_use]
+pub fn timeline_channels(visibility: &str) -> &'static [TimelineChannel] {
+    match visibility {
+        "public" => &[TimelineChannel::Global, TimelineChannel::Local],
+        _ => &[],
+    }
+}
+
+/// Timelines that could see a note before a visibility change but not after.
+#[must_use]
+pub fn lost_timeline_channels(old_visibility: &str, new_visibility: &str) -> Vec<TimelineChannel> {
+    let remaining = timeline_channels(new_visibility);
+    timeline_channels(old_visibility)
+        .iter()
+        .copied()
+        .filter(|c| !remaining.contains(c))
+        .collect()
+}
+
 /// Trait for publishing real-time events.
 ///
 /// This allows the core services to publish events
@@ -83,6 +122,18 @@ pub trait EventPublisher: Send + Sync {
     /// Publish a note updated event.
     async fn publish_note_updated(&self, id: &str) -> AppResult<()>;
 
+    /// Publish a note unavailable event after its visibility was narrowed.
+    ///
+    /// Delivered to the timelines that could see the note at `old_visibility`
+    /// but can no longer see it at `new_visibility`.
+    async fn publish_note_unavailable(
+        &self,
+        id: &str,
+        user_id: &str,
+        old_visibility: &str,
+        new_visibility: &str,
+    ) -> AppResult<()>;
+
     /// Publish a followed event.
     async fn publish_followed(&self, follower_id: &str, followee_id: &str) -> AppResult<()>;
 
@@ -161,6 +212,16 @@ impl EventPublisher for NoOpEventPublisher {
         Ok(())
     }
 
+    async fn publish_note_unavailable(
+        &self,
+        _id: &str,
+        _user_id: &str,
+        _old_visibility: &str,
+        _new_visibility: &str,
+    ) -> AppResult<()> {
+        Ok(())
+    }
+
     async fn publish_followed(&self, _follower_id: &str, _followee_id: &str) -> AppResult<()> {
         Ok(())
     }
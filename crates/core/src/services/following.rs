@@ -569,12 +569,14 @@ mod tests {
             is_silenced: false,
             is_admin: false,
             is_moderator: false,
+            is_system: false,
             followers_count: 0,
             following_count: 0,
             notes_count: 0,
             inbox: None,
             shared_inbox: None,
             featured: None,
+            followers_uri: None,
             uri: None,
             last_fetched_at: None,
             token: Some("test_token".to_string()),
@@ -3,7 +3,7 @@
 use std::io::Cursor;
 use std::path::Path;
 
-use image::{DynamicImage, GenericImageView, ImageReader, imageops::FilterType};
+use image::{DynamicImage, GenericImageView, ImageDecoder, ImageReader, imageops::FilterType};
 use serde::{Deserialize, Serialize};
 
 use misskey_common::{AppError, AppResult};
@@ -322,6 +322,29 @@ impl MediaService {
             .map_err(|e| AppError::Validation(format!("Failed to decode image: {e}")))
     }
 
+    /// Decode image from bytes and rotate/flip the pixels to match its EXIF orientation.
+    ///
+    /// Re-encoding never writes EXIF back, so the orientation tag is stripped along
+    /// with the rest of the metadata and viewers can't rotate the image twice.
+    fn decode_oriented_image(&self, data: &[u8]) -> AppResult<DynamicImage> {
+        let mut decoder = ImageReader::new(Cursor::new(data))
+            .with_guessed_format()
+            .map_err(|e| AppError::Validation(format!("Failed to detect image format: {e}")))?
+            .into_decoder()
+            .map_err(|e| AppError::Validation(format!("Failed to decode image: {e}")))?;
+
+        // A broken EXIF block shouldn't make the whole image unusable
+        let orientation = decoder
+            .orientation()
+            .unwrap_or(image::metadata::Orientation::NoTransforms);
+
+        let mut img = DynamicImage::from_decoder(decoder)
+            .map_err(|e| AppError::Validation(format!("Failed to decode image: {e}")))?;
+        img.apply_orientation(orientation);
+
+        Ok(img)
+    }
+
     /// Generate blurhash from decoded image.
     fn generate_blurhash_internal(&self, img: &DynamicImage) -> AppResult<String> {
         // Resize for blurhash (small size is fine for hash)
@@ -397,8 +420,8 @@ impl MediaService {
     ) -> AppResult<ProcessedImage> {
         let (max_width, max_height) = size.dimensions();
 
-        // Decode original image
-        let img = self.decode_image(data)?;
+        // Decode original image, upright
+        let img = self.decode_oriented_image(data)?;
 
         // Resize maintaining aspect ratio
         let thumbnail = img.resize(max_width, max_height, FilterType::Lanczos3);
@@ -463,7 +486,11 @@ impl MediaService {
         let output_format = options.format.unwrap_or(input_format);
 
         // Decode image
-        let mut img = self.decode_image(data)?;
+        let mut img = if options.auto_orient {
+            self.decode_oriented_image(data)?
+        } else {
+            self.decode_image(data)?
+        };
 
         // Resize if needed
         let (original_width, original_height) = img.dimensions();
@@ -658,3 +685,82 @@ impl MediaService {
         }
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    /// Encode a `width`x`height` JPEG carrying an EXIF orientation tag.
+    fn jpeg_with_orientation(width: u32, height: u32, orientation: u16) -> Vec<u8> {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, Rgb([200, 40, 40])));
+        let mut jpeg = Cursor::new(Vec::new());
+        img.write_to(&mut jpeg, image::ImageFormat::Jpeg).unwrap();
+        let jpeg = jpeg.into_inner();
+
+        // Big-endian TIFF header with a single IFD entry: Orientation (0x0112), SHORT, 1
+        let mut tiff =
+            b"MM\x00\x2a\x00\x00\x00\x08\x00\x01\x01\x12\x00\x03\x00\x00\x00\x01".to_vec();
+        tiff.extend_from_slice(&orientation.to_be_bytes());
+        tiff.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+
+        let mut app1 = b"Exif\x00\x00".to_vec();
+        app1.extend_from_slice(&tiff);
+        let len = u16::try_from(app1.len() + 2).unwrap();
+
+        // Insert the APP1 segment right after SOI
+        let mut out = jpeg[..2].to_vec();
+        out.extend_from_slice(&[0xFF, 0xE1]);
+        out.extend_from_slice(&len.to_be_bytes());
+        out.extend_from_slice(&app1);
+        out.extend_from_slice(&jpeg[2..]);
+        out
+    }
+
+    #[test]
+    fn test_process_image_applies_exif_rotation() {
+        let service = MediaService::new(MediaConfig::default());
+        let data = jpeg_with_orientation(40, 20, 6);
+
+        let processed = service
+            .process_image(&data, ImageProcessingOptions::default())
+            .unwrap();
+
+        assert_eq!(processed.dimensions.width, 20);
+        assert_eq!(processed.dimensions.height, 40);
+    }
+
+    #[test]
+    fn test_process_image_without_auto_orient_keeps_dimensions() {
+        let service = MediaService::new(MediaConfig::default());
+        let data = jpeg_with_orientation(40, 20, 6);
+        let options = ImageProcessingOptions {
+            auto_orient: false,
+            ..Default::default()
+        };
+
+        let processed = service.process_image(&data, options).unwrap();
+
+        assert_eq!(processed.dimensions.width, 40);
+        assert_eq!(processed.dimensions.height, 20);
+    }
+
+    #[test]
+    fn test_thumbnail_is_upright_and_exif_free() {
+        let service = MediaService::new(MediaConfig {
+            enable_webp_conversion: false,
+            ..Default::default()
+        });
+        let data = jpeg_with_orientation(40, 20, 6);
+
+        let thumbnail = service
+            .generate_thumbnail(&data, ThumbnailSize::Small)
+            .unwrap();
+
+        assert_eq!(thumbnail.dimensions.width, 75);
+        assert_eq!(thumbnail.dimensions.height, 150);
+        let reread = service.decode_oriented_image(&thumbnail.data).unwrap();
+        assert_eq!(reread.dimensions(), (75, 150));
+    }
+}
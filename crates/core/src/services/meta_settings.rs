@@ -1,7 +1,8 @@
 //! Meta settings service for instance configuration.
 
 use misskey_common::{AppError, AppResult};
-use misskey_db::entities::{meta_settings, meta_settings::META_SETTINGS_ID};
+use misskey_db::entities::{meta_settings, meta_settings::META_SETTINGS_ID, note::Visibility};
+use misskey_db::repositories::MetaSettingsRepository;
 use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -31,19 +32,53 @@ pub struct UpdateMetaSettingsInput {
     pub default_drive_capacity_mb: Option<i32>,
     pub max_file_size_mb: Option<i32>,
     pub bubble_instances: Option<Vec<String>>,
+    pub federation_hosts: Option<Vec<String>>,
+    pub default_note_visibility: Option<Visibility>,
+    pub default_is_locked: Option<bool>,
+    pub default_receive_dm_from_followers_only: Option<bool>,
+    pub default_theme: Option<String>,
+}
+
+/// Settings applied to accounts created after they were configured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewUserDefaults {
+    pub note_visibility: Visibility,
+    pub is_locked: bool,
+    pub receive_dm_from_followers_only: bool,
+    pub theme: Option<String>,
+}
+
+impl Default for NewUserDefaults {
+    fn default() -> Self {
+        Self {
+            note_visibility: Visibility::Public,
+            is_locked: false,
+            receive_dm_from_followers_only: false,
+            theme: None,
+        }
+    }
 }
 
 /// Meta settings service for managing instance configuration.
 #[derive(Clone)]
 pub struct MetaSettingsService {
     db: Arc<DatabaseConnection>,
+    cached_repo: Option<MetaSettingsRepository>,
 }
 
 impl MetaSettingsService {
     /// Create a new meta settings service.
     #[must_use]
     pub const fn new(db: Arc<DatabaseConnection>) -> Self {
-        Self { db }
+        Self {
+            db,
+            cached_repo: None,
+        }
+    }
+
+    /// Set the repository whose cached settings are invalidated on update.
+    pub fn set_cached_repo(&mut self, repo: MetaSettingsRepository) {
+        self.cached_repo = Some(repo);
     }
 
     /// Get meta settings, creating default if not exists.
@@ -82,6 +117,11 @@ impl MetaSettingsService {
                 default_drive_capacity_mb: Set(1024),
                 max_file_size_mb: Set(256),
                 bubble_instances: Set(Some(serde_json::json!([]))),
+                federation_hosts: Set(Some(serde_json::json!([]))),
+                default_note_visibility: Set(Visibility::Public),
+                default_is_locked: Set(false),
+                default_receive_dm_from_followers_only: Set(false),
+                default_theme: Set(None),
                 created_at: Set(now.into()),
                 updated_at: Set(None),
             };
@@ -173,12 +213,37 @@ impl MetaSettingsService {
         if let Some(bubble_instances) = input.bubble_instances {
             model.bubble_instances = Set(Some(serde_json::json!(bubble_instances)));
         }
+        if let Some(federation_hosts) = input.federation_hosts {
+            let hosts: Vec<String> = federation_hosts
+                .iter()
+                .map(|h| h.trim().to_lowercase())
+                .filter(|h| !h.is_empty())
+                .collect();
+            model.federation_hosts = Set(Some(serde_json::json!(hosts)));
+        }
+        if let Some(default_note_visibility) = input.default_note_visibility {
+            model.default_note_visibility = Set(default_note_visibility);
+        }
+        if let Some(default_is_locked) = input.default_is_locked {
+            model.default_is_locked = Set(default_is_locked);
+        }
+        if let Some(only_followers) = input.default_receive_dm_from_followers_only {
+            model.default_receive_dm_from_followers_only = Set(only_followers);
+        }
+        if let Some(default_theme) = input.default_theme {
+            let theme = default_theme.trim();
+            model.default_theme = Set((!theme.is_empty()).then(|| theme.to_string()));
+        }
 
         let result = model
             .update(self.db.as_ref())
             .await
             .map_err(|e| AppError::Database(e.to_string()))?;
 
+        if let Some(repo) = &self.cached_repo {
+            repo.invalidate_federation_hosts();
+        }
+
         Ok(result)
     }
 
@@ -206,6 +271,17 @@ impl MetaSettingsService {
         Ok(settings.force_nsfw_media)
     }
 
+    /// Get the defaults applied to newly registered users.
+    pub async fn new_user_defaults(&self) -> AppResult<NewUserDefaults> {
+        let settings = self.get().await?;
+        Ok(NewUserDefaults {
+            note_visibility: settings.default_note_visibility,
+            is_locked: settings.default_is_locked,
+            receive_dm_from_followers_only: settings.default_receive_dm_from_followers_only,
+            theme: settings.default_theme,
+        })
+    }
+
     /// Get bubble instances for bubble timeline.
     pub async fn get_bubble_instances(&self) -> AppResult<Vec<String>> {
         let settings = self.get().await?;
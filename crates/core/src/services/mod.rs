@@ -5,12 +5,15 @@
 pub mod account;
 pub mod announcement;
 pub mod antenna;
+pub mod ap_resolve;
 pub mod blocking;
 pub mod channel;
 pub mod clip;
+pub mod content_policy;
 pub mod delivery;
 pub mod drive;
 pub mod email;
+pub mod email_bounce;
 pub mod emoji;
 pub mod event_publisher;
 pub mod filter_group;
@@ -28,8 +31,10 @@ pub mod muting;
 pub mod note;
 pub mod note_favorite;
 pub mod notification;
+pub mod notification_debounce;
 pub mod oauth;
 pub mod page;
+pub mod password_reset;
 pub mod poll;
 pub mod push_notification;
 pub mod reaction;
@@ -38,6 +43,10 @@ pub mod registration_approval;
 pub mod scheduled_note;
 pub mod search;
 pub mod storage;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod thread_muting;
+pub mod timeline_cache;
 pub mod translation;
 pub mod two_factor;
 pub mod user;
@@ -55,18 +64,31 @@ pub use account::{
 };
 pub use announcement::AnnouncementService;
 pub use antenna::{AntennaService, CreateAntennaInput, NoteMatchContext, UpdateAntennaInput};
+pub use ap_resolve::{
+    ActorSummary, ApResolveService, InspectedObject, InspectedSummary, NoteSummary,
+    RemoteObjectFetcher, RemoteObjectFetcherService, ResolvedObject,
+};
 pub use blocking::BlockingService;
 pub use channel::{ChannelService, CreateChannelInput, UpdateChannelInput};
 pub use clip::ClipService;
-pub use delivery::{ActivityDelivery, DeliveryService, NoOpDelivery};
-pub use drive::{CreateFileInput, CreateFolderInput, DriveService, StorageUsage};
+pub use content_policy::{
+    ContentPolicy, ContentPolicyService, CreateNoteContext, PolicyDecision, WordFilterContentPolicy,
+};
+pub use delivery::{ActivityDelivery, DeliveryService, NoOpDelivery, follower_inboxes};
+pub use drive::{
+    CreateFileInput, CreateFolderInput, DriveService, FolderUsageBreakdown, StorageUsage,
+};
 pub use email::{
     EmailConfig, EmailDeliveryResult, EmailMessage, EmailNotificationType, EmailProvider,
     EmailService, EmailStatusResponse, EmailTemplateVars, MailgunConfig, SendGridConfig, SesConfig,
     SmtpConfig,
 };
+pub use email_bounce::{BounceEvent, BounceKind, EmailBounceService, should_send_email};
 pub use emoji::EmojiService;
-pub use event_publisher::{EventPublisher, EventPublisherService, NoOpEventPublisher, StreamEvent};
+pub use event_publisher::{
+    EventPublisher, EventPublisherService, NoOpEventPublisher, StreamEvent, TimelineChannel,
+    lost_timeline_channels, timeline_channels,
+};
 pub use filter_group::{
     CreateGroupInput as CreateFilterGroupInput, FilterGroupService,
     UpdateGroupInput as UpdateFilterGroupInput,
@@ -87,19 +109,27 @@ pub use media::{
     MediaService, MediaStatusResponse, ProcessedImage, ThumbnailSize, VideoMetadata,
 };
 pub use messaging::{ConversationSummary, CreateMessageInput, MessagingService};
-pub use meta_settings::{MetaSettingsService, UpdateMetaSettingsInput};
+pub use meta_settings::{MetaSettingsService, NewUserDefaults, UpdateMetaSettingsInput};
 pub use moderation::{
     CreateReportInput, CreateSuspensionInput, ModerationService, ReportStatus, ResolveReportInput,
 };
 pub use muting::MutingService;
-pub use note::{NoteService, UpdateNoteInput};
+pub use note::{
+    ComposeWarning, ComposeWarnings, NoteService, NoteWithAuthor, UpdateNoteInput,
+    fetch_timeline_page, is_pure_renote,
+};
 pub use note_favorite::NoteFavoriteService;
 pub use notification::NotificationService;
+pub use notification_debounce::{
+    InMemoryNotificationDebouncer, NotificationDebouncer, NotificationDebouncerService,
+    REACTION_AGGREGATION_WINDOW,
+};
 pub use oauth::{
     AuthorizeInput, AuthorizeResponse, AuthorizedAppResponse, CreateAppInput, OAuthAppResponse,
     OAuthAppWithSecretResponse, OAuthService, TokenExchangeInput, TokenResponse, UpdateAppInput,
 };
 pub use page::{CreatePageInput, PageResponse, PageService, UpdatePageInput};
+pub use password_reset::PasswordResetService;
 pub use poll::{CreatePollInput, PollService, PollWithStatus};
 pub use push_notification::{
     CreateSubscriptionInput, PushConfigResponse, PushNotificationService, PushNotificationType,
@@ -113,6 +143,11 @@ pub use scheduled_note::{
 };
 pub use search::{NoteDocument, SearchConfig, SearchHit, SearchService, SearchStats, UserDocument};
 pub use storage::{LocalStorage, NoOpStorage, StorageBackend, StorageService};
+pub use thread_muting::ThreadMutingService;
+pub use timeline_cache::{
+    CachedTimeline, InMemoryTimelineCache, TIMELINE_CACHE_PAGE_SIZE, TimelineCache,
+    TimelineCacheService,
+};
 pub use translation::{
     LanguageDetectionResponse, SupportedLanguage, TranslateInput, TranslationConfig,
     TranslationProvider, TranslationResponse, TranslationService,
@@ -121,7 +156,7 @@ pub use two_factor::{
     ConfirmTwoFactorInput, DisableTwoFactorInput, TwoFactorConfirmResponse, TwoFactorService,
     TwoFactorSetupResponse, VerifyTwoFactorInput,
 };
-pub use user::{UpdateUserInput, UserService};
+pub use user::{UpdateUserInput, UserProfileDetails, UserService};
 pub use user_list::{CreateListInput, UserListService};
 pub use webauthn::{
     BeginAuthenticationResponse, BeginRegistrationResponse, CompleteAuthenticationInput,
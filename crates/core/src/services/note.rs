@@ -1,17 +1,31 @@
 //! Note service.
 
 use crate::services::antenna::AntennaService;
+use crate::services::content_policy::{ContentPolicyService, CreateNoteContext, PolicyDecision};
 use crate::services::delivery::DeliveryService;
 use crate::services::event_publisher::EventPublisherService;
-use misskey_common::{AppError, AppResult, IdGenerator};
+use crate::services::notification::NotificationService;
+use crate::services::timeline_cache::{
+    CachedTimeline, TIMELINE_CACHE_PAGE_SIZE, TimelineCacheService, timelines_for_note,
+};
+use crate::services::word_filter::WordFilterService;
+use misskey_common::{
+    AppError, AppResult, AttachmentLimits, IdGenerator, QuoteReach, normalize_hashtag,
+};
 use misskey_db::{
     entities::note::{self, Visibility},
-    entities::note_edit,
-    repositories::{FollowingRepository, NoteRepository, UserListRepository, UserRepository},
+    entities::word_filter::FilterAction,
+    entities::{drive_file, note_edit, user},
+    repositories::{
+        DriveFileRepository, FollowingRepository, NoteRepository, PollRepository,
+        UserListRepository, UserProfileRepository, UserRepository,
+    },
 };
-use sea_orm::Set;
-use serde::Deserialize;
+use sea_orm::{NotSet, Set};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
 use validator::Validate;
 
 /// Note service for business logic.
@@ -21,9 +35,19 @@ pub struct NoteService {
     user_repo: UserRepository,
     following_repo: FollowingRepository,
     user_list_repo: Option<UserListRepository>,
+    profile_repo: Option<UserProfileRepository>,
+    poll_repo: Option<PollRepository>,
     delivery: Option<DeliveryService>,
     event_publisher: Option<EventPublisherService>,
     antenna_service: Option<AntennaService>,
+    content_policy: Option<ContentPolicyService>,
+    notification_service: Option<NotificationService>,
+    word_filter_service: Option<WordFilterService>,
+    drive_file_repo: Option<DriveFileRepository>,
+    attachment_limits: AttachmentLimits,
+    quote_reach: QuoteReach,
+    timeline_cache: Option<(TimelineCacheService, Duration)>,
+    url_template: Option<String>,
     server_url: String,
     id_gen: IdGenerator,
 }
@@ -60,9 +84,27 @@ const fn default_visibility() -> Visibility {
 }
 
 /// Note with author information.
+#[derive(Debug, Clone)]
 pub struct NoteWithAuthor {
     pub note: note::Model,
-    pub author: misskey_db::entities::user::Model,
+    pub author: user::Model,
+}
+
+/// Word filters of the author matched by a draft.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComposeWarnings {
+    pub warnings: Vec<ComposeWarning>,
+}
+
+/// A word filter matched by a draft.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComposeWarning {
+    pub filter_id: String,
+    pub phrase: String,
+    /// The filter's action: `hide`, `warn` or `cw`.
+    pub action: String,
 }
 
 /// Input for updating a note.
@@ -81,6 +123,9 @@ pub struct UpdateNoteInput {
     /// New file IDs.
     #[validate(length(max = 16))]
     pub file_ids: Option<Vec<String>>,
+
+    /// New visibility (None = no change). Visibility can only be narrowed.
+    pub visibility: Option<Visibility>,
 }
 
 impl NoteService {
@@ -96,9 +141,19 @@ impl NoteService {
             user_repo,
             following_repo,
             user_list_repo: None,
+            profile_repo: None,
+            poll_repo: None,
             delivery: None,
             event_publisher: None,
             antenna_service: None,
+            content_policy: None,
+            notification_service: None,
+            word_filter_service: None,
+            drive_file_repo: None,
+            attachment_limits: AttachmentLimits::default(),
+            quote_reach: QuoteReach::default(),
+            timeline_cache: None,
+            url_template: None,
             server_url: String::new(),
             id_gen: IdGenerator::new(),
         }
@@ -118,9 +173,19 @@ impl NoteService {
             user_repo,
             following_repo,
             user_list_repo: None,
+            profile_repo: None,
+            poll_repo: None,
             delivery: Some(delivery),
             event_publisher: None,
             antenna_service: None,
+            content_policy: None,
+            notification_service: None,
+            word_filter_service: None,
+            drive_file_repo: None,
+            attachment_limits: AttachmentLimits::default(),
+            quote_reach: QuoteReach::default(),
+            timeline_cache: None,
+            url_template: None,
             server_url,
             id_gen: IdGenerator::new(),
         }
@@ -131,12 +196,31 @@ impl NoteService {
         self.user_list_repo = Some(user_list_repo);
     }
 
+    /// Set the profile repository used to honor authors' quote opt-outs.
+    pub fn set_profile_repo(&mut self, profile_repo: UserProfileRepository) {
+        self.profile_repo = Some(profile_repo);
+    }
+
+    /// Set the poll repository used to tell renotes of polls from pure renotes.
+    pub fn set_poll_repo(&mut self, poll_repo: PollRepository) {
+        self.poll_repo = Some(poll_repo);
+    }
+
     /// Set the delivery service.
     pub fn set_delivery(&mut self, delivery: DeliveryService, server_url: String) {
         self.delivery = Some(delivery);
         self.server_url = server_url;
     }
 
+    /// Assign `uri` and a human-readable `url` to new local notes.
+    ///
+    /// `url_template` is a path relative to `server_url` in which `{id}` and
+    /// `{username}` are substituted.
+    pub fn set_note_urls(&mut self, server_url: String, url_template: String) {
+        self.server_url = server_url;
+        self.url_template = Some(url_template);
+    }
+
     /// Set the event publisher.
     pub fn set_event_publisher(&mut self, event_publisher: EventPublisherService) {
         self.event_publisher = Some(event_publisher);
@@ -147,8 +231,116 @@ impl NoteService {
         self.antenna_service = Some(antenna_service);
     }
 
+    /// Set the content policy evaluated before notes are created.
+    pub fn set_content_policy(&mut self, content_policy: ContentPolicyService) {
+        self.content_policy = Some(content_policy);
+    }
+
+    /// Set the notification service used for reply notifications.
+    pub fn set_notification_service(&mut self, notification_service: NotificationService) {
+        self.notification_service = Some(notification_service);
+    }
+
+    /// Set the word filter service used to warn authors about their drafts.
+    pub fn set_word_filter_service(&mut self, word_filter_service: WordFilterService) {
+        self.word_filter_service = Some(word_filter_service);
+    }
+
+    /// Set the drive file repository and limits used to validate attachments.
+    ///
+    /// Without a repository, attached file IDs are stored unchecked.
+    pub fn set_attachment_validation(
+        &mut self,
+        drive_file_repo: DriveFileRepository,
+        limits: AttachmentLimits,
+    ) {
+        self.drive_file_repo = Some(drive_file_repo);
+        self.attachment_limits = limits;
+    }
+
+    /// Set who receives quote renotes of public notes.
+    pub const fn set_quote_reach(&mut self, quote_reach: QuoteReach) {
+        self.quote_reach = quote_reach;
+    }
+
+    /// Cache the first page of public timelines for `ttl`.
+    pub fn set_timeline_cache(&mut self, cache: TimelineCacheService, ttl: Duration) {
+        self.timeline_cache = Some((cache, ttl));
+    }
+
+    /// Reject quoting a local author who opted out of being quoted.
+    async fn check_quote_allowed(&self, user_id: &str, quoted: &note::Model) -> AppResult<()> {
+        let Some(profile_repo) = &self.profile_repo else {
+            return Ok(());
+        };
+        if quoted.user_host.is_some() || quoted.user_id == user_id {
+            return Ok(());
+        }
+
+        let disallowed = profile_repo
+            .find_by_user_id(&quoted.user_id)
+            .await?
+            .is_some_and(|p| p.disallow_quotes);
+        if disallowed {
+            return Err(AppError::Forbidden(
+                "The author of this note does not allow quotes".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check a draft against the author's own word filters.
+    ///
+    /// Matches are only reported so the client can warn before posting;
+    /// creating the note is not affected. Filters match regardless of the
+    /// context they are scoped to.
+    pub async fn check_compose(
+        &self,
+        user_id: &str,
+        text: Option<&str>,
+        cw: Option<&str>,
+    ) -> AppResult<ComposeWarnings> {
+        let Some(word_filter_service) = &self.word_filter_service else {
+            return Ok(ComposeWarnings::default());
+        };
+        let content = match (cw, text) {
+            (Some(cw), Some(text)) => format!("{cw} {text}"),
+            (Some(cw), None) => cw.to_string(),
+            (None, Some(text)) => text.to_string(),
+            (None, None) => return Ok(ComposeWarnings::default()),
+        };
+
+        let filters = word_filter_service.get_active_filters(user_id).await?;
+        let mut warnings = Vec::new();
+        for filter in &filters {
+            let result = word_filter_service.apply_filters_with_cache(
+                std::slice::from_ref(filter),
+                &content,
+                filter.context,
+            )?;
+            if result.matched {
+                warnings.push(ComposeWarning {
+                    filter_id: filter.id.clone(),
+                    phrase: filter.phrase.clone(),
+                    action: match filter.action {
+                        FilterAction::Hide => "hide",
+                        FilterAction::Warn => "warn",
+                        FilterAction::ContentWarning => "cw",
+                    }
+                    .to_string(),
+                });
+            }
+        }
+
+        Ok(ComposeWarnings { warnings })
+    }
+
     /// Create a new note.
-    pub async fn create(&self, user_id: &str, input: CreateNoteInput) -> AppResult<note::Model> {
+    pub async fn create(
+        &self,
+        user_id: &str,
+        mut input: CreateNoteInput,
+    ) -> AppResult<note::Model> {
         input.validate()?;
 
         // Validate: text or renote required
@@ -158,6 +350,15 @@ impl NoteService {
             ));
         }
 
+        self.validate_attachments(user_id, &input.file_ids).await?;
+
+        // Let the content policy veto or flag the note
+        let flagged = match self.evaluate_content_policy(user_id, &input).await {
+            PolicyDecision::Allow => false,
+            PolicyDecision::Flag => true,
+            PolicyDecision::Reject(reason) => return Err(AppError::Forbidden(reason)),
+        };
+
         // Validate reply target exists
         let reply = if let Some(ref reply_id) = input.reply_id {
             Some(self.note_repo.get_by_id(reply_id).await?)
@@ -166,12 +367,29 @@ impl NoteService {
         };
 
         // Validate renote target exists
-        let renote = if let Some(ref renote_id) = input.renote_id {
+        let mut renote = if let Some(ref renote_id) = input.renote_id {
             Some(self.note_repo.get_by_id(renote_id).await?)
         } else {
             None
         };
 
+        // Renoting a pure renote boosts the original note instead, so that
+        // boosts are never nested. Quotes of renotes are kept as they are.
+        let is_quote = input.text.is_some() || input.cw.is_some() || !input.file_ids.is_empty();
+        if !is_quote
+            && let Some(target) = renote.as_ref()
+            && let Some(original_id) = target.renote_id.clone()
+            && self.is_pure_renote(target).await?
+        {
+            let original = self.note_repo.get_by_id(&original_id).await?;
+            input.renote_id = Some(original.id.clone());
+            renote = Some(original);
+        }
+
+        if is_quote && let Some(ref quoted) = renote {
+            self.check_quote_allowed(user_id, quoted).await?;
+        }
+
         // Get user
         let user = self.user_repo.get_by_id(user_id).await?;
 
@@ -188,6 +406,19 @@ impl NoteService {
             .or_else(|| reply.as_ref().map(|r| r.id.clone()));
 
         let note_id = self.id_gen.generate();
+        let (uri, url) = match self.url_template {
+            Some(ref template) if user.host.is_none() => (
+                Set(Some(format!("{}/notes/{note_id}", self.server_url))),
+                Set(Some(format!(
+                    "{}{}",
+                    self.server_url,
+                    template
+                        .replace("{id}", &note_id)
+                        .replace("{username}", &user.username)
+                ))),
+            ),
+            _ => (NotSet, NotSet),
+        };
 
         let model = note::ActiveModel {
             id: Set(note_id.clone()),
@@ -205,26 +436,47 @@ impl NoteService {
             tags: Set(json!(tags)),
             reactions: Set(json!({})),
             is_local: Set(user.host.is_none()),
+            uri,
+            url,
             channel_id: Set(input.channel_id.clone()),
             ..Default::default()
         };
 
         let note = self.note_repo.create(model).await?;
 
+        if flagged {
+            tracing::warn!(note_id = %note.id, user_id = %user_id, "Note flagged by content policy");
+        }
+
         // Update user's notes count
         self.user_repo.increment_notes_count(user_id).await?;
 
-        // Update reply count if this is a reply
+        self.invalidate_timeline_caches(&note).await;
+
+        // Update reply count if this is a reply; self-replies only continue a
+        // thread and are not counted
         if let Some(ref reply_note) = reply {
-            self.note_repo
-                .increment_replies_count(&reply_note.id)
-                .await?;
+            if !is_self_reply(user_id, &reply_note.user_id) {
+                self.note_repo
+                    .increment_replies_count(&reply_note.id)
+                    .await?;
+            }
             tracing::debug!(reply_to = %reply_note.id, "Created reply");
+
+            // Notify the local author of the note being replied to
+            if reply_note.user_host.is_none()
+                && let Some(ref notification_service) = self.notification_service
+                && let Err(e) = notification_service
+                    .create_reply_notification(&reply_note.user_id, user_id, &note.id)
+                    .await
+            {
+                tracing::warn!(error = %e, note_id = %note.id, "Failed to create reply notification");
+            }
         }
 
-        // Update renote count if this is a renote (pure renote without text)
+        // Update renote count if this is a pure renote
         if let Some(ref renote_note) = renote {
-            if note.text.is_none() {
+            if is_pure_renote(&note) {
                 self.note_repo
                     .increment_renote_count(&renote_note.id)
                     .await?;
@@ -236,10 +488,8 @@ impl NoteService {
         if let Some(ref delivery) = self.delivery {
             // Only deliver local notes
             if note.is_local {
-                // Determine if this is a pure renote (no text) or quote renote (with text)
-                let is_pure_renote = note.renote_id.is_some() && note.text.is_none();
-
-                if is_pure_renote {
+                // A pure renote is announced; quotes are created like any note
+                if is_pure_renote(&note) {
                     // Pure renote: send Announce activity
                     if let Err(e) = self
                         .queue_announce_delivery(&note, &user, renote.as_ref(), delivery)
@@ -261,12 +511,7 @@ impl NoteService {
 
         // Publish real-time event
         if let Some(ref event_publisher) = self.event_publisher {
-            let visibility_str = match note.visibility {
-                Visibility::Public => "public",
-                Visibility::Home => "home",
-                Visibility::Followers => "followers",
-                Visibility::Specified => "specified",
-            };
+            let visibility_str = visibility_str(&note.visibility);
             if let Err(e) = event_publisher
                 .publish_note_created(
                     &note.id,
@@ -397,6 +642,7 @@ impl NoteService {
             "id": note_url,
             "attributedTo": actor_url,
             "content": note.text.clone().unwrap_or_default(),
+            "url": note.url.clone(),
             "published": note.created_at.to_rfc3339(),
             "to": to_field,
             "cc": cc_field,
@@ -446,7 +692,20 @@ impl NoteService {
         });
 
         // Get follower inboxes
-        let inboxes = self.get_follower_inboxes(&user.id).await?;
+        let mut inboxes = self.get_follower_inboxes(&user.id).await?;
+
+        // Optionally also reach the followers of a quoted public note's local author
+        if self.quote_reach == QuoteReach::IncludeAuthorFollowers
+            && note.visibility == Visibility::Public
+            && let Some(quoted) = renote
+            && quoted.is_local
+            && quoted.visibility == Visibility::Public
+            && quoted.user_id != user.id
+        {
+            inboxes.extend(self.get_follower_inboxes(&quoted.user_id).await?);
+            inboxes.sort();
+            inboxes.dedup();
+        }
 
         if !inboxes.is_empty() {
             delivery
@@ -570,8 +829,8 @@ impl NoteService {
         self.note_repo.get_by_id(id).await
     }
 
-    /// Delete a note.
-    pub async fn delete(&self, note_id: &str, user_id: &str) -> AppResult<()> {
+    /// Delete a note, returning it as it was before deletion.
+    pub async fn delete(&self, note_id: &str, user_id: &str) -> AppResult<note::Model> {
         let note = self.note_repo.get_by_id(note_id).await?;
 
         // Check ownership
@@ -581,8 +840,12 @@ impl NoteService {
             ));
         }
 
-        // Queue ActivityPub Delete before actually deleting
-        // (we need the note data for building the activity)
+        // Delete first so that a failed delete is neither federated nor
+        // reported as a success
+        self.note_repo.delete(note_id).await?;
+        self.invalidate_timeline_caches(&note).await;
+
+        // Queue ActivityPub Delete with the note data we still hold
         if let Some(ref delivery) = self.delivery
             && note.is_local
             && let Err(e) = self.queue_delete_delivery(&note, delivery).await
@@ -590,20 +853,21 @@ impl NoteService {
             tracing::warn!(error = %e, note_id = %note.id, "Failed to queue ActivityPub Delete delivery");
         }
 
-        // Decrement reply count if this was a reply
-        if let Some(ref reply_id) = note.reply_id {
+        // Decrement reply count if this was a counted reply
+        if let Some(ref reply_id) = note.reply_id
+            && let Ok(Some(parent)) = self.note_repo.find_by_id(reply_id).await
+            && !is_self_reply(user_id, &parent.user_id)
+        {
             let _ = self.note_repo.decrement_replies_count(reply_id).await;
         }
 
         // Decrement renote count if this was a pure renote
         if let Some(ref renote_id) = note.renote_id
-            && note.text.is_none()
+            && is_pure_renote(&note)
         {
             let _ = self.note_repo.decrement_renote_count(renote_id).await;
         }
 
-        self.note_repo.delete(note_id).await?;
-
         // Update user's notes count
         self.user_repo.decrement_notes_count(user_id).await?;
 
@@ -614,7 +878,7 @@ impl NoteService {
             tracing::warn!(error = %e, note_id = %note_id, "Failed to publish note deleted event");
         }
 
-        Ok(())
+        Ok(note)
     }
 
     /// Queue `ActivityPub` Delete delivery for a note.
@@ -697,6 +961,7 @@ impl NoteService {
             "id": note_url,
             "attributedTo": actor_url,
             "content": note.text.clone().unwrap_or_default(),
+            "url": note.url.clone(),
             "published": note.created_at.to_rfc3339(),
             "updated": updated_at,
             "to": to_field,
@@ -730,6 +995,76 @@ impl NoteService {
         Ok(())
     }
 
+    /// Check that attached files exist, belong to the author and stay within
+    /// the configured limits.
+    async fn validate_attachments(&self, user_id: &str, file_ids: &[String]) -> AppResult<()> {
+        let Some(ref drive_file_repo) = self.drive_file_repo else {
+            return Ok(());
+        };
+        if file_ids.is_empty() {
+            return Ok(());
+        }
+
+        let files = drive_file_repo.find_by_ids(file_ids).await?;
+        if let Some(missing) = file_ids
+            .iter()
+            .find(|id| !files.iter().any(|f| &f.id == *id && f.user_id == user_id))
+        {
+            return Err(AppError::BadRequest(format!("File not found: {missing}")));
+        }
+
+        check_attachment_limits(&files, &self.attachment_limits)
+    }
+
+    /// Evaluate the configured content policy for a note about to be created.
+    async fn evaluate_content_policy(
+        &self,
+        user_id: &str,
+        input: &CreateNoteInput,
+    ) -> PolicyDecision {
+        let Some(ref policy) = self.content_policy else {
+            return PolicyDecision::Allow;
+        };
+
+        policy
+            .evaluate(&CreateNoteContext {
+                user_id,
+                text: input.text.as_deref(),
+                cw: input.cw.as_deref(),
+                visibility: &input.visibility,
+                reply_id: input.reply_id.as_deref(),
+                renote_id: input.renote_id.as_deref(),
+                file_count: input.file_ids.len(),
+            })
+            .await
+    }
+
+    /// Attach authors to notes for rendering.
+    ///
+    /// All distinct authors are loaded with a single query. Note order is
+    /// preserved; notes whose author no longer exists are dropped.
+    pub async fn hydrate(&self, notes: Vec<note::Model>) -> AppResult<Vec<NoteWithAuthor>> {
+        let mut author_ids: Vec<String> = notes.iter().map(|n| n.user_id.clone()).collect();
+        author_ids.sort_unstable();
+        author_ids.dedup();
+
+        let authors: HashMap<String, user::Model> = self
+            .user_repo
+            .find_by_ids(&author_ids)
+            .await?
+            .into_iter()
+            .map(|u| (u.id.clone(), u))
+            .collect();
+
+        Ok(notes
+            .into_iter()
+            .filter_map(|note| {
+                let author = authors.get(&note.user_id)?.clone();
+                Some(NoteWithAuthor { note, author })
+            })
+            .collect())
+    }
+
     /// Get local public timeline.
     ///
     /// # Arguments
@@ -742,9 +1077,17 @@ impl NoteService {
         until_id: Option<&str>,
         exclude_user_ids: Option<&[String]>,
     ) -> AppResult<Vec<note::Model>> {
-        self.note_repo
-            .find_local_public(limit, until_id, exclude_user_ids)
-            .await
+        self.cached_timeline(
+            CachedTimeline::Local,
+            limit,
+            until_id,
+            exclude_user_ids,
+            |limit| {
+                self.note_repo
+                    .find_local_public(limit, until_id, exclude_user_ids)
+            },
+        )
+        .await
     }
 
     /// Get global public timeline.
@@ -759,9 +1102,17 @@ impl NoteService {
         until_id: Option<&str>,
         exclude_user_ids: Option<&[String]>,
     ) -> AppResult<Vec<note::Model>> {
-        self.note_repo
-            .find_global_public(limit, until_id, exclude_user_ids)
-            .await
+        self.cached_timeline(
+            CachedTimeline::Global,
+            limit,
+            until_id,
+            exclude_user_ids,
+            |limit| {
+                self.note_repo
+                    .find_global_public(limit, until_id, exclude_user_ids)
+            },
+        )
+        .await
     }
 
     /// Get bubble timeline (local + whitelisted instances).
@@ -781,9 +1132,77 @@ impl NoteService {
         until_id: Option<&str>,
         exclude_user_ids: Option<&[String]>,
     ) -> AppResult<Vec<note::Model>> {
-        self.note_repo
-            .find_bubble_timeline(bubble_hosts, limit, until_id, exclude_user_ids)
-            .await
+        self.cached_timeline(
+            CachedTimeline::Bubble,
+            limit,
+            until_id,
+            exclude_user_ids,
+            |limit| {
+                self.note_repo
+                    .find_bubble_timeline(bubble_hosts, limit, until_id, exclude_user_ids)
+            },
+        )
+        .await
+    }
+
+    /// Serve the first page of a public timeline from the cache.
+    ///
+    /// Only viewer-independent first pages are cached; other requests and
+    /// cache errors fall through to `fetch`.
+    async fn cached_timeline<F, Fut>(
+        &self,
+        timeline: CachedTimeline,
+        limit: u64,
+        until_id: Option<&str>,
+        exclude_user_ids: Option<&[String]>,
+        fetch: F,
+    ) -> AppResult<Vec<note::Model>>
+    where
+        F: FnOnce(u64) -> Fut,
+        Fut: std::future::Future<Output = AppResult<Vec<note::Model>>>,
+    {
+        let cacheable = until_id.is_none()
+            && exclude_user_ids.is_none_or(<[String]>::is_empty)
+            && limit <= TIMELINE_CACHE_PAGE_SIZE;
+        let Some((cache, ttl)) = self.timeline_cache.as_ref().filter(|_| cacheable) else {
+            return fetch(limit).await;
+        };
+        let take = usize::try_from(limit).unwrap_or(usize::MAX);
+
+        match cache.get(timeline).await {
+            Ok(Some(notes)) => return Ok(notes.into_iter().take(take).collect()),
+            Ok(None) => {}
+            Err(e) => tracing::warn!(error = %e, ?timeline, "Failed to read timeline cache"),
+        }
+
+        let notes = fetch(TIMELINE_CACHE_PAGE_SIZE).await?;
+        if let Err(e) = cache.set(timeline, &notes, *ttl).await {
+            tracing::warn!(error = %e, ?timeline, "Failed to write timeline cache");
+        }
+        Ok(notes.into_iter().take(take).collect())
+    }
+
+    /// Whether `note` is a pure renote, also ruling out an attached poll.
+    async fn is_pure_renote(&self, note: &note::Model) -> AppResult<bool> {
+        if !is_pure_renote(note) {
+            return Ok(false);
+        }
+        match &self.poll_repo {
+            Some(poll_repo) => Ok(poll_repo.find_by_note_id(&note.id).await?.is_none()),
+            None => Ok(true),
+        }
+    }
+
+    /// Drop cached timeline pages a created or deleted note belongs on.
+    async fn invalidate_timeline_caches(&self, note: &note::Model) {
+        let Some((cache, _)) = &self.timeline_cache else {
+            return;
+        };
+        for timeline in timelines_for_note(note) {
+            if let Err(e) = cache.invalidate(timeline).await {
+                tracing::warn!(error = %e, ?timeline, "Failed to invalidate timeline cache");
+            }
+        }
     }
 
     /// Get home timeline (notes from followed users + own notes).
@@ -926,13 +1345,28 @@ impl NoteService {
         let old_cw = note.cw.clone();
         let old_file_ids: Vec<String> =
             serde_json::from_value(note.file_ids.clone()).unwrap_or_default();
+        let old_visibility = note.visibility.clone();
 
         // Check if anything actually changed
         let text_changed = input.text.is_some();
         let cw_changed = input.cw.is_some();
         let files_changed = input.file_ids.is_some();
+        let new_visibility = input.visibility.clone().filter(|v| *v != old_visibility);
 
-        if !text_changed && !cw_changed && !files_changed {
+        // Already-delivered copies can't be recalled, so only narrowing is allowed
+        if let Some(ref v) = new_visibility
+            && visibility_rank(v) < visibility_rank(&old_visibility)
+        {
+            return Err(AppError::BadRequest(
+                "Note visibility can only be narrowed".to_string(),
+            ));
+        }
+
+        if let Some(ref file_ids) = input.file_ids {
+            self.validate_attachments(user_id, file_ids).await?;
+        }
+
+        if !text_changed && !cw_changed && !files_changed && new_visibility.is_none() {
             // No changes, return the note as-is
             return Ok(note);
         }
@@ -962,7 +1396,7 @@ impl NoteService {
         self.note_repo.create_edit_history(edit_record).await?;
 
         // Update the note
-        let mut active_note: note::ActiveModel = note.into();
+        let mut active_note: note::ActiveModel = note.clone().into();
 
         if let Some(new_text_value) = input.text {
             // Re-extract mentions and hashtags
@@ -990,10 +1424,20 @@ impl NoteService {
             active_note.file_ids = Set(json!(file_ids));
         }
 
+        if let Some(ref visibility) = new_visibility {
+            active_note.visibility = Set(visibility.clone());
+        }
+
         active_note.updated_at = Set(Some(chrono::Utc::now().into()));
 
         let updated_note = self.note_repo.update(active_note).await?;
 
+        // Drop cached pages showing the old text or the old visibility
+        self.invalidate_timeline_caches(&note).await;
+        if new_visibility.is_some() {
+            self.invalidate_timeline_caches(&updated_note).await;
+        }
+
         // Queue ActivityPub Update delivery
         if let Some(ref delivery) = self.delivery
             && let Err(e) = self
@@ -1010,6 +1454,21 @@ impl NoteService {
             tracing::warn!(error = %e, note_id = %updated_note.id, "Failed to publish note updated event");
         }
 
+        // Tell subscribers who lost access to drop the note
+        if new_visibility.is_some()
+            && let Some(ref event_publisher) = self.event_publisher
+            && let Err(e) = event_publisher
+                .publish_note_unavailable(
+                    &updated_note.id,
+                    &updated_note.user_id,
+                    visibility_str(&old_visibility),
+                    visibility_str(&updated_note.visibility),
+                )
+                .await
+        {
+            tracing::warn!(error = %e, note_id = %updated_note.id, "Failed to publish note unavailable event");
+        }
+
         Ok(updated_note)
     }
 
@@ -1055,6 +1514,26 @@ impl NoteService {
     }
 }
 
+/// Wire name of a note visibility.
+const fn visibility_str(visibility: &Visibility) -> &'static str {
+    match visibility {
+        Visibility::Public => "public",
+        Visibility::Home => "home",
+        Visibility::Followers => "followers",
+        Visibility::Specified => "specified",
+    }
+}
+
+/// How widely a visibility reaches; lower is wider.
+const fn visibility_rank(visibility: &Visibility) -> u8 {
+    match visibility {
+        Visibility::Public => 0,
+        Visibility::Home => 1,
+        Visibility::Followers => 2,
+        Visibility::Specified => 3,
+    }
+}
+
 /// Extract @mentions from text.
 fn extract_mentions(text: &str) -> Vec<String> {
     let mut mentions = Vec::new();
@@ -1071,18 +1550,144 @@ fn extract_hashtags(text: &str) -> Vec<String> {
     let mut tags = Vec::new();
     for word in text.split_whitespace() {
         if word.starts_with('#') && word.len() > 1 {
-            tags.push(word[1..].to_lowercase());
+            tags.push(normalize_hashtag(&word[1..]));
         }
     }
     tags
 }
 
+/// Whether `note` only boosts its target: a renote without text, content
+/// warning or files.
+///
+/// Polls live in their own table; [`NoteService`] also checks for one before
+/// treating a renote it did not create as pure.
+#[must_use]
+pub fn is_pure_renote(note: &note::Model) -> bool {
+    note.renote_id.is_some()
+        && note.text.is_none()
+        && note.cw.is_none()
+        && note.file_ids.as_array().is_none_or(Vec::is_empty)
+}
+
+/// Maximum pages loaded to refill a timeline page shortened by collapsing.
+const MAX_COLLAPSE_PAGES: usize = 5;
+
+/// Whether a reply by `author_id` continues its own author's thread.
+#[must_use]
+pub fn is_self_reply(author_id: &str, parent_author_id: &str) -> bool {
+    author_id == parent_author_id
+}
+
+/// Load a timeline page, optionally collapsing self-reply threads.
+///
+/// `fetch` loads `limit` notes older than the given id, newest first. Since
+/// collapsing drops notes, older pages are loaded until `limit` notes remain
+/// or the timeline runs out.
+pub async fn fetch_timeline_page<F, Fut>(
+    limit: u64,
+    until_id: Option<&str>,
+    collapse: bool,
+    mut fetch: F,
+) -> AppResult<Vec<note::Model>>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: std::future::Future<Output = AppResult<Vec<note::Model>>>,
+{
+    let page = fetch(until_id.map(str::to_string)).await?;
+    if !collapse {
+        return Ok(page);
+    }
+
+    let take = usize::try_from(limit).unwrap_or(usize::MAX);
+    let mut loaded = page.len();
+    let mut notes = page;
+    let mut collapsed = collapse_self_replies(notes.clone());
+    for _ in 1..MAX_COLLAPSE_PAGES {
+        if collapsed.len() >= take || loaded < take {
+            break;
+        }
+        let cursor = notes.last().map(|n| n.id.clone());
+        let page = fetch(cursor).await?;
+        loaded = page.len();
+        notes.extend(page);
+        collapsed = collapse_self_replies(notes.clone());
+    }
+
+    collapsed.truncate(take);
+    Ok(collapsed)
+}
+
+/// Collapse consecutive self-replies in a newest-first timeline.
+///
+/// A note is dropped when the note directly above it is its author's reply to
+/// it, so a self-thread is shown once, by its latest post.
+#[must_use]
+pub fn collapse_self_replies(notes: Vec<note::Model>) -> Vec<note::Model> {
+    let mut result: Vec<note::Model> = Vec::with_capacity(notes.len());
+    let mut previous: Option<(String, Option<String>)> = None;
+
+    for note in notes {
+        let continued = previous.as_ref().is_some_and(|(user_id, reply_id)| {
+            is_self_reply(user_id, &note.user_id) && reply_id.as_deref() == Some(note.id.as_str())
+        });
+        previous = Some((note.user_id.clone(), note.reply_id.clone()));
+        if !continued {
+            result.push(note);
+        }
+    }
+
+    result
+}
+
+/// Check attached files against per-note limits.
+fn check_attachment_limits(
+    files: &[drive_file::Model],
+    limits: &AttachmentLimits,
+) -> AppResult<()> {
+    let count = |prefix: &str| {
+        files
+            .iter()
+            .filter(|f| f.content_type.starts_with(prefix))
+            .count()
+    };
+
+    if let Some(max) = limits.max_videos
+        && count("video/") > max
+    {
+        return Err(AppError::BadRequest(format!(
+            "Too many videos attached (max {max})"
+        )));
+    }
+    if let Some(max) = limits.max_images
+        && count("image/") > max
+    {
+        return Err(AppError::BadRequest(format!(
+            "Too many images attached (max {max})"
+        )));
+    }
+    if let Some(max) = limits.max_total_bytes {
+        let total: u64 = files
+            .iter()
+            .map(|f| u64::try_from(f.size).unwrap_or(0))
+            .sum();
+        if total > max {
+            return Err(AppError::BadRequest(format!(
+                "Attachments exceed the total size limit of {max} bytes"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::panic, dead_code)]
 mod tests {
     use super::*;
+    use crate::services::content_policy::ContentPolicy;
+    use crate::services::test_support::DeliveryRecorder;
     use chrono::Utc;
-    use misskey_db::entities::{following, user};
+    use misskey_db::entities::{following, user_profile};
     use sea_orm::{DatabaseBackend, MockDatabase};
     use serde_json::json;
     use std::sync::Arc;
@@ -1104,12 +1709,14 @@ mod tests {
             is_silenced: false,
             is_admin: false,
             is_moderator: false,
+            is_system: false,
             followers_count: 0,
             following_count: 0,
             notes_count: 0,
             inbox: None,
             shared_inbox: None,
             featured: None,
+            followers_uri: None,
             uri: None,
             last_fetched_at: None,
             token: Some("test_token".to_string()),
@@ -1195,6 +1802,44 @@ mod tests {
         assert_eq!(tags, vec!["rust", "programming"]);
     }
 
+    #[tokio::test]
+    async fn test_unicode_equivalent_hashtags_share_stored_tag() {
+        let precomposed = extract_hashtags("#caf\u{e9}");
+        let combining = extract_hashtags("#Cafe\u{301}");
+        assert_eq!(precomposed, vec!["caf\u{e9}"]);
+        assert_eq!(precomposed, combining);
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[create_test_note("note1", "user1", Some("#caf\u{e9}"))]])
+                .into_connection(),
+        );
+        let service = NoteService::new(
+            NoteRepository::new(note_db.clone()),
+            UserRepository::new(Arc::new(
+                MockDatabase::new(DatabaseBackend::Postgres).into_connection(),
+            )),
+            FollowingRepository::new(Arc::new(
+                MockDatabase::new(DatabaseBackend::Postgres).into_connection(),
+            )),
+        );
+
+        // Searching with the combining form finds the note stored with the precomposed one
+        let notes = service
+            .search_by_tag("cafe\u{301}", 10, None)
+            .await
+            .unwrap();
+        assert_eq!(notes.len(), 1);
+        drop(service);
+
+        let log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        let values = format!("{:?}", log[0].statements()[0].values);
+        assert!(values.contains("[\\\"caf\u{e9}\\\"]"), "{values}");
+    }
+
     #[test]
     fn test_extract_hashtags_empty() {
         let tags = extract_hashtags("No hashtags here");
@@ -1246,47 +1891,279 @@ mod tests {
         }
     }
 
-    #[tokio::test]
-    async fn test_delete_note_wrong_owner_returns_error() {
-        let note = create_test_note("note1", "user1", Some("Hello"));
+    struct BannedTokenPolicy;
 
-        let note_db = Arc::new(
-            MockDatabase::new(DatabaseBackend::Postgres)
-                .append_query_results([[note.clone()]])
-                .into_connection(),
-        );
+    #[async_trait::async_trait]
+    impl ContentPolicy for BannedTokenPolicy {
+        async fn evaluate(&self, ctx: &CreateNoteContext<'_>) -> PolicyDecision {
+            if ctx.text.is_some_and(|t| t.contains("bannedtoken")) {
+                PolicyDecision::Reject("banned token".to_string())
+            } else {
+                PolicyDecision::Allow
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_note_rejected_by_content_policy() {
+        // No queries are expected: the policy runs before any lookups
+        let note_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
         let user_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
         let following_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
 
-        let note_repo = NoteRepository::new(note_db);
-        let user_repo = UserRepository::new(user_db);
-        let following_repo = FollowingRepository::new(following_db);
+        let mut service = NoteService::new(
+            NoteRepository::new(note_db),
+            UserRepository::new(user_db),
+            FollowingRepository::new(following_db),
+        );
+        service.set_content_policy(Arc::new(BannedTokenPolicy));
 
-        let service = NoteService::new(note_repo, user_repo, following_repo);
+        let input = CreateNoteInput {
+            text: Some("buy now bannedtoken".to_string()),
+            cw: None,
+            visibility: Visibility::Public,
+            reply_id: None,
+            renote_id: None,
+            file_ids: vec![],
+            visible_user_ids: vec![],
+            channel_id: None,
+        };
 
-        // Try to delete note owned by user1 as user2
-        let result = service.delete("note1", "user2").await;
-        assert!(result.is_err());
-        match result {
-            Err(AppError::Forbidden(msg)) => {
-                assert!(msg.contains("Cannot delete other user's note"));
-            }
+        match service.create("user1", input).await {
+            Err(AppError::Forbidden(reason)) => assert_eq!(reason, "banned token"),
             _ => panic!("Expected Forbidden error"),
         }
     }
 
+    fn create_test_file(id: &str, content_type: &str) -> drive_file::Model {
+        drive_file::Model {
+            id: id.to_string(),
+            user_id: "user1".to_string(),
+            user_host: None,
+            name: format!("{id}.bin"),
+            content_type: content_type.to_string(),
+            size: 1000,
+            url: format!("https://example.com/files/{id}"),
+            thumbnail_url: None,
+            webpublic_url: None,
+            blurhash: None,
+            width: None,
+            height: None,
+            comment: None,
+            is_sensitive: false,
+            is_link: false,
+            md5: None,
+            storage_key: None,
+            folder_id: None,
+            uri: None,
+            created_at: Utc::now().into(),
+        }
+    }
+
     #[tokio::test]
-    async fn test_get_note_not_found() {
-        let note_db = Arc::new(
+    async fn test_create_note_rejects_too_many_videos() {
+        let note_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let user_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let following_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let drive_db = Arc::new(
             MockDatabase::new(DatabaseBackend::Postgres)
-                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([[
+                    create_test_file("file1", "video/mp4"),
+                    create_test_file("file2", "video/webm"),
+                ]])
                 .into_connection(),
         );
-        let user_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
-        let following_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
 
-        let note_repo = NoteRepository::new(note_db);
-        let user_repo = UserRepository::new(user_db);
+        let mut service = NoteService::new(
+            NoteRepository::new(note_db),
+            UserRepository::new(user_db),
+            FollowingRepository::new(following_db),
+        );
+        service.set_attachment_validation(
+            DriveFileRepository::new(drive_db),
+            AttachmentLimits {
+                max_videos: Some(1),
+                ..Default::default()
+            },
+        );
+
+        let input = CreateNoteInput {
+            text: Some("Two clips".to_string()),
+            cw: None,
+            visibility: Visibility::Public,
+            reply_id: None,
+            renote_id: None,
+            file_ids: vec!["file1".to_string(), "file2".to_string()],
+            visible_user_ids: vec![],
+            channel_id: None,
+        };
+
+        let result = service.create("user1", input).await;
+        assert!(
+            matches!(result, Err(AppError::BadRequest(ref msg)) if msg == "Too many videos attached (max 1)")
+        );
+    }
+
+    #[test]
+    fn test_attachments_within_limits_pass() {
+        let limits = AttachmentLimits {
+            max_total_bytes: Some(3000),
+            max_videos: Some(1),
+            max_images: Some(2),
+        };
+        let files = [
+            create_test_file("file1", "video/mp4"),
+            create_test_file("file2", "image/png"),
+            create_test_file("file3", "image/jpeg"),
+        ];
+        assert!(check_attachment_limits(&files, &limits).is_ok());
+
+        let too_large = AttachmentLimits {
+            max_total_bytes: Some(2999),
+            ..limits
+        };
+        assert!(matches!(
+            check_attachment_limits(&files, &too_large),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delete_note_wrong_owner_returns_error() {
+        let note = create_test_note("note1", "user1", Some("Hello"));
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[note.clone()]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let following_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let note_repo = NoteRepository::new(note_db);
+        let user_repo = UserRepository::new(user_db);
+        let following_repo = FollowingRepository::new(following_db);
+
+        let service = NoteService::new(note_repo, user_repo, following_repo);
+
+        // Try to delete note owned by user1 as user2
+        let result = service.delete("note1", "user2").await;
+        assert!(result.is_err());
+        match result {
+            Err(AppError::Forbidden(msg)) => {
+                assert!(msg.contains("Cannot delete other user's note"));
+            }
+            _ => panic!("Expected Forbidden error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_returns_deleted_note_for_redraft() {
+        let note = create_test_note("note1", "user1", Some("Hello"));
+        let exec_ok = sea_orm::MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        };
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[note]])
+                .append_exec_results([exec_ok.clone()])
+                .append_query_results([Vec::<note::Model>::new()])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_exec_results([exec_ok])
+                .into_connection(),
+        );
+        let following_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let service = NoteService::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            UserRepository::new(user_db),
+            FollowingRepository::new(following_db),
+        );
+
+        let deleted = service.delete("note1", "user1").await.unwrap();
+        assert_eq!(deleted.text.as_deref(), Some("Hello"));
+
+        // The note is gone afterwards
+        assert!(matches!(
+            service.get("note1").await,
+            Err(AppError::NoteNotFound(_))
+        ));
+        drop(service);
+
+        let log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert!(
+            log.iter()
+                .flat_map(sea_orm::Transaction::statements)
+                .any(|stmt| stmt.sql.starts_with(r#"DELETE FROM "note""#))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_compose_warns_on_self_muted_word() {
+        let muted = misskey_db::entities::word_filter::Model {
+            id: "filter1".to_string(),
+            user_id: "user1".to_string(),
+            phrase: "spoiler".to_string(),
+            is_regex: false,
+            case_sensitive: false,
+            whole_word: true,
+            action: FilterAction::Warn,
+            context: misskey_db::entities::word_filter::FilterContext::Home,
+            expires_at: None,
+            match_count: 0,
+            created_at: Utc::now().into(),
+            updated_at: None,
+            group_id: None,
+        };
+        let filter_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[muted]])
+                .into_connection(),
+        );
+        let note_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let user_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let following_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let mut service = NoteService::new(
+            NoteRepository::new(note_db),
+            UserRepository::new(user_db),
+            FollowingRepository::new(following_db),
+        );
+        service.set_word_filter_service(WordFilterService::new(
+            misskey_db::repositories::WordFilterRepository::new(filter_db),
+        ));
+
+        let checked = service
+            .check_compose("user1", Some("Huge Spoiler for the finale"), None)
+            .await
+            .unwrap();
+
+        assert_eq!(checked.warnings.len(), 1);
+        assert_eq!(checked.warnings[0].filter_id, "filter1");
+        assert_eq!(checked.warnings[0].phrase, "spoiler");
+        assert_eq!(checked.warnings[0].action, "warn");
+    }
+
+    #[tokio::test]
+    async fn test_get_note_not_found() {
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new()])
+                .into_connection(),
+        );
+        let user_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let following_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let note_repo = NoteRepository::new(note_db);
+        let user_repo = UserRepository::new(user_db);
         let following_repo = FollowingRepository::new(following_db);
 
         let service = NoteService::new(note_repo, user_repo, following_repo);
@@ -1322,6 +2199,372 @@ mod tests {
         assert_eq!(result.len(), 2);
     }
 
+    #[tokio::test]
+    async fn test_self_reply_is_neither_notified_nor_counted() {
+        let parent = create_test_note("note1", "user1", Some("Thread start"));
+        let mut reply = create_test_note("note2", "user1", Some("Continued"));
+        reply.reply_id = Some("note1".to_string());
+        reply.thread_id = Some("note1".to_string());
+
+        let exec_ok = sea_orm::MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        };
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[parent], [reply]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[create_test_user("user1", "alice")]])
+                .append_exec_results([exec_ok])
+                .into_connection(),
+        );
+        let following_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let notification_db =
+            Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let mut service = NoteService::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            UserRepository::new(user_db),
+            FollowingRepository::new(following_db),
+        );
+        service.set_notification_service(NotificationService::new(
+            misskey_db::repositories::NotificationRepository::new(Arc::clone(&notification_db)),
+        ));
+
+        let input = CreateNoteInput {
+            text: Some("Continued".to_string()),
+            cw: None,
+            visibility: Visibility::Public,
+            reply_id: Some("note1".to_string()),
+            renote_id: None,
+            file_ids: vec![],
+            visible_user_ids: vec![],
+            channel_id: None,
+        };
+
+        let note = service.create("user1", input).await.unwrap();
+        assert_eq!(note.reply_id.as_deref(), Some("note1"));
+        drop(service);
+
+        let log = Arc::try_unwrap(notification_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert!(log.is_empty());
+
+        // The parent's replies_count is left alone
+        let log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert!(
+            !log.iter()
+                .flat_map(sea_orm::Transaction::statements)
+                .any(|stmt| stmt.sql.starts_with("UPDATE") && stmt.sql.contains("replies_count"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_timeline_page_refills_collapsed_page() {
+        // A four-post self-thread followed by two unrelated notes, newest first
+        let mut timeline = Vec::new();
+        for i in (1..=4).rev() {
+            let mut note = create_test_note(&format!("t{i}"), "user1", Some("thread"));
+            if i > 1 {
+                note.reply_id = Some(format!("t{}", i - 1));
+            }
+            timeline.push(note);
+        }
+        timeline.push(create_test_note("a", "user2", Some("other")));
+        timeline.push(create_test_note("b", "user3", Some("other")));
+
+        let fetch = |until_id: Option<String>| {
+            let start = until_id.map_or(0, |id| {
+                timeline.iter().position(|n| n.id == id).unwrap() + 1
+            });
+            let page: Vec<_> = timeline.iter().skip(start).take(3).cloned().collect();
+            async move { Ok(page) }
+        };
+
+        let notes = fetch_timeline_page(3, None, true, fetch).await.unwrap();
+        let ids: Vec<_> = notes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["t4", "a", "b"]);
+
+        let notes = fetch_timeline_page(3, None, false, fetch).await.unwrap();
+        assert_eq!(notes.len(), 3);
+    }
+
+    #[test]
+    fn test_collapse_self_replies_keeps_latest_of_thread() {
+        let root = create_test_note("note1", "user1", Some("1/3"));
+        let mut second = create_test_note("note2", "user1", Some("2/3"));
+        second.reply_id = Some("note1".to_string());
+        let mut third = create_test_note("note3", "user1", Some("3/3"));
+        third.reply_id = Some("note2".to_string());
+        let mut other = create_test_note("note4", "user2", Some("Nice thread"));
+        other.reply_id = Some("note3".to_string());
+
+        // Newest first, as timelines are returned
+        let collapsed = collapse_self_replies(vec![other, third, second, root]);
+        let ids: Vec<_> = collapsed.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["note4", "note3"]);
+    }
+
+    #[tokio::test]
+    async fn test_renote_of_pure_renote_announces_original() {
+        let original = create_test_note("note1", "user2", Some("Original"));
+        let mut boost = create_test_note("note2", "user3", None);
+        boost.renote_id = Some("note1".to_string());
+        let mut created = create_test_note("note3", "user1", None);
+        created.renote_id = Some("note1".to_string());
+
+        let exec_ok = sea_orm::MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        };
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[boost], [original], [created]])
+                .append_exec_results([exec_ok.clone()])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[create_test_user("user1", "alice")]])
+                .append_exec_results([exec_ok])
+                .into_connection(),
+        );
+        let following_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[following::Model {
+                    id: "f1".to_string(),
+                    follower_id: "remote1".to_string(),
+                    followee_id: "user1".to_string(),
+                    follower_host: Some("remote.example".to_string()),
+                    followee_host: None,
+                    followee_inbox: Some("https://remote.example/inbox".to_string()),
+                    followee_shared_inbox: None,
+                    created_at: Utc::now().into(),
+                }]])
+                .into_connection(),
+        );
+
+        let recorder = Arc::new(DeliveryRecorder::default());
+        let service = NoteService::with_delivery(
+            NoteRepository::new(Arc::clone(&note_db)),
+            UserRepository::new(user_db),
+            FollowingRepository::new(following_db),
+            recorder.clone(),
+            "https://local.example".to_string(),
+        );
+
+        let input = CreateNoteInput {
+            text: None,
+            cw: None,
+            visibility: Visibility::Public,
+            reply_id: None,
+            renote_id: Some("note2".to_string()),
+            file_ids: vec![],
+            visible_user_ids: vec![],
+            channel_id: None,
+        };
+        service.create("user1", input).await.unwrap();
+        drop(service);
+
+        let announces = recorder.activities("announce");
+        assert_eq!(announces.len(), 1);
+        assert_eq!(announces[0]["object"], "https://local.example/notes/note1");
+
+        // The stored renote points at the original as well
+        let log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert!(
+            log.iter()
+                .flat_map(sea_orm::Transaction::statements)
+                .any(|stmt| stmt.sql.starts_with(r#"INSERT INTO "note""#)
+                    && format!("{:?}", stmt.values).contains(r#"String(Some("note1"))"#))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_media_only_renote_is_created_not_announced() {
+        let original = create_test_note("note1", "user2", Some("Original"));
+        let mut created = create_test_note("note2", "user1", None);
+        created.renote_id = Some("note1".to_string());
+        created.file_ids = json!(["file1"]);
+        assert!(!is_pure_renote(&created));
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[original], [created]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[create_test_user("user1", "alice")]])
+                .append_exec_results([sea_orm::MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                }])
+                .into_connection(),
+        );
+        let following_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![follower_of("user1", "https://a.example/inbox")]])
+                .into_connection(),
+        );
+
+        let recorder = Arc::new(DeliveryRecorder::default());
+        let service = NoteService::with_delivery(
+            NoteRepository::new(Arc::clone(&note_db)),
+            UserRepository::new(user_db),
+            FollowingRepository::new(following_db),
+            recorder.clone(),
+            "https://local.example".to_string(),
+        );
+
+        let input = CreateNoteInput {
+            text: None,
+            cw: None,
+            visibility: Visibility::Public,
+            reply_id: None,
+            renote_id: Some("note1".to_string()),
+            file_ids: vec!["file1".to_string()],
+            visible_user_ids: vec![],
+            channel_id: None,
+        };
+        service.create("user1", input).await.unwrap();
+        drop(service);
+
+        assert!(recorder.activities("announce").is_empty());
+        assert_eq!(recorder.activities("create_note").len(), 1);
+
+        // A quote does not count as a renote of its target
+        let log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert!(
+            !log.iter()
+                .flat_map(sea_orm::Transaction::statements)
+                .any(|stmt| stmt.sql.starts_with(r#"UPDATE "note" SET "renote_count""#))
+        );
+    }
+
+    fn follower_of(followee_id: &str, inbox: &str) -> following::Model {
+        following::Model {
+            id: format!("f-{inbox}"),
+            follower_id: format!("follower-{inbox}"),
+            followee_id: followee_id.to_string(),
+            follower_host: Some("remote.example".to_string()),
+            followee_host: None,
+            followee_inbox: Some(inbox.to_string()),
+            followee_shared_inbox: None,
+            created_at: Utc::now().into(),
+        }
+    }
+
+    async fn quote_create_inboxes(quote_reach: QuoteReach) -> Vec<String> {
+        let original = create_test_note("note1", "user2", Some("Original"));
+        let mut quote = create_test_note("note2", "user1", Some("Quoting this"));
+        quote.renote_id = Some("note1".to_string());
+
+        let following_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([
+                    vec![follower_of("user1", "https://a.example/inbox")],
+                    vec![follower_of("user2", "https://b.example/inbox")],
+                ])
+                .into_connection(),
+        );
+        let mut service = NoteService::new(
+            NoteRepository::new(Arc::new(
+                MockDatabase::new(DatabaseBackend::Postgres).into_connection(),
+            )),
+            UserRepository::new(Arc::new(
+                MockDatabase::new(DatabaseBackend::Postgres).into_connection(),
+            )),
+            FollowingRepository::new(following_db),
+        );
+        service.set_quote_reach(quote_reach);
+
+        let recorder = Arc::new(DeliveryRecorder::default());
+        let delivery: DeliveryService = recorder.clone();
+        service
+            .queue_create_delivery(
+                &quote,
+                &create_test_user("user1", "alice"),
+                Some(&original),
+                &delivery,
+            )
+            .await
+            .unwrap();
+
+        recorder.inboxes("create_note")
+    }
+
+    #[tokio::test]
+    async fn test_quote_reaches_only_renoter_followers_by_default() {
+        let inboxes = quote_create_inboxes(QuoteReach::default()).await;
+        assert_eq!(inboxes, vec!["https://a.example/inbox".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_quote_reach_can_include_author_followers() {
+        let inboxes = quote_create_inboxes(QuoteReach::IncludeAuthorFollowers).await;
+        assert_eq!(
+            inboxes,
+            vec![
+                "https://a.example/inbox".to_string(),
+                "https://b.example/inbox".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hydrate_loads_authors_in_one_query() {
+        let authors = ["user1", "user2", "user3"];
+        let notes: Vec<_> = (0..10)
+            .map(|i| create_test_note(&format!("note{i}"), authors[i % 3], Some("Hello")))
+            .collect();
+
+        let note_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([authors
+                    .iter()
+                    .map(|id| create_test_user(id, id))
+                    .collect::<Vec<_>>()])
+                .into_connection(),
+        );
+        let following_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let service = NoteService::new(
+            NoteRepository::new(note_db),
+            UserRepository::new(Arc::clone(&user_db)),
+            FollowingRepository::new(following_db),
+        );
+
+        let hydrated = service.hydrate(notes).await.unwrap();
+        assert_eq!(hydrated.len(), 10);
+        for (i, item) in hydrated.iter().enumerate() {
+            assert_eq!(item.note.id, format!("note{i}"));
+            assert_eq!(item.author.id, item.note.user_id);
+        }
+        drop(service);
+
+        let log = Arc::try_unwrap(user_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert_eq!(log.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_home_timeline() {
         let following1 = following::Model {
@@ -1384,4 +2627,281 @@ mod tests {
         let result = service.user_notes("user1", 10, None).await.unwrap();
         assert_eq!(result.len(), 2);
     }
+
+    fn quote_policy_profile(user_id: &str, disallow_quotes: bool) -> user_profile::Model {
+        user_profile::Model {
+            user_id: user_id.to_string(),
+            password: None,
+            email: None,
+            email_verified: false,
+            email_undeliverable: false,
+            two_factor_secret: None,
+            two_factor_enabled: false,
+            two_factor_pending: None,
+            two_factor_backup_codes: None,
+            auto_accept_followed: false,
+            always_mark_nsfw: false,
+            pinned_page_ids: json!([]),
+            pinned_note_ids: json!([]),
+            fields: json!([]),
+            muted_words: json!([]),
+            user_css: None,
+            birthday: None,
+            location: None,
+            lang: None,
+            pronouns: None,
+            also_known_as: None,
+            moved_to_uri: None,
+            hide_bots: false,
+            default_reaction: None,
+            receive_dm_from_followers_only: false,
+            secure_fetch_only: false,
+            disallow_quotes,
+            hide_online_status: false,
+            default_note_visibility: Visibility::Public,
+            theme: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn quote_input(renote_id: &str) -> CreateNoteInput {
+        CreateNoteInput {
+            text: Some("My thoughts".to_string()),
+            cw: None,
+            visibility: Visibility::Public,
+            reply_id: None,
+            renote_id: Some(renote_id.to_string()),
+            file_ids: vec![],
+            visible_user_ids: vec![],
+            channel_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quote_of_disallowing_local_user_is_rejected() {
+        // Quotes carrying only media are quotes too
+        let media_only = CreateNoteInput {
+            text: None,
+            file_ids: vec!["file1".to_string()],
+            ..quote_input("note1")
+        };
+
+        for input in [quote_input("note1"), media_only] {
+            let note_db = Arc::new(
+                MockDatabase::new(DatabaseBackend::Postgres)
+                    .append_query_results([[create_test_note("note1", "user2", Some("Original"))]])
+                    .into_connection(),
+            );
+            let profile_db = Arc::new(
+                MockDatabase::new(DatabaseBackend::Postgres)
+                    .append_query_results([[quote_policy_profile("user2", true)]])
+                    .into_connection(),
+            );
+            let user_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+            let following_db =
+                Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+            let mut service = NoteService::new(
+                NoteRepository::new(note_db),
+                UserRepository::new(user_db),
+                FollowingRepository::new(following_db),
+            );
+            service.set_profile_repo(UserProfileRepository::new(profile_db));
+
+            let result = service.create("user1", input).await;
+            assert!(matches!(result, Err(AppError::Forbidden(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quote_policy_ignores_own_and_remote_notes() {
+        // No profile lookups are expected, so an empty mock must not be queried
+        let profile_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let mut service = NoteService::new(
+            NoteRepository::new(Arc::clone(&db)),
+            UserRepository::new(Arc::clone(&db)),
+            FollowingRepository::new(db),
+        );
+        service.set_profile_repo(UserProfileRepository::new(profile_db));
+
+        let own = create_test_note("note1", "user1", Some("Mine"));
+        service.check_quote_allowed("user1", &own).await.unwrap();
+
+        let mut remote = create_test_note("note2", "remote1", Some("Theirs"));
+        remote.user_host = Some("remote.example".to_string());
+        service.check_quote_allowed("user1", &remote).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_new_public_note_invalidates_cached_local_timeline() {
+        let old = create_test_note("note1", "user2", Some("Old"));
+        let mut created = create_test_note("note2", "user1", Some("Fresh"));
+        created.is_local = true;
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([
+                    vec![old.clone()],
+                    vec![created.clone()],
+                    vec![created, old],
+                ])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[create_test_user("user1", "alice")]])
+                .append_exec_results([sea_orm::MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                }])
+                .into_connection(),
+        );
+        let following_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let mut service = NoteService::new(
+            NoteRepository::new(note_db),
+            UserRepository::new(user_db),
+            FollowingRepository::new(following_db),
+        );
+        service.set_timeline_cache(
+            Arc::new(crate::services::InMemoryTimelineCache::new()),
+            Duration::from_mins(1),
+        );
+
+        // The second request is served from the cache
+        let first = service.local_timeline(10, None, None).await.unwrap();
+        let cached = service.local_timeline(10, None, None).await.unwrap();
+        assert_eq!(first, cached);
+        assert_eq!(cached.len(), 1);
+
+        let input = CreateNoteInput {
+            text: Some("Fresh".to_string()),
+            cw: None,
+            visibility: Visibility::Public,
+            reply_id: None,
+            renote_id: None,
+            file_ids: vec![],
+            visible_user_ids: vec![],
+            channel_id: None,
+        };
+        service.create("user1", input).await.unwrap();
+
+        let refreshed = service.local_timeline(10, None, None).await.unwrap();
+        assert_eq!(refreshed.len(), 2);
+        assert_eq!(refreshed[0].id, "note2");
+    }
+
+    #[tokio::test]
+    async fn test_edited_note_invalidates_cached_local_timeline() {
+        let mut original = create_test_note("note1", "user1", Some("Old"));
+        original.is_local = true;
+        let mut edited = original.clone();
+        edited.text = Some("Edited".to_string());
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[original.clone()], [original.clone()]])
+                .append_query_results([[note_edit::Model {
+                    id: "edit1".to_string(),
+                    note_id: "note1".to_string(),
+                    old_text: Some("Old".to_string()),
+                    new_text: Some("Edited".to_string()),
+                    old_cw: None,
+                    new_cw: None,
+                    old_file_ids: json!([]),
+                    new_file_ids: json!([]),
+                    edited_at: chrono::Utc::now().into(),
+                }]])
+                .append_query_results([[edited.clone()], [edited]])
+                .into_connection(),
+        );
+        let db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let mut service = NoteService::new(
+            NoteRepository::new(note_db),
+            UserRepository::new(Arc::clone(&db)),
+            FollowingRepository::new(db),
+        );
+        service.set_timeline_cache(
+            Arc::new(crate::services::InMemoryTimelineCache::new()),
+            Duration::from_mins(1),
+        );
+
+        let cached = service.local_timeline(10, None, None).await.unwrap();
+        assert_eq!(cached[0].text.as_deref(), Some("Old"));
+
+        let input = UpdateNoteInput {
+            note_id: "note1".to_string(),
+            text: Some(Some("Edited".to_string())),
+            cw: None,
+            file_ids: None,
+            visibility: None,
+        };
+        service.update("user1", input).await.unwrap();
+
+        let refreshed = service.local_timeline(10, None, None).await.unwrap();
+        assert_eq!(refreshed[0].text.as_deref(), Some("Edited"));
+    }
+
+    #[tokio::test]
+    async fn test_local_note_gets_canonical_uri_and_templated_url() {
+        let mut created = create_test_note("note1", "user1", Some("Hello"));
+        created.is_local = true;
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[created]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[create_test_user("user1", "alice")]])
+                .append_exec_results([sea_orm::MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                }])
+                .into_connection(),
+        );
+        let following_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let mut service = NoteService::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            UserRepository::new(user_db),
+            FollowingRepository::new(following_db),
+        );
+        service.set_note_urls(
+            "https://local.example".to_string(),
+            "/@{username}/{id}".to_string(),
+        );
+
+        let input = CreateNoteInput {
+            text: Some("Hello".to_string()),
+            cw: None,
+            visibility: Visibility::Public,
+            reply_id: None,
+            renote_id: None,
+            file_ids: vec![],
+            visible_user_ids: vec![],
+            channel_id: None,
+        };
+        service.create("user1", input).await.unwrap();
+        drop(service);
+
+        let log = Arc::try_unwrap(note_db).unwrap().into_transaction_log();
+        let insert = log
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .map(|stmt| format!("{:?}", stmt.values))
+            .find(|values| values.contains("https://local.example/notes/"))
+            .unwrap();
+        let note_id = insert
+            .split("https://local.example/notes/")
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .unwrap();
+        assert!(!note_id.is_empty());
+        assert!(insert.contains(&format!("https://local.example/@alice/{note_id}")));
+    }
 }
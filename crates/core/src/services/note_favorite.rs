@@ -1,5 +1,7 @@
 //! Note favorite (bookmark) service.
 
+use std::collections::HashSet;
+
 use misskey_common::{AppError, AppResult, IdGenerator};
 use misskey_db::{
     entities::note_favorite,
@@ -65,6 +67,20 @@ impl NoteFavoriteService {
         self.favorite_repo.is_favorited(user_id, note_id).await
     }
 
+    /// Get which of the given notes are favorited by user.
+    pub async fn favorited_note_ids(
+        &self,
+        user_id: &str,
+        note_ids: &[String],
+    ) -> AppResult<HashSet<String>> {
+        Ok(self
+            .favorite_repo
+            .find_favorited_note_ids(user_id, note_ids)
+            .await?
+            .into_iter()
+            .collect())
+    }
+
     /// Get user's favorites (paginated).
     pub async fn get_favorites(
         &self,
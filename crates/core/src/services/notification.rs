@@ -2,13 +2,19 @@
 
 use crate::services::event_publisher::EventPublisherService;
 use crate::services::jobs::JobSender;
+use crate::services::note::is_self_reply;
+use crate::services::notification_debounce::{
+    NotificationDebouncerService, REACTION_AGGREGATION_WINDOW, reaction_debounce_key,
+};
 use crate::services::push_notification::{PushNotificationType, PushPayload};
+use crate::services::thread_muting::ThreadMutingService;
 use misskey_common::{AppResult, IdGenerator};
 use misskey_db::{
     entities::notification::{self, NotificationType},
     repositories::NotificationRepository,
 };
 use sea_orm::Set;
+use serde_json::json;
 
 /// Notification service for business logic.
 #[derive(Clone)]
@@ -16,6 +22,8 @@ pub struct NotificationService {
     notification_repo: NotificationRepository,
     event_publisher: Option<EventPublisherService>,
     job_sender: Option<JobSender>,
+    thread_muting_service: Option<ThreadMutingService>,
+    debouncer: Option<NotificationDebouncerService>,
     id_gen: IdGenerator,
 }
 
@@ -27,6 +35,8 @@ impl NotificationService {
             notification_repo,
             event_publisher: None,
             job_sender: None,
+            thread_muting_service: None,
+            debouncer: None,
             id_gen: IdGenerator::new(),
         }
     }
@@ -41,6 +51,24 @@ impl NotificationService {
         self.job_sender = Some(job_sender);
     }
 
+    /// Set the thread muting service used to suppress notifications from muted threads.
+    pub fn set_thread_muting_service(&mut self, thread_muting_service: ThreadMutingService) {
+        self.thread_muting_service = Some(thread_muting_service);
+    }
+
+    /// Set the debouncer used to aggregate bursts of reaction notifications.
+    pub fn set_debouncer(&mut self, debouncer: NotificationDebouncerService) {
+        self.debouncer = Some(debouncer);
+    }
+
+    /// Check if the notifiee has muted the thread of a note.
+    async fn is_thread_muted(&self, notifiee_id: &str, note_id: &str) -> AppResult<bool> {
+        match &self.thread_muting_service {
+            Some(service) => service.is_note_muted(notifiee_id, note_id).await,
+            None => Ok(false),
+        }
+    }
+
     /// Create a follow notification.
     pub async fn create_follow_notification(
         &self,
@@ -71,12 +99,18 @@ impl NotificationService {
     }
 
     /// Create a mention notification.
+    ///
+    /// Returns `None` when the notifiee has muted the note's thread.
     pub async fn create_mention_notification(
         &self,
         notifiee_id: &str,
         notifier_id: &str,
         note_id: &str,
-    ) -> AppResult<notification::Model> {
+    ) -> AppResult<Option<notification::Model>> {
+        if self.is_thread_muted(notifiee_id, note_id).await? {
+            return Ok(None);
+        }
+
         // Don't notify yourself
         if notifiee_id == notifier_id {
             return self
@@ -87,7 +121,8 @@ impl NotificationService {
                     Some(note_id),
                     None,
                 )
-                .await;
+                .await
+                .map(Some);
         }
 
         self.create_internal(
@@ -98,26 +133,25 @@ impl NotificationService {
             None,
         )
         .await
+        .map(Some)
     }
 
     /// Create a reply notification.
+    ///
+    /// Returns `None` when the notifiee has muted the note's thread.
     pub async fn create_reply_notification(
         &self,
         notifiee_id: &str,
         notifier_id: &str,
         note_id: &str,
-    ) -> AppResult<notification::Model> {
-        // Don't notify yourself
-        if notifiee_id == notifier_id {
-            return self
-                .create_internal(
-                    notifiee_id,
-                    Some(notifier_id),
-                    NotificationType::Reply,
-                    Some(note_id),
-                    None,
-                )
-                .await;
+    ) -> AppResult<Option<notification::Model>> {
+        // Don't notify yourself about self-replies
+        if is_self_reply(notifier_id, notifiee_id) {
+            return Ok(None);
+        }
+
+        if self.is_thread_muted(notifiee_id, note_id).await? {
+            return Ok(None);
         }
 
         self.create_internal(
@@ -128,15 +162,22 @@ impl NotificationService {
             None,
         )
         .await
+        .map(Some)
     }
 
     /// Create a renote notification.
+    ///
+    /// Returns `None` when the notifiee has muted the note's thread.
     pub async fn create_renote_notification(
         &self,
         notifiee_id: &str,
         notifier_id: &str,
         note_id: &str,
-    ) -> AppResult<notification::Model> {
+    ) -> AppResult<Option<notification::Model>> {
+        if self.is_thread_muted(notifiee_id, note_id).await? {
+            return Ok(None);
+        }
+
         // Don't notify yourself
         if notifiee_id == notifier_id {
             return self
@@ -147,7 +188,8 @@ impl NotificationService {
                     Some(note_id),
                     None,
                 )
-                .await;
+                .await
+                .map(Some);
         }
 
         self.create_internal(
@@ -158,15 +200,22 @@ impl NotificationService {
             None,
         )
         .await
+        .map(Some)
     }
 
     /// Create a quote notification.
+    ///
+    /// Returns `None` when the notifiee has muted the note's thread.
     pub async fn create_quote_notification(
         &self,
         notifiee_id: &str,
         notifier_id: &str,
         note_id: &str,
-    ) -> AppResult<notification::Model> {
+    ) -> AppResult<Option<notification::Model>> {
+        if self.is_thread_muted(notifiee_id, note_id).await? {
+            return Ok(None);
+        }
+
         // Don't notify yourself
         if notifiee_id == notifier_id {
             return self
@@ -177,7 +226,8 @@ impl NotificationService {
                     Some(note_id),
                     None,
                 )
-                .await;
+                .await
+                .map(Some);
         }
 
         self.create_internal(
@@ -188,30 +238,63 @@ impl NotificationService {
             None,
         )
         .await
+        .map(Some)
     }
 
     /// Create a reaction notification.
+    ///
+    /// With a debouncer configured, reactions from other users within
+    /// [`REACTION_AGGREGATION_WINDOW`] are folded into a single notification
+    /// instead of creating (and pushing) one each. Debouncer failures are
+    /// logged and the notification is created without aggregation.
+    ///
+    /// Returns `None` when the notifiee has muted the note's thread.
     pub async fn create_reaction_notification(
         &self,
         notifiee_id: &str,
         notifier_id: &str,
         note_id: &str,
         reaction: &str,
-    ) -> AppResult<notification::Model> {
-        // Don't notify yourself
-        if notifiee_id == notifier_id {
-            return self
-                .create_internal(
-                    notifiee_id,
-                    Some(notifier_id),
-                    NotificationType::Reaction,
-                    Some(note_id),
-                    Some(reaction),
-                )
-                .await;
+    ) -> AppResult<Option<notification::Model>> {
+        if self.is_thread_muted(notifiee_id, note_id).await? {
+            return Ok(None);
         }
 
-        self.create_internal(
+        let notification_id = self.id_gen.generate();
+        if notifiee_id != notifier_id
+            && let Some(ref debouncer) = self.debouncer
+        {
+            let key = reaction_debounce_key(note_id, notifiee_id);
+            let owner = match debouncer
+                .claim(&key, &notification_id, REACTION_AGGREGATION_WINDOW)
+                .await
+            {
+                Ok(owner) => owner,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to debounce reaction notification");
+                    None
+                }
+            };
+            if let Some(owner) = owner
+                && let Some(aggregated) = self
+                    .aggregate_reaction(
+                        &owner,
+                        notification_id.clone(),
+                        notifiee_id,
+                        notifier_id,
+                        reaction,
+                    )
+                    .await?
+            {
+                if let Err(e) = debouncer.transfer(&key, &aggregated.id).await {
+                    tracing::warn!(error = %e, "Failed to hand over reaction notification window");
+                }
+                return Ok(Some(aggregated));
+            }
+        }
+
+        self.create_with_id(
+            notification_id,
             notifiee_id,
             Some(notifier_id),
             NotificationType::Reaction,
@@ -219,6 +302,69 @@ impl NotificationService {
             Some(reaction),
         )
         .await
+        .map(Some)
+    }
+
+    /// Fold a reaction into the notification that owns the current window.
+    ///
+    /// The aggregate is updated in place and takes `notification_id` so that
+    /// it moves to the top of the list, recording every reacting user in `custom_data`
+    /// so that clients can render "N people reacted". No push is sent; the
+    /// owner already sent the one push for this window.
+    async fn aggregate_reaction(
+        &self,
+        owner_id: &str,
+        notification_id: String,
+        notifiee_id: &str,
+        notifier_id: &str,
+        reaction: &str,
+    ) -> AppResult<Option<notification::Model>> {
+        let Some(existing) = self.notification_repo.find_by_id(owner_id).await? else {
+            return Ok(None);
+        };
+        if existing.notifiee_id != notifiee_id {
+            return Ok(None);
+        }
+
+        let mut user_ids: Vec<String> = existing
+            .custom_data
+            .as_ref()
+            .and_then(|data| data.get("userIds"))
+            .and_then(|ids| serde_json::from_value(ids.clone()).ok())
+            .unwrap_or_else(|| existing.notifier_id.clone().into_iter().collect());
+        if !user_ids.iter().any(|id| id == notifier_id) {
+            user_ids.push(notifier_id.to_string());
+        }
+        let count = user_ids.len();
+
+        let model = notification::ActiveModel {
+            id: Set(notification_id),
+            notifier_id: Set(Some(notifier_id.to_string())),
+            reaction: Set(Some(reaction.to_string())),
+            custom_data: Set(Some(json!({ "userIds": user_ids, "count": count }))),
+            is_read: Set(false),
+            created_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        };
+        let Some(notification) = self.notification_repo.replace(&existing.id, model).await? else {
+            return Ok(None);
+        };
+
+        if let Some(ref event_publisher) = self.event_publisher
+            && let Err(e) = event_publisher
+                .publish_notification(
+                    &notification.id,
+                    notifiee_id,
+                    "reaction",
+                    Some(notifier_id),
+                    notification.note_id.as_deref(),
+                )
+                .await
+        {
+            tracing::warn!(error = %e, "Failed to publish notification event");
+        }
+
+        Ok(Some(notification))
     }
 
     /// Create a follow request notification.
@@ -292,6 +438,27 @@ impl NotificationService {
         reaction: Option<&str>,
     ) -> AppResult<notification::Model> {
         let notification_id = self.id_gen.generate();
+        self.create_with_id(
+            notification_id,
+            notifiee_id,
+            notifier_id,
+            notification_type,
+            note_id,
+            reaction,
+        )
+        .await
+    }
+
+    /// Create a notification with a pre-generated ID.
+    async fn create_with_id(
+        &self,
+        notification_id: String,
+        notifiee_id: &str,
+        notifier_id: Option<&str>,
+        notification_type: NotificationType,
+        note_id: Option<&str>,
+        reaction: Option<&str>,
+    ) -> AppResult<notification::Model> {
         let model = notification::ActiveModel {
             id: Set(notification_id.clone()),
             notifiee_id: Set(notifiee_id.to_string()),
@@ -448,4 +615,135 @@ mod tests {
         let _ = NotificationType::FollowRequestAccepted;
         let _ = NotificationType::App;
     }
+
+    fn reaction_notification(
+        id: &str,
+        notifier_id: &str,
+        custom_data: Option<serde_json::Value>,
+    ) -> notification::Model {
+        notification::Model {
+            id: id.to_string(),
+            notifiee_id: "author".to_string(),
+            notifier_id: Some(notifier_id.to_string()),
+            notification_type: NotificationType::Reaction,
+            note_id: Some("note1".to_string()),
+            follow_request_id: None,
+            reaction: Some("👍".to_string()),
+            custom_data,
+            is_read: false,
+            created_at: chrono::Utc::now().into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reactions_within_window_aggregate_into_one_notification() {
+        use crate::services::notification_debounce::{
+            InMemoryNotificationDebouncer, NotificationDebouncer,
+        };
+        use sea_orm::{DatabaseBackend, MockDatabase};
+        use std::sync::Arc;
+
+        // Each later reaction reads the current aggregate and moves it to a new
+        // ID in a single update
+        let aggregate = |i: usize| {
+            let user_ids: Vec<String> = (0..=i).map(|n| format!("user{n}")).collect();
+            let data = (i > 0).then(|| json!({ "userIds": user_ids, "count": i + 1 }));
+            reaction_notification(&format!("notif{i}"), &format!("user{i}"), data)
+        };
+        let mut db =
+            MockDatabase::new(DatabaseBackend::Postgres).append_query_results([[aggregate(0)]]);
+        for i in 1..3 {
+            db = db.append_query_results([[aggregate(i - 1)], [aggregate(i)]]);
+        }
+        let db = Arc::new(db.into_connection());
+
+        let debouncer = Arc::new(InMemoryNotificationDebouncer::new());
+        let mut service = NotificationService::new(NotificationRepository::new(db.clone()));
+        service.set_debouncer(debouncer.clone());
+        for i in 0..3 {
+            let notification = service
+                .create_reaction_notification("author", &format!("user{i}"), "note1", "👍")
+                .await
+                .unwrap();
+            // The aggregate moves up with every reaction
+            assert_eq!(notification.unwrap().id, format!("notif{i}"));
+        }
+        drop(service);
+
+        // The window now belongs to the latest aggregate
+        let owner = debouncer
+            .claim(
+                &reaction_debounce_key("note1", "author"),
+                "other",
+                REACTION_AGGREGATION_WINDOW,
+            )
+            .await
+            .unwrap();
+        assert_eq!(owner.as_deref(), Some("notif2"));
+
+        let log = Arc::try_unwrap(db).ok().unwrap().into_transaction_log();
+        let statements: Vec<_> = log
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .collect();
+        let count = |prefix: &str| {
+            statements
+                .iter()
+                .filter(|stmt| stmt.sql.starts_with(prefix))
+                .count()
+        };
+        assert_eq!(count(r#"INSERT INTO "notification""#), 1);
+        assert_eq!(count(r#"DELETE FROM "notification""#), 0);
+        let updates: Vec<_> = statements
+            .iter()
+            .filter(|stmt| stmt.sql.starts_with(r#"UPDATE "notification""#))
+            .collect();
+        assert_eq!(updates.len(), 2);
+
+        let values = format!("{:?}", updates[1].values);
+        assert!(values.contains("user2"));
+        assert!(values.contains(r#""count": Number(3)"#));
+    }
+
+    /// Debouncer whose store is unreachable.
+    struct FailingDebouncer;
+
+    #[async_trait::async_trait]
+    impl crate::services::notification_debounce::NotificationDebouncer for FailingDebouncer {
+        async fn claim(
+            &self,
+            _: &str,
+            _: &str,
+            _: std::time::Duration,
+        ) -> AppResult<Option<String>> {
+            Err(misskey_common::AppError::Internal(
+                "Redis error: connection refused".to_string(),
+            ))
+        }
+
+        async fn transfer(&self, _: &str, _: &str) -> AppResult<()> {
+            Err(misskey_common::AppError::Internal(
+                "Redis error: connection refused".to_string(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_debouncer_failure_still_creates_notification() {
+        use sea_orm::{DatabaseBackend, MockDatabase};
+        use std::sync::Arc;
+
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[reaction_notification("notif0", "user0", None)]])
+            .into_connection();
+        let mut service = NotificationService::new(NotificationRepository::new(Arc::new(db)));
+        service.set_debouncer(Arc::new(FailingDebouncer));
+
+        let notification = service
+            .create_reaction_notification("author", "user0", "note1", "👍")
+            .await
+            .unwrap();
+
+        assert_eq!(notification.unwrap().id, "notif0");
+    }
 }
@@ -0,0 +1,132 @@
+//! Debouncing for high-volume notifications.
+//!
+//! A burst of reactions on one note should not produce one notification and
+//! one push per reaction. The first reaction in a window claims the window for
+//! its notification; later reactions in the same window are folded into a
+//! fresh notification that takes over the window.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use misskey_common::{AppError, AppResult};
+
+/// Window within which reactions to the same note are aggregated.
+pub const REACTION_AGGREGATION_WINDOW: Duration = Duration::from_mins(1);
+
+/// Debounce store keyed by an arbitrary string (e.g. note and recipient).
+#[async_trait]
+pub trait NotificationDebouncer: Send + Sync {
+    /// Try to open a window for `key` owned by `notification_id`.
+    ///
+    /// Returns `None` if this call opened the window, or the ID of the
+    /// notification that owns the window that is already open.
+    async fn claim(
+        &self,
+        key: &str,
+        notification_id: &str,
+        window: Duration,
+    ) -> AppResult<Option<String>>;
+
+    /// Hand the open window for `key` over to `notification_id`.
+    ///
+    /// The window keeps its expiry; nothing happens if it already closed.
+    async fn transfer(&self, key: &str, notification_id: &str) -> AppResult<()>;
+}
+
+/// In-process debouncer for single-instance deployments and tests.
+#[derive(Default)]
+pub struct InMemoryNotificationDebouncer {
+    windows: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl InMemoryNotificationDebouncer {
+    /// Create a new in-memory debouncer.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl NotificationDebouncer for InMemoryNotificationDebouncer {
+    async fn claim(
+        &self,
+        key: &str,
+        notification_id: &str,
+        window: Duration,
+    ) -> AppResult<Option<String>> {
+        let mut windows = self
+            .windows
+            .lock()
+            .map_err(|_| AppError::Internal("Debouncer lock poisoned".to_string()))?;
+        let now = Instant::now();
+        windows.retain(|_, (_, expires_at)| *expires_at > now);
+
+        if let Some((owner, _)) = windows.get(key) {
+            return Ok(Some(owner.clone()));
+        }
+        windows.insert(key.to_string(), (notification_id.to_string(), now + window));
+        Ok(None)
+    }
+
+    async fn transfer(&self, key: &str, notification_id: &str) -> AppResult<()> {
+        let mut windows = self
+            .windows
+            .lock()
+            .map_err(|_| AppError::Internal("Debouncer lock poisoned".to_string()))?;
+        if let Some((owner, _)) = windows.get_mut(key) {
+            *owner = notification_id.to_string();
+        }
+        Ok(())
+    }
+}
+
+/// Type alias for the notification debouncer.
+pub type NotificationDebouncerService = Arc<dyn NotificationDebouncer>;
+
+/// Debounce key for reaction notifications on a note.
+#[must_use]
+pub fn reaction_debounce_key(note_id: &str, notifiee_id: &str) -> String {
+    format!("notification:reaction:{note_id}:{notifiee_id}")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_in_memory_claim_within_window() {
+        let debouncer = InMemoryNotificationDebouncer::new();
+        let window = Duration::from_mins(1);
+
+        assert_eq!(debouncer.claim("k", "n1", window).await.unwrap(), None);
+        assert_eq!(
+            debouncer.claim("k", "n2", window).await.unwrap(),
+            Some("n1".to_string())
+        );
+        assert_eq!(debouncer.claim("other", "n3", window).await.unwrap(), None);
+
+        debouncer.transfer("k", "n4").await.unwrap();
+        assert_eq!(
+            debouncer.claim("k", "n5", window).await.unwrap(),
+            Some("n4".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_window_expires() {
+        let debouncer = InMemoryNotificationDebouncer::new();
+
+        assert_eq!(
+            debouncer.claim("k", "n1", Duration::ZERO).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            debouncer.claim("k", "n2", Duration::ZERO).await.unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,363 @@
+//! Password reset service.
+
+use chrono::{Duration, Utc};
+use misskey_common::{AppError, AppResult, IdGenerator};
+use misskey_db::{
+    entities::password_reset_request,
+    repositories::{
+        OAuthRepository, PasswordResetRequestRepository, UserProfileRepository, UserRepository,
+    },
+};
+use sea_orm::Set;
+use sha2::{Digest, Sha256};
+
+use crate::services::email::{EmailNotificationType, EmailService, EmailTemplateVars};
+use crate::services::user::hash_password;
+
+/// How long an emailed reset link stays valid.
+pub const RESET_TOKEN_TTL_MINUTES: i64 = 30;
+
+/// Maximum reset requests per account within [`RESET_REQUEST_WINDOW_MINUTES`].
+pub const MAX_RESET_REQUESTS: u64 = 3;
+
+/// Window for counting reset requests.
+pub const RESET_REQUEST_WINDOW_MINUTES: i64 = 60;
+
+/// Password reset service handling reset requests and confirmation.
+#[derive(Clone)]
+pub struct PasswordResetService {
+    reset_repo: PasswordResetRequestRepository,
+    profile_repo: UserProfileRepository,
+    user_repo: UserRepository,
+    oauth_repo: OAuthRepository,
+    email_service: EmailService,
+    base_url: String,
+    id_gen: IdGenerator,
+}
+
+impl PasswordResetService {
+    /// Create a new password reset service.
+    #[must_use]
+    pub const fn new(
+        reset_repo: PasswordResetRequestRepository,
+        profile_repo: UserProfileRepository,
+        user_repo: UserRepository,
+        oauth_repo: OAuthRepository,
+        email_service: EmailService,
+        base_url: String,
+    ) -> Self {
+        Self {
+            reset_repo,
+            profile_repo,
+            user_repo,
+            oauth_repo,
+            email_service,
+            base_url,
+            id_gen: IdGenerator::new(),
+        }
+    }
+
+    /// Request a password reset for the account with the given email.
+    ///
+    /// Succeeds silently when no account uses the address or the account has
+    /// hit [`MAX_RESET_REQUESTS`], so the endpoint cannot be used to discover
+    /// registered emails.
+    pub async fn request(&self, email: &str) -> AppResult<()> {
+        if !self.email_service.is_enabled() {
+            return Err(AppError::NotConfigured(
+                "Email service not configured".to_string(),
+            ));
+        }
+
+        let email = email.trim();
+        let Some(profile) = self.profile_repo.find_by_email(email).await? else {
+            return Ok(());
+        };
+
+        let since = Utc::now() - Duration::minutes(RESET_REQUEST_WINDOW_MINUTES);
+        let recent = self
+            .reset_repo
+            .count_by_user_since(&profile.user_id, since)
+            .await?;
+        if recent >= MAX_RESET_REQUESTS {
+            tracing::debug!(user_id = %profile.user_id, "Password reset request limit reached");
+            return Ok(());
+        }
+
+        let token = self.id_gen.generate_token();
+        let now = Utc::now();
+        let model = password_reset_request::ActiveModel {
+            id: Set(self.id_gen.generate()),
+            user_id: Set(profile.user_id.clone()),
+            token_hash: Set(hash_token(&token)),
+            expires_at: Set((now + Duration::minutes(RESET_TOKEN_TTL_MINUTES)).into()),
+            created_at: Set(now.into()),
+        };
+        self.reset_repo.create(model).await?;
+
+        let user = self.user_repo.get_by_id(&profile.user_id).await?;
+        let vars = EmailTemplateVars {
+            user_name: user.name,
+            username: Some(user.username),
+            action_url: Some(format!(
+                "{}/reset-password/{token}",
+                self.base_url.trim_end_matches('/')
+            )),
+            ..Default::default()
+        };
+        self.email_service
+            .send_to_profile(&profile, EmailNotificationType::PasswordReset, vars)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Reset the password using an emailed token.
+    ///
+    /// All outstanding reset tokens, the user's access token and their OAuth
+    /// tokens are invalidated so that existing sessions must sign in again.
+    pub async fn confirm(&self, token: &str, new_password: &str) -> AppResult<()> {
+        if !(8..=128).contains(&new_password.chars().count()) {
+            return Err(AppError::Validation(
+                "Password must be between 8 and 128 characters".to_string(),
+            ));
+        }
+
+        let invalid = || AppError::BadRequest("Invalid or expired reset token".to_string());
+        let request = self
+            .reset_repo
+            .find_by_token_hash(&hash_token(token))
+            .await?
+            .ok_or_else(invalid)?;
+        if request.expires_at < Utc::now() {
+            return Err(invalid());
+        }
+
+        let password_hash = hash_password(new_password)?;
+        self.profile_repo
+            .update_password(&request.user_id, &password_hash)
+            .await?;
+
+        self.reset_repo.delete_by_user(&request.user_id).await?;
+        self.user_repo
+            .set_token(&request.user_id, &self.id_gen.generate_token())
+            .await?;
+        self.oauth_repo
+            .revoke_tokens_for_user(&request.user_id)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Hash a reset token for storage.
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::services::email::{EmailConfig, EmailProvider, MailgunConfig};
+    use misskey_db::entities::{note::Visibility, user_profile};
+    use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase, MockExecResult};
+    use std::sync::Arc;
+
+    fn reset_request(expires_in_minutes: i64) -> password_reset_request::Model {
+        password_reset_request::Model {
+            id: "reset1".to_string(),
+            user_id: "user1".to_string(),
+            token_hash: hash_token("secret-token"),
+            expires_at: (Utc::now() + Duration::minutes(expires_in_minutes)).into(),
+            created_at: Utc::now().into(),
+        }
+    }
+
+    fn profile() -> user_profile::Model {
+        user_profile::Model {
+            user_id: "user1".to_string(),
+            password: None,
+            email: Some("alice@example.com".to_string()),
+            email_verified: true,
+            email_undeliverable: false,
+            two_factor_secret: None,
+            two_factor_enabled: false,
+            two_factor_pending: None,
+            two_factor_backup_codes: None,
+            auto_accept_followed: false,
+            always_mark_nsfw: false,
+            pinned_page_ids: serde_json::json!([]),
+            pinned_note_ids: serde_json::json!([]),
+            fields: serde_json::json!([]),
+            muted_words: serde_json::json!([]),
+            user_css: None,
+            birthday: None,
+            location: None,
+            lang: None,
+            pronouns: None,
+            also_known_as: None,
+            moved_to_uri: None,
+            hide_bots: false,
+            default_reaction: None,
+            receive_dm_from_followers_only: false,
+            secure_fetch_only: false,
+            disallow_quotes: false,
+            hide_online_status: false,
+            default_note_visibility: Visibility::Public,
+            theme: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn exec_ok(rows_affected: u64) -> MockExecResult {
+        MockExecResult {
+            last_insert_id: 0,
+            rows_affected,
+        }
+    }
+
+    fn create_service(
+        reset_db: Arc<DatabaseConnection>,
+        profile_db: Arc<DatabaseConnection>,
+        user_db: Arc<DatabaseConnection>,
+        oauth_db: Arc<DatabaseConnection>,
+    ) -> PasswordResetService {
+        PasswordResetService::new(
+            PasswordResetRequestRepository::new(reset_db),
+            UserProfileRepository::new(profile_db),
+            UserRepository::new(user_db),
+            OAuthRepository::new(oauth_db),
+            EmailService::new(None),
+            "https://example.com".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_request_over_limit_succeeds_without_sending() {
+        let reset_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[maplit::btreemap! {
+                    "num_items" => sea_orm::Value::BigInt(Some(3)),
+                }]])
+                .into_connection(),
+        );
+        let profile_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[profile()]])
+                .into_connection(),
+        );
+        let empty = || Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let email_service = EmailService::new(Some(EmailConfig {
+            provider: EmailProvider::Mailgun(MailgunConfig {
+                api_key: "key".to_string(),
+                domain: "mg.example.com".to_string(),
+                eu_region: false,
+            }),
+            from_address: "noreply@example.com".to_string(),
+            from_name: "Example".to_string(),
+            reply_to: None,
+            instance_name: "Example".to_string(),
+            instance_url: "https://example.com".to_string(),
+        }));
+        let service = PasswordResetService::new(
+            PasswordResetRequestRepository::new(reset_db.clone()),
+            UserProfileRepository::new(profile_db),
+            UserRepository::new(empty()),
+            OAuthRepository::new(empty()),
+            email_service,
+            "https://example.com".to_string(),
+        );
+
+        service.request("alice@example.com").await.unwrap();
+        drop(service);
+
+        // Only the count query ran; no new reset request was stored.
+        let log = Arc::try_unwrap(reset_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert_eq!(log.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_confirm_rejects_expired_token() {
+        let reset_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[reset_request(-1)]])
+                .into_connection(),
+        );
+        let profile_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let empty = || Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let service = create_service(reset_db, profile_db.clone(), empty(), empty());
+
+        let result = service.confirm("secret-token", "new-password").await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        drop(service);
+
+        let log = Arc::try_unwrap(profile_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert!(log.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_confirm_resets_password_and_invalidates_tokens() {
+        let reset_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![reset_request(10)], vec![]])
+                .append_exec_results([exec_ok(2)])
+                .into_connection(),
+        );
+        let profile_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[profile()], [profile()]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_exec_results([exec_ok(1)])
+                .into_connection(),
+        );
+        let oauth_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_exec_results([exec_ok(1)])
+                .into_connection(),
+        );
+        let service = create_service(reset_db.clone(), profile_db, user_db.clone(), oauth_db);
+
+        service
+            .confirm("secret-token", "new-password")
+            .await
+            .unwrap();
+
+        // The token is single-use: a second attempt finds nothing.
+        let result = service.confirm("secret-token", "another-password").await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        drop(service);
+
+        let log = Arc::try_unwrap(reset_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert!(
+            log.iter()
+                .flat_map(sea_orm::Transaction::statements)
+                .any(|stmt| stmt
+                    .sql
+                    .starts_with(r#"DELETE FROM "password_reset_request""#))
+        );
+
+        let log = Arc::try_unwrap(user_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert!(
+            log.iter()
+                .flat_map(sea_orm::Transaction::statements)
+                .any(|stmt| stmt.sql.starts_with(r#"UPDATE "user" SET "token""#))
+        );
+    }
+}
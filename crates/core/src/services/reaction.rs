@@ -2,20 +2,31 @@
 
 use crate::services::delivery::DeliveryService;
 use crate::services::event_publisher::EventPublisherService;
-use misskey_common::{AppError, AppResult, IdGenerator};
+use misskey_common::{
+    AppError, AppResult, IdGenerator,
+    reaction::{DEFAULT_REACTION, is_valid_reaction},
+};
 use misskey_db::{
-    entities::reaction,
-    repositories::{NoteRepository, ReactionRepository, UserRepository},
+    entities::{reaction, reaction_usage},
+    repositories::{
+        NoteRepository, ReactionRepository, ReactionUsageRepository, UserProfileRepository,
+        UserRepository,
+    },
 };
 use sea_orm::Set;
 use serde_json::json;
 
+/// Maximum number of entries returned for a user's recent reactions.
+pub const MAX_RECENT_REACTIONS: u64 = 30;
+
 /// Reaction service for business logic.
 #[derive(Clone)]
 pub struct ReactionService {
     reaction_repo: ReactionRepository,
     note_repo: NoteRepository,
     user_repo: Option<UserRepository>,
+    profile_repo: Option<UserProfileRepository>,
+    usage_repo: Option<ReactionUsageRepository>,
     delivery: Option<DeliveryService>,
     event_publisher: Option<EventPublisherService>,
     server_url: String,
@@ -30,6 +41,8 @@ impl ReactionService {
             reaction_repo,
             note_repo,
             user_repo: None,
+            profile_repo: None,
+            usage_repo: None,
             delivery: None,
             event_publisher: None,
             server_url: String::new(),
@@ -50,6 +63,8 @@ impl ReactionService {
             reaction_repo,
             note_repo,
             user_repo: Some(user_repo),
+            profile_repo: None,
+            usage_repo: None,
             delivery: Some(delivery),
             event_publisher: None,
             server_url,
@@ -74,23 +89,64 @@ impl ReactionService {
         self.event_publisher = Some(event_publisher);
     }
 
-    /// System default reaction emoji (fallback when user has no default set).
-    const DEFAULT_LIKE_EMOJI: &'static str = "👍";
+    /// Set the profile repository used to look up users' default reactions.
+    pub fn set_profile_repo(&mut self, profile_repo: UserProfileRepository) {
+        self.profile_repo = Some(profile_repo);
+    }
+
+    /// Set the repository tracking which reactions each user uses.
+    pub fn set_usage_repo(&mut self, usage_repo: ReactionUsageRepository) {
+        self.usage_repo = Some(usage_repo);
+    }
+
+    /// Get the reaction a user applies with a one-button like or favourite.
+    ///
+    /// Returns the user's configured `default_reaction` if it is a legal
+    /// reaction, otherwise the system default (👍).
+    pub async fn default_reaction(&self, user_id: &str) -> AppResult<String> {
+        let Some(profile_repo) = &self.profile_repo else {
+            return Ok(DEFAULT_REACTION.to_string());
+        };
+
+        let configured = profile_repo
+            .find_by_user_id(user_id)
+            .await?
+            .and_then(|p| p.default_reaction)
+            .filter(|r| is_valid_reaction(r));
+
+        Ok(configured.unwrap_or_else(|| DEFAULT_REACTION.to_string()))
+    }
 
     /// Like a note using the user's default reaction or system default.
     ///
-    /// This is the "one-button like" feature that simplifies reacting to notes.
-    /// It uses the user's configured `default_reaction` if set, otherwise falls back
-    /// to the system default (👍).
-    pub async fn like(
-        &self,
-        user_id: &str,
-        note_id: &str,
-        default_reaction: Option<&str>,
-    ) -> AppResult<reaction::Model> {
-        // Use user's default reaction, or fall back to system default
-        let reaction = default_reaction.unwrap_or(Self::DEFAULT_LIKE_EMOJI);
-        self.create(user_id, note_id, reaction).await
+    /// This is the "one-button like" feature that simplifies reacting to notes,
+    /// also used for Mastodon-style favourites.
+    pub async fn like(&self, user_id: &str, note_id: &str) -> AppResult<reaction::Model> {
+        let reaction = self.default_reaction(user_id).await?;
+        self.create(user_id, note_id, &reaction).await
+    }
+
+    /// Remove a like added with [`Self::like`].
+    ///
+    /// Only a reaction matching the user's default reaction is removed; any
+    /// other reaction the user left on the note stays. Returns whether a
+    /// reaction was removed.
+    pub async fn unlike(&self, user_id: &str, note_id: &str) -> AppResult<bool> {
+        let Some(reaction) = self
+            .reaction_repo
+            .find_by_user_and_note(user_id, note_id)
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        let default = Self::normalize_reaction(&self.default_reaction(user_id).await?);
+        if reaction.reaction != default {
+            return Ok(false);
+        }
+
+        self.delete(user_id, note_id).await?;
+        Ok(true)
     }
 
     /// Create a reaction on a note.
@@ -126,6 +182,13 @@ impl ReactionService {
         // Increment note reaction count
         self.note_repo.increment_reactions_count(note_id).await?;
 
+        // Track usage for the user's reaction shortcuts
+        if let Some(ref usage_repo) = self.usage_repo
+            && let Err(e) = usage_repo.record_use(user_id, &normalized_reaction).await
+        {
+            tracing::warn!(error = %e, "Failed to record reaction usage");
+        }
+
         // Queue ActivityPub Like activity for remote note authors
         if let (Some(delivery), Some(user_repo)) = (&self.delivery, &self.user_repo) {
             // Get note author
@@ -219,15 +282,27 @@ impl ReactionService {
             .await
     }
 
+    /// Get the reactions a user uses most, with how often they used each.
+    ///
+    /// `limit` is capped at [`MAX_RECENT_REACTIONS`].
+    pub async fn recent_reactions(
+        &self,
+        user_id: &str,
+        limit: u64,
+    ) -> AppResult<Vec<reaction_usage::Model>> {
+        let Some(usage_repo) = &self.usage_repo else {
+            return Ok(vec![]);
+        };
+
+        usage_repo
+            .find_by_user(user_id, limit.min(MAX_RECENT_REACTIONS))
+            .await
+    }
+
     /// Normalize a reaction string.
     fn normalize_reaction(reaction: &str) -> String {
-        // If it's a custom emoji format like :emoji:, keep as-is
-        if reaction.starts_with(':') && reaction.ends_with(':') && reaction.len() > 2 {
-            return reaction.to_string();
-        }
-
-        // If it looks like a Unicode emoji, keep as-is
-        if !reaction.is_ascii() {
+        // Custom emoji shortcodes and Unicode emojis are kept as-is
+        if is_valid_reaction(reaction) {
             return reaction.to_string();
         }
 
@@ -332,8 +407,8 @@ impl ReactionService {
 mod tests {
     use super::*;
     use chrono::Utc;
-    use misskey_db::entities::note;
-    use sea_orm::{DatabaseBackend, MockDatabase};
+    use misskey_db::entities::{note, user_profile};
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
     use serde_json::json;
     use std::sync::Arc;
 
@@ -380,6 +455,147 @@ mod tests {
         }
     }
 
+    fn create_test_profile(user_id: &str, default_reaction: Option<&str>) -> user_profile::Model {
+        user_profile::Model {
+            user_id: user_id.to_string(),
+            password: None,
+            email: None,
+            email_verified: false,
+            email_undeliverable: false,
+            two_factor_secret: None,
+            two_factor_enabled: false,
+            two_factor_pending: None,
+            two_factor_backup_codes: None,
+            auto_accept_followed: false,
+            always_mark_nsfw: false,
+            pinned_page_ids: json!([]),
+            pinned_note_ids: json!([]),
+            fields: json!([]),
+            muted_words: json!([]),
+            user_css: None,
+            birthday: None,
+            location: None,
+            lang: None,
+            pronouns: None,
+            also_known_as: None,
+            moved_to_uri: None,
+            hide_bots: false,
+            default_reaction: default_reaction.map(String::from),
+            receive_dm_from_followers_only: false,
+            secure_fetch_only: false,
+            disallow_quotes: false,
+            hide_online_status: false,
+            default_note_visibility: note::Visibility::Public,
+            theme: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn profile_service(reaction_db: MockDatabase, profile: user_profile::Model) -> ReactionService {
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[create_test_note("note1", "author1")]])
+                .append_exec_results([MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                }])
+                .into_connection(),
+        );
+        let profile_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[profile]])
+                .into_connection(),
+        );
+
+        let mut service = ReactionService::new(
+            ReactionRepository::new(Arc::new(reaction_db.into_connection())),
+            NoteRepository::new(note_db),
+        );
+        service.set_profile_repo(UserProfileRepository::new(profile_db));
+        service
+    }
+
+    #[tokio::test]
+    async fn test_favourite_uses_configured_default_reaction() {
+        let reaction_db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([Vec::<reaction::Model>::new()])
+            .append_query_results([[create_test_reaction("r1", "user1", "note1", "🎉")]]);
+        let service = profile_service(reaction_db, create_test_profile("user1", Some("🎉")));
+
+        let reaction = service.like("user1", "note1").await.unwrap();
+
+        assert_eq!(reaction.reaction, "🎉");
+    }
+
+    #[tokio::test]
+    async fn test_unlike_keeps_other_reaction() {
+        let reaction_db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[create_test_reaction("r1", "user1", "note1", "🎉")]]);
+        let service = profile_service(reaction_db, create_test_profile("user1", None));
+
+        // The mock has no delete result, so removing the reaction would fail
+        assert!(!service.unlike("user1", "note1").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_stored_default_reaction_falls_back() {
+        let service = profile_service(
+            MockDatabase::new(DatabaseBackend::Postgres),
+            create_test_profile("user1", Some("like")),
+        );
+
+        let reaction = service.default_reaction("user1").await.unwrap();
+
+        assert_eq!(reaction, DEFAULT_REACTION);
+    }
+
+    #[tokio::test]
+    async fn test_reaction_appears_in_recent_reactions() {
+        let reaction_db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([Vec::<reaction::Model>::new()])
+            .append_query_results([[create_test_reaction("r1", "user1", "note1", ":tada:")]]);
+        let mut service = profile_service(reaction_db, create_test_profile("user1", None));
+
+        let usage_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_exec_results([MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                }])
+                .append_query_results([[reaction_usage::Model {
+                    id: "u1".to_string(),
+                    user_id: "user1".to_string(),
+                    reaction: ":tada:".to_string(),
+                    count: 1,
+                    last_used_at: Utc::now().into(),
+                }]])
+                .into_connection(),
+        );
+        service.set_usage_repo(ReactionUsageRepository::new(Arc::clone(&usage_db)));
+
+        service.create("user1", "note1", ":tada:").await.unwrap();
+        let recent = service.recent_reactions("user1", 100).await.unwrap();
+        drop(service);
+
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].reaction, ":tada:");
+        assert_eq!(recent[0].count, 1);
+
+        // The reaction was upserted for the user, and the lookup was capped
+        let log = Arc::try_unwrap(usage_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        let upsert = &log[0].statements()[0];
+        assert!(upsert.sql.contains("ON CONFLICT"));
+        assert!(format!("{:?}", upsert.values).contains(":tada:"));
+        assert!(
+            format!("{:?}", log[1].statements()[0].values)
+                .contains(&format!("BigUnsigned(Some({MAX_RECENT_REACTIONS}))"))
+        );
+    }
+
     // Unit tests for normalize_reaction
     #[test]
     fn test_normalize_reaction_custom_emoji() {
@@ -0,0 +1,155 @@
+//! Shared test doubles for service tests.
+
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use misskey_common::AppResult;
+use serde_json::Value;
+
+use crate::services::delivery::ActivityDelivery;
+
+/// An activity queued through [`DeliveryRecorder`].
+#[derive(Debug, Clone)]
+pub struct RecordedDelivery {
+    /// Name of the queue method without its `queue_` prefix, e.g. `"announce"`.
+    pub kind: &'static str,
+    pub activity: Value,
+    pub inboxes: Vec<String>,
+}
+
+/// Delivery that records every queued activity instead of sending it.
+#[derive(Default)]
+pub struct DeliveryRecorder {
+    deliveries: Mutex<Vec<RecordedDelivery>>,
+}
+
+impl DeliveryRecorder {
+    fn record(&self, kind: &'static str, activity: Value, inboxes: Vec<String>) -> AppResult<()> {
+        self.deliveries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(RecordedDelivery {
+                kind,
+                activity,
+                inboxes,
+            });
+        Ok(())
+    }
+
+    fn of_kind(&self, kind: &str) -> Vec<RecordedDelivery> {
+        self.deliveries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+            .filter(|d| d.kind == kind)
+            .cloned()
+            .collect()
+    }
+
+    /// Activities queued with `queue_{kind}`, in queue order.
+    pub fn activities(&self, kind: &str) -> Vec<Value> {
+        self.of_kind(kind).into_iter().map(|d| d.activity).collect()
+    }
+
+    /// Inboxes targeted with `queue_{kind}`, in queue order.
+    pub fn inboxes(&self, kind: &str) -> Vec<String> {
+        self.of_kind(kind)
+            .into_iter()
+            .flat_map(|d| d.inboxes)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ActivityDelivery for DeliveryRecorder {
+    async fn queue_create_note(
+        &self,
+        _: &str,
+        _: &str,
+        activity: Value,
+        inboxes: Vec<String>,
+    ) -> AppResult<()> {
+        self.record("create_note", activity, inboxes)
+    }
+
+    async fn queue_delete_note(
+        &self,
+        _: &str,
+        _: &str,
+        activity: Value,
+        inboxes: Vec<String>,
+    ) -> AppResult<()> {
+        self.record("delete_note", activity, inboxes)
+    }
+
+    async fn queue_follow(&self, _: &str, target_inbox: &str, activity: Value) -> AppResult<()> {
+        self.record("follow", activity, vec![target_inbox.to_string()])
+    }
+
+    async fn queue_accept_follow(
+        &self,
+        _: &str,
+        target_inbox: &str,
+        activity: Value,
+    ) -> AppResult<()> {
+        self.record("accept_follow", activity, vec![target_inbox.to_string()])
+    }
+
+    async fn queue_reject_follow(
+        &self,
+        _: &str,
+        target_inbox: &str,
+        activity: Value,
+    ) -> AppResult<()> {
+        self.record("reject_follow", activity, vec![target_inbox.to_string()])
+    }
+
+    async fn queue_undo(&self, _: &str, inboxes: Vec<String>, activity: Value) -> AppResult<()> {
+        self.record("undo", activity, inboxes)
+    }
+
+    async fn queue_like(&self, _: &str, target_inbox: &str, activity: Value) -> AppResult<()> {
+        self.record("like", activity, vec![target_inbox.to_string()])
+    }
+
+    async fn queue_announce(
+        &self,
+        _: &str,
+        inboxes: Vec<String>,
+        activity: Value,
+    ) -> AppResult<()> {
+        self.record("announce", activity, inboxes)
+    }
+
+    async fn queue_update_note(
+        &self,
+        _: &str,
+        _: &str,
+        activity: Value,
+        inboxes: Vec<String>,
+    ) -> AppResult<()> {
+        self.record("update_note", activity, inboxes)
+    }
+
+    async fn queue_move(&self, _: &str, activity: Value, inboxes: Vec<String>) -> AppResult<()> {
+        self.record("move", activity, inboxes)
+    }
+
+    async fn queue_update_actor(
+        &self,
+        _: &str,
+        activity: Value,
+        inboxes: Vec<String>,
+    ) -> AppResult<()> {
+        self.record("update_actor", activity, inboxes)
+    }
+
+    async fn queue_delete_actor(
+        &self,
+        _: &str,
+        activity: Value,
+        inboxes: Vec<String>,
+    ) -> AppResult<()> {
+        self.record("delete_actor", activity, inboxes)
+    }
+}
@@ -0,0 +1,223 @@
+//! Note thread muting service.
+
+use std::collections::HashSet;
+
+use misskey_common::{AppError, AppResult, IdGenerator};
+use misskey_db::{
+    entities::{note, note_thread_muting},
+    repositories::{NoteRepository, NoteThreadMutingRepository},
+};
+use sea_orm::Set;
+
+/// Thread muting service for silencing notifications from a conversation.
+#[derive(Clone)]
+pub struct ThreadMutingService {
+    thread_muting_repo: NoteThreadMutingRepository,
+    note_repo: NoteRepository,
+    id_gen: IdGenerator,
+}
+
+impl ThreadMutingService {
+    /// Create a new thread muting service.
+    #[must_use]
+    pub const fn new(
+        thread_muting_repo: NoteThreadMutingRepository,
+        note_repo: NoteRepository,
+    ) -> Self {
+        Self {
+            thread_muting_repo,
+            note_repo,
+            id_gen: IdGenerator::new(),
+        }
+    }
+
+    /// Get the root note ID of the thread a note belongs to.
+    #[must_use]
+    pub fn thread_id_of(note: &note::Model) -> &str {
+        note.thread_id.as_deref().unwrap_or(&note.id)
+    }
+
+    /// Mute the thread containing a note.
+    pub async fn mute(&self, user_id: &str, note_id: &str) -> AppResult<note_thread_muting::Model> {
+        let note = self.note_repo.get_by_id(note_id).await?;
+        let thread_id = Self::thread_id_of(&note);
+
+        if self.thread_muting_repo.is_muted(user_id, thread_id).await? {
+            return Err(AppError::Conflict("Thread already muted".to_string()));
+        }
+
+        let model = note_thread_muting::ActiveModel {
+            id: Set(self.id_gen.generate()),
+            user_id: Set(user_id.to_string()),
+            thread_id: Set(thread_id.to_string()),
+            created_at: Set(chrono::Utc::now().into()),
+        };
+
+        self.thread_muting_repo.create(model).await
+    }
+
+    /// Unmute the thread containing a note.
+    pub async fn unmute(&self, user_id: &str, note_id: &str) -> AppResult<()> {
+        let note = self.note_repo.get_by_id(note_id).await?;
+        let thread_id = Self::thread_id_of(&note);
+
+        if !self.thread_muting_repo.is_muted(user_id, thread_id).await? {
+            return Err(AppError::NotFound("Thread not muted".to_string()));
+        }
+
+        self.thread_muting_repo
+            .delete_by_user_and_thread(user_id, thread_id)
+            .await
+    }
+
+    /// Check if a note belongs to a thread the user has muted.
+    pub async fn is_muted(&self, user_id: &str, note: &note::Model) -> AppResult<bool> {
+        self.thread_muting_repo
+            .is_muted(user_id, Self::thread_id_of(note))
+            .await
+    }
+
+    /// Check by ID if a note belongs to a thread the user has muted.
+    ///
+    /// Unknown notes are treated as not muted.
+    pub async fn is_note_muted(&self, user_id: &str, note_id: &str) -> AppResult<bool> {
+        match self.note_repo.find_by_id(note_id).await? {
+            Some(note) => self.is_muted(user_id, &note).await,
+            None => Ok(false),
+        }
+    }
+
+    /// Get the IDs of the given notes that belong to threads the user has muted.
+    pub async fn muted_note_ids(
+        &self,
+        user_id: &str,
+        notes: &[note::Model],
+    ) -> AppResult<HashSet<String>> {
+        let thread_ids: Vec<String> = notes
+            .iter()
+            .map(|n| Self::thread_id_of(n).to_string())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let muted: HashSet<String> = self
+            .thread_muting_repo
+            .find_muted_thread_ids(user_id, &thread_ids)
+            .await?
+            .into_iter()
+            .collect();
+
+        Ok(notes
+            .iter()
+            .filter(|n| muted.contains(Self::thread_id_of(n)))
+            .map(|n| n.id.clone())
+            .collect())
+    }
+
+    /// Get which of the given note IDs belong to threads the user has muted.
+    pub async fn muted_among(
+        &self,
+        user_id: &str,
+        note_ids: &[String],
+    ) -> AppResult<HashSet<String>> {
+        if note_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let notes = self.note_repo.find_by_ids(note_ids).await?;
+        self.muted_note_ids(user_id, &notes).await
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::services::notification::NotificationService;
+    use chrono::Utc;
+    use misskey_db::repositories::NotificationRepository;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn reply_in_thread() -> note::Model {
+        note::Model {
+            id: "reply1".to_string(),
+            user_id: "bob".to_string(),
+            user_host: None,
+            text: Some("Reply".to_string()),
+            cw: None,
+            visibility: note::Visibility::Public,
+            reply_id: Some("root1".to_string()),
+            renote_id: None,
+            thread_id: Some("root1".to_string()),
+            mentions: json!([]),
+            visible_user_ids: json!([]),
+            file_ids: json!([]),
+            tags: json!([]),
+            reactions: json!({}),
+            replies_count: 0,
+            renote_count: 0,
+            reaction_count: 0,
+            is_local: true,
+            uri: None,
+            url: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+            channel_id: None,
+        }
+    }
+
+    fn root_muted_by_alice() -> note_thread_muting::Model {
+        note_thread_muting::Model {
+            id: "mute1".to_string(),
+            user_id: "alice".to_string(),
+            thread_id: "root1".to_string(),
+            created_at: Utc::now().into(),
+        }
+    }
+
+    #[test]
+    fn test_thread_id_of_root_is_own_id() {
+        let mut note = reply_in_thread();
+        assert_eq!(ThreadMutingService::thread_id_of(&note), "root1");
+        note.thread_id = None;
+        assert_eq!(ThreadMutingService::thread_id_of(&note), "reply1");
+    }
+
+    #[tokio::test]
+    async fn test_muted_thread_reports_muted_and_suppresses_reply_notification() {
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[reply_in_thread()]])
+                .into_connection(),
+        );
+        let muting_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[root_muted_by_alice()], [root_muted_by_alice()]])
+                .into_connection(),
+        );
+        let notification_db =
+            Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let service = ThreadMutingService::new(
+            NoteThreadMutingRepository::new(muting_db),
+            NoteRepository::new(note_db),
+        );
+        assert!(service.is_muted("alice", &reply_in_thread()).await.unwrap());
+
+        let mut notification_service =
+            NotificationService::new(NotificationRepository::new(Arc::clone(&notification_db)));
+        notification_service.set_thread_muting_service(service);
+        let notification = notification_service
+            .create_reply_notification("alice", "bob", "reply1")
+            .await
+            .unwrap();
+
+        assert!(notification.is_none());
+        drop(notification_service);
+        let log = Arc::try_unwrap(notification_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert!(log.is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+//! Timeline caching.
+//!
+//! Caches the first page of the public timelines, which are otherwise
+//! recomputed on every request. Entries are dropped when a note that would
+//! appear on them is created, and expire after a short TTL to pick up notes
+//! stored through other paths (e.g. federation).
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use misskey_common::{AppError, AppResult};
+use misskey_db::entities::note;
+
+/// Number of notes cached per timeline; requests for more bypass the cache.
+pub const TIMELINE_CACHE_PAGE_SIZE: u64 = 100;
+
+/// Public timelines whose first page is cached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CachedTimeline {
+    Local,
+    Global,
+    Bubble,
+}
+
+impl CachedTimeline {
+    /// Cache key for this timeline.
+    #[must_use]
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Local => "timeline:local",
+            Self::Global => "timeline:global",
+            Self::Bubble => "timeline:bubble",
+        }
+    }
+}
+
+/// Store for cached timeline pages.
+#[async_trait]
+pub trait TimelineCache: Send + Sync {
+    /// Get the cached first page of a timeline.
+    async fn get(&self, timeline: CachedTimeline) -> AppResult<Option<Vec<note::Model>>>;
+
+    /// Cache the first page of a timeline for `ttl`.
+    async fn set(
+        &self,
+        timeline: CachedTimeline,
+        notes: &[note::Model],
+        ttl: Duration,
+    ) -> AppResult<()>;
+
+    /// Drop the cached page of a timeline.
+    async fn invalidate(&self, timeline: CachedTimeline) -> AppResult<()>;
+}
+
+/// In-process timeline cache for single-instance deployments and tests.
+#[derive(Default)]
+pub struct InMemoryTimelineCache {
+    pages: Mutex<HashMap<CachedTimeline, (Vec<note::Model>, Instant)>>,
+}
+
+impl InMemoryTimelineCache {
+    /// Create a new in-memory timeline cache.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TimelineCache for InMemoryTimelineCache {
+    async fn get(&self, timeline: CachedTimeline) -> AppResult<Option<Vec<note::Model>>> {
+        let pages = self
+            .pages
+            .lock()
+            .map_err(|_| AppError::Internal("Timeline cache lock poisoned".to_string()))?;
+        Ok(pages
+            .get(&timeline)
+            .filter(|(_, expires_at)| *expires_at > Instant::now())
+            .map(|(notes, _)| notes.clone()))
+    }
+
+    async fn set(
+        &self,
+        timeline: CachedTimeline,
+        notes: &[note::Model],
+        ttl: Duration,
+    ) -> AppResult<()> {
+        let mut pages = self
+            .pages
+            .lock()
+            .map_err(|_| AppError::Internal("Timeline cache lock poisoned".to_string()))?;
+        pages.insert(timeline, (notes.to_vec(), Instant::now() + ttl));
+        Ok(())
+    }
+
+    async fn invalidate(&self, timeline: CachedTimeline) -> AppResult<()> {
+        let mut pages = self
+            .pages
+            .lock()
+            .map_err(|_| AppError::Internal("Timeline cache lock poisoned".to_string()))?;
+        pages.remove(&timeline);
+        Ok(())
+    }
+}
+
+/// Type alias for the timeline cache.
+pub type TimelineCacheService = Arc<dyn TimelineCache>;
+
+/// Timelines a newly created note appears on.
+#[must_use]
+pub fn timelines_for_note(note: &note::Model) -> Vec<CachedTimeline> {
+    if note.visibility != note::Visibility::Public {
+        return vec![];
+    }
+    if note.is_local {
+        vec![
+            CachedTimeline::Local,
+            CachedTimeline::Global,
+            CachedTimeline::Bubble,
+        ]
+    } else {
+        vec![CachedTimeline::Global, CachedTimeline::Bubble]
+    }
+}
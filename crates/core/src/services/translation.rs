@@ -77,6 +77,68 @@ impl Default for TranslationConfig {
     }
 }
 
+impl TranslationConfig {
+    /// Build the service configuration from the `[translation]` config section.
+    ///
+    /// Provider defaults are kept for settings the section leaves unset.
+    pub fn from_config(config: &misskey_common::TranslationConfig) -> AppResult<Self> {
+        let mut translation = Self {
+            cache_enabled: config.cache_ttl_secs > 0,
+            cache_ttl_seconds: config.cache_ttl_secs,
+            ..Self::default()
+        };
+        let api_key = config.api_key.clone();
+
+        match config.provider.to_lowercase().as_str() {
+            "deepl" => {
+                translation.provider = TranslationProvider::DeepL;
+                translation.deepl_api_key = api_key;
+            }
+            "google" => {
+                translation.provider = TranslationProvider::Google;
+                translation.google_api_key = api_key;
+            }
+            "libretranslate" => {
+                translation.provider = TranslationProvider::LibreTranslate;
+                translation.libretranslate_api_key = api_key;
+                if config.url.is_some() {
+                    translation.libretranslate_url.clone_from(&config.url);
+                }
+            }
+            "openai" => {
+                translation.provider = TranslationProvider::OpenAI;
+                translation.openai_api_key = api_key;
+                if config.model.is_some() {
+                    translation.openai_model.clone_from(&config.model);
+                }
+            }
+            "anthropic" => {
+                translation.provider = TranslationProvider::Anthropic;
+                translation.anthropic_api_key = api_key;
+                if config.model.is_some() {
+                    translation.anthropic_model.clone_from(&config.model);
+                }
+            }
+            "ollama" => {
+                translation.provider = TranslationProvider::Ollama;
+                if config.url.is_some() {
+                    translation.ollama_url.clone_from(&config.url);
+                }
+                if config.model.is_some() {
+                    translation.ollama_model.clone_from(&config.model);
+                }
+            }
+            other => {
+                return Err(AppError::Validation(format!(
+                    "Unknown translation provider: {other}"
+                )));
+            }
+        }
+
+        Ok(translation)
+    }
+}
+
 /// Translation request input.
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
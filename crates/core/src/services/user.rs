@@ -4,14 +4,26 @@ use argon2::{
     Argon2,
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
 };
-use misskey_common::{AppError, AppResult, Config, IdGenerator, generate_rsa_keypair};
+use misskey_common::{
+    AppError, AppResult, Config, IdGenerator, generate_rsa_keypair, reaction::is_valid_reaction,
+};
 use misskey_db::{
-    entities::{user, user_keypair, user_profile},
-    repositories::{NoteRepository, UserKeypairRepository, UserProfileRepository, UserRepository},
+    entities::{note, user, user_keypair, user_profile},
+    repositories::{
+        FollowingRepository, NoteRepository, UserKeypairRepository, UserProfileRepository,
+        UserRelation, UserRepository,
+    },
 };
+use misskey_federation::{UrlConfig, UserToApPerson};
 use sea_orm::Set;
 use serde::Deserialize;
-use validator::Validate;
+use serde_json::json;
+use url::Url;
+use validator::{Validate, ValidationError};
+
+use crate::services::account::ProfileField;
+use crate::services::delivery::{DeliveryService, follower_inboxes};
+use crate::services::meta_settings::{MetaSettingsService, NewUserDefaults};
 
 /// Maximum number of notes that can be pinned to a user's profile.
 const MAX_PINNED_NOTES: usize = 5;
@@ -23,10 +35,22 @@ pub struct UserService {
     profile_repo: UserProfileRepository,
     keypair_repo: UserKeypairRepository,
     note_repo: NoteRepository,
+    meta_settings_service: Option<MetaSettingsService>,
+    following_repo: Option<FollowingRepository>,
+    delivery: Option<DeliveryService>,
     id_gen: IdGenerator,
     server_url: String,
 }
 
+/// Profile details of a user as shown to a viewer.
+#[derive(Debug, Clone, Default)]
+pub struct UserProfileDetails {
+    /// Profile fields.
+    pub fields: Vec<ProfileField>,
+    /// Pinned notes that still exist and the viewer may see, in pin order.
+    pub pinned_notes: Vec<note::Model>,
+}
+
 /// Input for creating a new user.
 #[derive(Debug, Deserialize, Validate)]
 pub struct CreateUserInput {
@@ -72,14 +96,22 @@ pub struct UpdateUserInput {
     /// Hide notes from bot accounts in timeline
     pub hide_bots: Option<bool>,
 
-    /// Default reaction emoji (e.g., "👍", ":like:", custom emoji shortcode)
-    #[validate(length(max = 256))]
+    /// Default reaction emoji (e.g., "👍", ":like:", custom emoji shortcode).
+    /// An empty string clears the setting.
+    #[validate(length(max = 256), custom(function = "validate_default_reaction"))]
     pub default_reaction: Option<String>,
 
     /// Require HTTP signature verification for requests to this user's resources.
     /// When enabled, unauthenticated fetches of this user's profile and notes
     /// will be rejected (Authorized Fetch / Secure Mode).
     pub secure_fetch_only: Option<bool>,
+
+    /// Reject quotes of this user's notes. Advertised in the actor document
+    /// so remote servers can honor it too.
+    pub disallow_quotes: Option<bool>,
+
+    /// Hide whether this user is online from other users.
+    pub hide_online_status: Option<bool>,
 }
 
 impl UserService {
@@ -97,11 +129,25 @@ impl UserService {
             profile_repo,
             keypair_repo,
             note_repo,
+            meta_settings_service: None,
+            following_repo: None,
+            delivery: None,
             id_gen: IdGenerator::new(),
             server_url: config.server.url.clone(),
         }
     }
 
+    /// Set the meta settings service used for new user defaults.
+    pub fn set_meta_settings_service(&mut self, meta_settings_service: MetaSettingsService) {
+        self.meta_settings_service = Some(meta_settings_service);
+    }
+
+    /// Set the delivery service used to federate profile updates to followers.
+    pub fn set_delivery(&mut self, delivery: DeliveryService, following_repo: FollowingRepository) {
+        self.delivery = Some(delivery);
+        self.following_repo = Some(following_repo);
+    }
+
     /// Create a new local user.
     pub async fn create(&self, input: CreateUserInput) -> AppResult<user::Model> {
         input.validate()?;
@@ -119,6 +165,12 @@ impl UserService {
         // Hash password
         let password_hash = hash_password(&input.password)?;
 
+        // Instance defaults apply only at registration
+        let defaults = match &self.meta_settings_service {
+            Some(service) => service.new_user_defaults().await?,
+            None => NewUserDefaults::default(),
+        };
+
         // Generate token and user ID
         let user_id = self.id_gen.generate();
         let token = self.id_gen.generate_token();
@@ -131,6 +183,7 @@ impl UserService {
             host: Set(None),
             token: Set(Some(token)),
             name: Set(input.name),
+            is_locked: Set(defaults.is_locked),
             ..Default::default()
         };
 
@@ -144,6 +197,9 @@ impl UserService {
             pinned_note_ids: Set(serde_json::json!([])),
             fields: Set(serde_json::json!([])),
             muted_words: Set(serde_json::json!([])),
+            receive_dm_from_followers_only: Set(defaults.receive_dm_from_followers_only),
+            default_note_visibility: Set(defaults.note_visibility),
+            theme: Set(defaults.theme),
             ..Default::default()
         };
 
@@ -273,6 +329,8 @@ impl UserService {
             || input.hide_bots.is_some()
             || input.default_reaction.is_some()
             || input.secure_fetch_only.is_some()
+            || input.disallow_quotes.is_some()
+            || input.hide_online_status.is_some()
         {
             let profile = self.profile_repo.get_by_user_id(id).await?;
             let mut profile_active: user_profile::ActiveModel = profile.into();
@@ -295,12 +353,73 @@ impl UserService {
             if let Some(secure_fetch_only) = input.secure_fetch_only {
                 profile_active.secure_fetch_only = Set(secure_fetch_only);
             }
+            if let Some(disallow_quotes) = input.disallow_quotes {
+                profile_active.disallow_quotes = Set(disallow_quotes);
+            }
+            if let Some(hide_online_status) = input.hide_online_status {
+                profile_active.hide_online_status = Set(hide_online_status);
+            }
 
             profile_active.updated_at = Set(Some(chrono::Utc::now().into()));
             self.profile_repo.update(profile_active).await?;
         }
 
-        self.user_repo.update(active).await
+        let updated = self.user_repo.update(active).await?;
+
+        if updated.host.is_none()
+            && let Err(e) = self.queue_update_person(&updated).await
+        {
+            tracing::warn!(error = %e, user_id = %updated.id, "Failed to queue Update activity");
+        }
+
+        Ok(updated)
+    }
+
+    /// Queue an `Update(Person)` activity to the instances of the user's followers.
+    async fn queue_update_person(&self, user: &user::Model) -> AppResult<()> {
+        let (Some(delivery), Some(following_repo)) = (&self.delivery, &self.following_repo) else {
+            return Ok(());
+        };
+
+        let inboxes = follower_inboxes(following_repo, &self.user_repo, &user.id).await?;
+        if inboxes.is_empty() {
+            return Ok(());
+        }
+
+        let public_key_pem = self
+            .keypair_repo
+            .find_by_user_id(&user.id)
+            .await?
+            .map(|k| k.public_key);
+        let disallow_quotes = self
+            .profile_repo
+            .find_by_user_id(&user.id)
+            .await?
+            .is_some_and(|p| p.disallow_quotes);
+
+        // Same actor document as served by the ActivityPub user endpoint
+        let base_url = Url::parse(&self.server_url)
+            .map_err(|e| AppError::Internal(format!("Invalid server URL: {e}")))?;
+        let mut person = user.to_ap_person(&UrlConfig::new(base_url), public_key_pem.as_deref());
+        person.disallow_quotes = disallow_quotes.then_some(true);
+
+        let actor_url = person.id.to_string();
+        let activity = json!({
+            "@context": [
+                "https://www.w3.org/ns/activitystreams",
+                "https://w3id.org/security/v1"
+            ],
+            "id": format!("{actor_url}#updates/{}", self.id_gen.generate()),
+            "type": "Update",
+            "actor": actor_url,
+            "to": ["https://www.w3.org/ns/activitystreams#Public"],
+            "cc": [person.followers],
+            "object": person,
+        });
+
+        delivery
+            .queue_update_actor(&user.id, activity, inboxes)
+            .await
     }
 
     /// Search users by username or display name.
@@ -316,11 +435,82 @@ impl UserService {
             .await
     }
 
+    /// Filter `user_ids` down to the users whose online status `viewer_id` may see.
+    ///
+    /// Users always see their own status, even when they hide it from others.
+    pub async fn online_status_visible_to(
+        &self,
+        user_ids: &[String],
+        viewer_id: &str,
+    ) -> AppResult<Vec<String>> {
+        let hidden = self
+            .profile_repo
+            .find_hiding_online_status(user_ids)
+            .await?;
+        Ok(user_ids
+            .iter()
+            .filter(|id| *id == viewer_id || !hidden.contains(id))
+            .cloned()
+            .collect())
+    }
+
+    /// Get the relationship of `viewer_id` to `user_id`.
+    pub async fn get_relation(&self, viewer_id: &str, user_id: &str) -> AppResult<UserRelation> {
+        self.user_repo.find_relation(viewer_id, user_id).await
+    }
+
     /// Get pinned note IDs for a user.
     pub async fn get_pinned_note_ids(&self, user_id: &str) -> AppResult<Vec<String>> {
         self.profile_repo.get_pinned_note_ids(user_id).await
     }
 
+    /// Get the profile fields and pinned notes of a user as seen by `viewer_id`.
+    ///
+    /// Pinned notes are loaded in one query; deleted ones are skipped.
+    pub async fn get_profile_details(
+        &self,
+        user_id: &str,
+        viewer_id: Option<&str>,
+    ) -> AppResult<UserProfileDetails> {
+        // Remote users have no profile row
+        let Some(profile) = self.profile_repo.find_by_user_id(user_id).await? else {
+            return Ok(UserProfileDetails::default());
+        };
+
+        let fields: Vec<ProfileField> = serde_json::from_value(profile.fields).unwrap_or_default();
+        let pinned_ids: Vec<String> =
+            serde_json::from_value(profile.pinned_note_ids).unwrap_or_default();
+        if pinned_ids.is_empty() {
+            return Ok(UserProfileDetails {
+                fields,
+                pinned_notes: Vec::new(),
+            });
+        }
+
+        let mut pinned_notes = self.note_repo.find_by_ids(&pinned_ids).await?;
+        pinned_notes.sort_by_key(|note| pinned_ids.iter().position(|id| *id == note.id));
+
+        // Only look up the follow relation if a followers-only note needs it
+        let is_other_viewer = viewer_id.is_some_and(|viewer_id| viewer_id != user_id);
+        let viewer_follows = match (viewer_id, &self.following_repo) {
+            (Some(viewer_id), Some(following_repo))
+                if is_other_viewer
+                    && pinned_notes
+                        .iter()
+                        .any(|note| note.visibility == note::Visibility::Followers) =>
+            {
+                following_repo.is_following(viewer_id, user_id).await?
+            }
+            _ => false,
+        };
+        pinned_notes.retain(|note| is_visible_to(note, viewer_id, viewer_follows));
+
+        Ok(UserProfileDetails {
+            fields,
+            pinned_notes,
+        })
+    }
+
     /// Pin a note to the user's profile.
     pub async fn pin_note(&self, user_id: &str, note_id: &str) -> AppResult<Vec<String>> {
         // Verify the note exists
@@ -396,18 +586,47 @@ impl UserService {
             Ok(None)
         }
     }
+}
 
-    /// Get the default reaction setting for a user.
-    ///
-    /// Returns the user's preferred reaction emoji, or None if not set.
-    pub async fn get_default_reaction(&self, user_id: &str) -> AppResult<Option<String>> {
-        let profile = self.profile_repo.find_by_user_id(user_id).await?;
-        Ok(profile.and_then(|p| p.default_reaction))
+/// Whether `viewer_id` may see `note`; `viewer_follows` tells whether the
+/// viewer follows the note's author.
+pub(crate) fn is_visible_to(
+    note: &note::Model,
+    viewer_id: Option<&str>,
+    viewer_follows: bool,
+) -> bool {
+    let Some(viewer_id) = viewer_id else {
+        return matches!(
+            note.visibility,
+            note::Visibility::Public | note::Visibility::Home
+        );
+    };
+    if note.user_id == viewer_id {
+        return true;
+    }
+
+    match note.visibility {
+        note::Visibility::Public | note::Visibility::Home => true,
+        note::Visibility::Followers => viewer_follows,
+        note::Visibility::Specified => {
+            let visible_user_ids: Vec<String> =
+                serde_json::from_value(note.visible_user_ids.clone()).unwrap_or_default();
+            visible_user_ids.iter().any(|id| id == viewer_id)
+        }
+    }
+}
+
+/// Validate that a default reaction is empty (clearing it) or a legal reaction.
+fn validate_default_reaction(reaction: &str) -> Result<(), ValidationError> {
+    if reaction.is_empty() || is_valid_reaction(reaction) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_reaction"))
     }
 }
 
 /// Hash a password using Argon2.
-fn hash_password(password: &str) -> AppResult<String> {
+pub(crate) fn hash_password(password: &str) -> AppResult<String> {
     let salt = SaltString::generate(&mut OsRng);
     let argon2 = Argon2::default();
 
@@ -431,8 +650,12 @@ fn verify_password(password: &str, hash: &str) -> AppResult<bool> {
 #[allow(clippy::unwrap_used, clippy::panic)]
 mod tests {
     use super::*;
+    use crate::services::test_support::DeliveryRecorder;
     use chrono::Utc;
-    use misskey_common::config::{DatabaseConfig, FederationConfig, RedisConfig, ServerConfig};
+    use misskey_common::config::{
+        DatabaseConfig, FederationConfig, FederationMode, RedisConfig, ServerConfig,
+    };
+    use misskey_db::entities::{following, meta_settings, note::Visibility};
     use sea_orm::{DatabaseBackend, MockDatabase};
     use std::sync::Arc;
 
@@ -448,6 +671,7 @@ mod tests {
                 read_replicas: Vec::new(),
                 max_connections: 10,
                 min_connections: 1,
+                slow_query_threshold_ms: 500,
             },
             redis: RedisConfig {
                 url: "redis://localhost".to_string(),
@@ -459,7 +683,17 @@ mod tests {
                 instance_description: Some("A test instance".to_string()),
                 maintainer_name: None,
                 maintainer_email: None,
+                mode: FederationMode::Open,
+                min_account_age_secs: 0,
+                inbox_rate_limit_window_secs: 60,
+                shared_inbox_rate_limit: 300,
+                user_inbox_rate_limit: 100,
             },
+            storage: misskey_common::StorageConfig::default(),
+            email: misskey_common::EmailConfig::default(),
+            translation: misskey_common::TranslationConfig::default(),
+            push: misskey_common::PushConfig::default(),
+            notes: misskey_common::NoteConfig::default(),
         }
     }
 
@@ -480,12 +714,14 @@ mod tests {
             is_silenced: false,
             is_admin: false,
             is_moderator: false,
+            is_system: false,
             followers_count: 0,
             following_count: 0,
             notes_count: 0,
             inbox: None,
             shared_inbox: None,
             featured: None,
+            followers_uri: None,
             uri: None,
             last_fetched_at: None,
             token: Some("test_token".to_string()),
@@ -666,6 +902,205 @@ mod tests {
         assert!(input.validate().is_ok());
     }
 
+    fn settings_with_default_visibility(visibility: Visibility) -> meta_settings::Model {
+        meta_settings::Model {
+            id: meta_settings::META_SETTINGS_ID.to_string(),
+            name: None,
+            short_name: None,
+            description: None,
+            maintainer_name: None,
+            maintainer_email: None,
+            langs: serde_json::json!(["en"]),
+            icon_url: None,
+            banner_url: None,
+            theme_color: None,
+            disable_registration: false,
+            email_required_for_signup: false,
+            require_registration_approval: false,
+            force_nsfw_media: false,
+            default_blur_nsfw: true,
+            default_hide_ads: false,
+            max_note_text_length: 3000,
+            max_remote_note_text_length: 10000,
+            max_page_content_length: 65536,
+            max_pages_per_user: 100,
+            default_drive_capacity_mb: 1024,
+            max_file_size_mb: 256,
+            bubble_instances: None,
+            federation_hosts: None,
+            default_note_visibility: visibility,
+            default_is_locked: false,
+            default_receive_dm_from_followers_only: false,
+            default_theme: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn create_test_profile(user_id: &str, visibility: Visibility) -> user_profile::Model {
+        user_profile::Model {
+            user_id: user_id.to_string(),
+            password: None,
+            email: None,
+            email_verified: false,
+            email_undeliverable: false,
+            two_factor_secret: None,
+            two_factor_enabled: false,
+            two_factor_pending: None,
+            two_factor_backup_codes: None,
+            auto_accept_followed: false,
+            always_mark_nsfw: false,
+            pinned_page_ids: serde_json::json!([]),
+            pinned_note_ids: serde_json::json!([]),
+            fields: serde_json::json!([]),
+            muted_words: serde_json::json!([]),
+            user_css: None,
+            birthday: None,
+            location: None,
+            lang: None,
+            pronouns: None,
+            also_known_as: None,
+            moved_to_uri: None,
+            hide_bots: false,
+            default_reaction: None,
+            receive_dm_from_followers_only: false,
+            secure_fetch_only: false,
+            disallow_quotes: false,
+            hide_online_status: false,
+            default_note_visibility: visibility,
+            theme: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_applies_instance_default_visibility() {
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([
+                    Vec::<user::Model>::new(),
+                    vec![create_test_user("user1", "newbie")],
+                ])
+                .into_connection(),
+        );
+        let profile_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[create_test_profile("user1", Visibility::Home)]])
+                .into_connection(),
+        );
+        let keypair_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[user_keypair::Model {
+                    user_id: "user1".to_string(),
+                    public_key: String::new(),
+                    private_key: String::new(),
+                    key_id: String::new(),
+                    created_at: Utc::now().into(),
+                }]])
+                .into_connection(),
+        );
+        let note_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let meta_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[settings_with_default_visibility(Visibility::Home)]])
+                .into_connection(),
+        );
+
+        let mut service =
+            create_test_service(user_db, Arc::clone(&profile_db), keypair_db, note_db);
+        service.set_meta_settings_service(MetaSettingsService::new(meta_db));
+
+        service
+            .create(CreateUserInput {
+                username: "newbie".to_string(),
+                password: "password123".to_string(),
+                name: None,
+            })
+            .await
+            .unwrap();
+        drop(service);
+
+        let log = Arc::try_unwrap(profile_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        let insert = log
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .find(|stmt| stmt.sql.starts_with(r#"INSERT INTO "user_profile""#))
+            .unwrap();
+        assert!(insert.sql.contains(r#""default_note_visibility""#));
+        assert!(format!("{:?}", insert.values).contains(r#""home""#));
+    }
+
+    fn create_test_note(id: &str, user_id: &str) -> note::Model {
+        note::Model {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            user_host: None,
+            text: Some(format!("Pinned {id}")),
+            cw: None,
+            visibility: Visibility::Public,
+            reply_id: None,
+            renote_id: None,
+            thread_id: None,
+            mentions: json!([]),
+            visible_user_ids: json!([]),
+            file_ids: json!([]),
+            tags: json!([]),
+            reactions: json!({}),
+            replies_count: 0,
+            renote_count: 0,
+            reaction_count: 0,
+            is_local: true,
+            uri: None,
+            url: None,
+            channel_id: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_profile_details_skip_deleted_and_hidden_pinned_notes() {
+        let mut profile = create_test_profile("user1", Visibility::Public);
+        profile.pinned_note_ids = json!(["note2", "deleted", "note1", "note3"]);
+        profile.fields = json!([{ "name": "Site", "value": "https://example.com" }]);
+        let profile_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[profile]])
+                .into_connection(),
+        );
+
+        // The deleted note is gone; note3 was narrowed to followers after pinning
+        let mut followers_only = create_test_note("note3", "user1");
+        followers_only.visibility = Visibility::Followers;
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[
+                    create_test_note("note1", "user1"),
+                    create_test_note("note2", "user1"),
+                    followers_only,
+                ]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let keypair_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let service = create_test_service(user_db, profile_db, keypair_db, note_db);
+        let details = service.get_profile_details("user1", None).await.unwrap();
+
+        let pinned: Vec<&str> = details
+            .pinned_notes
+            .iter()
+            .map(|note| note.id.as_str())
+            .collect();
+        assert_eq!(pinned, ["note2", "note1"]);
+        assert_eq!(details.fields.len(), 1);
+        assert_eq!(details.fields[0].name, "Site");
+    }
+
     #[tokio::test]
     async fn test_update_user_input_validation() {
         // Test description too long
@@ -683,6 +1118,8 @@ mod tests {
             hide_bots: None,
             default_reaction: None,
             secure_fetch_only: None,
+            disallow_quotes: None,
+            hide_online_status: None,
         };
         assert!(input.validate().is_err());
 
@@ -701,6 +1138,8 @@ mod tests {
             hide_bots: Some(true),
             default_reaction: Some("👍".to_string()),
             secure_fetch_only: Some(false),
+            disallow_quotes: None,
+            hide_online_status: None,
         };
         assert!(input.validate().is_ok());
 
@@ -719,7 +1158,118 @@ mod tests {
             hide_bots: None,
             default_reaction: Some("a".repeat(300)),
             secure_fetch_only: None,
+            disallow_quotes: None,
+            hide_online_status: None,
         };
         assert!(input.validate().is_err());
+
+        // Test default_reaction that is not a legal reaction
+        let input = UpdateUserInput {
+            default_reaction: Some("like".to_string()),
+            ..input
+        };
+        assert!(input.validate().is_err());
+    }
+
+    fn follow(id: &str, follower: &user::Model) -> following::Model {
+        following::Model {
+            id: id.to_string(),
+            follower_id: follower.id.clone(),
+            followee_id: "user1".to_string(),
+            follower_host: follower.host.clone(),
+            followee_host: None,
+            followee_inbox: None,
+            followee_shared_inbox: None,
+            created_at: Utc::now().into(),
+        }
+    }
+
+    fn remote_user(id: &str, host: &str, shared_inbox: bool) -> user::Model {
+        let mut user = create_test_user(id, id);
+        user.host = Some(host.to_string());
+        user.inbox = Some(format!("https://{host}/users/{id}/inbox"));
+        user.shared_inbox = shared_inbox.then(|| format!("https://{host}/inbox"));
+        user
+    }
+
+    #[tokio::test]
+    async fn test_profile_update_targets_only_follower_inboxes() {
+        let alice = remote_user("alice", "a.example", true);
+        let amy = remote_user("amy", "a.example", true);
+        let bob = remote_user("bob", "b.example", false);
+        let local_follower = create_test_user("carol", "carol");
+
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([
+                    vec![create_test_user("user1", "me")],
+                    vec![create_test_user("user1", "me")],
+                    vec![alice.clone(), amy.clone(), bob.clone()],
+                ])
+                .into_connection(),
+        );
+        let following_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[
+                    follow("f1", &alice),
+                    follow("f2", &amy),
+                    follow("f3", &bob),
+                    follow("f4", &local_follower),
+                ]])
+                .into_connection(),
+        );
+        let keypair_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<user_keypair::Model>::new()])
+                .into_connection(),
+        );
+        let profile_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<user_profile::Model>::new()])
+                .into_connection(),
+        );
+        let note_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let delivery = Arc::new(DeliveryRecorder::default());
+        let mut service = create_test_service(user_db, profile_db, keypair_db, note_db);
+        service.set_delivery(delivery.clone(), FollowingRepository::new(following_db));
+
+        service
+            .update(
+                "user1",
+                UpdateUserInput {
+                    name: Some("New Name".to_string()),
+                    description: None,
+                    avatar_id: None,
+                    banner_id: None,
+                    avatar_url: None,
+                    banner_url: None,
+                    is_bot: None,
+                    is_cat: None,
+                    is_locked: None,
+                    pronouns: None,
+                    hide_bots: None,
+                    default_reaction: None,
+                    secure_fetch_only: None,
+                    disallow_quotes: None,
+                    hide_online_status: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        let updates = delivery.activities("update_actor");
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0]["type"], "Update");
+        assert_eq!(updates[0]["object"]["type"], "Person");
+
+        let inboxes = delivery.inboxes("update_actor");
+        assert_eq!(
+            inboxes,
+            vec![
+                "https://a.example/inbox".to_string(),
+                "https://b.example/users/bob/inbox".to_string(),
+            ]
+        );
     }
 }
@@ -3,6 +3,8 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::note::Visibility;
+
 /// Instance-wide settings and configuration.
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "meta_settings")]
@@ -106,6 +108,27 @@ pub struct Model {
     #[sea_orm(column_type = "JsonBinary", nullable)]
     pub bubble_instances: Option<Json>,
 
+    // Federation settings
+    /// Hosts listed for the allowlist/blocklist federation mode (JSON array of hostnames)
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub federation_hosts: Option<Json>,
+
+    // Defaults for new users
+    /// Default note visibility for newly registered users
+    pub default_note_visibility: Visibility,
+
+    /// Whether newly registered accounts start locked
+    #[sea_orm(default_value = false)]
+    pub default_is_locked: bool,
+
+    /// Whether newly registered users only accept DMs from followers
+    #[sea_orm(default_value = false)]
+    pub default_receive_dm_from_followers_only: bool,
+
+    /// Default client theme for newly registered users
+    #[sea_orm(nullable)]
+    pub default_theme: Option<String>,
+
     // Timestamps
     pub created_at: DateTimeWithTimeZone,
 
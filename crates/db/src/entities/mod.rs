@@ -36,15 +36,18 @@ pub mod muting;
 pub mod note;
 pub mod note_edit;
 pub mod note_favorite;
+pub mod note_thread_muting;
 pub mod notification;
 pub mod oauth_app;
 pub mod oauth_token;
 pub mod page;
 pub mod page_like;
+pub mod password_reset_request;
 pub mod poll;
 pub mod poll_vote;
 pub mod push_subscription;
 pub mod reaction;
+pub mod reaction_usage;
 pub mod recurring_post;
 pub mod registration_approval;
 pub mod scheduled_note;
@@ -90,15 +93,18 @@ pub use muting::Entity as Muting;
 pub use note::Entity as Note;
 pub use note_edit::Entity as NoteEdit;
 pub use note_favorite::Entity as NoteFavorite;
+pub use note_thread_muting::Entity as NoteThreadMuting;
 pub use notification::Entity as Notification;
 pub use oauth_app::Entity as OAuthApp;
 pub use oauth_token::Entity as OAuthToken;
 pub use page::Entity as Page;
 pub use page_like::Entity as PageLike;
+pub use password_reset_request::Entity as PasswordResetRequest;
 pub use poll::Entity as Poll;
 pub use poll_vote::Entity as PollVote;
 pub use push_subscription::Entity as PushSubscription;
 pub use reaction::Entity as Reaction;
+pub use reaction_usage::Entity as ReactionUsage;
 pub use recurring_post::Entity as RecurringPost;
 pub use registration_approval::Entity as RegistrationApproval;
 pub use scheduled_note::Entity as ScheduledNote;
@@ -0,0 +1,42 @@
+//! Password reset request entity (pending reset tokens).
+
+use sea_orm::entity::prelude::*;
+
+/// Password reset request entity.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "password_reset_request")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+
+    /// User whose password may be reset.
+    pub user_id: String,
+
+    /// SHA-256 hex digest of the emailed token (the token itself is never stored).
+    pub token_hash: String,
+
+    /// When the token stops being accepted.
+    pub expires_at: DateTimeWithTimeZone,
+
+    /// When the reset was requested.
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,42 @@
+//! Reaction usage entity (how often a user has used each reaction).
+
+use sea_orm::entity::prelude::*;
+
+/// Reaction usage entity.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "reaction_usage")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+
+    /// User who reacted.
+    pub user_id: String,
+
+    /// Reaction used (Unicode emoji or `:shortcode:`).
+    pub reaction: String,
+
+    /// Number of times the user has reacted with it.
+    pub count: i32,
+
+    /// When the user last reacted with it.
+    pub last_used_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -78,6 +78,10 @@ pub struct Model {
     #[sea_orm(default_value = false)]
     pub is_moderator: bool,
 
+    /// Is this a system account (e.g. the instance actor) rather than a person?
+    #[sea_orm(default_value = false)]
+    pub is_system: bool,
+
     /// `ActivityPub` inbox URL (remote users)
     #[sea_orm(nullable)]
     pub inbox: Option<String>,
@@ -90,6 +94,10 @@ pub struct Model {
     #[sea_orm(nullable)]
     pub featured: Option<String>,
 
+    /// `ActivityPub` followers collection URL
+    #[sea_orm(nullable)]
+    pub followers_uri: Option<String>,
+
     /// `ActivityPub` URI
     #[sea_orm(nullable)]
     pub uri: Option<String>,
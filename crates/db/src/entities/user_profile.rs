@@ -3,6 +3,8 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::note::Visibility;
+
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "user_profile")]
 pub struct Model {
@@ -22,6 +24,10 @@ pub struct Model {
     #[sea_orm(default_value = false)]
     pub email_verified: bool,
 
+    /// Has the mail provider reported the address as undeliverable?
+    #[sea_orm(default_value = false)]
+    pub email_undeliverable: bool,
+
     /// Two-factor authentication secret
     #[sea_orm(nullable)]
     pub two_factor_secret: Option<String>,
@@ -108,6 +114,21 @@ pub struct Model {
     #[sea_orm(default_value = false)]
     pub secure_fetch_only: bool,
 
+    /// Reject quotes of this user's notes (advertised to remote servers)
+    #[sea_orm(default_value = false)]
+    pub disallow_quotes: bool,
+
+    /// Hide whether this user is online from other users
+    #[sea_orm(default_value = false)]
+    pub hide_online_status: bool,
+
+    /// Default visibility for new notes
+    pub default_note_visibility: Visibility,
+
+    /// Client theme preference
+    #[sea_orm(nullable)]
+    pub theme: Option<String>,
+
     pub created_at: DateTimeWithTimeZone,
 
     #[sea_orm(nullable)]
@@ -7,6 +7,7 @@
 //! - **Repositories**: Data access patterns in [`repositories`]
 //! - **Test utilities**: Mock database support in [`test_utils`]
 //! - **Read Replicas**: Automatic read/write splitting via [`DatabasePool`]
+//! - **Slow queries**: Threshold-based slow-query logging in [`slow_query`]
 //!
 //! # Example
 //!
@@ -31,10 +32,12 @@
 pub mod entities;
 pub mod migrations;
 pub mod repositories;
+pub mod slow_query;
 pub mod test_utils;
 
 use misskey_common::{AppError, Config};
 use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use slow_query::{SlowQueryLogger, init_slow_query_logger, log_slow_queries};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
@@ -154,19 +157,35 @@ fn create_connect_options(url: &str, max_conns: u32, min_conns: u32) -> ConnectO
     opt
 }
 
+/// Install the configured slow-query threshold (first call wins).
+fn init_slow_query_logging(config: &Config) {
+    let logger = SlowQueryLogger::from_config(&config.database);
+    let threshold = logger.threshold();
+    if init_slow_query_logger(logger).is_ok() {
+        info!(
+            threshold_ms = threshold.as_millis() as u64,
+            "Slow query logging configured"
+        );
+    }
+}
+
 /// Initialize database connection (single primary).
 ///
 /// For read replica support, use [`init_pool`] instead.
 pub async fn init(config: &Config) -> Result<DatabaseConnection, AppError> {
+    init_slow_query_logging(config);
+
     let opt = create_connect_options(
         &config.database.url,
         config.database.max_connections,
         config.database.min_connections,
     );
 
-    Database::connect(opt)
+    let mut db = Database::connect(opt)
         .await
-        .map_err(|e| AppError::Database(e.to_string()))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    log_slow_queries(&mut db);
+    Ok(db)
 }
 
 /// Initialize database pool with optional read replicas.
@@ -175,6 +194,8 @@ pub async fn init(config: &Config) -> Result<DatabaseConnection, AppError> {
 /// to those replicas will be established. Failed replica connections are
 /// logged as warnings but don't prevent startup.
 pub async fn init_pool(config: &Config) -> Result<DatabasePool, AppError> {
+    init_slow_query_logging(config);
+
     // Connect to primary
     let primary_opt = create_connect_options(
         &config.database.url,
@@ -182,9 +203,10 @@ pub async fn init_pool(config: &Config) -> Result<DatabasePool, AppError> {
         config.database.min_connections,
     );
 
-    let primary = Database::connect(primary_opt)
+    let mut primary = Database::connect(primary_opt)
         .await
         .map_err(|e| AppError::Database(format!("Failed to connect to primary: {e}")))?;
+    log_slow_queries(&mut primary);
 
     info!("Connected to primary database");
 
@@ -202,7 +224,8 @@ pub async fn init_pool(config: &Config) -> Result<DatabasePool, AppError> {
                 create_connect_options(url, conns_per_replica.max(1), min_per_replica.max(1));
 
             match Database::connect(replica_opt).await {
-                Ok(conn) => {
+                Ok(mut conn) => {
+                    log_slow_queries(&mut conn);
                     info!(replica = i, "Connected to read replica");
                     replicas.push(conn);
                 }
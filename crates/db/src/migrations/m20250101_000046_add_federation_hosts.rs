@@ -0,0 +1,47 @@
+//! Migration to add `federation_hosts` column to `meta_settings` table.
+//!
+//! This holds the host list used by the allowlist and blocklist federation
+//! modes.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add federation_hosts column (JSON array of hostnames)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MetaSettings::Table)
+                    .add_column(
+                        ColumnDef::new(MetaSettings::FederationHosts)
+                            .json_binary()
+                            .null()
+                            .default(Value::String(Some(Box::new("[]".to_string())))),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MetaSettings::Table)
+                    .drop_column(MetaSettings::FederationHosts)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Meta settings table for the migration.
+#[derive(Iden)]
+enum MetaSettings {
+    Table,
+    FederationHosts,
+}
@@ -0,0 +1,102 @@
+//! Migration to add instance-wide defaults for new users.
+//!
+//! Adds the admin-managed defaults to `meta_settings` and the matching
+//! per-user columns to `user_profile`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MetaSettings::Table)
+                    .add_column(
+                        ColumnDef::new(MetaSettings::DefaultNoteVisibility)
+                            .string_len(16)
+                            .not_null()
+                            .default("public"),
+                    )
+                    .add_column(
+                        ColumnDef::new(MetaSettings::DefaultIsLocked)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column(
+                        ColumnDef::new(MetaSettings::DefaultReceiveDmFromFollowersOnly)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column(
+                        ColumnDef::new(MetaSettings::DefaultTheme)
+                            .string_len(128)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserProfile::Table)
+                    .add_column(
+                        ColumnDef::new(UserProfile::DefaultNoteVisibility)
+                            .string_len(16)
+                            .not_null()
+                            .default("public"),
+                    )
+                    .add_column(ColumnDef::new(UserProfile::Theme).string_len(128).null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserProfile::Table)
+                    .drop_column(UserProfile::DefaultNoteVisibility)
+                    .drop_column(UserProfile::Theme)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MetaSettings::Table)
+                    .drop_column(MetaSettings::DefaultNoteVisibility)
+                    .drop_column(MetaSettings::DefaultIsLocked)
+                    .drop_column(MetaSettings::DefaultReceiveDmFromFollowersOnly)
+                    .drop_column(MetaSettings::DefaultTheme)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Meta settings table for the migration.
+#[derive(Iden)]
+enum MetaSettings {
+    Table,
+    DefaultNoteVisibility,
+    DefaultIsLocked,
+    DefaultReceiveDmFromFollowersOnly,
+    DefaultTheme,
+}
+
+/// User profile table for the migration.
+#[derive(Iden)]
+enum UserProfile {
+    Table,
+    DefaultNoteVisibility,
+    Theme,
+}
@@ -0,0 +1,79 @@
+//! Create `note_thread_muting` table for muting note threads.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NoteThreadMuting::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NoteThreadMuting::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(NoteThreadMuting::UserId).string().not_null())
+                    .col(
+                        ColumnDef::new(NoteThreadMuting::ThreadId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NoteThreadMuting::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(NoteThreadMuting::Table, NoteThreadMuting::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // A user mutes a thread at most once
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_note_thread_muting_user_thread")
+                    .table(NoteThreadMuting::Table)
+                    .col(NoteThreadMuting::UserId)
+                    .col(NoteThreadMuting::ThreadId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NoteThreadMuting::Table).to_owned())
+            .await
+    }
+}
+
+/// Note thread muting table for the migration.
+#[derive(Iden)]
+enum NoteThreadMuting {
+    Table,
+    Id,
+    UserId,
+    ThreadId,
+    CreatedAt,
+}
+
+/// User table for the migration.
+#[derive(Iden)]
+enum User {
+    Table,
+    Id,
+}
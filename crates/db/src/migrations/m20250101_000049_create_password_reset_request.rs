@@ -0,0 +1,99 @@
+//! Create `password_reset_request` table for the password reset flow.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PasswordResetRequest::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PasswordResetRequest::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PasswordResetRequest::UserId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PasswordResetRequest::TokenHash)
+                            .string_len(64)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PasswordResetRequest::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PasswordResetRequest::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PasswordResetRequest::Table, PasswordResetRequest::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_password_reset_request_token_hash")
+                    .table(PasswordResetRequest::Table)
+                    .col(PasswordResetRequest::TokenHash)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // For per-user rate limiting and invalidation
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_password_reset_request_user_created")
+                    .table(PasswordResetRequest::Table)
+                    .col(PasswordResetRequest::UserId)
+                    .col(PasswordResetRequest::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PasswordResetRequest::Table).to_owned())
+            .await
+    }
+}
+
+/// Password reset request table for the migration.
+#[derive(Iden)]
+enum PasswordResetRequest {
+    Table,
+    Id,
+    UserId,
+    TokenHash,
+    ExpiresAt,
+    CreatedAt,
+}
+
+/// User table for the migration.
+#[derive(Iden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,44 @@
+//! Migration to track undeliverable email addresses on `user_profile`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Set when the mail provider reports a hard bounce or complaint;
+        // non-essential mail is no longer sent to the address
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserProfile::Table)
+                    .add_column(
+                        ColumnDef::new(UserProfile::EmailUndeliverable)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserProfile::Table)
+                    .drop_column(UserProfile::EmailUndeliverable)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum UserProfile {
+    Table,
+    EmailUndeliverable,
+}
@@ -0,0 +1,82 @@
+//! Create `reaction_usage` table for per-user reaction shortcuts.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ReactionUsage::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ReactionUsage::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ReactionUsage::UserId).string().not_null())
+                    .col(ColumnDef::new(ReactionUsage::Reaction).string().not_null())
+                    .col(
+                        ColumnDef::new(ReactionUsage::Count)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .col(
+                        ColumnDef::new(ReactionUsage::LastUsedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ReactionUsage::Table, ReactionUsage::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // One row per user and reaction, upserted on each use
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_reaction_usage_user_reaction")
+                    .table(ReactionUsage::Table)
+                    .col(ReactionUsage::UserId)
+                    .col(ReactionUsage::Reaction)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ReactionUsage::Table).to_owned())
+            .await
+    }
+}
+
+/// Reaction usage table for the migration.
+#[derive(Iden)]
+enum ReactionUsage {
+    Table,
+    Id,
+    UserId,
+    Reaction,
+    Count,
+    LastUsedAt,
+}
+
+/// User table for the migration.
+#[derive(Iden)]
+enum User {
+    Table,
+    Id,
+}
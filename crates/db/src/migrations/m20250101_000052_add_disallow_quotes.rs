@@ -0,0 +1,44 @@
+//! Migration to add the quote opt-out setting to `user_profile`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add disallow_quotes field to user_profile
+        // When true, other users cannot quote this user's notes
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserProfile::Table)
+                    .add_column(
+                        ColumnDef::new(UserProfile::DisallowQuotes)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserProfile::Table)
+                    .drop_column(UserProfile::DisallowQuotes)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum UserProfile {
+    Table,
+    DisallowQuotes,
+}
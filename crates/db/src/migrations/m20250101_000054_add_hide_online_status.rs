@@ -0,0 +1,44 @@
+//! Migration to add the online status opt-out setting to `user_profile`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add hide_online_status field to user_profile
+        // When true, other users cannot see whether this user is online
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserProfile::Table)
+                    .add_column(
+                        ColumnDef::new(UserProfile::HideOnlineStatus)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserProfile::Table)
+                    .drop_column(UserProfile::HideOnlineStatus)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum UserProfile {
+    Table,
+    HideOnlineStatus,
+}
@@ -0,0 +1,55 @@
+//! Migration to drop self-replies from note reply counts.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Local self-replies used to be counted when created; they only
+        // continue a thread now and are no longer decremented on delete
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r"
+                UPDATE note AS parent
+                SET replies_count = GREATEST(parent.replies_count - self_replies.count, 0)
+                FROM (
+                    SELECT reply.reply_id, COUNT(*) AS count
+                    FROM note AS reply
+                    JOIN note AS replied ON replied.id = reply.reply_id
+                    WHERE reply.is_local AND reply.user_id = replied.user_id
+                    GROUP BY reply.reply_id
+                ) AS self_replies
+                WHERE parent.id = self_replies.reply_id;
+                ",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r"
+                UPDATE note AS parent
+                SET replies_count = parent.replies_count + self_replies.count
+                FROM (
+                    SELECT reply.reply_id, COUNT(*) AS count
+                    FROM note AS reply
+                    JOIN note AS replied ON replied.id = reply.reply_id
+                    WHERE reply.is_local AND reply.user_id = replied.user_id
+                    GROUP BY reply.reply_id
+                ) AS self_replies
+                WHERE parent.id = self_replies.reply_id;
+                ",
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -51,6 +51,17 @@ mod m20250101_000042_add_authorized_fetch_settings;
 mod m20250101_000043_add_smart_clip_features;
 mod m20250101_000044_add_recurring_posts;
 mod m20250101_000045_add_filter_groups;
+mod m20250101_000046_add_federation_hosts;
+mod m20250101_000047_add_new_user_defaults;
+mod m20250101_000048_create_note_thread_muting;
+mod m20250101_000049_create_password_reset_request;
+mod m20250101_000050_add_email_undeliverable;
+mod m20250101_000051_create_reaction_usage;
+mod m20250101_000052_add_disallow_quotes;
+mod m20250101_000053_add_user_is_system;
+mod m20250101_000054_add_hide_online_status;
+mod m20250101_000055_add_user_followers_uri;
+mod m20250101_000056_uncount_self_replies;
 
 pub struct Migrator;
 
@@ -103,6 +114,17 @@ impl MigratorTrait for Migrator {
             Box::new(m20250101_000043_add_smart_clip_features::Migration),
             Box::new(m20250101_000044_add_recurring_posts::Migration),
             Box::new(m20250101_000045_add_filter_groups::Migration),
+            Box::new(m20250101_000046_add_federation_hosts::Migration),
+            Box::new(m20250101_000047_add_new_user_defaults::Migration),
+            Box::new(m20250101_000048_create_note_thread_muting::Migration),
+            Box::new(m20250101_000049_create_password_reset_request::Migration),
+            Box::new(m20250101_000050_add_email_undeliverable::Migration),
+            Box::new(m20250101_000051_create_reaction_usage::Migration),
+            Box::new(m20250101_000052_add_disallow_quotes::Migration),
+            Box::new(m20250101_000053_add_user_is_system::Migration),
+            Box::new(m20250101_000054_add_hide_online_status::Migration),
+            Box::new(m20250101_000055_add_user_followers_uri::Migration),
+            Box::new(m20250101_000056_uncount_self_replies::Migration),
         ]
     }
 }
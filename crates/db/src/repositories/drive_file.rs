@@ -5,10 +5,21 @@ use std::sync::Arc;
 use crate::entities::{DriveFile, drive_file};
 use misskey_common::{AppError, AppResult};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait, QueryFilter,
-    QueryOrder, QuerySelect,
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, FromQueryResult, ModelTrait,
+    QueryFilter, QueryOrder, QuerySelect,
 };
 
+/// Storage used by a user's files in one drive folder.
+#[derive(Debug, Clone, PartialEq, Eq, FromQueryResult)]
+pub struct FolderUsage {
+    /// Folder ID, `None` for the drive root.
+    pub folder_id: Option<String>,
+    /// Number of stored (non-link) files.
+    pub file_count: i64,
+    /// Logical size of the files in bytes.
+    pub bytes: i64,
+}
+
 /// Drive file repository for database operations.
 #[derive(Clone)]
 pub struct DriveFileRepository {
@@ -142,6 +153,60 @@ impl DriveFileRepository {
         Ok(result.and_then(|r| r.total).unwrap_or(0))
     }
 
+    /// Get file count and logical size per folder for a user.
+    ///
+    /// Remote link files take no local storage and are excluded.
+    pub async fn usage_by_folder(&self, user_id: &str) -> AppResult<Vec<FolderUsage>> {
+        use sea_orm::sea_query::{Alias, Expr};
+
+        DriveFile::find()
+            .filter(drive_file::Column::UserId.eq(user_id))
+            .filter(drive_file::Column::IsLink.eq(false))
+            .select_only()
+            .column(drive_file::Column::FolderId)
+            .column_as(drive_file::Column::Id.count(), "file_count")
+            .column_as(
+                Expr::col(drive_file::Column::Size)
+                    .sum()
+                    .cast_as(Alias::new("bigint")),
+                "bytes",
+            )
+            .group_by(drive_file::Column::FolderId)
+            .order_by_asc(drive_file::Column::FolderId)
+            .into_model::<FolderUsage>()
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Get the physical storage used by a user, counting files with the same
+    /// content (MD5) only once.
+    pub async fn get_physical_storage_used(&self, user_id: &str) -> AppResult<i64> {
+        use sea_orm::{ConnectionTrait, Statement};
+
+        let sql = "
+            SELECT COALESCE(SUM(size), 0)::bigint AS total FROM (
+                SELECT DISTINCT ON (COALESCE(md5, id)) size
+                FROM drive_file
+                WHERE user_id = $1 AND is_link = false
+            ) AS unique_files
+        ";
+
+        let row = self
+            .db
+            .query_one(Statement::from_sql_and_values(
+                sea_orm::DatabaseBackend::Postgres,
+                sql,
+                [user_id.into()],
+            ))
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(row
+            .and_then(|r| r.try_get::<i64>("", "total").ok())
+            .unwrap_or(0))
+    }
+
     /// Find unattached files for a user (files not used in notes, pages, etc.)
     /// Returns files that are not referenced anywhere.
     pub async fn find_unattached(
@@ -6,7 +6,7 @@ use crate::entities::{Emoji, emoji};
 use misskey_common::{AppError, AppResult};
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait, PaginatorTrait,
-    QueryFilter, QueryOrder, QuerySelect,
+    QueryFilter, QueryOrder, QuerySelect, Set,
 };
 
 /// Emoji repository for database operations.
@@ -195,4 +195,34 @@ impl EmojiRepository {
 
         self.create(model).await
     }
+
+    /// Insert a remote emoji, or refresh the stored image if it already exists.
+    ///
+    /// Remote emojis are keyed by `(name, host)`. When the incoming image URL differs
+    /// from the stored one, the existing row is updated in place.
+    pub async fn upsert_remote(&self, model: emoji::ActiveModel) -> AppResult<emoji::Model> {
+        let (Some(name), Some(Some(host)), Some(original_url)) = (
+            model.name.try_as_ref().cloned(),
+            model.host.try_as_ref().cloned(),
+            model.original_url.try_as_ref().cloned(),
+        ) else {
+            return Err(AppError::Internal(
+                "Remote emoji requires name, host and original URL".to_string(),
+            ));
+        };
+
+        let Some(existing) = self.find_by_name_and_host(&name, Some(&host)).await? else {
+            return self.create(model).await;
+        };
+
+        if existing.original_url == original_url {
+            return Ok(existing);
+        }
+
+        let mut active: emoji::ActiveModel = existing.into();
+        active.original_url = Set(original_url);
+        active.content_type = model.content_type;
+        active.updated_at = Set(Some(chrono::Utc::now()));
+        self.update(active).await
+    }
 }
@@ -4,7 +4,7 @@ use std::sync::Arc;
 
 use crate::entities::{Hashtag, hashtag};
 use chrono::Utc;
-use misskey_common::{AppError, AppResult, IdGenerator};
+use misskey_common::{AppError, AppResult, IdGenerator, normalize_hashtag};
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
     QuerySelect, Set,
@@ -29,7 +29,7 @@ impl HashtagRepository {
 
     /// Find a hashtag by name.
     pub async fn find_by_name(&self, name: &str) -> AppResult<Option<hashtag::Model>> {
-        let name_lower = name.to_lowercase();
+        let name_lower = normalize_hashtag(name);
         Hashtag::find()
             .filter(hashtag::Column::Name.eq(&name_lower))
             .one(self.db.as_ref())
@@ -39,7 +39,7 @@ impl HashtagRepository {
 
     /// Get or create a hashtag.
     pub async fn get_or_create(&self, name: &str) -> AppResult<hashtag::Model> {
-        let name_lower = name.to_lowercase();
+        let name_lower = normalize_hashtag(name);
 
         // Try to find existing
         if let Some(tag) = self.find_by_name(&name_lower).await? {
@@ -143,7 +143,7 @@ impl HashtagRepository {
 
     /// Search hashtags by prefix.
     pub async fn search(&self, query: &str, limit: u64) -> AppResult<Vec<hashtag::Model>> {
-        let query_lower = query.to_lowercase();
+        let query_lower = normalize_hashtag(query);
         let pattern = format!("{query_lower}%");
 
         Hashtag::find()
@@ -1,26 +1,54 @@
 //! Meta settings repository.
 
-use std::sync::Arc;
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
 
-use crate::entities::{MetaSettings, meta_settings};
+use crate::entities::{MetaSettings, meta_settings, note::Visibility};
 use misskey_common::{AppError, AppResult};
 use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
 use serde_json::json;
 
 /// Repository for instance-wide settings.
+///
+/// The federation host list is cached and shared by all clones. The cache
+/// is dropped whenever the settings are updated through this repository and
+/// expires after [`FEDERATION_HOSTS_TTL`], so other processes pick up
+/// changes made elsewhere.
 #[derive(Clone)]
 pub struct MetaSettingsRepository {
     db: Arc<DatabaseConnection>,
+    federation_hosts: Arc<RwLock<Option<CachedHosts>>>,
+    federation_hosts_ttl: Duration,
 }
 
 /// Singleton ID for the meta settings
 pub const META_SETTINGS_ID: &str = "instance";
 
+/// Cached federation host list and when it expires.
+type CachedHosts = (Vec<String>, Instant);
+
+/// How long the cached federation host list is trusted.
+pub const FEDERATION_HOSTS_TTL: Duration = Duration::from_secs(30);
+
 impl MetaSettingsRepository {
     /// Create a new meta settings repository.
     #[must_use]
-    pub const fn new(db: Arc<DatabaseConnection>) -> Self {
-        Self { db }
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self {
+            db,
+            federation_hosts: Arc::default(),
+            federation_hosts_ttl: FEDERATION_HOSTS_TTL,
+        }
+    }
+
+    /// Keep the cached federation host list for `ttl` instead of
+    /// [`FEDERATION_HOSTS_TTL`].
+    #[must_use]
+    pub const fn with_federation_hosts_ttl(mut self, ttl: Duration) -> Self {
+        self.federation_hosts_ttl = ttl;
+        self
     }
 
     /// Get the instance settings, creating default if not exists.
@@ -55,6 +83,11 @@ impl MetaSettingsRepository {
             default_drive_capacity_mb: Set(1024),
             max_file_size_mb: Set(256),
             bubble_instances: Set(Some(json!([]))),
+            federation_hosts: Set(Some(json!([]))),
+            default_note_visibility: Set(Visibility::Public),
+            default_is_locked: Set(false),
+            default_receive_dm_from_followers_only: Set(false),
+            default_theme: Set(None),
             created_at: Set(now.into()),
             updated_at: Set(None),
         };
@@ -74,14 +107,127 @@ impl MetaSettingsRepository {
             .map_err(|e| AppError::Database(e.to_string()))
     }
 
+    /// Get the host list used by the allowlist/blocklist federation mode.
+    ///
+    /// Served from the cache until it expires.
+    pub async fn federation_hosts(&self) -> AppResult<Vec<String>> {
+        if let Some(hosts) = self.federation_hosts.read().ok().and_then(|cached| {
+            cached
+                .as_ref()
+                .filter(|(_, expires_at)| *expires_at > Instant::now())
+                .map(|(hosts, _)| hosts.clone())
+        }) {
+            return Ok(hosts);
+        }
+
+        let hosts = self
+            .find()
+            .await?
+            .and_then(|s| s.federation_hosts)
+            .and_then(|v| serde_json::from_value::<Vec<String>>(v).ok())
+            .unwrap_or_default();
+        if let Ok(mut cached) = self.federation_hosts.write() {
+            *cached = Some((hosts.clone(), Instant::now() + self.federation_hosts_ttl));
+        }
+        Ok(hosts)
+    }
+
+    /// Drop the cached federation host list so the next read hits the database.
+    pub fn invalidate_federation_hosts(&self) {
+        if let Ok(mut cached) = self.federation_hosts.write() {
+            *cached = None;
+        }
+    }
+
     /// Update the instance settings.
     pub async fn update(
         &self,
         model: meta_settings::ActiveModel,
     ) -> AppResult<meta_settings::Model> {
-        model
+        let settings = model
             .update(self.db.as_ref())
             .await
-            .map_err(|e| AppError::Database(e.to_string()))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        self.invalidate_federation_hosts();
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    fn settings(hosts: &[&str]) -> meta_settings::Model {
+        meta_settings::Model {
+            id: META_SETTINGS_ID.to_string(),
+            name: None,
+            short_name: None,
+            description: None,
+            maintainer_name: None,
+            maintainer_email: None,
+            langs: json!([]),
+            icon_url: None,
+            banner_url: None,
+            theme_color: None,
+            disable_registration: false,
+            email_required_for_signup: false,
+            require_registration_approval: false,
+            force_nsfw_media: false,
+            default_blur_nsfw: true,
+            default_hide_ads: false,
+            max_note_text_length: 3000,
+            max_remote_note_text_length: 10000,
+            max_page_content_length: 65536,
+            max_pages_per_user: 100,
+            default_drive_capacity_mb: 1024,
+            max_file_size_mb: 256,
+            bubble_instances: None,
+            federation_hosts: Some(json!(hosts)),
+            default_note_visibility: Visibility::Public,
+            default_is_locked: false,
+            default_receive_dm_from_followers_only: false,
+            default_theme: None,
+            created_at: chrono::Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_federation_hosts_changed_by_another_instance_are_seen_after_ttl() {
+        // Another process (API replica, queue worker) reads the host list
+        let reader_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[settings(&["old.example"])]])
+                .append_query_results([[settings(&["new.example"])]])
+                .into_connection(),
+        );
+        let reader = MetaSettingsRepository::new(Arc::clone(&reader_db))
+            .with_federation_hosts_ttl(Duration::from_millis(50));
+        assert_eq!(reader.federation_hosts().await.unwrap(), ["old.example"]);
+
+        // An admin updates the list through a second instance
+        let writer_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[settings(&["new.example"])]])
+                .into_connection(),
+        );
+        let writer = MetaSettingsRepository::new(writer_db);
+        let mut model: meta_settings::ActiveModel = settings(&["old.example"]).into();
+        model.federation_hosts = Set(Some(json!(["new.example"])));
+        writer.update(model).await.unwrap();
+
+        // The reader serves its cache until it expires, then sees the change
+        assert_eq!(reader.federation_hosts().await.unwrap(), ["old.example"]);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(reader.federation_hosts().await.unwrap(), ["new.example"]);
+
+        drop(reader);
+        let log = Arc::try_unwrap(reader_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert_eq!(log.len(), 2);
     }
 }
@@ -24,12 +24,15 @@ pub mod moderation;
 pub mod muting;
 pub mod note;
 pub mod note_favorite;
+pub mod note_thread_muting;
 pub mod notification;
 pub mod oauth;
 pub mod page;
+pub mod password_reset_request;
 pub mod poll;
 pub mod push_subscription;
 pub mod reaction;
+pub mod reaction_usage;
 pub mod recurring_post;
 pub mod scheduled_note;
 pub mod security_key;
@@ -46,7 +49,7 @@ pub use antenna::AntennaRepository;
 pub use blocking::BlockingRepository;
 pub use channel::ChannelRepository;
 pub use clip::{ClipRepository, SmartClipConditions};
-pub use drive_file::DriveFileRepository;
+pub use drive_file::{DriveFileRepository, FolderUsage};
 pub use drive_folder::DriveFolderRepository;
 pub use emoji::EmojiRepository;
 pub use export_job::ExportJobRepository;
@@ -64,18 +67,21 @@ pub use moderation::ModerationRepository;
 pub use muting::MutingRepository;
 pub use note::NoteRepository;
 pub use note_favorite::NoteFavoriteRepository;
+pub use note_thread_muting::NoteThreadMutingRepository;
 pub use notification::NotificationRepository;
 pub use oauth::OAuthRepository;
 pub use page::PageRepository;
+pub use password_reset_request::PasswordResetRequestRepository;
 pub use poll::{PollRepository, PollVoteRepository};
 pub use push_subscription::PushSubscriptionRepository;
 pub use reaction::ReactionRepository;
+pub use reaction_usage::ReactionUsageRepository;
 pub use recurring_post::{
     CreateRecurringPostInput, RecurringPostRepository, UpdateRecurringPostInput,
 };
 pub use scheduled_note::ScheduledNoteRepository;
 pub use security_key::SecurityKeyRepository;
-pub use user::UserRepository;
+pub use user::{UserRelation, UserRepository};
 pub use user_keypair::UserKeypairRepository;
 pub use user_list::UserListRepository;
 pub use user_profile::UserProfileRepository;
@@ -3,7 +3,7 @@
 use std::sync::Arc;
 
 use crate::entities::{Note, NoteEdit, note, note_edit};
-use misskey_common::{AppError, AppResult};
+use misskey_common::{AppError, AppResult, normalize_hashtag};
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, DbBackend, EntityTrait, PaginatorTrait,
     QueryFilter, QueryOrder, QuerySelect, Statement, sea_query::Expr,
@@ -760,8 +760,7 @@ impl NoteRepository {
 
         // Search in JSON tags array
         // PostgreSQL: tags @> '["tag"]'::jsonb
-        let tag_lower = tag.to_lowercase();
-        let tag_json = format!("[\"{tag_lower}\"]");
+        let tag_json = format!("[\"{}\"]", normalize_hashtag(tag));
 
         let mut condition =
             Condition::all().add(note::Column::Visibility.eq(note::Visibility::Public));
@@ -791,6 +790,20 @@ impl NoteRepository {
             .map_err(|e| AppError::Database(e.to_string()))
     }
 
+    /// Count notes a user has created since `since`.
+    pub async fn count_by_user_since(
+        &self,
+        user_id: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<u64> {
+        Note::find()
+            .filter(note::Column::UserId.eq(user_id))
+            .filter(note::Column::CreatedAt.gte(since))
+            .count(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
     // ==================== Channel Timeline ====================
 
     /// Get channel timeline (notes posted to a specific channel).
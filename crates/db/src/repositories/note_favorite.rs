@@ -52,6 +52,27 @@ impl NoteFavoriteRepository {
             .is_some())
     }
 
+    /// Get which of the given notes a user has favorited (single query).
+    pub async fn find_favorited_note_ids(
+        &self,
+        user_id: &str,
+        note_ids: &[String],
+    ) -> AppResult<Vec<String>> {
+        if note_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        NoteFavorite::find()
+            .select_only()
+            .column(note_favorite::Column::NoteId)
+            .filter(note_favorite::Column::UserId.eq(user_id))
+            .filter(note_favorite::Column::NoteId.is_in(note_ids.iter().cloned()))
+            .into_tuple()
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
     /// Create a new favorite.
     pub async fn create(
         &self,
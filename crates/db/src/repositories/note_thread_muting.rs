@@ -0,0 +1,88 @@
+//! Note thread muting repository.
+
+use std::sync::Arc;
+
+use crate::entities::{NoteThreadMuting, note_thread_muting};
+use misskey_common::{AppError, AppResult};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect,
+};
+
+/// Note thread muting repository for database operations.
+#[derive(Clone)]
+pub struct NoteThreadMutingRepository {
+    db: Arc<DatabaseConnection>,
+}
+
+impl NoteThreadMutingRepository {
+    /// Create a new note thread muting repository.
+    #[must_use]
+    pub const fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    /// Find a thread mute by user and thread.
+    pub async fn find_by_user_and_thread(
+        &self,
+        user_id: &str,
+        thread_id: &str,
+    ) -> AppResult<Option<note_thread_muting::Model>> {
+        NoteThreadMuting::find()
+            .filter(note_thread_muting::Column::UserId.eq(user_id))
+            .filter(note_thread_muting::Column::ThreadId.eq(thread_id))
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Check if a user has muted a thread.
+    pub async fn is_muted(&self, user_id: &str, thread_id: &str) -> AppResult<bool> {
+        Ok(self
+            .find_by_user_and_thread(user_id, thread_id)
+            .await?
+            .is_some())
+    }
+
+    /// Get which of the given threads a user has muted (single query).
+    pub async fn find_muted_thread_ids(
+        &self,
+        user_id: &str,
+        thread_ids: &[String],
+    ) -> AppResult<Vec<String>> {
+        if thread_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        NoteThreadMuting::find()
+            .select_only()
+            .column(note_thread_muting::Column::ThreadId)
+            .filter(note_thread_muting::Column::UserId.eq(user_id))
+            .filter(note_thread_muting::Column::ThreadId.is_in(thread_ids.iter().cloned()))
+            .into_tuple()
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Create a new thread mute.
+    pub async fn create(
+        &self,
+        model: note_thread_muting::ActiveModel,
+    ) -> AppResult<note_thread_muting::Model> {
+        model
+            .insert(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Delete a thread mute by user and thread.
+    pub async fn delete_by_user_and_thread(&self, user_id: &str, thread_id: &str) -> AppResult<()> {
+        NoteThreadMuting::delete_many()
+            .filter(note_thread_muting::Column::UserId.eq(user_id))
+            .filter(note_thread_muting::Column::ThreadId.eq(thread_id))
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+}
@@ -38,6 +38,24 @@ impl NotificationRepository {
             .map_err(|e| AppError::Database(e.to_string()))
     }
 
+    /// Overwrite the columns set in `model` on notification `id` in a single
+    /// statement; `model` may set a new ID.
+    ///
+    /// Returns `None` if the notification no longer exists.
+    pub async fn replace(
+        &self,
+        id: &str,
+        model: notification::ActiveModel,
+    ) -> AppResult<Option<notification::Model>> {
+        Notification::update_many()
+            .set(model)
+            .filter(notification::Column::Id.eq(id))
+            .exec_with_returning(self.db.as_ref())
+            .await
+            .map(|updated| updated.into_iter().next())
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
     /// Delete a notification.
     pub async fn delete(&self, id: &str) -> AppResult<()> {
         let notification = self.find_by_id(id).await?;
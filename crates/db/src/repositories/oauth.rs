@@ -191,6 +191,18 @@ impl OAuthRepository {
         Ok(result.rows_affected)
     }
 
+    /// Revoke all tokens issued to a user, across applications.
+    pub async fn revoke_tokens_for_user(&self, user_id: &str) -> AppResult<u64> {
+        let result = OAuthToken::update_many()
+            .col_expr(oauth_token::Column::IsRevoked, Expr::value(true))
+            .filter(oauth_token::Column::UserId.eq(user_id))
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected)
+    }
+
     /// Delete expired tokens (for cleanup).
     pub async fn delete_expired_tokens(&self) -> AppResult<u64> {
         let now = chrono::Utc::now().fixed_offset();
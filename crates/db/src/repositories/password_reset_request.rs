@@ -0,0 +1,68 @@
+//! Password reset request repository.
+
+use std::sync::Arc;
+
+use crate::entities::{PasswordResetRequest, password_reset_request};
+use chrono::{DateTime, Utc};
+use misskey_common::{AppError, AppResult};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+};
+
+/// Password reset request repository for database operations.
+#[derive(Clone)]
+pub struct PasswordResetRequestRepository {
+    db: Arc<DatabaseConnection>,
+}
+
+impl PasswordResetRequestRepository {
+    /// Create a new password reset request repository.
+    #[must_use]
+    pub const fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    /// Find a reset request by token hash.
+    pub async fn find_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> AppResult<Option<password_reset_request::Model>> {
+        PasswordResetRequest::find()
+            .filter(password_reset_request::Column::TokenHash.eq(token_hash))
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Count reset requests made for a user since the given time.
+    pub async fn count_by_user_since(&self, user_id: &str, since: DateTime<Utc>) -> AppResult<u64> {
+        PasswordResetRequest::find()
+            .filter(password_reset_request::Column::UserId.eq(user_id))
+            .filter(password_reset_request::Column::CreatedAt.gte(since))
+            .count(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Create a new reset request.
+    pub async fn create(
+        &self,
+        model: password_reset_request::ActiveModel,
+    ) -> AppResult<password_reset_request::Model> {
+        model
+            .insert(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Delete all reset requests for a user.
+    pub async fn delete_by_user(&self, user_id: &str) -> AppResult<u64> {
+        let result = PasswordResetRequest::delete_many()
+            .filter(password_reset_request::Column::UserId.eq(user_id))
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected)
+    }
+}
@@ -0,0 +1,74 @@
+//! Reaction usage repository.
+
+use std::sync::Arc;
+
+use crate::entities::{ReactionUsage, reaction_usage};
+use chrono::Utc;
+use misskey_common::{AppError, AppResult, IdGenerator};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+    sea_query::{Expr, OnConflict},
+};
+
+/// Reaction usage repository for database operations.
+#[derive(Clone)]
+pub struct ReactionUsageRepository {
+    db: Arc<DatabaseConnection>,
+    id_gen: IdGenerator,
+}
+
+impl ReactionUsageRepository {
+    /// Create a new reaction usage repository.
+    #[must_use]
+    pub const fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self {
+            db,
+            id_gen: IdGenerator::new(),
+        }
+    }
+
+    /// Record that a user reacted with `reaction` (single upsert query).
+    pub async fn record_use(&self, user_id: &str, reaction: &str) -> AppResult<()> {
+        let model = reaction_usage::ActiveModel {
+            id: Set(self.id_gen.generate()),
+            user_id: Set(user_id.to_string()),
+            reaction: Set(reaction.to_string()),
+            count: Set(1),
+            last_used_at: Set(Utc::now().into()),
+        };
+
+        ReactionUsage::insert(model)
+            .on_conflict(
+                OnConflict::columns([
+                    reaction_usage::Column::UserId,
+                    reaction_usage::Column::Reaction,
+                ])
+                .value(
+                    reaction_usage::Column::Count,
+                    Expr::col((ReactionUsage, reaction_usage::Column::Count)).add(1),
+                )
+                .update_column(reaction_usage::Column::LastUsedAt)
+                .to_owned(),
+            )
+            .exec_without_returning(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Get a user's reactions, most used first, most recent breaking ties.
+    pub async fn find_by_user(
+        &self,
+        user_id: &str,
+        limit: u64,
+    ) -> AppResult<Vec<reaction_usage::Model>> {
+        ReactionUsage::find()
+            .filter(reaction_usage::Column::UserId.eq(user_id))
+            .order_by_desc(reaction_usage::Column::Count)
+            .order_by_desc(reaction_usage::Column::LastUsedAt)
+            .limit(limit)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+}
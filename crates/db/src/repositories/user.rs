@@ -5,10 +5,26 @@ use std::sync::Arc;
 use crate::entities::{User, user};
 use misskey_common::{AppError, AppResult};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
-    QueryOrder, QuerySelect, Set, sea_query::Expr,
+    ActiveModelTrait, ColumnTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
+    FromQueryResult, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, Statement,
+    sea_query::Expr,
 };
 
+/// Relationship of a viewer to another user.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromQueryResult)]
+pub struct UserRelation {
+    /// The viewer follows the user.
+    pub is_following: bool,
+    /// The user follows the viewer.
+    pub is_followed: bool,
+    /// The viewer blocks the user.
+    pub is_blocking: bool,
+    /// The user blocks the viewer.
+    pub is_blocked: bool,
+    /// The viewer has an unexpired mute on the user.
+    pub is_muted: bool,
+}
+
 /// User repository for database operations.
 #[derive(Clone)]
 pub struct UserRepository {
@@ -50,6 +66,36 @@ impl UserRepository {
             .map_err(|e| AppError::Database(e.to_string()))
     }
 
+    /// Find the relationship of `viewer_id` to `user_id` in one query.
+    pub async fn find_relation(&self, viewer_id: &str, user_id: &str) -> AppResult<UserRelation> {
+        let sql = "
+            SELECT
+                EXISTS(SELECT 1 FROM following WHERE follower_id = $1 AND followee_id = $2)
+                    AS is_following,
+                EXISTS(SELECT 1 FROM following WHERE follower_id = $2 AND followee_id = $1)
+                    AS is_followed,
+                EXISTS(SELECT 1 FROM blocking WHERE blocker_id = $1 AND blockee_id = $2)
+                    AS is_blocking,
+                EXISTS(SELECT 1 FROM blocking WHERE blocker_id = $2 AND blockee_id = $1)
+                    AS is_blocked,
+                EXISTS(
+                    SELECT 1 FROM muting WHERE muter_id = $1 AND mutee_id = $2
+                    AND (expires_at IS NULL OR expires_at > NOW())
+                ) AS is_muted
+        ";
+
+        let relation = UserRelation::find_by_statement(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            sql,
+            [viewer_id.into(), user_id.into()],
+        ))
+        .one(self.db.as_ref())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(relation.unwrap_or_default())
+    }
+
     /// Find a user by username and host.
     pub async fn find_by_username_and_host(
         &self,
@@ -109,6 +155,7 @@ impl UserRepository {
         User::find()
             .filter(user::Column::Host.is_null())
             .filter(user::Column::IsSuspended.eq(false))
+            .filter(user::Column::IsSystem.eq(false))
             .order_by_desc(user::Column::CreatedAt)
             .offset(offset)
             .limit(limit)
@@ -201,6 +248,21 @@ impl UserRepository {
         Ok(())
     }
 
+    /// Replace a user's access token, invalidating the current one.
+    pub async fn set_token(&self, user_id: &str, token: &str) -> AppResult<()> {
+        User::update_many()
+            .col_expr(user::Column::Token, Expr::value(token))
+            .col_expr(
+                user::Column::UpdatedAt,
+                Expr::value(chrono::Utc::now().fixed_offset()),
+            )
+            .filter(user::Column::Id.eq(user_id))
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
     /// Mark a user as deleted/suspended (for remote actor deletion).
     pub async fn mark_as_deleted(&self, user_id: &str) -> AppResult<()> {
         let user = self.get_by_id(user_id).await?;
@@ -237,6 +299,7 @@ impl UserRepository {
         User::find()
             .filter(user::Column::Host.is_null())
             .filter(user::Column::IsSuspended.eq(false))
+            .filter(user::Column::IsSystem.eq(false))
             .count(self.db.as_ref())
             .await
             .map_err(|e| AppError::Database(e.to_string()))
@@ -250,6 +313,7 @@ impl UserRepository {
         User::find()
             .filter(user::Column::Host.is_null())
             .filter(user::Column::IsSuspended.eq(false))
+            .filter(user::Column::IsSystem.eq(false))
             .filter(user::Column::UpdatedAt.gte(one_month_ago))
             .count(self.db.as_ref())
             .await
@@ -264,6 +328,7 @@ impl UserRepository {
         User::find()
             .filter(user::Column::Host.is_null())
             .filter(user::Column::IsSuspended.eq(false))
+            .filter(user::Column::IsSystem.eq(false))
             .filter(user::Column::UpdatedAt.gte(six_months_ago))
             .count(self.db.as_ref())
             .await
@@ -285,6 +350,7 @@ impl UserRepository {
 
         let mut condition = Condition::all()
             .add(user::Column::IsSuspended.eq(false))
+            .add(user::Column::IsSystem.eq(false))
             .add(
                 Condition::any()
                     .add(user::Column::UsernameLower.like(format!("%{query_lower}%")))
@@ -345,12 +411,14 @@ mod tests {
             is_silenced: false,
             is_admin: false,
             is_moderator: false,
+            is_system: false,
             followers_count: 0,
             following_count: 0,
             notes_count: 0,
             inbox: None,
             shared_inbox: None,
             featured: None,
+            followers_uri: None,
             uri: None,
             last_fetched_at: None,
             token: Some("test_token".to_string()),
@@ -490,4 +558,51 @@ mod tests {
 
         assert_eq!(result.len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_search_excludes_system_accounts() {
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<user::Model>::new()])
+                .into_connection(),
+        );
+
+        let repo = UserRepository::new(Arc::clone(&db));
+        repo.search("instance", 10, 0, true).await.unwrap();
+        drop(repo);
+
+        let log = Arc::try_unwrap(db).ok().unwrap().into_transaction_log();
+        assert!(format!("{log:?}").contains(r#"\"is_system\" = "#));
+    }
+
+    #[tokio::test]
+    async fn test_find_relation_uses_one_query() {
+        let row = std::collections::BTreeMap::from([
+            ("is_following", sea_orm::Value::from(true)),
+            ("is_followed", sea_orm::Value::from(false)),
+            ("is_blocking", sea_orm::Value::from(false)),
+            ("is_blocked", sea_orm::Value::from(false)),
+            ("is_muted", sea_orm::Value::from(true)),
+        ]);
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[row]])
+                .into_connection(),
+        );
+
+        let repo = UserRepository::new(Arc::clone(&db));
+        let relation = repo.find_relation("viewer", "user1").await.unwrap();
+        drop(repo);
+
+        assert_eq!(
+            relation,
+            UserRelation {
+                is_following: true,
+                is_muted: true,
+                ..Default::default()
+            }
+        );
+        let log = Arc::try_unwrap(db).ok().unwrap().into_transaction_log();
+        assert_eq!(log.len(), 1);
+    }
 }
@@ -4,7 +4,10 @@ use std::sync::Arc;
 
 use crate::entities::{UserProfile, user_profile};
 use misskey_common::{AppError, AppResult};
-use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, Set,
+    sea_query::{Expr, OnConflict},
+};
 use serde_json::json;
 
 /// User profile repository for database operations.
@@ -43,8 +46,58 @@ impl UserProfileRepository {
             .map_err(|e| AppError::Database(e.to_string()))
     }
 
+    /// Of the given users, the IDs of those hiding their online status.
+    pub async fn find_hiding_online_status(&self, user_ids: &[String]) -> AppResult<Vec<String>> {
+        if user_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        UserProfile::find()
+            .filter(user_profile::Column::UserId.is_in(user_ids.iter().cloned()))
+            .filter(user_profile::Column::HideOnlineStatus.eq(true))
+            .select_only()
+            .column(user_profile::Column::UserId)
+            .into_tuple::<String>()
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Find a user profile by email address.
+    pub async fn find_by_email(&self, email: &str) -> AppResult<Option<user_profile::Model>> {
+        UserProfile::find()
+            .filter(user_profile::Column::Email.eq(email))
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Mark an email address undeliverable after a bounce or complaint.
+    ///
+    /// The address also stops counting as verified. Returns the number of
+    /// profiles using the address.
+    pub async fn mark_email_undeliverable(&self, email: &str) -> AppResult<u64> {
+        let result = UserProfile::update_many()
+            .col_expr(user_profile::Column::EmailUndeliverable, Expr::value(true))
+            .col_expr(user_profile::Column::EmailVerified, Expr::value(false))
+            .filter(user_profile::Column::Email.eq(email))
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(result.rows_affected)
+    }
+
     /// Update a user profile.
-    pub async fn update(&self, model: user_profile::ActiveModel) -> AppResult<user_profile::Model> {
+    ///
+    /// Changing the email address clears its undeliverable flag, since the
+    /// new address has not bounced.
+    pub async fn update(
+        &self,
+        mut model: user_profile::ActiveModel,
+    ) -> AppResult<user_profile::Model> {
+        if model.email.is_set() && !model.email_undeliverable.is_set() {
+            model.email_undeliverable = Set(false);
+        }
         model
             .update(self.db.as_ref())
             .await
@@ -158,6 +211,26 @@ impl UserProfileRepository {
         Ok(pinned)
     }
 
+    /// Replace the pinned notes of a remote user, creating their profile row
+    /// if they have none yet.
+    pub async fn set_pinned_note_ids(&self, user_id: &str, note_ids: &[String]) -> AppResult<()> {
+        let model = user_profile::ActiveModel {
+            user_id: Set(user_id.to_string()),
+            pinned_note_ids: Set(json!(note_ids)),
+            ..Default::default()
+        };
+        UserProfile::insert(model)
+            .on_conflict(
+                OnConflict::column(user_profile::Column::UserId)
+                    .update_column(user_profile::Column::PinnedNoteIds)
+                    .to_owned(),
+            )
+            .exec_without_returning(self.db.as_ref())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
     /// Reorder pinned notes.
     pub async fn reorder_pinned_notes(
         &self,
@@ -0,0 +1,162 @@
+//! Slow query logging.
+//!
+//! Hooks into each connection's metric callback so every query is timed,
+//! fed to the global [`Metrics`] and logged at warn level when it exceeds the
+//! configured threshold.
+
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use misskey_common::config::DatabaseConfig;
+use misskey_common::metrics::{Metrics, get_metrics};
+use sea_orm::DatabaseConnection;
+use tracing::warn;
+
+/// Maximum number of SQL characters included in a slow-query log line.
+const MAX_LOGGED_SQL_CHARS: usize = 500;
+
+/// Threshold used until [`init_slow_query_logger`] is called.
+const DEFAULT_THRESHOLD: Duration = Duration::from_millis(500);
+
+/// Global slow-query logger.
+static SLOW_QUERY_LOGGER: OnceLock<SlowQueryLogger> = OnceLock::new();
+
+/// Get the global slow-query logger.
+pub fn slow_query_logger() -> &'static SlowQueryLogger {
+    SLOW_QUERY_LOGGER.get_or_init(|| SlowQueryLogger::new(DEFAULT_THRESHOLD, get_metrics().clone()))
+}
+
+/// Initialize the global slow-query logger.
+pub fn init_slow_query_logger(logger: SlowQueryLogger) -> Result<(), SlowQueryLogger> {
+    SLOW_QUERY_LOGGER.set(logger)
+}
+
+/// Report every query run on `db` to the global slow-query logger.
+///
+/// SQL is rendered with the connection's own backend, and only for slow queries.
+pub fn log_slow_queries(db: &mut DatabaseConnection) {
+    db.set_metric_callback(|info| {
+        slow_query_logger().record(|| info.statement.to_string(), info.elapsed, info.failed);
+    });
+}
+
+/// Times queries and reports those slower than a threshold.
+#[derive(Debug, Clone)]
+pub struct SlowQueryLogger {
+    /// Queries taking at least this long are reported; zero disables reporting.
+    threshold: Duration,
+    metrics: Arc<Metrics>,
+}
+
+impl SlowQueryLogger {
+    /// Create a new logger reporting into the given metrics.
+    #[must_use]
+    pub const fn new(threshold: Duration, metrics: Arc<Metrics>) -> Self {
+        Self { threshold, metrics }
+    }
+
+    /// Create a logger from the database configuration using the global metrics.
+    #[must_use]
+    pub fn from_config(config: &DatabaseConfig) -> Self {
+        Self::new(
+            Duration::from_millis(config.slow_query_threshold_ms),
+            get_metrics().clone(),
+        )
+    }
+
+    /// Get the configured threshold.
+    #[must_use]
+    pub const fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// Record a finished query, logging it if slow.
+    ///
+    /// `sql` is only rendered when the query turns out to be slow.
+    pub fn record<S>(&self, sql: S, elapsed: Duration, failed: bool)
+    where
+        S: FnOnce() -> String,
+    {
+        self.metrics.record_db_query(elapsed, failed);
+        if self.is_slow(elapsed) {
+            self.report(&sql(), elapsed);
+        }
+    }
+
+    /// Check whether a query duration exceeds the threshold.
+    #[must_use]
+    pub fn is_slow(&self, elapsed: Duration) -> bool {
+        !self.threshold.is_zero() && elapsed >= self.threshold
+    }
+
+    fn report(&self, sql: &str, elapsed: Duration) {
+        self.metrics.record_slow_query();
+        warn!(
+            duration_ms = elapsed.as_millis() as u64,
+            threshold_ms = self.threshold.as_millis() as u64,
+            sql = %truncate_sql(sql),
+            "Slow database query"
+        );
+    }
+}
+
+/// Truncate SQL to [`MAX_LOGGED_SQL_CHARS`] characters for logging.
+fn truncate_sql(sql: &str) -> String {
+    match sql.char_indices().nth(MAX_LOGGED_SQL_CHARS) {
+        Some((idx, _)) => format!("{}...", &sql[..idx]),
+        None => sql.to_string(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn test_slow_query_increments_counter() {
+        let metrics = Arc::new(Metrics::new());
+        let logger = SlowQueryLogger::new(Duration::from_millis(5), metrics.clone());
+
+        logger.record(
+            || "SELECT * FROM note".to_string(),
+            Duration::from_millis(20),
+            false,
+        );
+
+        assert_eq!(metrics.db_slow_queries_total.load(Ordering::Relaxed), 1);
+        assert_eq!(metrics.db_queries_total.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_fast_query_not_counted() {
+        let metrics = Arc::new(Metrics::new());
+        let logger = SlowQueryLogger::new(Duration::from_secs(60), metrics.clone());
+
+        logger.record(
+            || panic!("SQL rendered for a fast query"),
+            Duration::from_millis(1),
+            false,
+        );
+
+        assert_eq!(metrics.db_slow_queries_total.load(Ordering::Relaxed), 0);
+        assert_eq!(metrics.db_queries_total.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_zero_threshold_disables_reporting() {
+        let logger = SlowQueryLogger::new(Duration::ZERO, Arc::new(Metrics::new()));
+        assert!(!logger.is_slow(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_truncate_sql() {
+        assert_eq!(truncate_sql("SELECT 1"), "SELECT 1");
+
+        let long = "x".repeat(MAX_LOGGED_SQL_CHARS + 10);
+        let truncated = truncate_sql(&long);
+        assert_eq!(truncated.len(), MAX_LOGGED_SQL_CHARS + 3);
+        assert!(truncated.ends_with("..."));
+    }
+}
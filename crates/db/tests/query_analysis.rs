@@ -183,6 +183,7 @@ async fn setup_test_data(db: &sea_orm::DatabaseConnection) {
             name VARCHAR(128),
             is_admin BOOLEAN NOT NULL DEFAULT false,
             is_moderator BOOLEAN NOT NULL DEFAULT false,
+            is_system BOOLEAN NOT NULL DEFAULT false,
             is_bot BOOLEAN NOT NULL DEFAULT false,
             is_cat BOOLEAN NOT NULL DEFAULT false,
             is_locked BOOLEAN NOT NULL DEFAULT false,
@@ -4,6 +4,8 @@ use activitypub_federation::kinds::activity::LikeType;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+use super::EmojiTag;
+
 /// `ActivityPub` Like activity.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -20,6 +22,10 @@ pub struct LikeActivity {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
+
+    /// Custom emoji definitions for the reaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<Vec<EmojiTag>>,
 }
 
 impl LikeActivity {
@@ -33,6 +39,7 @@ impl LikeActivity {
             object,
             misskey_reaction: None,
             content: None,
+            tag: None,
         }
     }
 
@@ -5,6 +5,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+use super::EmojiTag;
 use crate::actors::ApPerson;
 use crate::objects::ApNote;
 
@@ -36,6 +37,8 @@ pub enum UpdateObject {
     Person(ApPerson),
     /// A full Note object (for note content updates).
     Note(ApNote),
+    /// A custom emoji definition (for remote emoji image changes).
+    Emoji(EmojiTag),
     /// Just a URL reference to an object.
     ObjectUrl(Url),
 }
@@ -0,0 +1,44 @@
+//! `ActivityPub` Application actor for the instance itself.
+
+use activitypub_federation::kinds::actor::ApplicationType;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::person::ApPublicKey;
+
+/// `ActivityPub` Application actor representing this server.
+///
+/// Signs activities and fetches that are not attributable to a single user,
+/// such as relay subscriptions, reports and authorized fetch requests.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApApplication {
+    #[serde(rename = "type")]
+    pub kind: ApplicationType,
+
+    /// Unique identifier (URI) of the instance actor.
+    pub id: Url,
+
+    /// Username of the instance actor.
+    pub preferred_username: String,
+
+    /// Inbox URL for receiving activities.
+    pub inbox: Url,
+
+    /// Outbox URL (always empty).
+    pub outbox: Url,
+
+    /// Display name (the instance name).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Public key for verifying HTTP signatures.
+    pub public_key: ApPublicKey,
+
+    /// URL to the human-readable page for this actor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<Url>,
+
+    /// The instance actor never accepts followers automatically.
+    pub manually_approves_followers: bool,
+}
@@ -2,8 +2,10 @@
 
 #![allow(missing_docs)]
 
+mod application;
 mod group;
 mod person;
 
+pub use application::ApApplication;
 pub use group::ApGroup;
 pub use person::{ApImage, ApPerson, ApPublicKey};
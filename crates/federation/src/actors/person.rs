@@ -59,6 +59,13 @@ pub struct ApPerson {
 
     #[serde(rename = "isCat", skip_serializing_if = "Option::is_none")]
     pub is_cat: Option<bool>,
+
+    /// The user does not want their notes quoted
+    #[serde(
+        rename = "_misskey_disallowQuotes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub disallow_quotes: Option<bool>,
 }
 
 /// `ActivityPub` Image object.
@@ -105,6 +112,7 @@ impl ApPerson {
             also_known_as: None,
             misskey_summary: None,
             is_cat: None,
+            disallow_quotes: None,
         }
     }
 
@@ -4,10 +4,14 @@
 
 #![allow(missing_docs)]
 
+use async_trait::async_trait;
+
+use crate::collection::{collect_items, collection_pages};
 use crate::signature::HttpSigner;
-use reqwest::Client;
+use reqwest::{Client, header::HeaderMap};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::{debug, error, info, warn};
 use url::Url;
@@ -30,6 +34,8 @@ pub enum ApClientError {
 pub struct ApClient {
     client: Client,
     user_agent: String,
+    /// Signs fetches as the instance actor for servers requiring authorized fetch.
+    signer: Option<Arc<HttpSigner>>,
 }
 
 impl ApClient {
@@ -45,7 +51,27 @@ impl ApClient {
 
         let user_agent = format!("misskey-rs/0.1.0 (+{instance_url})");
 
-        Self { client, user_agent }
+        Self {
+            client,
+            user_agent,
+            signer: None,
+        }
+    }
+
+    /// Sign outgoing fetches with the given (instance actor) key.
+    #[must_use]
+    pub fn with_signer(mut self, signer: HttpSigner) -> Self {
+        self.signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Signature headers for a GET request, if a signer is configured.
+    fn fetch_headers(&self, url: &str) -> Result<HeaderMap, ApClientError> {
+        let Some(signer) = &self.signer else {
+            return Ok(HeaderMap::new());
+        };
+        let url = Url::parse(url).map_err(|e| ApClientError::InvalidUrl(e.to_string()))?;
+        Ok(signer.sign_request("GET", &url, None, &HashMap::new())?)
     }
 
     /// Deliver an activity to a remote inbox.
@@ -127,6 +153,7 @@ impl ApClient {
         let response = self
             .client
             .get(actor_url)
+            .headers(self.fetch_headers(actor_url)?)
             .header("User-Agent", &self.user_agent)
             .header("Accept", "application/activity+json, application/ld+json; profile=\"https://www.w3.org/ns/activitystreams\"")
             .send()
@@ -153,6 +180,7 @@ impl ApClient {
         let response = self
             .client
             .get(object_url)
+            .headers(self.fetch_headers(object_url)?)
             .header("User-Agent", &self.user_agent)
             .header("Accept", "application/activity+json, application/ld+json")
             .send()
@@ -207,6 +235,37 @@ impl Default for ApClient {
     }
 }
 
+/// Source of remote objects, e.g. notes referenced by incoming activities.
+#[async_trait]
+pub trait ObjectFetcher: Send + Sync {
+    /// Fetch the JSON object identified by `url`.
+    async fn fetch_object(&self, url: &Url) -> Result<Value, ApClientError>;
+
+    /// Fetch up to `limit` items of a remote collection, following its pages.
+    ///
+    /// Ordered and unordered collections are both supported; items are
+    /// returned as links or embedded objects, as the server sent them.
+    /// Only pages on the collection's own host are followed.
+    async fn fetch_collection_items(
+        &self,
+        collection_url: &Url,
+        limit: usize,
+    ) -> Result<Vec<Value>, ApClientError> {
+        let collection = self.fetch_object(collection_url).await?;
+        let pages = collection_pages(collection_url, collection, |url| async move {
+            self.fetch_object(&url).await
+        });
+        collect_items(pages, limit).await
+    }
+}
+
+#[async_trait]
+impl ObjectFetcher for ApClient {
+    async fn fetch_object(&self, url: &Url) -> Result<Value, ApClientError> {
+        Self::fetch_object(self, url.as_str()).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
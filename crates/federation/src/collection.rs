@@ -0,0 +1,285 @@
+//! Paging through remote collections.
+//!
+//! Servers publish followers, featured notes and outboxes either as an
+//! `OrderedCollection` (`orderedItems`) or as an unordered `Collection`
+//! (`items`), inline or split into pages linked by `first` and `next`.
+//! [`collection_pages`] walks every shape the same way.
+
+use std::future::Future;
+
+use futures::{Stream, StreamExt, stream};
+use serde_json::Value;
+use tracing::debug;
+use url::Url;
+
+use crate::client::ApClientError;
+
+/// Maximum number of pages fetched from one collection.
+pub const MAX_COLLECTION_PAGES: usize = 32;
+
+/// One page of a remote collection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CollectionPage {
+    /// Items of the page, as links or embedded objects.
+    pub items: Vec<Value>,
+    /// Page the items continue on.
+    pub next: Option<Url>,
+}
+
+impl CollectionPage {
+    /// Normalize a collection or collection page, ordered or not.
+    ///
+    /// A collection without inline items continues at its `first` page,
+    /// which is parsed directly when embedded. Returns `None` for objects
+    /// that are not collections.
+    #[must_use]
+    pub fn parse(value: &Value) -> Option<Self> {
+        let kind = value.get("type").and_then(Value::as_str)?;
+        if !matches!(
+            kind,
+            "Collection" | "OrderedCollection" | "CollectionPage" | "OrderedCollectionPage"
+        ) {
+            return None;
+        }
+
+        if let Some(items) = value.get("orderedItems").or_else(|| value.get("items")) {
+            return Some(Self {
+                items: match items {
+                    Value::Array(items) => items.clone(),
+                    Value::Null => Vec::new(),
+                    item => vec![item.clone()],
+                },
+                next: value.get("next").and_then(link),
+            });
+        }
+
+        let first = value.get("first");
+        if let Some(page) = first.and_then(Self::parse) {
+            return Some(page);
+        }
+        Some(Self {
+            items: Vec::new(),
+            next: first.and_then(link),
+        })
+    }
+}
+
+/// URL of a link given as a string or as an object with an `href` or `id`.
+fn link(value: &Value) -> Option<Url> {
+    let url = match value {
+        Value::String(url) => url,
+        Value::Object(object) => object.get("href").or_else(|| object.get("id"))?.as_str()?,
+        _ => return None,
+    };
+    Url::parse(url).ok()
+}
+
+/// Where a collection walk continues.
+enum Cursor {
+    Page(Value),
+    Link(Url),
+    Done,
+}
+
+/// Stream the item pages of `collection`, fetching linked pages with `fetch`.
+///
+/// `collection` was fetched from `origin`; only pages on the same host are
+/// followed. Pages without items are skipped. The walk stops after
+/// [`MAX_COLLECTION_PAGES`] fetches, on a page seen before or on another
+/// host, or on the first object that is not a collection.
+pub fn collection_pages<F, Fut>(
+    origin: &Url,
+    collection: Value,
+    fetch: F,
+) -> impl Stream<Item = Result<Vec<Value>, ApClientError>>
+where
+    F: FnMut(Url) -> Fut,
+    Fut: Future<Output = Result<Value, ApClientError>>,
+{
+    let host = origin.host_str().map(str::to_string);
+    let state = (Cursor::Page(collection), fetch, Vec::<Url>::new());
+    stream::unfold(state, move |(mut cursor, mut fetch, mut visited)| {
+        let host = host.clone();
+        async move {
+            loop {
+                let value = match cursor {
+                    Cursor::Done => return None,
+                    Cursor::Page(value) => value,
+                    Cursor::Link(url) => {
+                        if visited.len() >= MAX_COLLECTION_PAGES
+                            || visited.contains(&url)
+                            || url.host_str() != host.as_deref()
+                        {
+                            debug!(url = %url, "Stopping collection walk");
+                            return None;
+                        }
+                        visited.push(url.clone());
+                        match fetch(url).await {
+                            Ok(value) => value,
+                            Err(e) => return Some((Err(e), (Cursor::Done, fetch, visited))),
+                        }
+                    }
+                };
+
+                let Some(page) = CollectionPage::parse(&value) else {
+                    debug!(object_type = ?value.get("type"), "Not a collection page");
+                    return None;
+                };
+                cursor = page.next.map_or(Cursor::Done, Cursor::Link);
+                if !page.items.is_empty() {
+                    return Some((Ok(page.items), (cursor, fetch, visited)));
+                }
+            }
+        }
+    })
+}
+
+/// Collect up to `limit` items from a stream of collection pages.
+pub async fn collect_items<S>(pages: S, limit: usize) -> Result<Vec<Value>, ApClientError>
+where
+    S: Stream<Item = Result<Vec<Value>, ApClientError>>,
+{
+    let mut pages = std::pin::pin!(pages);
+    let mut items = Vec::new();
+    while items.len() < limit
+        && let Some(page) = pages.next().await
+    {
+        items.extend(page?);
+    }
+    items.truncate(limit);
+    Ok(items)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    /// Fetch pages from fixtures keyed by URL.
+    async fn backfill(collection: Value, pages: &HashMap<&str, Value>) -> Vec<Value> {
+        let fetch = |url: Url| {
+            let page = pages.get(url.as_str()).cloned();
+            async move {
+                page.ok_or_else(|| ApClientError::DeliveryFailed {
+                    status: 404,
+                    body: String::new(),
+                })
+            }
+        };
+        let origin = Url::parse("https://remote.example/collection").unwrap();
+        collect_items(collection_pages(&origin, collection, fetch), 100)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unordered_collection_yields_same_items_as_ordered() {
+        let ordered = json!({
+            "type": "OrderedCollection",
+            "id": "https://remote.example/users/alice/featured",
+            "totalItems": 3,
+            "first": "https://remote.example/users/alice/featured?page=1",
+        });
+        let ordered_pages = HashMap::from([
+            (
+                "https://remote.example/users/alice/featured?page=1",
+                json!({
+                    "type": "OrderedCollectionPage",
+                    "orderedItems": [
+                        "https://remote.example/notes/1",
+                        { "type": "Note", "id": "https://remote.example/notes/2" },
+                    ],
+                    "next": "https://remote.example/users/alice/featured?page=2",
+                }),
+            ),
+            (
+                "https://remote.example/users/alice/featured?page=2",
+                json!({
+                    "type": "OrderedCollectionPage",
+                    "orderedItems": ["https://remote.example/notes/3"],
+                }),
+            ),
+        ]);
+
+        // The unordered variant embeds its first page and links the next one
+        let unordered = json!({
+            "type": "Collection",
+            "id": "https://remote.example/users/alice/featured",
+            "totalItems": 3,
+            "first": {
+                "type": "CollectionPage",
+                "items": [
+                    "https://remote.example/notes/1",
+                    { "type": "Note", "id": "https://remote.example/notes/2" },
+                ],
+                "next": {
+                    "type": "Link",
+                    "href": "https://remote.example/users/alice/featured?page=2",
+                },
+            },
+        });
+        let unordered_pages = HashMap::from([(
+            "https://remote.example/users/alice/featured?page=2",
+            json!({
+                "type": "CollectionPage",
+                "items": "https://remote.example/notes/3",
+            }),
+        )]);
+
+        let ordered_items = backfill(ordered, &ordered_pages).await;
+        let unordered_items = backfill(unordered, &unordered_pages).await;
+
+        assert_eq!(ordered_items.len(), 3);
+        assert_eq!(ordered_items, unordered_items);
+    }
+
+    #[tokio::test]
+    async fn test_collection_walk_stops_on_page_cycle() {
+        let collection = json!({
+            "type": "Collection",
+            "first": "https://remote.example/followers?page=1",
+        });
+        let pages = HashMap::from([(
+            "https://remote.example/followers?page=1",
+            json!({
+                "type": "CollectionPage",
+                "items": ["https://remote.example/users/bob"],
+                "next": "https://remote.example/followers?page=1",
+            }),
+        )]);
+
+        assert_eq!(backfill(collection, &pages).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_collection_walk_stays_on_collection_host() {
+        let collection = json!({
+            "type": "OrderedCollection",
+            "first": "https://remote.example/followers?page=1",
+        });
+        let pages = HashMap::from([
+            (
+                "https://remote.example/followers?page=1",
+                json!({
+                    "type": "OrderedCollectionPage",
+                    "orderedItems": ["https://remote.example/users/bob"],
+                    "next": "https://elsewhere.example/followers?page=2",
+                }),
+            ),
+            (
+                "https://elsewhere.example/followers?page=2",
+                json!({
+                    "type": "OrderedCollectionPage",
+                    "orderedItems": ["https://elsewhere.example/users/mallory"],
+                }),
+            ),
+        ]);
+
+        assert_eq!(
+            backfill(collection, &pages).await,
+            [json!("https://remote.example/users/bob")]
+        );
+    }
+}
@@ -50,6 +50,7 @@ impl NoteToApNote for note::Model {
                     kind: "Mention".to_string(),
                     href: None,
                     name: Some(format!("@{user_id}")),
+                    icon: None,
                 })
             })
             .collect();
@@ -62,6 +63,7 @@ impl NoteToApNote for note::Model {
                 kind: "Hashtag".to_string(),
                 href: None,
                 name: Some(format!("#{tag}")),
+                icon: None,
             })
             .collect();
 
@@ -106,6 +108,8 @@ impl NoteToApNote for note::Model {
             attributed_to,
             content,
             published,
+            url: self.url.as_deref().and_then(|url| Url::parse(url).ok()),
+            name: None,
             to,
             cc,
             in_reply_to,
@@ -7,17 +7,93 @@ use url::Url;
 
 use crate::actors::{ApImage, ApPerson, ApPublicKey};
 
+/// Path of note objects, also used as the note page when no template is configured.
+const NOTE_OBJECT_PATH: &str = "/notes/{id}";
+
+/// Placeholders understood in `notes.url_template`.
+#[allow(clippy::literal_string_with_formatting_args)]
+const ID_PLACEHOLDER: &str = "{id}";
+#[allow(clippy::literal_string_with_formatting_args)]
+const USERNAME_PLACEHOLDER: &str = "{username}";
+
 /// Configuration for generating user URLs.
 #[derive(Clone)]
 pub struct UrlConfig {
     pub base_url: Url,
+    /// Note page path with `{id}` and `{username}` placeholders.
+    note_url_template: Option<String>,
 }
 
 impl UrlConfig {
     /// Create a new URL config.
     #[must_use]
     pub const fn new(base_url: Url) -> Self {
-        Self { base_url }
+        Self {
+            base_url,
+            note_url_template: None,
+        }
+    }
+
+    /// Use `template` (a path with `{id}` and `{username}` placeholders) for note pages.
+    #[must_use]
+    pub fn with_note_url_template(mut self, template: String) -> Self {
+        self.note_url_template = Some(template);
+        self
+    }
+
+    /// The note page path template.
+    #[must_use]
+    pub fn note_url_template(&self) -> &str {
+        self.note_url_template
+            .as_deref()
+            .unwrap_or(NOTE_OBJECT_PATH)
+    }
+
+    /// Whether note pages are served at the note object URL itself.
+    #[must_use]
+    pub fn note_page_is_object_url(&self) -> bool {
+        self.note_url_template() == NOTE_OBJECT_PATH
+    }
+
+    /// Match `path` against the note page template.
+    ///
+    /// Returns the note ID and, if the template contains one, the username.
+    #[must_use]
+    pub fn match_note_page(&self, path: &str) -> Option<(String, Option<String>)> {
+        let template = self.note_url_template();
+        let mut template_segments = template.trim_start_matches('/').split('/');
+        let mut path_segments = path.trim_start_matches('/').split('/');
+        let (mut id, mut username) = (None, None);
+
+        loop {
+            match (template_segments.next(), path_segments.next()) {
+                (None, None) => break,
+                (Some(pattern), Some(segment)) => {
+                    let placeholder = [ID_PLACEHOLDER, USERNAME_PLACEHOLDER]
+                        .into_iter()
+                        .find_map(|p| pattern.find(p).map(|at| (p, at)));
+                    let Some((placeholder, at)) = placeholder else {
+                        if pattern != segment {
+                            return None;
+                        }
+                        continue;
+                    };
+                    let (prefix, suffix) = (&pattern[..at], &pattern[at + placeholder.len()..]);
+                    let value = segment
+                        .strip_prefix(prefix)
+                        .and_then(|rest| rest.strip_suffix(suffix))
+                        .filter(|value| !value.is_empty())?;
+                    if placeholder == ID_PLACEHOLDER {
+                        id = Some(value.to_string());
+                    } else {
+                        username = Some(value.to_string());
+                    }
+                }
+                _ => return None,
+            }
+        }
+
+        Some((id?, username))
     }
 
     /// Generate user URL.
@@ -66,6 +142,24 @@ impl UrlConfig {
             .expect("valid URL")
     }
 
+    /// Generate the human-readable profile page URL.
+    #[must_use]
+    pub fn profile_url(&self, username: &str) -> Url {
+        self.base_url
+            .join(&format!("/@{username}"))
+            .expect("valid URL")
+    }
+
+    /// Generate the human-readable page URL for a note.
+    #[must_use]
+    pub fn note_page_url(&self, username: &str, note_id: &str) -> Url {
+        let path = self
+            .note_url_template()
+            .replace(ID_PLACEHOLDER, note_id)
+            .replace(USERNAME_PLACEHOLDER, username);
+        self.base_url.join(&path).expect("valid URL")
+    }
+
     /// Generate public key URL.
     #[must_use]
     pub fn public_key_url(&self, username: &str) -> String {
@@ -143,6 +237,7 @@ impl UserToApPerson for user::Model {
             also_known_as: None, // Set by account migration
             misskey_summary: self.description.clone(),
             is_cat: Some(self.is_cat),
+            disallow_quotes: None, // Set from the profile by the actor handler
         }
     }
 }
@@ -1,6 +1,7 @@
 //! Inbox handler for receiving `ActivityPub` activities.
 
 use axum::{
+    Extension,
     body::Bytes,
     extract::State,
     http::{HeaderMap, StatusCode},
@@ -8,8 +9,9 @@ use axum::{
 };
 use misskey_common::{AppError, AppResult};
 use misskey_db::repositories::{
-    DriveFileRepository, FollowRequestRepository, FollowingRepository, NoteRepository,
-    ReactionRepository, UserKeypairRepository, UserProfileRepository, UserRepository,
+    BlockingRepository, DriveFileRepository, EmojiRepository, FollowRequestRepository,
+    FollowingRepository, NoteRepository, ReactionRepository, UserKeypairRepository,
+    UserProfileRepository, UserRepository,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -20,13 +22,23 @@ use crate::{
     AcceptActivity, AnnounceActivity, CreateActivity, DeleteActivity, EmojiReactActivity,
     FollowActivity, LikeActivity, MoveActivity, RejectActivity, UndoActivity, UpdateActivity,
     client::ApClient,
+    middleware::{SignatureVerificationFailed, SignatureVerified},
+    policy::FederationPolicy,
     processor::{
-        AcceptProcessor, AnnounceProcessor, CreateProcessor, EmojiReactProcessor, FollowProcessor,
-        LikeProcessor, MoveProcessor, ParsedUndoActivity, UndoProcessor, UpdateProcessor,
+        AcceptProcessor, ActorFetcher, AnnounceProcessor, CreateProcessor, EmojiReactProcessor,
+        FollowProcessor, LikeProcessor, MoveProcessor, ParsedUndoActivity, UndoProcessor,
+        UpdateProcessor,
     },
+    security::{FederationRateLimiter, RateLimitError},
     signature::{HttpVerifier, verify_digest},
 };
 
+/// Collection addressing a public activity.
+const PUBLIC_COLLECTION: &str = "https://www.w3.org/ns/activitystreams#Public";
+
+/// Rate limit bucket shared by activities without a verified signature.
+const UNVERIFIED_RATE_LIMIT_KEY: &str = "(unverified)";
+
 /// Wrapper for incoming activities that can be any type.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(untagged)]
@@ -96,8 +108,14 @@ pub struct InboxState {
     pub following_repo: FollowingRepository,
     pub follow_request_repo: FollowRequestRepository,
     pub reaction_repo: ReactionRepository,
+    pub emoji_repo: EmojiRepository,
+    pub blocking_repo: Option<BlockingRepository>,
     pub ap_client: ApClient,
     pub base_url: url::Url,
+    pub federation_policy: FederationPolicy,
+    pub shared_inbox_rate_limiter: Option<FederationRateLimiter>,
+    pub user_inbox_rate_limiter: Option<FederationRateLimiter>,
+    pub min_account_age: std::time::Duration,
 }
 
 impl InboxState {
@@ -112,6 +130,7 @@ impl InboxState {
         following_repo: FollowingRepository,
         follow_request_repo: FollowRequestRepository,
         reaction_repo: ReactionRepository,
+        emoji_repo: EmojiRepository,
         base_url: url::Url,
     ) -> Self {
         let ap_client = ApClient::new(base_url.as_str());
@@ -124,22 +143,110 @@ impl InboxState {
             following_repo,
             follow_request_repo,
             reaction_repo,
+            emoji_repo,
+            blocking_repo: None,
             ap_client,
             base_url,
+            federation_policy: FederationPolicy::open(),
+            shared_inbox_rate_limiter: None,
+            user_inbox_rate_limiter: None,
+            min_account_age: std::time::Duration::ZERO,
         }
     }
+
+    /// Enable reversing remote blocks on Undo(Block).
+    #[must_use]
+    pub fn with_blocking_repo(mut self, blocking_repo: BlockingRepository) -> Self {
+        self.blocking_repo = Some(blocking_repo);
+        self
+    }
+
+    /// Set the federation policy used to reject activities from disallowed hosts.
+    #[must_use]
+    pub fn with_federation_policy(mut self, policy: FederationPolicy) -> Self {
+        self.federation_policy = policy;
+        self
+    }
+
+    /// Rate-limit the shared inbox and user inboxes per remote host, each in
+    /// its own buckets.
+    #[must_use]
+    pub fn with_rate_limiters(
+        mut self,
+        shared_inbox: FederationRateLimiter,
+        user_inbox: FederationRateLimiter,
+    ) -> Self {
+        self.shared_inbox_rate_limiter = Some(shared_inbox);
+        self.user_inbox_rate_limiter = Some(user_inbox);
+        self
+    }
+
+    /// Restrict notes of remote accounts first seen less than `min_age` ago.
+    #[must_use]
+    pub const fn with_min_account_age(mut self, min_age: std::time::Duration) -> Self {
+        self.min_account_age = min_age;
+        self
+    }
+}
+
+/// Inbox an activity was delivered to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InboxKind {
+    Shared,
+    User,
 }
 
 /// Handle incoming `ActivityPub` activities.
 ///
 /// This is the main inbox endpoint that receives activities from remote servers.
+/// When [`SignatureVerificationLayer`](crate::SignatureVerificationLayer) has
+/// already checked the request, its result is reused instead of verifying again.
 pub async fn inbox_handler(
     State(state): State<InboxState>,
+    verified: Option<Extension<SignatureVerified>>,
+    failed: Option<Extension<SignatureVerificationFailed>>,
     headers: HeaderMap,
     body: Bytes,
 ) -> impl IntoResponse {
+    let checked = LayerCheck::from_extensions(verified, failed);
+    handle_inbox(&state, InboxKind::Shared, checked, &headers, &body).await
+}
+
+/// Outcome of the signature layer, if it ran.
+enum LayerCheck {
+    Verified(String),
+    Failed(&'static str),
+    NotChecked,
+}
+
+impl LayerCheck {
+    fn from_extensions(
+        verified: Option<Extension<SignatureVerified>>,
+        failed: Option<Extension<SignatureVerificationFailed>>,
+    ) -> Self {
+        match (verified, failed) {
+            (
+                Some(Extension(SignatureVerified {
+                    actor_url: Some(signer),
+                })),
+                _,
+            ) => Self::Verified(signer),
+            (_, Some(Extension(SignatureVerificationFailed { reason }))) => Self::Failed(reason),
+            _ => Self::NotChecked,
+        }
+    }
+}
+
+/// Receive an activity delivered to the shared or a user inbox.
+async fn handle_inbox(
+    state: &InboxState,
+    inbox: InboxKind,
+    checked: LayerCheck,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> StatusCode {
     // Parse the body as JSON
-    let activity: InboxActivity = match serde_json::from_slice(&body) {
+    let activity: InboxActivity = match serde_json::from_slice(body) {
         Ok(a) => a,
         Err(e) => {
             warn!(error = %e, "Failed to parse activity");
@@ -150,19 +257,73 @@ pub async fn inbox_handler(
     info!(
         activity_type = activity.activity_type(),
         actor = ?activity.actor(),
+        inbox = ?inbox,
         "Received activity"
     );
 
-    // Verify HTTP signature
-    if let Err(e) = verify_incoming_signature(&state, &headers, &body, &activity).await {
-        warn!(error = %e, "Signature verification failed");
-        // In production, you might want to reject unsigned requests
-        // For development, we'll log and continue
-        debug!("Continuing despite signature verification failure");
+    // Discard activities from hosts we don't federate with before doing any
+    // work; they are acknowledged so the sender does not keep retrying
+    if let Some(actor) = activity.actor() {
+        match state.federation_policy.is_url_allowed(actor).await {
+            Ok(true) => {}
+            Ok(false) => {
+                debug!(actor = %actor, "Discarding activity from disallowed host");
+                return StatusCode::ACCEPTED;
+            }
+            Err(e) => {
+                error!(error = %e, "Failed to evaluate federation policy");
+                return StatusCode::INTERNAL_SERVER_ERROR;
+            }
+        }
+    }
+
+    // Verify HTTP signature unless the signature layer already checked it
+    let signer = match checked {
+        LayerCheck::Verified(signer) => {
+            debug!(signer = %signer, "Using signature verified by middleware");
+            Some(signer)
+        }
+        LayerCheck::Failed(reason) => {
+            warn!(error = %reason, "Signature verification failed in middleware");
+            debug!("Continuing despite signature verification failure");
+            None
+        }
+        LayerCheck::NotChecked => {
+            match verify_incoming_signature(state, headers, body, &activity).await {
+                Ok(signer) => Some(signer),
+                Err(e) => {
+                    warn!(error = %e, "Signature verification failed");
+                    // In production, you might want to reject unsigned requests
+                    // For development, we'll log and continue
+                    debug!("Continuing despite signature verification failure");
+                    None
+                }
+            }
+        }
+    };
+
+    // Throttle busy hosts, counting each inbox separately. Hosts are only
+    // known from a verified signature; unverified activities share one
+    // budget so they cannot use up the budget of the host they claim
+    let rate_limiter = match inbox {
+        InboxKind::Shared => state.shared_inbox_rate_limiter.as_ref(),
+        InboxKind::User => state.user_inbox_rate_limiter.as_ref(),
+    };
+    if let Some(rate_limiter) = rate_limiter {
+        let signer_host = signer
+            .as_deref()
+            .and_then(|signer| url::Url::parse(signer).ok())
+            .and_then(|signer| signer.host_str().map(str::to_string));
+        let key = signer_host.as_deref().unwrap_or(UNVERIFIED_RATE_LIMIT_KEY);
+        match rate_limiter.check(key).await {
+            Ok(_) => {}
+            Err(RateLimitError::Exceeded { .. }) => return StatusCode::TOO_MANY_REQUESTS,
+            Err(e) => warn!(error = %e, "Inbox rate limit check failed, allowing activity"),
+        }
     }
 
     // Process the activity
-    let result = process_activity(&state, &activity).await;
+    let result = process_activity(state, &activity).await;
 
     match result {
         Ok(()) => StatusCode::ACCEPTED,
@@ -173,13 +334,13 @@ pub async fn inbox_handler(
     }
 }
 
-/// Verify the HTTP signature on an incoming request.
+/// Verify the HTTP signature on an incoming request, returning the signer's actor URL.
 async fn verify_incoming_signature(
     state: &InboxState,
     headers: &HeaderMap,
     body: &[u8],
     _activity: &InboxActivity,
-) -> AppResult<()> {
+) -> AppResult<String> {
     // Get signature header
     let signature_header = headers
         .get("signature")
@@ -199,6 +360,12 @@ async fn verify_incoming_signature(
 
     // Fetch the public key from the actor
     let public_key_pem = fetch_actor_public_key(state, &components.key_id).await?;
+    let signer = components
+        .key_id
+        .split('#')
+        .next()
+        .unwrap_or(&components.key_id)
+        .to_string();
 
     // Build headers map for verification
     let mut verify_headers = HashMap::new();
@@ -229,7 +396,7 @@ async fn verify_incoming_signature(
     }
 
     debug!(key_id = %components.key_id, "Signature verified successfully");
-    Ok(())
+    Ok(signer)
 }
 
 /// Fetch an actor's public key from their profile.
@@ -271,13 +438,29 @@ async fn process_activity(state: &InboxState, activity: &InboxActivity) -> AppRe
     match activity {
         InboxActivity::Create(create) => {
             info!(note_id = %create.object.id, "Processing Create activity");
+            // Non-public notes are only stored for the local users they address
+            let recipients = if is_public(create) {
+                Vec::new()
+            } else {
+                let recipients = local_recipients(state, create).await?;
+                if recipients.is_empty() {
+                    debug!(note_id = %create.object.id, "Create addresses no local user, ignoring");
+                    return Ok(());
+                }
+                recipients
+            };
             let processor = CreateProcessor::new(
                 state.note_repo.clone(),
                 state.drive_file_repo.clone(),
                 state.user_repo.clone(),
                 state.ap_client.clone(),
-            );
-            processor.process(create).await?;
+            )
+            .with_base_url(state.base_url.clone())
+            .with_federation_policy(state.federation_policy.clone())
+            .with_emoji_repo(state.emoji_repo.clone())
+            .with_profile_repo(state.user_profile_repo.clone())
+            .with_min_account_age(state.min_account_age);
+            processor.process_for(create, &recipients).await?;
         }
         InboxActivity::Delete(delete) => {
             info!(object = %delete.object, "Processing Delete activity");
@@ -328,7 +511,9 @@ async fn process_activity(state: &InboxState, activity: &InboxActivity) -> AppRe
                 state.note_repo.clone(),
                 state.reaction_repo.clone(),
                 state.ap_client.clone(),
-            );
+            )
+            .with_emoji_repo(state.emoji_repo.clone())
+            .with_profile_repo(state.user_profile_repo.clone());
             processor.process(like).await?;
         }
         InboxActivity::EmojiReact(emoji_react) => {
@@ -342,7 +527,8 @@ async fn process_activity(state: &InboxState, activity: &InboxActivity) -> AppRe
                 state.note_repo.clone(),
                 state.reaction_repo.clone(),
                 state.ap_client.clone(),
-            );
+            )
+            .with_emoji_repo(state.emoji_repo.clone());
             processor.process(emoji_react).await?;
         }
         InboxActivity::Undo(undo) => {
@@ -350,17 +536,22 @@ async fn process_activity(state: &InboxState, activity: &InboxActivity) -> AppRe
             // We need to fetch the original activity to know what we're undoing
             // For now, handle common cases using the Undo processor
             let parsed = parse_undo_activity(state, undo).await?;
-            let processor = UndoProcessor::new(
+            let mut processor = UndoProcessor::new(
                 state.user_repo.clone(),
                 state.following_repo.clone(),
                 state.reaction_repo.clone(),
                 state.note_repo.clone(),
-            );
+            )
+            .with_follow_request_repo(state.follow_request_repo.clone());
+            if let Some(ref blocking_repo) = state.blocking_repo {
+                processor = processor.with_blocking_repo(blocking_repo.clone());
+            }
             processor.process(&parsed).await?;
         }
         InboxActivity::Update(update) => {
             info!("Processing Update activity");
-            let processor = UpdateProcessor::new(state.user_repo.clone(), state.note_repo.clone());
+            let processor = UpdateProcessor::new(state.user_repo.clone(), state.note_repo.clone())
+                .with_emoji_repo(state.emoji_repo.clone());
             processor.process(update).await?;
         }
         InboxActivity::Announce(announce) => {
@@ -406,13 +597,85 @@ async fn process_activity(state: &InboxState, activity: &InboxActivity) -> AppRe
             }
         }
         InboxActivity::Unknown(value) => {
-            warn!(activity_type = ?value.get("type"), "Received unknown activity type");
+            warn!(
+                activity_type = ?value.get("type"),
+                object_type = ?value.pointer("/object/type"),
+                "Received unknown activity type"
+            );
         }
     }
 
     Ok(())
 }
 
+/// Addresses of a Create activity and its object.
+fn addresses(create: &CreateActivity) -> Vec<&url::Url> {
+    let mut addresses: Vec<&url::Url> =
+        [&create.to, &create.cc, &create.object.to, &create.object.cc]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect();
+    addresses.sort();
+    addresses.dedup();
+    addresses
+}
+
+/// Whether a Create is addressed to the public collection.
+fn is_public(create: &CreateActivity) -> bool {
+    addresses(create)
+        .iter()
+        .any(|url| url.as_str() == PUBLIC_COLLECTION)
+}
+
+/// Local users a Create is addressed to, directly or as followers of its actor.
+///
+/// Followers are addressed through the collection the actor publishes, whose
+/// URL is chosen by the remote server.
+async fn local_recipients(state: &InboxState, create: &CreateActivity) -> AppResult<Vec<String>> {
+    let mut candidate_ids = Vec::new();
+    let mut other_addresses = Vec::new();
+    for url in addresses(create) {
+        if url.host_str() == state.base_url.host_str()
+            && let Some(user_id) = url.path().strip_prefix("/users/")
+            && !user_id.is_empty()
+            && !user_id.contains('/')
+        {
+            candidate_ids.push(user_id.to_string());
+        } else {
+            other_addresses.push(url);
+        }
+    }
+
+    if !other_addresses.is_empty()
+        && let Some(actor) = state.user_repo.find_by_uri(create.actor.as_str()).await?
+    {
+        let actor_fetcher = ActorFetcher::new(state.user_repo.clone(), state.ap_client.clone());
+        if let Some(followers_url) = actor_fetcher.followers_uri(&actor).await?
+            && other_addresses
+                .iter()
+                .any(|url| url.as_str() == followers_url)
+        {
+            candidate_ids.extend(state.following_repo.find_follower_ids(&actor.id).await?);
+        }
+    }
+    candidate_ids.sort();
+    candidate_ids.dedup();
+
+    if candidate_ids.is_empty() {
+        return Ok(candidate_ids);
+    }
+
+    Ok(state
+        .user_repo
+        .find_by_ids(&candidate_ids)
+        .await?
+        .into_iter()
+        .filter(|user| user.host.is_none())
+        .map(|user| user.id)
+        .collect())
+}
+
 /// Parse an Undo activity to determine what is being undone.
 async fn parse_undo_activity(
     state: &InboxState,
@@ -459,9 +722,347 @@ async fn parse_undo_activity(
 /// Handle incoming activities for a specific user's inbox.
 pub async fn user_inbox_handler(
     State(state): State<InboxState>,
+    verified: Option<Extension<SignatureVerified>>,
+    failed: Option<Extension<SignatureVerificationFailed>>,
     headers: HeaderMap,
     body: Bytes,
 ) -> impl IntoResponse {
-    // For now, delegate to the shared inbox handler
-    inbox_handler(State(state), headers, body).await
+    let checked = LayerCheck::from_extensions(verified, failed);
+    handle_inbox(&state, InboxKind::User, checked, &headers, &body).await
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use misskey_common::FederationMode;
+    use misskey_db::{
+        entities::{meta_settings, note, user},
+        repositories::MetaSettingsRepository,
+    };
+    use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn allowlist_settings() -> meta_settings::Model {
+        meta_settings::Model {
+            id: "instance".to_string(),
+            name: None,
+            short_name: None,
+            description: None,
+            maintainer_name: None,
+            maintainer_email: None,
+            langs: json!(["en"]),
+            icon_url: None,
+            banner_url: None,
+            theme_color: None,
+            disable_registration: false,
+            email_required_for_signup: false,
+            require_registration_approval: false,
+            force_nsfw_media: false,
+            default_blur_nsfw: true,
+            default_hide_ads: false,
+            max_note_text_length: 3000,
+            max_remote_note_text_length: 10000,
+            max_page_content_length: 65536,
+            max_pages_per_user: 100,
+            default_drive_capacity_mb: 1024,
+            max_file_size_mb: 256,
+            bubble_instances: None,
+            federation_hosts: Some(json!(["friends.example"])),
+            default_note_visibility: note::Visibility::Public,
+            default_is_locked: false,
+            default_receive_dm_from_followers_only: false,
+            default_theme: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn allowlist_state(db: DatabaseConnection) -> InboxState {
+        let db = Arc::new(db);
+        InboxState::new(
+            UserRepository::new(Arc::clone(&db)),
+            UserKeypairRepository::new(Arc::clone(&db)),
+            UserProfileRepository::new(Arc::clone(&db)),
+            NoteRepository::new(Arc::clone(&db)),
+            DriveFileRepository::new(Arc::clone(&db)),
+            FollowingRepository::new(Arc::clone(&db)),
+            FollowRequestRepository::new(Arc::clone(&db)),
+            ReactionRepository::new(Arc::clone(&db)),
+            EmojiRepository::new(Arc::clone(&db)),
+            url::Url::parse("https://local.example").unwrap(),
+        )
+        .with_federation_policy(FederationPolicy::new(
+            FederationMode::Allowlist,
+            MetaSettingsRepository::new(db),
+        ))
+    }
+
+    fn delete_from(host: &str) -> Bytes {
+        let body = json!({
+            "type": "Delete",
+            "id": format!("https://{host}/activities/1"),
+            "actor": format!("https://{host}/users/alice"),
+            "object": format!("https://{host}/notes/1"),
+        });
+        Bytes::from(serde_json::to_vec(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_discards_unlisted_host() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[allowlist_settings()]])
+            .into_connection();
+
+        let response = inbox_handler(
+            State(allowlist_state(db)),
+            None,
+            None,
+            HeaderMap::new(),
+            delete_from("stranger.example"),
+        )
+        .await
+        .into_response();
+
+        // Accepted without processing: the mock has no rows for the Delete
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_accepts_listed_host() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[allowlist_settings()]])
+            .append_query_results([Vec::<note::Model>::new()])
+            .into_connection();
+
+        let response = inbox_handler(
+            State(allowlist_state(db)),
+            None,
+            None,
+            HeaderMap::new(),
+            delete_from("friends.example"),
+        )
+        .await
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    fn test_user(id: &str, host: Option<&str>) -> user::Model {
+        user::Model {
+            id: id.to_string(),
+            username: id.to_string(),
+            username_lower: id.to_string(),
+            host: host.map(ToString::to_string),
+            token: None,
+            name: None,
+            description: None,
+            avatar_url: None,
+            banner_url: None,
+            followers_count: 0,
+            following_count: 0,
+            notes_count: 0,
+            is_bot: false,
+            is_cat: false,
+            is_locked: false,
+            is_suspended: false,
+            is_silenced: false,
+            is_admin: false,
+            is_moderator: false,
+            is_system: false,
+            inbox: None,
+            shared_inbox: None,
+            featured: None,
+            followers_uri: None,
+            uri: host.map(|host| format!("https://{host}/users/{id}")),
+            last_fetched_at: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn stored_note() -> note::Model {
+        note::Model {
+            id: "note1".to_string(),
+            user_id: "alice".to_string(),
+            user_host: Some("remote.example".to_string()),
+            text: Some("Hi".to_string()),
+            cw: None,
+            visibility: note::Visibility::Specified,
+            reply_id: None,
+            renote_id: None,
+            thread_id: None,
+            mentions: json!([]),
+            visible_user_ids: json!(["user1"]),
+            file_ids: json!([]),
+            tags: json!([]),
+            reactions: json!({}),
+            replies_count: 0,
+            renote_count: 0,
+            reaction_count: 0,
+            is_local: false,
+            uri: Some("https://remote.example/notes/1".to_string()),
+            url: None,
+            channel_id: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn direct_create(host: &str) -> Bytes {
+        let recipients = json!(["https://local.example/users/user1"]);
+        let body = json!({
+            "type": "Create",
+            "id": format!("https://{host}/activities/1"),
+            "actor": format!("https://{host}/users/alice"),
+            "published": "2024-06-01T12:00:00Z",
+            "to": recipients,
+            "object": {
+                "type": "Note",
+                "id": format!("https://{host}/notes/1"),
+                "attributedTo": format!("https://{host}/users/alice"),
+                "content": "<p>Hi</p>",
+                "published": "2024-06-01T12:00:00Z",
+                "to": recipients,
+            },
+        });
+        Bytes::from(serde_json::to_vec(&body).unwrap())
+    }
+
+    fn inbox_state(db: &Arc<DatabaseConnection>) -> InboxState {
+        InboxState::new(
+            UserRepository::new(Arc::clone(db)),
+            UserKeypairRepository::new(Arc::clone(db)),
+            UserProfileRepository::new(Arc::clone(db)),
+            NoteRepository::new(Arc::clone(db)),
+            DriveFileRepository::new(Arc::clone(db)),
+            FollowingRepository::new(Arc::clone(db)),
+            FollowRequestRepository::new(Arc::clone(db)),
+            ReactionRepository::new(Arc::clone(db)),
+            EmojiRepository::new(Arc::clone(db)),
+            url::Url::parse("https://local.example").unwrap(),
+        )
+    }
+
+    fn signed_by(host: &str) -> Option<Extension<SignatureVerified>> {
+        Some(Extension(SignatureVerified {
+            actor_url: Some(format!("https://{host}/users/alice")),
+        }))
+    }
+
+    fn inserted_note_values(db: Arc<DatabaseConnection>) -> String {
+        let log = Arc::try_unwrap(db).ok().unwrap().into_transaction_log();
+        let insert = log
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .find(|stmt| stmt.sql.starts_with(r#"INSERT INTO "note""#))
+            .unwrap();
+        format!("{:?}", insert.values)
+    }
+
+    #[tokio::test]
+    async fn test_shared_inbox_delivers_to_addressed_user_and_limits_per_signer_host() {
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([[test_user("user1", None)]])
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([[test_user("alice", Some("remote.example"))]])
+                .append_query_results([[stored_note()]])
+                .append_query_results([Vec::<note::Model>::new()])
+                .into_connection(),
+        );
+        let state = inbox_state(&db).with_rate_limiters(
+            FederationRateLimiter::in_memory(60, 1),
+            FederationRateLimiter::in_memory(60, 100),
+        );
+
+        let deliver = |signer: Option<Extension<SignatureVerified>>, body: Bytes| {
+            inbox_handler(State(state.clone()), signer, None, HeaderMap::new(), body)
+        };
+
+        // An unsigned activity claiming the host does not use up its budget
+        let response = deliver(None, delete_from("remote.example"))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        // The direct note is stored, visible to the local user it addresses
+        let response = deliver(signed_by("remote.example"), direct_create("remote.example"))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        // The signer's host has used up its shared inbox budget; other hosts
+        // have not, while unsigned activities share what is left of theirs
+        let response = deliver(signed_by("remote.example"), direct_create("remote.example"))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let response = deliver(None, delete_from("other.example"))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let response = deliver(signed_by("other.example"), delete_from("other.example"))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        drop(state);
+
+        assert!(inserted_note_values(db).contains("user1"));
+    }
+
+    #[tokio::test]
+    async fn test_followers_only_note_uses_published_followers_collection() {
+        let mut alice = test_user("alice", Some("remote.example"));
+        alice.followers_uri = Some("https://remote.example/users/alice/fans".to_string());
+        let follower_row = std::collections::BTreeMap::from([(
+            "follower_id",
+            sea_orm::Value::from("user1".to_string()),
+        )]);
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[alice.clone()]])
+                .append_query_results([[follower_row]])
+                .append_query_results([[test_user("user1", None)]])
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([[alice]])
+                .append_query_results([[stored_note()]])
+                .into_connection(),
+        );
+        let state = inbox_state(&db);
+
+        let followers = json!(["https://remote.example/users/alice/fans"]);
+        let body = json!({
+            "type": "Create",
+            "id": "https://remote.example/activities/1",
+            "actor": "https://remote.example/users/alice",
+            "published": "2024-06-01T12:00:00Z",
+            "to": followers,
+            "object": {
+                "type": "Note",
+                "id": "https://remote.example/notes/1",
+                "attributedTo": "https://remote.example/users/alice",
+                "content": "<p>Hi followers</p>",
+                "published": "2024-06-01T12:00:00Z",
+                "to": followers,
+            },
+        });
+
+        let response = inbox_handler(
+            State(state),
+            signed_by("remote.example"),
+            None,
+            HeaderMap::new(),
+            Bytes::from(serde_json::to_vec(&body).unwrap()),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        assert!(inserted_note_values(db).contains("https://remote.example/notes/1"));
+    }
 }
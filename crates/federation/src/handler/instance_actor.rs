@@ -0,0 +1,82 @@
+//! `ActivityPub` instance actor (Application) endpoint handlers.
+
+#![allow(clippy::expect_used)] // URL joins with known-valid paths cannot fail
+
+use axum::{
+    Json,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use tracing::error;
+
+use super::negotiation::{ACTIVITY_JSON_HEADERS, redirect_to_html, wants_html};
+use crate::instance_actor::InstanceActorService;
+
+/// State required for the instance actor handlers.
+#[derive(Clone)]
+pub struct InstanceActorState {
+    pub service: InstanceActorService,
+    pub instance_name: String,
+}
+
+impl InstanceActorState {
+    /// Create a new instance actor state.
+    #[must_use]
+    pub const fn new(service: InstanceActorService, instance_name: String) -> Self {
+        Self {
+            service,
+            instance_name,
+        }
+    }
+}
+
+/// Handle GET /actor for the instance actor.
+///
+/// Browsers are redirected to the instance's front page.
+pub async fn instance_actor_handler(
+    State(state): State<InstanceActorState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let base_url = state.service.base_url();
+    if wants_html(&headers) {
+        return redirect_to_html(base_url);
+    }
+
+    let actor = match state.service.get_or_create().await {
+        Ok(actor) => actor,
+        Err(e) => {
+            error!(error = %e, "Failed to load instance actor");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+        }
+    };
+
+    let mut application =
+        serde_json::to_value(actor.to_ap_application(base_url, Some(state.instance_name.clone())))
+            .unwrap_or_default();
+    application["@context"] = serde_json::json!([
+        "https://www.w3.org/ns/activitystreams",
+        "https://w3id.org/security/v1",
+    ]);
+
+    (StatusCode::OK, ACTIVITY_JSON_HEADERS, Json(application)).into_response()
+}
+
+/// Handle GET /actor/outbox; the instance actor publishes nothing.
+pub async fn instance_actor_outbox_handler(
+    State(state): State<InstanceActorState>,
+) -> impl IntoResponse {
+    let outbox = serde_json::json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "type": "OrderedCollection",
+        "id": state.service.base_url().join("/actor/outbox").expect("valid URL").to_string(),
+        "totalItems": 0,
+        "orderedItems": [],
+    });
+
+    (
+        StatusCode::OK,
+        [("Content-Type", "application/activity+json; charset=utf-8")],
+        Json(outbox),
+    )
+}
@@ -5,7 +5,10 @@
 mod channel;
 mod collections;
 mod inbox;
+mod instance_actor;
+mod negotiation;
 mod nodeinfo;
+mod note;
 mod user;
 mod webfinger;
 
@@ -18,6 +21,10 @@ pub use collections::{
     clips_list_handler, followers_handler, following_handler, outbox_handler,
 };
 pub use inbox::{InboxActivity, InboxState, inbox_handler, user_inbox_handler};
+pub use instance_actor::{
+    InstanceActorState, instance_actor_handler, instance_actor_outbox_handler,
+};
 pub use nodeinfo::{NodeInfoState, nodeinfo_2_1, well_known_nodeinfo};
+pub use note::{NoteApState, note_handler, note_page_handler};
 pub use user::{UserApState, user_by_username_handler, user_handler};
 pub use webfinger::{WebfingerResponse, WebfingerState, webfinger_handler};
@@ -0,0 +1,96 @@
+//! Content negotiation between `ActivityPub` clients and browsers.
+//!
+//! Object URLs double as web links: clients sending an `Accept` header
+//! without `application/activity+json` or `application/ld+json` are
+//! redirected to the human-readable page, everyone else gets the object.
+//! Both responses carry `Vary: Accept` so caches keep them apart.
+
+use axum::{
+    http::{HeaderMap, HeaderName, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use url::Url;
+
+/// Media types that select the `ActivityPub` representation.
+const ACTIVITY_MEDIA_TYPES: [&str; 2] = ["application/activity+json", "application/ld+json"];
+
+/// Headers of an `ActivityPub` object served at a negotiated URL.
+pub const ACTIVITY_JSON_HEADERS: [(HeaderName, &str); 2] = [
+    (
+        header::CONTENT_TYPE,
+        "application/activity+json; charset=utf-8",
+    ),
+    (header::VARY, "Accept"),
+];
+
+/// Check whether the request's `Accept` header asks for `ActivityPub` JSON.
+pub fn wants_activity_json(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|range| range.split(';').next())
+        .map(str::trim)
+        .any(|media_type| {
+            ACTIVITY_MEDIA_TYPES
+                .iter()
+                .any(|ap| media_type.eq_ignore_ascii_case(ap))
+        })
+}
+
+/// Check whether the request should be redirected to the human-readable page.
+///
+/// Requests without an `Accept` header keep getting the object, as they did
+/// before negotiation was added.
+pub fn wants_html(headers: &HeaderMap) -> bool {
+    headers.contains_key(header::ACCEPT) && !wants_activity_json(headers)
+}
+
+/// Redirect a browser to the human-readable page for an object.
+pub fn redirect_to_html(url: &Url) -> Response {
+    (
+        StatusCode::FOUND,
+        [(header::LOCATION, url.as_str()), (header::VARY, "Accept")],
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn accept(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_activity_json_is_ap() {
+        assert!(wants_activity_json(&accept("application/activity+json")));
+    }
+
+    #[test]
+    fn test_ld_json_with_profile_is_ap() {
+        assert!(wants_activity_json(&accept(
+            r#"application/ld+json; profile="https://www.w3.org/ns/activitystreams""#
+        )));
+    }
+
+    #[test]
+    fn test_browser_accept_is_not_ap() {
+        assert!(!wants_activity_json(&accept(
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"
+        )));
+        assert!(!wants_activity_json(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn test_only_requests_with_non_ap_accept_want_html() {
+        assert!(wants_html(&accept("text/html")));
+        assert!(!wants_html(&accept("application/activity+json")));
+        assert!(!wants_html(&HeaderMap::new()));
+    }
+}
@@ -69,6 +69,9 @@ pub struct NodeInfoMetadata {
     pub node_description: String,
     pub maintainer: NodeInfoMaintainer,
     pub theme_color: String,
+    /// URL of the instance actor signing server-level activities.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance_actor: Option<String>,
 }
 
 /// `NodeInfo` maintainer.
@@ -88,6 +91,7 @@ pub struct NodeInfoState {
     pub open_registrations: bool,
     pub user_repo: Option<Arc<UserRepository>>,
     pub note_repo: Option<Arc<NoteRepository>>,
+    pub instance_actor_url: Option<Url>,
 }
 
 impl NodeInfoState {
@@ -108,6 +112,7 @@ impl NodeInfoState {
             open_registrations,
             user_repo: None,
             note_repo: None,
+            instance_actor_url: None,
         }
     }
 
@@ -130,8 +135,16 @@ impl NodeInfoState {
             open_registrations,
             user_repo: Some(Arc::new(user_repo)),
             note_repo: Some(Arc::new(note_repo)),
+            instance_actor_url: None,
         }
     }
+
+    /// Advertise the instance actor in the metadata.
+    #[must_use]
+    pub fn with_instance_actor(mut self, url: Url) -> Self {
+        self.instance_actor_url = Some(url);
+        self
+    }
 }
 
 /// Handle /.well-known/nodeinfo
@@ -183,6 +196,7 @@ pub async fn nodeinfo_2_1(State(state): State<NodeInfoState>) -> impl IntoRespon
                 email: None,
             },
             theme_color: "#86b300".to_string(),
+            instance_actor: state.instance_actor_url.map(|url| url.to_string()),
         },
     };
 
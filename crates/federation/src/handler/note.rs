@@ -0,0 +1,347 @@
+//! `ActivityPub` note (Note) endpoint handler.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode, Uri, header},
+    response::{IntoResponse, Response},
+};
+use misskey_db::{
+    entities::{
+        note::{self, Visibility},
+        user,
+    },
+    repositories::{DriveFileRepository, NoteRepository, UserRepository},
+};
+use tracing::{error, info};
+use url::Url;
+
+use super::negotiation::{ACTIVITY_JSON_HEADERS, redirect_to_html, wants_html};
+use crate::convert::{NoteToApNote, UrlConfig};
+
+/// State required for note `ActivityPub` handler.
+#[derive(Clone)]
+pub struct NoteApState {
+    pub note_repo: NoteRepository,
+    pub user_repo: UserRepository,
+    pub drive_file_repo: DriveFileRepository,
+    pub url_config: UrlConfig,
+}
+
+impl NoteApState {
+    /// Create a new note AP state.
+    #[must_use]
+    pub const fn new(
+        note_repo: NoteRepository,
+        user_repo: UserRepository,
+        drive_file_repo: DriveFileRepository,
+        base_url: Url,
+    ) -> Self {
+        Self {
+            note_repo,
+            user_repo,
+            drive_file_repo,
+            url_config: UrlConfig::new(base_url),
+        }
+    }
+
+    /// Serve note pages at `template` (see [`UrlConfig::with_note_url_template`]).
+    #[must_use]
+    pub fn with_note_url_template(mut self, template: String) -> Self {
+        self.url_config = self.url_config.with_note_url_template(template);
+        self
+    }
+}
+
+/// Handle GET /notes/{id} for `ActivityPub` Note retrieval.
+///
+/// Returns a local public or home note as an `ActivityPub` Note; browsers
+/// (see [`wants_html`]) are redirected to the note page.
+pub async fn note_handler(
+    State(state): State<NoteApState>,
+    Path(note_id): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    info!(note_id = %note_id, "ActivityPub note lookup");
+
+    let (note, author) = match find_public_note(&state, &note_id).await {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+
+    if wants_html(&headers) {
+        return redirect_to_html(&state.url_config.note_page_url(&author.username, &note.id));
+    }
+
+    ap_note_response(&state, &note, &author).await
+}
+
+/// Handle GET on the human-readable note page URL (`notes.url_template`),
+/// routed at the template itself.
+///
+/// `ActivityPub` clients resolving a note's `url` get the Note. The page is
+/// rendered by the web frontend, so browsers get the same 404 as any other
+/// path this server does not serve.
+pub async fn note_page_handler(
+    State(state): State<NoteApState>,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Response {
+    if wants_html(&headers) {
+        return (StatusCode::NOT_FOUND, [(header::VARY, "Accept")]).into_response();
+    }
+    let Some((note_id, username)) = state.url_config.match_note_page(uri.path()) else {
+        return (StatusCode::NOT_FOUND, "Note not found").into_response();
+    };
+
+    info!(note_id = %note_id, "ActivityPub note page lookup");
+
+    let (note, author) = match find_public_note(&state, &note_id).await {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+
+    if username.is_some_and(|username| author.username_lower != username.to_lowercase()) {
+        return (StatusCode::NOT_FOUND, "Note not found").into_response();
+    }
+
+    ap_note_response(&state, &note, &author).await
+}
+
+/// Find a local note readable without authentication, with its author.
+async fn find_public_note(
+    state: &NoteApState,
+    note_id: &str,
+) -> Result<(note::Model, user::Model), Response> {
+    let note = match state.note_repo.find_by_id(note_id).await {
+        Ok(Some(n)) => n,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "Note not found").into_response()),
+        Err(e) => {
+            error!(error = %e, "Failed to fetch note");
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response());
+        }
+    };
+
+    // Only local notes readable without authentication are served
+    if note.user_host.is_some() || !matches!(note.visibility, Visibility::Public | Visibility::Home)
+    {
+        return Err((StatusCode::NOT_FOUND, "Note not found").into_response());
+    }
+
+    match state.user_repo.find_by_id(&note.user_id).await {
+        Ok(Some(u)) if !u.is_suspended => Ok((note, u)),
+        Ok(_) => Err((StatusCode::NOT_FOUND, "Note not found").into_response()),
+        Err(e) => {
+            error!(error = %e, "Failed to fetch note author");
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response())
+        }
+    }
+}
+
+/// Render a note as an `ActivityPub` Note response.
+async fn ap_note_response(
+    state: &NoteApState,
+    note: &note::Model,
+    author: &user::Model,
+) -> Response {
+    let file_ids: Vec<String> = serde_json::from_value(note.file_ids.clone()).unwrap_or_default();
+    let files = if file_ids.is_empty() {
+        vec![]
+    } else {
+        state
+            .drive_file_repo
+            .find_by_ids(&file_ids)
+            .await
+            .unwrap_or_default()
+    };
+
+    let ap_note = note.to_ap_note(&state.url_config, &author.username, &files);
+
+    (StatusCode::OK, ACTIVITY_JSON_HEADERS, Json(ap_note)).into_response()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, http::Request, routing::get};
+    use chrono::Utc;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use serde_json::json;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    const TEMPLATE: &str = "/@{username}/posts/{id}";
+
+    fn local_note() -> note::Model {
+        note::Model {
+            id: "note1".to_string(),
+            user_id: "user1".to_string(),
+            user_host: None,
+            text: Some("Hello".to_string()),
+            cw: None,
+            visibility: Visibility::Public,
+            reply_id: None,
+            renote_id: None,
+            thread_id: None,
+            mentions: json!([]),
+            visible_user_ids: json!([]),
+            file_ids: json!([]),
+            tags: json!([]),
+            reactions: json!({}),
+            replies_count: 0,
+            renote_count: 0,
+            reaction_count: 0,
+            is_local: true,
+            uri: None,
+            url: None,
+            channel_id: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn local_user() -> user::Model {
+        user::Model {
+            id: "user1".to_string(),
+            username: "alice".to_string(),
+            username_lower: "alice".to_string(),
+            host: None,
+            token: Some("token".to_string()),
+            name: Some("Alice".to_string()),
+            description: None,
+            avatar_url: None,
+            banner_url: None,
+            is_bot: false,
+            is_cat: false,
+            is_locked: false,
+            is_suspended: false,
+            is_silenced: false,
+            is_admin: false,
+            is_moderator: false,
+            is_system: false,
+            followers_count: 0,
+            following_count: 0,
+            notes_count: 0,
+            inbox: None,
+            shared_inbox: None,
+            featured: None,
+            followers_uri: None,
+            uri: None,
+            last_fetched_at: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn router_with_template(db: sea_orm::DatabaseConnection, template: &str) -> Router {
+        let db = Arc::new(db);
+        let state = NoteApState::new(
+            NoteRepository::new(Arc::clone(&db)),
+            UserRepository::new(Arc::clone(&db)),
+            DriveFileRepository::new(db),
+            Url::parse("https://example.com").unwrap(),
+        )
+        .with_note_url_template(template.to_string());
+        Router::new()
+            .route("/notes/{id}", get(note_handler).with_state(state.clone()))
+            .route("/inbox", get(|| async { "inbox" }))
+            .route(template, get(note_page_handler).with_state(state))
+    }
+
+    async fn get_with_accept(db: sea_orm::DatabaseConnection, uri: &str, accept: &str) -> Response {
+        router_with_template(db, TEMPLATE)
+            .oneshot(
+                Request::get(uri)
+                    .header(header::ACCEPT, accept)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_note_object_redirects_browsers_to_templated_page() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[local_note()]])
+            .append_query_results([[local_user()]])
+            .into_connection();
+
+        let response = get_with_accept(db, "/notes/note1", "text/html").await;
+
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(
+            response.headers().get(header::LOCATION).unwrap(),
+            "https://example.com/@alice/posts/note1"
+        );
+        assert_eq!(response.headers().get(header::VARY).unwrap(), "Accept");
+    }
+
+    #[tokio::test]
+    async fn test_templated_page_returns_note_to_activity_json_clients() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[local_note()]])
+            .append_query_results([[local_user()]])
+            .into_connection();
+
+        let response =
+            get_with_accept(db, "/@alice/posts/note1", "application/activity+json").await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("application/activity+json")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_templated_page_checks_username() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[local_note()]])
+            .append_query_results([[local_user()]])
+            .into_connection();
+
+        let response =
+            get_with_accept(db, "/@mallory/posts/note1", "application/activity+json").await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_templated_page_leaves_browsers_to_the_frontend() {
+        // No lookups are expected, so an empty mock must not be queried
+        let db = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+
+        let response = get_with_accept(db, "/@alice/posts/note1", "text/html").await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers().get(header::VARY).unwrap(), "Accept");
+    }
+
+    #[tokio::test]
+    async fn test_root_template_does_not_shadow_other_routes() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+
+        let response = router_with_template(db, "/{id}")
+            .oneshot(
+                Request::get("/inbox")
+                    .header(header::ACCEPT, "application/activity+json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"inbox");
+    }
+}
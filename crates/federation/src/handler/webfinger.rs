@@ -13,6 +13,8 @@ use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 use url::Url;
 
+use crate::instance_actor::{INSTANCE_ACTOR_USERNAME, instance_actor_url};
+
 /// `WebFinger` query parameters.
 #[derive(Debug, Deserialize)]
 pub struct WebfingerQuery {
@@ -188,11 +190,16 @@ async fn webfinger_user(
         return (StatusCode::GONE, "User is suspended").into_response();
     }
 
-    // Build actor URL using user ID for consistency
-    let actor_url = state
-        .base_url
-        .join(&format!("/users/{}", user.id))
-        .expect("valid URL");
+    // Build actor URL using user ID for consistency; the instance actor has
+    // its own stable URL
+    let actor_url = if user.username == INSTANCE_ACTOR_USERNAME {
+        instance_actor_url(&state.base_url)
+    } else {
+        state
+            .base_url
+            .join(&format!("/users/{}", user.id))
+            .expect("valid URL")
+    };
 
     let response = WebfingerResponse {
         subject: resource.to_string(),
@@ -0,0 +1,304 @@
+//! Instance actor for server-level federation.
+//!
+//! Relay subscriptions, reports and authorized fetch requests are signed by an
+//! `Application` actor representing the server rather than by any user. The
+//! actor is stored as a local bot user with its own keypair, generated on first
+//! start and reused afterwards, so remote servers see a stable key.
+
+#![allow(clippy::expect_used)] // URL joins with known-valid paths cannot fail
+
+use std::sync::{Arc, OnceLock};
+
+use activitypub_federation::kinds::actor::ApplicationType;
+use misskey_common::{AppError, AppResult, IdGenerator, generate_rsa_keypair};
+use misskey_db::{
+    entities::{user, user_keypair},
+    repositories::{UserKeypairRepository, UserRepository},
+};
+use sea_orm::Set;
+use tracing::info;
+use url::Url;
+
+use crate::actors::{ApApplication, ApPublicKey};
+use crate::signature::HttpSigner;
+
+/// Username of the instance actor.
+pub const INSTANCE_ACTOR_USERNAME: &str = "instance.actor";
+
+/// URL the instance actor is served at.
+#[must_use]
+pub fn instance_actor_url(base_url: &Url) -> Url {
+    base_url.join("/actor").expect("valid URL")
+}
+
+/// The persisted instance actor.
+#[derive(Debug, Clone)]
+pub struct InstanceActor {
+    /// The local bot user backing the actor.
+    pub user: user::Model,
+    /// The actor's signing keypair.
+    pub keypair: user_keypair::Model,
+    /// The actor's `ActivityPub` ID.
+    pub url: Url,
+}
+
+impl InstanceActor {
+    /// Key ID used in HTTP signatures.
+    #[must_use]
+    pub fn key_id(&self) -> &str {
+        &self.keypair.key_id
+    }
+
+    /// Create a signer for requests made on behalf of the instance.
+    pub fn signer(&self) -> AppResult<HttpSigner> {
+        HttpSigner::new(&self.keypair.private_key, self.keypair.key_id.clone())
+            .map_err(|e| AppError::Internal(format!("Invalid instance actor key: {e}")))
+    }
+
+    /// Render the actor as an `ActivityPub` Application.
+    #[must_use]
+    pub fn to_ap_application(&self, base_url: &Url, name: Option<String>) -> ApApplication {
+        ApApplication {
+            kind: ApplicationType::Application,
+            id: self.url.clone(),
+            preferred_username: self.user.username.clone(),
+            inbox: base_url.join("/inbox").expect("valid URL"),
+            outbox: base_url.join("/actor/outbox").expect("valid URL"),
+            name,
+            public_key: ApPublicKey {
+                id: self.keypair.key_id.clone(),
+                owner: self.url.clone(),
+                public_key_pem: self.keypair.public_key.clone(),
+            },
+            url: Some(base_url.clone()),
+            manually_approves_followers: true,
+        }
+    }
+}
+
+/// Service loading, and on first use creating, the instance actor.
+#[derive(Clone)]
+pub struct InstanceActorService {
+    user_repo: UserRepository,
+    keypair_repo: UserKeypairRepository,
+    base_url: Url,
+    id_gen: IdGenerator,
+    actor: Arc<OnceLock<InstanceActor>>,
+}
+
+impl InstanceActorService {
+    /// Create a new instance actor service.
+    #[must_use]
+    pub fn new(
+        user_repo: UserRepository,
+        keypair_repo: UserKeypairRepository,
+        base_url: Url,
+    ) -> Self {
+        Self {
+            user_repo,
+            keypair_repo,
+            base_url,
+            id_gen: IdGenerator::new(),
+            actor: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Base URL of this server.
+    #[must_use]
+    pub const fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Load the instance actor, creating its user and keypair if missing.
+    ///
+    /// Safe to call on every start and from concurrent instances: existing
+    /// rows are always reused, and a lost creation race re-reads the winner.
+    pub async fn get_or_create(&self) -> AppResult<InstanceActor> {
+        if let Some(actor) = self.actor.get() {
+            return Ok(actor.clone());
+        }
+
+        let user = self.find_or_create_user().await?;
+        let keypair = self.find_or_create_keypair(&user.id).await?;
+        let actor = InstanceActor {
+            user,
+            keypair,
+            url: instance_actor_url(&self.base_url),
+        };
+
+        Ok(self.actor.get_or_init(|| actor).clone())
+    }
+
+    async fn find_or_create_user(&self) -> AppResult<user::Model> {
+        if let Some(user) = self.find_user().await? {
+            return Ok(user);
+        }
+
+        let model = user::ActiveModel {
+            id: Set(self.id_gen.generate()),
+            username: Set(INSTANCE_ACTOR_USERNAME.to_string()),
+            username_lower: Set(INSTANCE_ACTOR_USERNAME.to_string()),
+            host: Set(None),
+            token: Set(None),
+            is_bot: Set(true),
+            is_locked: Set(true),
+            is_system: Set(true),
+            ..Default::default()
+        };
+
+        match self.user_repo.create(model).await {
+            Ok(user) => {
+                info!(user_id = %user.id, "Created instance actor");
+                Ok(user)
+            }
+            Err(e) => self.find_user().await?.ok_or(e),
+        }
+    }
+
+    async fn find_user(&self) -> AppResult<Option<user::Model>> {
+        self.user_repo
+            .find_by_username_and_host(INSTANCE_ACTOR_USERNAME, None)
+            .await
+    }
+
+    async fn find_or_create_keypair(&self, user_id: &str) -> AppResult<user_keypair::Model> {
+        if let Some(keypair) = self.keypair_repo.find_by_user_id(user_id).await? {
+            return Ok(keypair);
+        }
+
+        let keypair = generate_rsa_keypair()?;
+        let model = user_keypair::ActiveModel {
+            user_id: Set(user_id.to_string()),
+            public_key: Set(keypair.public_key_pem),
+            private_key: Set(keypair.private_key_pem),
+            key_id: Set(format!("{}#main-key", instance_actor_url(&self.base_url))),
+            ..Default::default()
+        };
+
+        match self.keypair_repo.create(model).await {
+            Ok(keypair) => Ok(keypair),
+            Err(e) => self.keypair_repo.find_by_user_id(user_id).await?.ok_or(e),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase};
+
+    fn actor_user() -> user::Model {
+        user::Model {
+            id: "actor1".to_string(),
+            username: INSTANCE_ACTOR_USERNAME.to_string(),
+            username_lower: INSTANCE_ACTOR_USERNAME.to_string(),
+            host: None,
+            token: None,
+            name: None,
+            description: None,
+            avatar_url: None,
+            banner_url: None,
+            followers_count: 0,
+            following_count: 0,
+            notes_count: 0,
+            is_bot: true,
+            is_cat: false,
+            is_locked: true,
+            is_suspended: false,
+            is_silenced: false,
+            is_admin: false,
+            is_moderator: false,
+            is_system: true,
+            inbox: None,
+            shared_inbox: None,
+            featured: None,
+            followers_uri: None,
+            uri: None,
+            last_fetched_at: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn actor_keypair() -> user_keypair::Model {
+        user_keypair::Model {
+            user_id: "actor1".to_string(),
+            public_key: "public".to_string(),
+            private_key: "private".to_string(),
+            key_id: "https://example.com/actor#main-key".to_string(),
+            created_at: Utc::now().into(),
+        }
+    }
+
+    fn create_service(
+        user_db: Arc<DatabaseConnection>,
+        keypair_db: Arc<DatabaseConnection>,
+    ) -> InstanceActorService {
+        InstanceActorService::new(
+            UserRepository::new(user_db),
+            UserKeypairRepository::new(keypair_db),
+            Url::parse("https://example.com").unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_key_id_stable_across_initializations() {
+        // First start: nothing stored yet, so the user and keypair are created
+        let keypair_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<user_keypair::Model>::new()])
+                .append_query_results([[actor_keypair()]])
+                .into_connection(),
+        );
+        let first = create_service(
+            Arc::new(
+                MockDatabase::new(DatabaseBackend::Postgres)
+                    .append_query_results([Vec::<user::Model>::new()])
+                    .append_query_results([[actor_user()]])
+                    .into_connection(),
+            ),
+            keypair_db.clone(),
+        );
+        let created = first.get_or_create().await.unwrap();
+        drop(first);
+
+        let log = Arc::try_unwrap(keypair_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert!(
+            log.iter()
+                .flat_map(sea_orm::Transaction::statements)
+                .any(|stmt| stmt.sql.starts_with(r#"INSERT INTO "user_keypair""#)
+                    && format!("{:?}", stmt.values).contains("https://example.com/actor#main-key"))
+        );
+
+        // Second start: the stored rows are reused
+        let second = create_service(
+            Arc::new(
+                MockDatabase::new(DatabaseBackend::Postgres)
+                    .append_query_results([[actor_user()]])
+                    .into_connection(),
+            ),
+            Arc::new(
+                MockDatabase::new(DatabaseBackend::Postgres)
+                    .append_query_results([[actor_keypair()]])
+                    .into_connection(),
+            ),
+        );
+        let loaded = second.get_or_create().await.unwrap();
+
+        assert_eq!(created.key_id(), "https://example.com/actor#main-key");
+        assert_eq!(created.key_id(), loaded.key_id());
+        assert_eq!(created.user.id, loaded.user.id);
+        assert_eq!(loaded.url.as_str(), "https://example.com/actor");
+
+        // Later calls are answered from memory
+        assert_eq!(
+            second.get_or_create().await.unwrap().key_id(),
+            loaded.key_id()
+        );
+    }
+}
@@ -3,10 +3,10 @@
 //! This crate implements the `ActivityPub` protocol for federated social networking:
 //!
 //! - **Activities**: Create, Delete, Follow, Like, Announce, Update, Undo
-//! - **Actors**: Person actor implementation
+//! - **Actors**: Person actor implementation and the instance (Application) actor
 //! - **Objects**: Note, Question, Image objects
 //! - **Handlers**: `WebFinger`, `NodeInfo`, inbox/outbox endpoints
-//! - **Security**: HTTP signatures, replay protection, rate limiting
+//! - **Security**: HTTP signatures, replay protection, rate limiting, host allow/blocklists
 //! - **Delivery**: Activity delivery with retry and dead letter queue
 //!
 //! # `ActivityPub` Compliance
@@ -18,11 +18,14 @@ pub mod activities;
 pub mod actors;
 pub mod cache;
 pub mod client;
+pub mod collection;
 pub mod convert;
 pub mod delivery;
 pub mod handler;
+pub mod instance_actor;
 pub mod middleware;
 pub mod objects;
+pub mod policy;
 pub mod processor;
 pub mod security;
 pub mod signature;
@@ -30,17 +33,25 @@ pub mod signature;
 pub use activities::*;
 pub use actors::*;
 pub use cache::{CacheError, CacheStats, CachedRemoteActor, RemoteActorCache};
-pub use client::{ApClient, ApClientError};
+pub use client::{ApClient, ApClientError, ObjectFetcher};
+pub use collection::{CollectionPage, collect_items, collection_pages};
 pub use convert::*;
 pub use delivery::DeliveryService;
 pub use handler::*;
-pub use middleware::{SignatureVerificationLayer, SignatureVerificationState, SignatureVerified};
+pub use instance_actor::{
+    INSTANCE_ACTOR_USERNAME, InstanceActor, InstanceActorService, instance_actor_url,
+};
+pub use middleware::{
+    PublicKeyFetcher, SignatureVerificationFailed, SignatureVerificationLayer,
+    SignatureVerificationState, SignatureVerified,
+};
 pub use objects::*;
+pub use policy::FederationPolicy;
 pub use processor::{
     AcceptProcessor, ActorFetcher, AnnounceProcessor, CreateProcessor, DeleteProcessor,
-    DeleteResult, EmojiReactProcessor, FollowProcessResult, FollowProcessor, LikeProcessor,
-    MoveProcessResult, MoveProcessor, ParsedUndoActivity, RejectProcessor, UndoProcessor,
-    UndoResult, UpdateProcessor, UpdateResult,
+    DeleteResult, EmojiImporter, EmojiReactProcessor, FeaturedImporter, FollowProcessResult,
+    FollowProcessor, LikeProcessor, MoveProcessResult, MoveProcessor, ParsedUndoActivity,
+    RejectProcessor, UndoProcessor, UndoResult, UpdateProcessor, UpdateResult,
 };
 pub use security::{
     ActivitySecurityChecker, FederationRateLimiter, RateLimitError, RateLimitStatus, ReplayError,
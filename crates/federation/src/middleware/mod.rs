@@ -3,5 +3,6 @@
 mod signature_verification;
 
 pub use signature_verification::{
-    SignatureVerificationLayer, SignatureVerificationState, SignatureVerified,
+    PublicKeyFetcher, SignatureVerificationFailed, SignatureVerificationLayer,
+    SignatureVerificationState, SignatureVerified,
 };
@@ -6,9 +6,10 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use axum::{
     body::Body,
-    http::{Request, StatusCode},
+    http::{Request, StatusCode, request::Parts},
     response::{IntoResponse, Response},
 };
 use futures::future::BoxFuture;
@@ -16,23 +17,43 @@ use tower::Layer;
 use tracing::{debug, warn};
 
 use crate::client::ApClient;
-use crate::signature::HttpVerifier;
+use crate::signature::{HttpVerifier, verify_digest};
+
+/// Largest request body buffered for verification (matches axum's default limit).
+const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Source of the public keys that request signatures are checked against.
+#[async_trait]
+pub trait PublicKeyFetcher: Send + Sync {
+    /// Fetch the PEM-encoded public key identified by `key_id`.
+    async fn fetch_public_key(&self, key_id: &str) -> Result<String, String>;
+}
+
+#[async_trait]
+impl PublicKeyFetcher for ApClient {
+    async fn fetch_public_key(&self, key_id: &str) -> Result<String, String> {
+        fetch_public_key(self, key_id).await
+    }
+}
 
 /// State required for signature verification.
 #[derive(Clone)]
 pub struct SignatureVerificationState {
-    /// `ActivityPub` client for fetching actor public keys.
-    pub ap_client: ApClient,
-    /// Whether signature verification is globally required.
+    /// Fetcher for the signing actor's public key.
+    pub key_fetcher: Arc<dyn PublicKeyFetcher>,
+    /// Whether unsigned or badly signed requests are rejected.
+    ///
+    /// When false, signatures are still verified if present, but failures
+    /// only record [`SignatureVerificationFailed`].
     pub require_signatures: bool,
 }
 
 impl SignatureVerificationState {
     /// Create a new signature verification state.
     #[must_use]
-    pub const fn new(ap_client: ApClient, require_signatures: bool) -> Self {
+    pub fn new(ap_client: ApClient, require_signatures: bool) -> Self {
         Self {
-            ap_client,
+            key_fetcher: Arc::new(ap_client),
             require_signatures,
         }
     }
@@ -40,14 +61,25 @@ impl SignatureVerificationState {
 
 /// Marker type indicating the request signature was verified.
 ///
-/// Can be extracted in handlers via `Extension<SignatureVerified>` to confirm
-/// the request was properly signed.
+/// Inserted by [`SignatureVerificationLayer`] only when the digest and
+/// signature checked out, so handlers can extract it via
+/// `Option<Extension<SignatureVerified>>` instead of verifying again.
 #[derive(Clone, Debug)]
 pub struct SignatureVerified {
     /// The actor URL that signed this request.
     pub actor_url: Option<String>,
 }
 
+/// Marker type indicating the layer checked the signature and it did not verify.
+///
+/// Only inserted when signatures are not required; handlers use it to skip
+/// verifying a request the layer already rejected.
+#[derive(Clone, Debug)]
+pub struct SignatureVerificationFailed {
+    /// Why verification failed.
+    pub reason: &'static str,
+}
+
 /// Layer for adding signature verification to routes.
 #[derive(Clone)]
 pub struct SignatureVerificationLayer {
@@ -98,91 +130,97 @@ where
         tower::Service::poll_ready(&mut self.inner, cx)
     }
 
-    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
         let state = self.state.clone();
         let mut inner = self.inner.clone();
 
         Box::pin(async move {
-            // Check if signature verification is required
-            if !state.require_signatures {
-                // Add marker that verification was skipped (not required)
-                req.extensions_mut()
-                    .insert(SignatureVerified { actor_url: None });
-                return tower::Service::call(&mut inner, req).await;
-            }
-
-            // Extract signature header
-            let signature_header = req
-                .headers()
-                .get("signature")
-                .and_then(|v| v.to_str().ok())
-                .map(String::from);
-
-            let Some(signature_header) = signature_header else {
-                warn!("Missing signature header on request requiring authorization");
-                return Ok((StatusCode::UNAUTHORIZED, "HTTP signature required").into_response());
+            // Buffer the body once; digest and signature checks share it and the
+            // handler receives the same bytes.
+            let (parts, body) = req.into_parts();
+            let Ok(bytes) = axum::body::to_bytes(body, MAX_BODY_SIZE).await else {
+                return Ok(
+                    (StatusCode::PAYLOAD_TOO_LARGE, "Request body too large").into_response()
+                );
             };
+            let verification = verify_request(&state, &parts, &bytes).await;
+            let mut req = Request::from_parts(parts, Body::from(bytes));
 
-            // Parse signature components
-            let components = match HttpVerifier::parse_signature_header(&signature_header) {
-                Ok(c) => c,
-                Err(e) => {
-                    warn!(error = %e, "Invalid signature header format");
-                    return Ok(
-                        (StatusCode::UNAUTHORIZED, "Invalid signature header format")
-                            .into_response(),
-                    );
-                }
-            };
-
-            // Extract actor URL from key_id
-            let actor_url = extract_actor_url(&components.key_id);
-
-            // Fetch actor's public key
-            let public_key_pem = match fetch_public_key(&state.ap_client, &components.key_id).await
-            {
-                Ok(key) => key,
-                Err(e) => {
-                    warn!(error = %e, key_id = %components.key_id, "Failed to fetch public key");
-                    return Ok(
-                        (StatusCode::UNAUTHORIZED, "Failed to fetch actor public key")
-                            .into_response(),
-                    );
-                }
-            };
-
-            // Build headers map for verification
-            let headers_map = build_headers_map(&req, &components.headers);
-
-            // Extract method and path for verification
-            let method = req.method().as_str();
-            let path = req.uri().path_and_query().map_or_else(
-                || req.uri().path().to_string(),
-                std::string::ToString::to_string,
-            );
-
-            // Verify the signature
-            match HttpVerifier::verify(&public_key_pem, &components, method, &path, &headers_map) {
-                Ok(true) => {
-                    debug!(actor = ?actor_url, "Signature verified successfully");
+            match verification {
+                Ok(actor_url) => {
+                    debug!(actor = %actor_url, "Signature verified successfully");
                     req.extensions_mut().insert(SignatureVerified {
-                        actor_url: actor_url.clone(),
+                        actor_url: Some(actor_url),
                     });
-                    tower::Service::call(&mut inner, req).await
                 }
-                Ok(false) => {
-                    warn!(actor = ?actor_url, "Signature verification failed");
-                    Ok((StatusCode::UNAUTHORIZED, "Signature verification failed").into_response())
+                Err(e) if state.require_signatures => {
+                    warn!(error = %e, "Rejecting request with invalid HTTP signature");
+                    return Ok((StatusCode::UNAUTHORIZED, e).into_response());
                 }
                 Err(e) => {
-                    warn!(error = %e, "Signature verification error");
-                    Ok((StatusCode::UNAUTHORIZED, "Signature verification error").into_response())
+                    // Not required: record the failure so handlers know the
+                    // request is unauthenticated without checking it again.
+                    debug!(error = %e, "Signature not verified (not required)");
+                    req.extensions_mut()
+                        .insert(SignatureVerificationFailed { reason: e });
                 }
             }
+
+            tower::Service::call(&mut inner, req).await
         })
     }
 }
 
+/// Verify the digest and signature of a buffered request.
+///
+/// Returns the URL of the actor that signed the request.
+async fn verify_request(
+    state: &SignatureVerificationState,
+    req: &Parts,
+    body: &[u8],
+) -> Result<String, &'static str> {
+    let signature_header = req
+        .headers
+        .get("signature")
+        .and_then(|v| v.to_str().ok())
+        .ok_or("HTTP signature required")?;
+
+    let components = HttpVerifier::parse_signature_header(signature_header)
+        .map_err(|_| "Invalid signature header format")?;
+
+    if let Some(digest) = req.headers.get("digest") {
+        let digest = digest.to_str().map_err(|_| "Invalid digest header")?;
+        if !verify_digest(body, digest) {
+            return Err("Digest mismatch");
+        }
+    } else if !body.is_empty() && components.headers.iter().any(|h| h == "digest") {
+        return Err("Missing digest header");
+    }
+
+    let actor_url = extract_actor_url(&components.key_id).ok_or("Invalid key id")?;
+    let public_key_pem = state
+        .key_fetcher
+        .fetch_public_key(&components.key_id)
+        .await
+        .map_err(|e| {
+            warn!(error = %e, key_id = %components.key_id, "Failed to fetch public key");
+            "Failed to fetch actor public key"
+        })?;
+
+    let headers_map = build_headers_map(req, &components.headers);
+    let method = req.method.as_str();
+    let path = req.uri.path_and_query().map_or_else(
+        || req.uri.path().to_string(),
+        std::string::ToString::to_string,
+    );
+
+    match HttpVerifier::verify(&public_key_pem, &components, method, &path, &headers_map) {
+        Ok(true) => Ok(actor_url),
+        Ok(false) => Err("Signature verification failed"),
+        Err(_) => Err("Signature verification error"),
+    }
+}
+
 /// Extract actor URL from `key_id` (removes #main-key fragment).
 fn extract_actor_url(key_id: &str) -> Option<String> {
     key_id.split('#').next().map(String::from)
@@ -213,18 +251,18 @@ async fn fetch_public_key(ap_client: &ApClient, key_id: &str) -> Result<String,
 }
 
 /// Build headers map for signature verification.
-fn build_headers_map(req: &Request<Body>, signed_headers: &[String]) -> HashMap<String, String> {
+fn build_headers_map(req: &Parts, signed_headers: &[String]) -> HashMap<String, String> {
     let mut headers = HashMap::new();
 
     for header_name in signed_headers {
         let value = if header_name == "(request-target)" {
-            let method = req.method().as_str().to_lowercase();
-            let path = req.uri().path_and_query().map_or_else(
-                || req.uri().path().to_string(),
+            let method = req.method.as_str().to_lowercase();
+            let path = req.uri.path_and_query().map_or_else(
+                || req.uri.path().to_string(),
                 std::string::ToString::to_string,
             );
             format!("{method} {path}")
-        } else if let Some(value) = req.headers().get(header_name.as_str()) {
+        } else if let Some(value) = req.headers.get(header_name.as_str()) {
             value.to_str().unwrap_or("").to_string()
         } else {
             continue;
@@ -252,3 +290,107 @@ pub const fn user_requires_authorized_fetch(secure_fetch_only: bool) -> bool {
 pub const fn instance_requires_authorized_fetch(require_authorized_fetch: bool) -> bool {
     require_authorized_fetch
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::signature::HttpSigner;
+    use axum::{Extension, Router, body::Bytes, routing::post};
+    use misskey_common::generate_rsa_keypair;
+    use tower::ServiceExt;
+    use url::Url;
+
+    const ACTOR_ID: &str = "https://remote.example/users/alice";
+
+    /// Key fetcher that serves a single actor's key without network access.
+    struct StaticKey(String);
+
+    #[async_trait]
+    impl PublicKeyFetcher for StaticKey {
+        async fn fetch_public_key(&self, key_id: &str) -> Result<String, String> {
+            if key_id == format!("{ACTOR_ID}#main-key") {
+                Ok(self.0.clone())
+            } else {
+                Err(format!("Unknown key {key_id}"))
+            }
+        }
+    }
+
+    fn inbox_router(public_key_pem: &str, require_signatures: bool) -> Router {
+        Router::new()
+            .route(
+                "/inbox",
+                post(
+                    |verified: Option<Extension<SignatureVerified>>,
+                     failed: Option<Extension<SignatureVerificationFailed>>,
+                     body: Bytes| async move {
+                        let signer = verified
+                            .and_then(|Extension(v)| v.actor_url)
+                            .unwrap_or_default();
+                        let failure = failed.map(|Extension(f)| f.reason).unwrap_or_default();
+                        format!("{signer}|{failure}|{}", body.len())
+                    },
+                ),
+            )
+            .layer(SignatureVerificationLayer::new(
+                SignatureVerificationState {
+                    key_fetcher: Arc::new(StaticKey(public_key_pem.to_string())),
+                    require_signatures,
+                },
+            ))
+    }
+
+    async fn response_text(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_handler_reads_verified_actor_set_by_layer() {
+        let keypair = generate_rsa_keypair().unwrap();
+
+        let body = br#"{"type":"Follow"}"#.to_vec();
+        let url = Url::parse("https://example.com/inbox").unwrap();
+        let signer =
+            HttpSigner::new(&keypair.private_key_pem, format!("{ACTOR_ID}#main-key")).unwrap();
+        let headers = signer
+            .sign_request("POST", &url, Some(&body), &HashMap::new())
+            .unwrap();
+
+        let mut request = Request::post("/inbox")
+            .body(Body::from(body.clone()))
+            .unwrap();
+        request.headers_mut().extend(headers);
+        request
+            .headers_mut()
+            .insert("host", "example.com".parse().unwrap());
+
+        let response = inbox_router(&keypair.public_key_pem, true)
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        // The handler sees the signer and the full body the layer buffered.
+        assert_eq!(
+            response_text(response).await,
+            format!("{ACTOR_ID}||{}", body.len())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_request_rejected_only_when_required() {
+        let request = || Request::post("/inbox").body(Body::from("{}")).unwrap();
+
+        let response = inbox_router("", true).oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // The failure is passed on so the handler need not verify again.
+        let response = inbox_router("", false).oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response_text(response).await, "|HTTP signature required|2");
+    }
+}
@@ -4,11 +4,29 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+use crate::activities::{EmojiIcon, EmojiTag};
+
 /// Object type for notes and questions.
+///
+/// Other common content types are accepted so that inbound objects of those
+/// types can be stored as notes on a best-effort basis.
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub enum ApObjectType {
     Note,
     Question,
+    Article,
+    Page,
+    Video,
+    Audio,
+    Event,
+}
+
+impl ApObjectType {
+    /// Check if this type maps directly onto a note (`Note` or `Question`).
+    #[must_use]
+    pub const fn is_native(&self) -> bool {
+        matches!(self, Self::Note | Self::Question)
+    }
 }
 
 /// `ActivityPub` Note object.
@@ -19,9 +37,25 @@ pub struct ApNote {
     pub kind: ApObjectType,
     pub id: Url,
     pub attributed_to: Url,
+    /// HTML content; may be absent on non-note types such as `Video`.
+    #[serde(default)]
     pub content: String,
+    /// Missing or invalid timestamps are read as the time of receipt.
+    #[serde(default = "Utc::now", deserialize_with = "deserialize_published")]
     pub published: DateTime<Utc>,
 
+    /// Human-readable URL; link objects and arrays are ignored on input.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_link_url",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub url: Option<Url>,
+
+    /// Title of non-note types such as `Article`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub to: Option<Vec<Url>>,
 
@@ -87,6 +121,31 @@ pub struct ApNote {
     pub misskey_reaction: Option<String>,
 }
 
+/// Deserialize `published`, falling back to now if it is not a valid timestamp.
+fn deserialize_published<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value
+        .as_ref()
+        .and_then(serde_json::Value::as_str)
+        .and_then(|published| DateTime::parse_from_rfc3339(published).ok())
+        .map_or_else(Utc::now, |published| published.with_timezone(&Utc)))
+}
+
+/// Deserialize a `url` given as a plain string, ignoring other forms.
+fn deserialize_link_url<'de, D>(deserializer: D) -> Result<Option<Url>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value
+        .as_ref()
+        .and_then(serde_json::Value::as_str)
+        .and_then(|url| Url::parse(url).ok()))
+}
+
 /// `ActivityPub` poll option (for Question objects).
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -122,7 +181,7 @@ impl ApPollOption {
     }
 }
 
-/// `ActivityPub` tag (mention or hashtag).
+/// `ActivityPub` tag (mention, hashtag, or custom emoji).
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ApTag {
@@ -132,6 +191,25 @@ pub struct ApTag {
     pub href: Option<Url>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// Emoji image (only present on `Emoji` tags).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<EmojiIcon>,
+}
+
+impl ApTag {
+    /// Convert this tag into an [`EmojiTag`] if it describes a custom emoji.
+    #[must_use]
+    pub fn as_emoji(&self) -> Option<EmojiTag> {
+        if self.kind != "Emoji" {
+            return None;
+        }
+
+        Some(EmojiTag {
+            kind: self.kind.clone(),
+            name: self.name.clone()?,
+            icon: self.icon.clone(),
+        })
+    }
 }
 
 /// `ActivityPub` attachment (file).
@@ -168,6 +246,8 @@ impl ApNote {
             attributed_to,
             content,
             published,
+            url: None,
+            name: None,
             to: None,
             cc: None,
             in_reply_to: None,
@@ -211,6 +291,8 @@ impl ApNote {
             attributed_to,
             content,
             published,
+            url: None,
+            name: None,
             to: None,
             cc: None,
             in_reply_to: None,
@@ -316,6 +398,25 @@ mod tests {
         assert_eq!(note.content, "Hello, world!");
     }
 
+    #[test]
+    fn test_missing_or_invalid_published_falls_back_to_now() {
+        let before = Utc::now();
+        for published in [None, Some("yesterday")] {
+            let mut json = serde_json::json!({
+                "type": "Note",
+                "id": "https://example.com/notes/123",
+                "attributedTo": "https://example.com/users/alice",
+                "content": "Hello"
+            });
+            if let Some(published) = published {
+                json["published"] = published.into();
+            }
+
+            let note: ApNote = serde_json::from_value(json).unwrap();
+            assert!(note.published >= before);
+        }
+    }
+
     #[test]
     fn test_fep_c16b_quote_url() {
         let note = ApNote::new(
@@ -416,6 +517,7 @@ mod tests {
             kind: "Hashtag".to_string(),
             href: Some(test_url("/tags/rust")),
             name: Some("#rust".to_string()),
+            icon: None,
         };
 
         let json = serde_json::to_string(&tag).unwrap();
@@ -429,6 +531,7 @@ mod tests {
             kind: "Mention".to_string(),
             href: Some(test_url("/users/alice")),
             name: Some("@alice".to_string()),
+            icon: None,
         };
 
         let json = serde_json::to_string(&mention).unwrap();
@@ -0,0 +1,171 @@
+//! Instance-level federation policy.
+//!
+//! Decides which remote hosts this instance federates with, based on the
+//! configured [`FederationMode`] and the host list from the instance settings.
+
+use misskey_common::{AppResult, FederationMode};
+use misskey_db::repositories::MetaSettingsRepository;
+use tracing::debug;
+use url::Url;
+
+/// Federation policy shared by the inbox and delivery paths.
+#[derive(Clone)]
+pub struct FederationPolicy {
+    mode: FederationMode,
+    meta_settings_repo: Option<MetaSettingsRepository>,
+}
+
+impl FederationPolicy {
+    /// Create a policy that federates with every host.
+    #[must_use]
+    pub const fn open() -> Self {
+        Self {
+            mode: FederationMode::Open,
+            meta_settings_repo: None,
+        }
+    }
+
+    /// Create a policy for `mode`, reading the host list from the instance settings.
+    #[must_use]
+    pub const fn new(mode: FederationMode, meta_settings_repo: MetaSettingsRepository) -> Self {
+        Self {
+            mode,
+            meta_settings_repo: Some(meta_settings_repo),
+        }
+    }
+
+    /// Get the federation mode.
+    #[must_use]
+    pub const fn mode(&self) -> FederationMode {
+        self.mode
+    }
+
+    /// Check whether federation with `host` is allowed.
+    ///
+    /// In open mode this never touches the database.
+    pub async fn is_allowed(&self, host: &str) -> AppResult<bool> {
+        if self.mode == FederationMode::Open {
+            return Ok(true);
+        }
+
+        let hosts = match &self.meta_settings_repo {
+            Some(repo) => repo.federation_hosts().await?,
+            None => Vec::new(),
+        };
+
+        let allowed = self.mode.allows(host, &hosts);
+        if !allowed {
+            debug!(host = %host, mode = ?self.mode, "Host not permitted by federation policy");
+        }
+        Ok(allowed)
+    }
+
+    /// Check whether federation with the host of `url` is allowed.
+    ///
+    /// URLs without a host are never allowed outside of open mode.
+    pub async fn is_url_allowed(&self, url: &Url) -> AppResult<bool> {
+        match url.host_str() {
+            Some(host) => self.is_allowed(host).await,
+            None => Ok(self.mode == FederationMode::Open),
+        }
+    }
+}
+
+impl Default for FederationPolicy {
+    fn default() -> Self {
+        Self::open()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use misskey_db::entities::{meta_settings, note};
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn settings_with_hosts(hosts: &[&str]) -> meta_settings::Model {
+        meta_settings::Model {
+            id: "instance".to_string(),
+            name: None,
+            short_name: None,
+            description: None,
+            maintainer_name: None,
+            maintainer_email: None,
+            langs: json!(["en"]),
+            icon_url: None,
+            banner_url: None,
+            theme_color: None,
+            disable_registration: false,
+            email_required_for_signup: false,
+            require_registration_approval: false,
+            force_nsfw_media: false,
+            default_blur_nsfw: true,
+            default_hide_ads: false,
+            max_note_text_length: 3000,
+            max_remote_note_text_length: 10000,
+            max_page_content_length: 65536,
+            max_pages_per_user: 100,
+            default_drive_capacity_mb: 1024,
+            max_file_size_mb: 256,
+            bubble_instances: None,
+            federation_hosts: Some(json!(hosts)),
+            default_note_visibility: note::Visibility::Public,
+            default_is_locked: false,
+            default_receive_dm_from_followers_only: false,
+            default_theme: None,
+            created_at: Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn policy(mode: FederationMode, hosts: &[&str]) -> FederationPolicy {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[settings_with_hosts(hosts)]])
+            .into_connection();
+        FederationPolicy::new(mode, MetaSettingsRepository::new(Arc::new(db)))
+    }
+
+    #[tokio::test]
+    async fn test_open_mode_allows_without_query() {
+        let policy = FederationPolicy::open();
+        assert!(policy.is_allowed("anywhere.example").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_rejects_unlisted_host() {
+        let policy = policy(FederationMode::Allowlist, &["friends.example"]);
+        assert!(!policy.is_allowed("stranger.example").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_accepts_listed_host() {
+        let policy = policy(FederationMode::Allowlist, &["friends.example"]);
+        assert!(policy.is_allowed("Friends.Example").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_host_list_is_cached_until_invalidated() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[settings_with_hosts(&["friends.example"])]])
+            .append_query_results([[settings_with_hosts(&["stranger.example"])]])
+            .into_connection();
+        let repo = MetaSettingsRepository::new(Arc::new(db));
+        let policy = FederationPolicy::new(FederationMode::Allowlist, repo.clone());
+
+        assert!(policy.is_allowed("friends.example").await.unwrap());
+        assert!(!policy.is_allowed("stranger.example").await.unwrap());
+
+        repo.invalidate_federation_hosts();
+        assert!(policy.is_allowed("stranger.example").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_blocklist_rejects_listed_host() {
+        let policy = policy(FederationMode::Blocklist, &["spam.example"]);
+        assert!(!policy.is_allowed("spam.example").await.unwrap());
+    }
+}
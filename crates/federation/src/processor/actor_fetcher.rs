@@ -5,7 +5,7 @@ use misskey_common::{AppError, AppResult, IdGenerator};
 use misskey_db::{entities::user, repositories::UserRepository};
 use sea_orm::Set;
 use serde_json::Value;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 use url::Url;
 
 use crate::client::ApClient;
@@ -50,6 +50,47 @@ impl ActorFetcher {
             .await
     }
 
+    /// Find an existing remote actor or store one that was already fetched.
+    pub async fn find_or_create(&self, actor: &Value, actor_url: &Url) -> AppResult<user::Model> {
+        if let Some(user) = self.user_repo.find_by_uri(actor_url.as_str()).await? {
+            debug!(actor_url = %actor_url, "Found existing remote actor");
+            return Ok(user);
+        }
+
+        self.create_remote_user_from_actor(actor, actor_url).await
+    }
+
+    /// The followers collection of a remote actor.
+    ///
+    /// Actors stored before the collection was recorded are fetched again
+    /// and updated; `None` if that fails or the actor publishes none.
+    pub async fn followers_uri(&self, actor: &user::Model) -> AppResult<Option<String>> {
+        if actor.followers_uri.is_some() || actor.host.is_none() {
+            return Ok(actor.followers_uri.clone());
+        }
+        let Some(ref actor_url) = actor.uri else {
+            return Ok(None);
+        };
+
+        let actor_json = match self.ap_client.fetch_actor(actor_url).await {
+            Ok(actor_json) => actor_json,
+            Err(e) => {
+                warn!(actor_url = %actor_url, error = %e, "Failed to fetch followers collection");
+                return Ok(None);
+            }
+        };
+        let Some(followers) = followers_of(&actor_json) else {
+            return Ok(None);
+        };
+
+        let mut active: user::ActiveModel = actor.clone().into();
+        active.followers_uri = Set(Some(followers.clone()));
+        active.last_fetched_at = Set(Some(Utc::now().into()));
+        self.user_repo.update(active).await?;
+
+        Ok(Some(followers))
+    }
+
     /// Create a remote user from an `ActivityPub` actor JSON.
     async fn create_remote_user_from_actor(
         &self,
@@ -132,6 +173,8 @@ impl ActorFetcher {
             .and_then(Value::as_str)
             .map(String::from);
 
+        let followers = followers_of(actor);
+
         // Check if a user with the same username@host already exists
         if let Some(existing) = self
             .user_repo
@@ -148,6 +191,7 @@ impl ActorFetcher {
             active.uri = Set(Some(actor_url.to_string()));
             active.inbox = Set(inbox);
             active.shared_inbox = Set(shared_inbox);
+            active.followers_uri = Set(followers);
             active.last_fetched_at = Set(Some(Utc::now().into()));
             active.updated_at = Set(Some(Utc::now().into()));
             return self.user_repo.update(active).await;
@@ -172,6 +216,7 @@ impl ActorFetcher {
             inbox: Set(inbox),
             shared_inbox: Set(shared_inbox),
             featured: Set(featured),
+            followers_uri: Set(followers),
             uri: Set(Some(actor_url.to_string())),
             last_fetched_at: Set(Some(Utc::now().into())),
             created_at: Set(Utc::now().into()),
@@ -190,3 +235,11 @@ impl ActorFetcher {
         Ok(user)
     }
 }
+
+/// The `followers` collection URL of an actor document.
+fn followers_of(actor: &Value) -> Option<String> {
+    actor
+        .get("followers")
+        .and_then(Value::as_str)
+        .map(String::from)
+}
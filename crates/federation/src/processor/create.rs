@@ -1,34 +1,50 @@
 //! Create activity processor.
 
-use misskey_common::{AppResult, IdGenerator};
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use misskey_common::{AppError, AppResult, IdGenerator, normalize_hashtag};
 use misskey_db::{
     entities::{drive_file, note, user},
-    repositories::{DriveFileRepository, NoteRepository, UserRepository},
+    repositories::{
+        DriveFileRepository, EmojiRepository, NoteRepository, UserProfileRepository, UserRepository,
+    },
 };
 use sea_orm::Set;
 use serde_json::json;
 use tracing::{info, warn};
+use url::Url;
 
-use super::ActorFetcher;
+use super::{ActorFetcher, EmojiImporter};
 use crate::{
     CreateActivity,
-    client::ApClient,
-    objects::{ApAttachment, ApNote},
+    client::{ApClient, ObjectFetcher},
+    objects::{ApAttachment, ApNote, ApTag},
+    policy::FederationPolicy,
 };
 
+/// Maximum number of missing ancestors fetched to link a reply into its thread.
+const MAX_ANCESTOR_FETCH: usize = 16;
+
 /// Processor for Create activities (notes).
 #[derive(Clone)]
 pub struct CreateProcessor {
     note_repo: NoteRepository,
     drive_file_repo: DriveFileRepository,
     actor_fetcher: ActorFetcher,
+    object_fetcher: Arc<dyn ObjectFetcher>,
+    federation_policy: FederationPolicy,
+    emoji_importer: Option<EmojiImporter>,
+    profile_repo: Option<UserProfileRepository>,
+    min_account_age: Option<Duration>,
+    base_url: Option<Url>,
     id_gen: IdGenerator,
 }
 
 impl CreateProcessor {
     /// Create a new create processor.
     #[must_use]
-    pub const fn new(
+    pub fn new(
         note_repo: NoteRepository,
         drive_file_repo: DriveFileRepository,
         user_repo: UserRepository,
@@ -37,34 +53,182 @@ impl CreateProcessor {
         Self {
             note_repo,
             drive_file_repo,
-            actor_fetcher: ActorFetcher::new(user_repo, ap_client),
+            actor_fetcher: ActorFetcher::new(user_repo, ap_client.clone()),
+            object_fetcher: Arc::new(ap_client),
+            federation_policy: FederationPolicy::open(),
+            emoji_importer: None,
+            profile_repo: None,
+            min_account_age: None,
+            base_url: None,
             id_gen: IdGenerator::new(),
         }
     }
 
+    /// Enable importing of custom emojis referenced by incoming notes.
+    #[must_use]
+    pub fn with_emoji_repo(mut self, emoji_repo: EmojiRepository) -> Self {
+        self.emoji_importer = Some(EmojiImporter::new(emoji_repo));
+        self
+    }
+
+    /// Downgrade quotes of local users who disallow quotes to plain links.
+    #[must_use]
+    pub fn with_profile_repo(mut self, profile_repo: UserProfileRepository) -> Self {
+        self.profile_repo = Some(profile_repo);
+        self
+    }
+
+    /// Resolve references to notes on this server (`base_url`) by their ID.
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        self.base_url = Some(base_url);
+        self
+    }
+
+    /// Refuse to fetch reply ancestors from hosts the federation policy does not allow.
+    #[must_use]
+    pub fn with_federation_policy(mut self, policy: FederationPolicy) -> Self {
+        self.federation_policy = policy;
+        self
+    }
+
+    /// Fetch objects referenced by incoming notes through `fetcher`.
+    #[must_use]
+    pub fn with_object_fetcher(mut self, fetcher: Arc<dyn ObjectFetcher>) -> Self {
+        self.object_fetcher = fetcher;
+        self
+    }
+
+    /// Restrict notes of remote accounts first seen less than `min_age` ago:
+    /// public notes are stored as home-only and mentions and direct
+    /// recipients are dropped. A zero duration disables the check.
+    #[must_use]
+    pub fn with_min_account_age(mut self, min_age: std::time::Duration) -> Self {
+        self.min_account_age = Duration::from_std(min_age)
+            .ok()
+            .filter(|min_age| *min_age > Duration::zero());
+        self
+    }
+
+    /// Whether `url` points to this server.
+    fn is_local_url(&self, url: &Url) -> bool {
+        self.base_url
+            .as_ref()
+            .is_some_and(|base_url| url.origin() == base_url.origin())
+    }
+
+    /// The ID of the local note at `url`, if `url` points to this server.
+    fn local_note_id<'a>(&self, url: &'a Url) -> Option<&'a str> {
+        if !self.is_local_url(url) {
+            return None;
+        }
+        url.path()
+            .strip_prefix("/notes/")
+            .filter(|id| !id.is_empty() && !id.contains('/'))
+    }
+
+    /// Find the local note at `url` by its ID.
+    async fn find_local_note(&self, url: &Url) -> AppResult<Option<note::Model>> {
+        let Some(id) = self.local_note_id(url) else {
+            return Ok(None);
+        };
+        Ok(self
+            .note_repo
+            .find_by_id(id)
+            .await?
+            .filter(|n| n.user_host.is_none()))
+    }
+
+    /// Whether `author` is a remote account too new to reach local users.
+    fn is_restricted(&self, author: &user::Model) -> bool {
+        let Some(min_age) = self.min_account_age else {
+            return false;
+        };
+        author.host.is_some() && Utc::now() - author.created_at.with_timezone(&Utc) < min_age
+    }
+
     /// Process an incoming Create activity (Note).
     pub async fn process(&self, activity: &CreateActivity) -> AppResult<note::Model> {
+        self.process_for(activity, &[]).await
+    }
+
+    /// Process an incoming Create activity addressed to `local_recipients`,
+    /// the local users a direct note is shown to.
+    pub async fn process_for(
+        &self,
+        activity: &CreateActivity,
+        local_recipients: &[String],
+    ) -> AppResult<note::Model> {
         info!(
             actor = %activity.actor,
             note_id = %activity.object.id,
+            recipients = local_recipients.len(),
             "Processing Create activity"
         );
 
+        self.store_note(&activity.object, &activity.actor, local_recipients)
+            .await
+    }
+
+    /// Store a Note object fetched directly rather than delivered in an
+    /// activity, e.g. one resolved by URL. The author is `attributedTo`,
+    /// which must be on the note's own host.
+    pub async fn ingest_note(&self, ap_note: &ApNote) -> AppResult<note::Model> {
+        info!(note_id = %ap_note.id, "Ingesting fetched note");
+
+        if !is_attributed_on_own_host(ap_note) {
+            return Err(AppError::BadRequest(
+                "Note is attributed to an actor on another host".to_string(),
+            ));
+        }
+
+        self.store_note(ap_note, &ap_note.attributed_to, &[]).await
+    }
+
+    /// Store a remote note unless it is already known.
+    async fn store_note(
+        &self,
+        ap_note: &ApNote,
+        actor_url: &Url,
+        local_recipients: &[String],
+    ) -> AppResult<note::Model> {
         // Check if we already have this note
-        if let Some(existing) = self
-            .note_repo
-            .find_by_uri(activity.object.id.as_str())
-            .await?
-        {
+        if let Some(existing) = self.note_repo.find_by_uri(ap_note.id.as_str()).await? {
             info!(note_id = %existing.id, "Note already exists");
             return Ok(existing);
         }
 
+        if !ap_note.kind.is_native() {
+            info!(
+                object_type = ?ap_note.kind,
+                object_id = %ap_note.id,
+                "Storing unsupported object type as a note"
+            );
+        }
+
+        let parent = self.resolve_parent(ap_note).await?;
+        self.store_new_note(ap_note, actor_url, parent.as_ref(), local_recipients)
+            .await
+    }
+
+    /// Store a note that is not known yet as a reply to `parent`.
+    async fn store_new_note(
+        &self,
+        ap_note: &ApNote,
+        actor_url: &Url,
+        parent: Option<&note::Model>,
+        local_recipients: &[String],
+    ) -> AppResult<note::Model> {
         // Find or fetch the author
-        let author = self.find_or_fetch_author(&activity.actor).await?;
+        let author = self.find_or_fetch_author(actor_url).await?;
 
         // Convert ActivityPub Note to local note
-        let note = self.create_note_from_ap(&activity.object, &author).await?;
+        let note = self
+            .create_note_from_ap(ap_note, &author, parent, local_recipients)
+            .await?;
+
+        // Cache custom emojis used by the note so they render locally
+        self.import_emojis(ap_note, &author).await;
 
         info!(
             note_id = %note.id,
@@ -75,37 +239,188 @@ impl CreateProcessor {
         Ok(note)
     }
 
+    /// Find the note `ap_note` replies to, fetching missing ancestors.
+    ///
+    /// Unknown ancestors are fetched up the `inReplyTo` chain until a known
+    /// note or the thread root is reached (at most [`MAX_ANCESTOR_FETCH`]),
+    /// then stored oldest first so every note links to its thread root.
+    /// Ancestors on this server are looked up by ID and never fetched.
+    async fn resolve_parent(&self, ap_note: &ApNote) -> AppResult<Option<note::Model>> {
+        let Some(ref reply_url) = ap_note.in_reply_to else {
+            return Ok(None);
+        };
+        if let Some(parent) = self.note_repo.find_by_uri(reply_url.as_str()).await? {
+            return Ok(Some(parent));
+        }
+        if self.is_local_url(reply_url) {
+            return self.find_local_note(reply_url).await;
+        }
+
+        let mut ancestors = Vec::new();
+        let mut known = None;
+        let mut next = Some(reply_url.clone());
+        while let Some(url) = next.take() {
+            if ancestors.len() >= MAX_ANCESTOR_FETCH {
+                break;
+            }
+            if self.is_local_url(&url) {
+                known = self.find_local_note(&url).await?;
+                break;
+            }
+            if !ancestors.is_empty()
+                && let Some(note) = self.note_repo.find_by_uri(url.as_str()).await?
+            {
+                known = Some(note);
+                break;
+            }
+            let Some(ancestor) = self.fetch_ancestor(&url).await else {
+                break;
+            };
+            next.clone_from(&ancestor.in_reply_to);
+            ancestors.push(ancestor);
+        }
+
+        let mut parent = known;
+        for ancestor in ancestors.iter().rev() {
+            match self
+                .store_new_note(ancestor, &ancestor.attributed_to, parent.as_ref(), &[])
+                .await
+            {
+                Ok(note) => parent = Some(note),
+                Err(e) => {
+                    warn!(note_id = %ancestor.id, error = %e, "Failed to store ancestor note");
+                    return Ok(None);
+                }
+            }
+        }
+
+        Ok(parent)
+    }
+
+    /// Fetch a note in a reply chain, trusting only its own host.
+    async fn fetch_ancestor(&self, url: &Url) -> Option<ApNote> {
+        match self.federation_policy.is_url_allowed(url).await {
+            Ok(true) => {}
+            Ok(false) => {
+                info!(url = %url, "Federation policy blocks ancestor note host");
+                return None;
+            }
+            Err(e) => {
+                warn!(url = %url, error = %e, "Failed to check federation policy");
+                return None;
+            }
+        }
+
+        let object = match self.object_fetcher.fetch_object(url).await {
+            Ok(object) => object,
+            Err(e) => {
+                warn!(url = %url, error = %e, "Failed to fetch ancestor note");
+                return None;
+            }
+        };
+
+        let ap_note: ApNote = match serde_json::from_value(object) {
+            Ok(ap_note) => ap_note,
+            Err(e) => {
+                warn!(url = %url, error = %e, "Invalid ancestor note");
+                return None;
+            }
+        };
+        if ap_note.id.host_str() != url.host_str() {
+            warn!(url = %url, "Ancestor note ID does not match its host");
+            return None;
+        }
+        if !is_attributed_on_own_host(&ap_note) {
+            warn!(url = %url, "Ancestor note is attributed to an actor on another host");
+            return None;
+        }
+
+        Some(ap_note)
+    }
+
     /// Find an existing author or fetch from remote.
-    async fn find_or_fetch_author(&self, actor_url: &url::Url) -> AppResult<user::Model> {
+    async fn find_or_fetch_author(&self, actor_url: &Url) -> AppResult<user::Model> {
         self.actor_fetcher.find_or_fetch(actor_url).await
     }
 
+    /// Import custom emojis from the note's `Emoji` tags as remote emojis.
+    async fn import_emojis(&self, ap_note: &ApNote, author: &user::Model) {
+        let (Some(importer), Some(host)) = (&self.emoji_importer, &author.host) else {
+            return;
+        };
+
+        let emoji_tags: Vec<_> = ap_note
+            .tag
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(ApTag::as_emoji)
+            .collect();
+
+        if !emoji_tags.is_empty() {
+            importer.import_all(&emoji_tags, host).await;
+        }
+    }
+
     /// Create a note from an `ActivityPub` Note object.
     async fn create_note_from_ap(
         &self,
         ap_note: &ApNote,
         author: &user::Model,
+        parent: Option<&note::Model>,
+        local_recipients: &[String],
     ) -> AppResult<note::Model> {
-        // Parse reply target
-        let reply_id = if let Some(ref reply_url) = ap_note.in_reply_to {
-            // Try to find the reply target in our database
-            if let Some(reply_note) = self.note_repo.find_by_uri(reply_url.as_str()).await? {
-                Some(reply_note.id)
-            } else {
-                // Reply to unknown note - we might want to fetch it
-                // For now, we'll just skip the reply chain
-                None
+        // Replies join their parent's thread, rooted at the parent if it has none
+        let reply_id = parent.map(|p| p.id.clone());
+        let thread_id = parent.map(|p| p.thread_id.clone().unwrap_or_else(|| p.id.clone()));
+
+        // Determine visibility; direct notes are shown to their local recipients
+        let mut visibility = self.determine_visibility(ap_note);
+        let mut visible_user_ids = if visibility == note::Visibility::Specified {
+            local_recipients
+        } else {
+            &[]
+        };
+
+        // Accounts that are too new stay out of public timelines and reach no local user
+        let restricted = self.is_restricted(author);
+        if restricted {
+            info!(
+                note_id = %ap_note.id,
+                author = %author.id,
+                "Author account is too new; restricting note"
+            );
+            if visibility == note::Visibility::Public {
+                visibility = note::Visibility::Home;
             }
+            visible_user_ids = &[];
+        }
+
+        // Non-note types carry a title and abstract rather than a CW
+        let (mut text, cw) = if ap_note.kind.is_native() {
+            (strip_html_basic(&ap_note.content), ap_note.summary.clone())
         } else {
-            None
+            (best_effort_text(ap_note), None)
         };
 
-        // Determine visibility
-        let visibility = self.determine_visibility(ap_note);
+        // Link quotes of known notes; refused quotes keep only a plain link
+        let renote_id = match self.resolve_quote(ap_note).await? {
+            Some(QuoteTarget::Linked(id)) => Some(id),
+            Some(QuoteTarget::Refused(url)) => {
+                if !text.contains(url.as_str()) {
+                    text = format!("{text}\n\nRE: {url}");
+                }
+                None
+            }
+            None => None,
+        };
 
-        // Extract mentions and tags from content
-        let content = &ap_note.content;
-        let mentions = self.extract_mentions_from_tags(ap_note);
+        // Extract mentions and tags
+        let mentions = if restricted {
+            Vec::new()
+        } else {
+            self.extract_mentions_from_tags(ap_note)
+        };
         let tags = self.extract_hashtags_from_tags(ap_note);
 
         // Process attachments
@@ -113,32 +428,81 @@ impl CreateProcessor {
             .process_attachments(&author.id, ap_note.attachment.as_deref())
             .await;
 
-        let note_id = self.id_gen.generate();
+        // Keep the remote creation time so the note sorts where it was posted;
+        // future timestamps would pin it above newer notes, so they are clamped
+        let created_at = ap_note.published.min(Utc::now());
+        let note_id = self.id_gen.generate_at(created_at);
 
         let model = note::ActiveModel {
             id: Set(note_id),
             user_id: Set(author.id.clone()),
             user_host: Set(author.host.clone()),
-            text: Set(Some(strip_html_basic(content))),
-            cw: Set(ap_note.summary.clone()),
+            text: Set(Some(text)),
+            cw: Set(cw),
             visibility: Set(visibility),
-            reply_id: Set(reply_id.clone()),
-            renote_id: Set(None),
-            thread_id: Set(reply_id),
+            reply_id: Set(reply_id),
+            renote_id: Set(renote_id),
+            thread_id: Set(thread_id),
             mentions: Set(json!(mentions)),
-            visible_user_ids: Set(json!([])),
+            visible_user_ids: Set(json!(visible_user_ids)),
             file_ids: Set(json!(file_ids)),
             tags: Set(json!(tags)),
             reactions: Set(json!({})),
             is_local: Set(false),
             uri: Set(Some(ap_note.id.to_string())),
-            url: Set(None), // Note URL not available in current schema
+            url: Set(ap_note.url.as_ref().map(ToString::to_string)),
+            created_at: Set(created_at.into()),
             ..Default::default()
         };
 
         self.note_repo.create(model).await
     }
 
+    /// Resolve the note quoted via `quoteUrl`/`_misskey_quote`, if known.
+    async fn resolve_quote(&self, ap_note: &ApNote) -> AppResult<Option<QuoteTarget>> {
+        let Some(quote_url) = ap_note.quote_url() else {
+            return Ok(None);
+        };
+
+        let quoted = match self.note_repo.find_by_uri(quote_url.as_str()).await? {
+            Some(note) => Some(note),
+            None => self.find_local_note(quote_url).await?,
+        };
+        let Some(quoted) = quoted else {
+            return Ok(None);
+        };
+
+        // Only notes anyone may see can be embedded in a remote quote
+        if !matches!(
+            quoted.visibility,
+            note::Visibility::Public | note::Visibility::Home
+        ) {
+            info!(
+                note_id = %ap_note.id,
+                quoted = %quoted.id,
+                "Quoted note is not public; ignoring quote"
+            );
+            return Ok(None);
+        }
+
+        if quoted.user_host.is_none()
+            && let Some(profile_repo) = &self.profile_repo
+            && profile_repo
+                .find_by_user_id(&quoted.user_id)
+                .await?
+                .is_some_and(|p| p.disallow_quotes)
+        {
+            info!(
+                note_id = %ap_note.id,
+                quoted = %quoted.id,
+                "Quoted author disallows quotes; storing as a plain link"
+            );
+            return Ok(Some(QuoteTarget::Refused(quote_url.clone())));
+        }
+
+        Ok(Some(QuoteTarget::Linked(quoted.id)))
+    }
+
     /// Determine visibility from `ActivityPub` addressing.
     fn determine_visibility(&self, ap_note: &ApNote) -> note::Visibility {
         let public = "https://www.w3.org/ns/activitystreams#Public";
@@ -190,7 +554,7 @@ impl CreateProcessor {
                 tags.iter()
                     .filter(|tag| tag.kind == "Hashtag")
                     .filter_map(|tag| tag.name.clone())
-                    .map(|name| name.trim_start_matches('#').to_lowercase())
+                    .map(|name| normalize_hashtag(&name))
                     .collect()
             })
             .unwrap_or_default()
@@ -210,8 +574,11 @@ impl CreateProcessor {
         let mut file_ids = Vec::new();
 
         for attachment in attachments {
-            // Only process Document type attachments (media files)
-            if attachment.kind != "Document" {
+            // Only process media file attachments
+            if !matches!(
+                attachment.kind.as_str(),
+                "Document" | "Image" | "Video" | "Audio"
+            ) {
                 continue;
             }
 
@@ -275,6 +642,29 @@ impl CreateProcessor {
     }
 }
 
+/// Outcome of resolving an inbound quote.
+enum QuoteTarget {
+    /// The quote is stored as a renote of this note.
+    Linked(String),
+    /// The quoted author disallows quotes; only the URL is kept.
+    Refused(Url),
+}
+
+/// Build note text for a non-note object (e.g. `Article`): its title, its
+/// content (or summary when there is none) and a link to the original.
+fn best_effort_text(ap_note: &ApNote) -> String {
+    let body = Some(strip_html_basic(&ap_note.content))
+        .filter(|body| !body.is_empty())
+        .or_else(|| ap_note.summary.as_deref().map(strip_html_basic));
+
+    [ap_note.name.clone(), body, Some(ap_note.id.to_string())]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
 /// Basic HTML stripping (for converting `ActivityPub` content to plain text).
 /// A more robust solution would use an HTML parser.
 fn strip_html_basic(html: &str) -> String {
@@ -310,9 +700,41 @@ fn strip_html_basic(html: &str) -> String {
         .to_string()
 }
 
+/// Whether the note's author is on the host that serves the note, so a
+/// server cannot plant notes under another server's actors.
+fn is_attributed_on_own_host(ap_note: &ApNote) -> bool {
+    ap_note.attributed_to.host_str() == ap_note.id.host_str()
+}
+
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
+    use crate::processor::test_support::{StubFetcher, remote_author, stored_note};
+    use misskey_db::entities::{emoji, user_profile};
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use std::sync::Arc;
+
+    fn stored_emoji() -> emoji::Model {
+        emoji::Model {
+            id: "emoji1".to_string(),
+            name: "blobcat".to_string(),
+            category: None,
+            original_url: "https://remote.example/emoji/blobcat.png".to_string(),
+            static_url: None,
+            content_type: "image/png".to_string(),
+            aliases: json!([]),
+            host: Some("remote.example".to_string()),
+            license: None,
+            is_sensitive: false,
+            local_only: false,
+            width: None,
+            height: None,
+            size: None,
+            created_at: chrono::Utc::now(),
+            updated_at: None,
+        }
+    }
 
     #[test]
     fn test_strip_html_basic() {
@@ -321,4 +743,802 @@ mod tests {
         assert_eq!(strip_html_basic("a &amp; b"), "a & b");
         assert_eq!(strip_html_basic("line1<br>line2"), "line1\nline2");
     }
+
+    #[tokio::test]
+    async fn test_ingested_note_keeps_published_time() {
+        let ap_note: ApNote = serde_json::from_value(json!({
+            "type": "Note",
+            "id": "https://remote.example/notes/1",
+            "attributedTo": "https://remote.example/users/alice",
+            "content": "<p>Posted a while ago</p>",
+            "published": "2024-06-01T12:00:00Z",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"]
+        }))
+        .unwrap();
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([[stored_note()]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_author()]])
+                .into_connection(),
+        );
+        let drive_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let processor = CreateProcessor::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            DriveFileRepository::new(drive_db),
+            UserRepository::new(user_db),
+            ApClient::new("https://local.example"),
+        );
+        processor.ingest_note(&ap_note).await.unwrap();
+        drop(processor);
+
+        let log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        let insert = log
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .find(|stmt| stmt.sql.starts_with(r#"INSERT INTO "note""#))
+            .unwrap();
+        let values = format!("{:?}", insert.values);
+        assert!(values.contains("2024-06-01T12:00:00"));
+
+        // The id sorts by the published time too
+        let expected_id_prefix = &IdGenerator::new().generate_at(ap_note.published)[..10];
+        assert!(values.contains(&format!("\"{expected_id_prefix}")));
+    }
+
+    #[tokio::test]
+    async fn test_new_account_public_note_is_held_back() {
+        let activity: CreateActivity = serde_json::from_value(json!({
+            "type": "Create",
+            "id": "https://remote.example/activities/1",
+            "actor": "https://remote.example/users/alice",
+            "published": "2025-01-01T00:00:00Z",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"],
+            "object": {
+                "type": "Note",
+                "id": "https://remote.example/notes/1",
+                "attributedTo": "https://remote.example/users/alice",
+                "content": "<p>@bob buy now</p>",
+                "published": "2025-01-01T00:00:00Z",
+                "to": ["https://www.w3.org/ns/activitystreams#Public"],
+                "tag": [{
+                    "type": "Mention",
+                    "href": "https://local.example/users/bob",
+                    "name": "@bob@local.example"
+                }]
+            }
+        }))
+        .unwrap();
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([[stored_note()]])
+                .into_connection(),
+        );
+        // The author was first seen just now
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_author()]])
+                .into_connection(),
+        );
+        let drive_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let processor = CreateProcessor::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            DriveFileRepository::new(drive_db),
+            UserRepository::new(user_db),
+            ApClient::new("https://local.example"),
+        )
+        .with_min_account_age(std::time::Duration::from_secs(86400));
+        processor.process(&activity).await.unwrap();
+        drop(processor);
+
+        let log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        let insert = log
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .find(|stmt| stmt.sql.starts_with(r#"INSERT INTO "note""#))
+            .unwrap();
+        let values = format!("{:?}", insert.values);
+        assert!(values.contains("\"home\""));
+        assert!(!values.contains("\"public\""));
+        assert!(!values.contains("bob@local.example"));
+    }
+
+    #[tokio::test]
+    async fn test_reply_to_remote_note_joins_parent_thread() {
+        let ap_note: ApNote = serde_json::from_value(json!({
+            "type": "Note",
+            "id": "https://remote.example/notes/3",
+            "attributedTo": "https://remote.example/users/alice",
+            "content": "<p>Replying deeper</p>",
+            "inReplyTo": "https://other.example/notes/2",
+            "published": "2024-06-01T12:00:00Z",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"]
+        }))
+        .unwrap();
+
+        // The parent is itself a reply in the thread rooted at `root1`
+        let mut parent = stored_note();
+        parent.id = "parent1".to_string();
+        parent.uri = Some("https://other.example/notes/2".to_string());
+        parent.reply_id = Some("root1".to_string());
+        parent.thread_id = Some("root1".to_string());
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([[parent]])
+                .append_query_results([[stored_note()]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_author()]])
+                .into_connection(),
+        );
+        let drive_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let processor = CreateProcessor::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            DriveFileRepository::new(drive_db),
+            UserRepository::new(user_db),
+            ApClient::new("https://local.example"),
+        );
+        processor.ingest_note(&ap_note).await.unwrap();
+        drop(processor);
+
+        let log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        let insert = log
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .find(|stmt| stmt.sql.starts_with(r#"INSERT INTO "note""#))
+            .unwrap();
+        let values = format!("{:?}", insert.values);
+        assert!(values.contains("\"parent1\""));
+        assert!(values.contains("\"root1\""));
+    }
+
+    fn inserted_notes(note_db: Arc<sea_orm::DatabaseConnection>) -> Vec<String> {
+        Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log()
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .filter(|stmt| stmt.sql.starts_with(r#"INSERT INTO "note""#))
+            .map(|stmt| format!("{:?}", stmt.values))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_reply_to_unknown_note_fetches_missing_ancestor() {
+        let ap_note: ApNote = serde_json::from_value(json!({
+            "type": "Note",
+            "id": "https://remote.example/notes/3",
+            "attributedTo": "https://remote.example/users/alice",
+            "content": "<p>Replying</p>",
+            "inReplyTo": "https://other.example/notes/2",
+            "published": "2024-06-01T12:00:00Z",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"]
+        }))
+        .unwrap();
+        let fetcher = Arc::new(StubFetcher {
+            objects: vec![json!({
+                "type": "Note",
+                "id": "https://other.example/notes/2",
+                "attributedTo": "https://other.example/users/bob",
+                "content": "<p>Thread root</p>",
+                "published": "2024-06-01T11:00:00Z",
+                "to": ["https://www.w3.org/ns/activitystreams#Public"]
+            })],
+            ..Default::default()
+        });
+
+        let mut parent = stored_note();
+        parent.id = "parent1".to_string();
+        parent.uri = Some("https://other.example/notes/2".to_string());
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([[parent]])
+                .append_query_results([[stored_note()]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_author()]])
+                .append_query_results([[remote_author()]])
+                .into_connection(),
+        );
+        let drive_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let processor = CreateProcessor::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            DriveFileRepository::new(drive_db),
+            UserRepository::new(user_db),
+            ApClient::new("https://local.example"),
+        )
+        .with_object_fetcher(fetcher.clone());
+        processor.ingest_note(&ap_note).await.unwrap();
+        drop(processor);
+
+        assert_eq!(
+            *fetcher.fetched.lock().unwrap(),
+            ["https://other.example/notes/2"]
+        );
+        let inserts = inserted_notes(note_db);
+        assert_eq!(inserts.len(), 2);
+        assert!(inserts[0].contains("\"https://other.example/notes/2\""));
+        assert!(inserts[1].contains("\"parent1\""));
+    }
+
+    #[tokio::test]
+    async fn test_reply_to_ancestor_attributed_to_another_host_drops_the_ancestor() {
+        let ap_note: ApNote = serde_json::from_value(json!({
+            "type": "Note",
+            "id": "https://remote.example/notes/3",
+            "attributedTo": "https://remote.example/users/alice",
+            "content": "<p>Replying</p>",
+            "inReplyTo": "https://evil.example/notes/2",
+            "published": "2024-06-01T12:00:00Z",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"]
+        }))
+        .unwrap();
+        let fetcher = Arc::new(StubFetcher {
+            objects: vec![json!({
+                "type": "Note",
+                "id": "https://evil.example/notes/2",
+                "attributedTo": "https://victim.example/users/bob",
+                "content": "<p>Spoofed</p>",
+                "published": "2024-06-01T11:00:00Z",
+                "to": ["https://www.w3.org/ns/activitystreams#Public"]
+            })],
+            ..Default::default()
+        });
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([[stored_note()]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_author()]])
+                .into_connection(),
+        );
+        let drive_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let processor = CreateProcessor::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            DriveFileRepository::new(drive_db),
+            UserRepository::new(user_db),
+            ApClient::new("https://local.example"),
+        )
+        .with_object_fetcher(fetcher);
+        processor.ingest_note(&ap_note).await.unwrap();
+        drop(processor);
+
+        // Only the reply is stored, without a parent
+        let inserts = inserted_notes(note_db);
+        assert_eq!(inserts.len(), 1);
+        assert!(inserts[0].contains("\"https://remote.example/notes/3\""));
+        assert!(!inserts[0].contains("evil.example"));
+    }
+
+    #[tokio::test]
+    async fn test_ingest_note_attributed_to_another_host_is_rejected() {
+        let ap_note: ApNote = serde_json::from_value(json!({
+            "type": "Note",
+            "id": "https://evil.example/notes/1",
+            "attributedTo": "https://victim.example/users/bob",
+            "content": "<p>Spoofed</p>",
+            "published": "2024-06-01T12:00:00Z",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"]
+        }))
+        .unwrap();
+
+        let note_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let user_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let drive_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let processor = CreateProcessor::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            DriveFileRepository::new(drive_db),
+            UserRepository::new(user_db),
+            ApClient::new("https://local.example"),
+        );
+        let result = processor.ingest_note(&ap_note).await;
+        drop(processor);
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        assert!(inserted_notes(note_db).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reply_to_local_note_is_resolved_by_id_without_fetching() {
+        let ap_note: ApNote = serde_json::from_value(json!({
+            "type": "Note",
+            "id": "https://remote.example/notes/3",
+            "attributedTo": "https://remote.example/users/alice",
+            "content": "<p>Replying to a local note</p>",
+            "inReplyTo": "https://local.example/notes/local1",
+            "published": "2024-06-01T12:00:00Z",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"]
+        }))
+        .unwrap();
+        let fetcher = Arc::new(StubFetcher::default());
+
+        // Local notes may have no stored URI, so only the ID lookup finds it
+        let mut local = stored_note();
+        local.id = "local1".to_string();
+        local.user_host = None;
+        local.is_local = true;
+        local.uri = None;
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([[local]])
+                .append_query_results([[stored_note()]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_author()]])
+                .into_connection(),
+        );
+        let drive_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let processor = CreateProcessor::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            DriveFileRepository::new(drive_db),
+            UserRepository::new(user_db),
+            ApClient::new("https://local.example"),
+        )
+        .with_base_url(Url::parse("https://local.example").unwrap())
+        .with_object_fetcher(fetcher.clone());
+        processor.ingest_note(&ap_note).await.unwrap();
+        drop(processor);
+
+        assert!(fetcher.fetched.lock().unwrap().is_empty());
+        let inserts = inserted_notes(note_db);
+        assert_eq!(inserts.len(), 1);
+        assert!(inserts[0].contains("\"local1\""));
+    }
+
+    #[tokio::test]
+    async fn test_process_article_is_stored_as_note() {
+        let activity: CreateActivity = serde_json::from_value(json!({
+            "type": "Create",
+            "id": "https://blog.example/activities/1",
+            "actor": "https://remote.example/users/alice",
+            "published": "2025-01-01T00:00:00Z",
+            "object": {
+                "type": "Article",
+                "id": "https://blog.example/posts/hello",
+                "attributedTo": "https://remote.example/users/alice",
+                "name": "Hello World",
+                "summary": "A first post",
+                "content": "<p>Long <b>article</b> body</p>",
+                "published": "2025-01-01T00:00:00Z",
+                "to": ["https://www.w3.org/ns/activitystreams#Public"],
+                "attachment": [{
+                    "type": "Image",
+                    "url": "https://blog.example/cover.png",
+                    "mediaType": "image/png"
+                }]
+            }
+        }))
+        .unwrap();
+        assert_eq!(activity.object.kind, crate::objects::ApObjectType::Article);
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([[stored_note()]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_author()]])
+                .into_connection(),
+        );
+        let drive_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[drive_file::Model {
+                    id: "file1".to_string(),
+                    user_id: "author1".to_string(),
+                    user_host: None,
+                    name: "cover.png".to_string(),
+                    content_type: "image/png".to_string(),
+                    size: 0,
+                    url: "https://blog.example/cover.png".to_string(),
+                    thumbnail_url: None,
+                    webpublic_url: None,
+                    blurhash: None,
+                    width: None,
+                    height: None,
+                    comment: None,
+                    is_sensitive: false,
+                    is_link: true,
+                    md5: None,
+                    storage_key: None,
+                    folder_id: None,
+                    uri: Some("https://blog.example/cover.png".to_string()),
+                    created_at: chrono::Utc::now().into(),
+                }]])
+                .into_connection(),
+        );
+
+        let processor = CreateProcessor::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            DriveFileRepository::new(Arc::clone(&drive_db)),
+            UserRepository::new(user_db),
+            ApClient::new("https://local.example"),
+        );
+
+        processor.process(&activity).await.unwrap();
+        drop(processor);
+
+        let log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        let insert = log
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .find(|stmt| stmt.sql.starts_with(r#"INSERT INTO "note""#))
+            .unwrap();
+        let values = format!("{:?}", insert.values);
+        assert!(
+            values.contains(
+                "Hello World\\n\\nLong article body\\n\\nhttps://blog.example/posts/hello"
+            )
+        );
+        // The summary is an abstract, not a content warning
+        assert!(!values.contains("A first post"));
+
+        // The image attachment was kept as a remote drive file
+        let drive_log = Arc::try_unwrap(drive_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert!(
+            drive_log
+                .iter()
+                .flat_map(sea_orm::Transaction::statements)
+                .any(|stmt| stmt.sql.starts_with(r#"INSERT INTO "drive_file""#))
+        );
+    }
+
+    #[test]
+    fn test_best_effort_text_falls_back_to_summary() {
+        let mut video = ApNote::new(
+            "https://video.example/videos/1".parse().unwrap(),
+            "https://video.example/users/bob".parse().unwrap(),
+            String::new(),
+            chrono::Utc::now(),
+        );
+        video.kind = crate::objects::ApObjectType::Video;
+        video.name = Some("My video".to_string());
+        video.summary = Some("<p>About it</p>".to_string());
+
+        assert_eq!(
+            best_effort_text(&video),
+            "My video\n\nAbout it\n\nhttps://video.example/videos/1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_process_note_with_emoji_tag_creates_remote_emoji() {
+        let activity: CreateActivity = serde_json::from_value(json!({
+            "type": "Create",
+            "id": "https://remote.example/activities/1",
+            "actor": "https://remote.example/users/alice",
+            "published": "2025-01-01T00:00:00Z",
+            "object": {
+                "type": "Note",
+                "id": "https://remote.example/notes/1",
+                "attributedTo": "https://remote.example/users/alice",
+                "content": "Hello :blobcat:",
+                "published": "2025-01-01T00:00:00Z",
+                "tag": [{
+                    "type": "Emoji",
+                    "name": ":blobcat:",
+                    "icon": {
+                        "type": "Image",
+                        "mediaType": "image/png",
+                        "url": "https://remote.example/emoji/blobcat.png"
+                    }
+                }]
+            }
+        }))
+        .unwrap();
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new()])
+                .append_query_results([[stored_note()]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_author()]])
+                .into_connection(),
+        );
+        let drive_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let emoji_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<emoji::Model>::new()])
+                .append_query_results([[stored_emoji()]])
+                .into_connection(),
+        );
+
+        let processor = CreateProcessor::new(
+            NoteRepository::new(note_db),
+            DriveFileRepository::new(drive_db),
+            UserRepository::new(user_db),
+            ApClient::new("https://local.example"),
+        )
+        .with_emoji_repo(EmojiRepository::new(Arc::clone(&emoji_db)));
+
+        let note = processor.process(&activity).await.unwrap();
+        assert_eq!(note.id, "note1");
+        drop(processor);
+
+        let log = Arc::try_unwrap(emoji_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        let insert = log
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .find(|stmt| stmt.sql.starts_with(r#"INSERT INTO "emoji""#))
+            .unwrap();
+        let values = format!("{:?}", insert.values);
+        assert!(values.contains("blobcat"));
+        assert!(values.contains("remote.example"));
+    }
+
+    #[tokio::test]
+    async fn test_quote_of_disallowing_local_user_is_downgraded_to_link() {
+        let activity: CreateActivity = serde_json::from_value(json!({
+            "type": "Create",
+            "id": "https://remote.example/activities/2",
+            "actor": "https://remote.example/users/alice",
+            "published": "2025-01-01T00:00:00Z",
+            "object": {
+                "type": "Note",
+                "id": "https://remote.example/notes/2",
+                "attributedTo": "https://remote.example/users/alice",
+                "content": "<p>Look at this</p>",
+                "published": "2025-01-01T00:00:00Z",
+                "_misskey_quote": "https://local.example/notes/local1",
+                "to": ["https://www.w3.org/ns/activitystreams#Public"]
+            }
+        }))
+        .unwrap();
+
+        let mut quoted = stored_note();
+        quoted.id = "local1".to_string();
+        quoted.user_id = "localuser".to_string();
+        quoted.user_host = None;
+        quoted.uri = None;
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new(), Vec::new()])
+                .append_query_results([[quoted], [stored_note()]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_author()]])
+                .into_connection(),
+        );
+        let profile_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[user_profile::Model {
+                    user_id: "localuser".to_string(),
+                    password: None,
+                    email: None,
+                    email_verified: false,
+                    email_undeliverable: false,
+                    two_factor_secret: None,
+                    two_factor_enabled: false,
+                    two_factor_pending: None,
+                    two_factor_backup_codes: None,
+                    auto_accept_followed: false,
+                    always_mark_nsfw: false,
+                    pinned_page_ids: json!([]),
+                    pinned_note_ids: json!([]),
+                    fields: json!([]),
+                    muted_words: json!([]),
+                    user_css: None,
+                    birthday: None,
+                    location: None,
+                    lang: None,
+                    pronouns: None,
+                    also_known_as: None,
+                    moved_to_uri: None,
+                    hide_bots: false,
+                    default_reaction: None,
+                    receive_dm_from_followers_only: false,
+                    secure_fetch_only: false,
+                    disallow_quotes: true,
+                    hide_online_status: false,
+                    default_note_visibility: note::Visibility::Public,
+                    theme: None,
+                    created_at: chrono::Utc::now().into(),
+                    updated_at: None,
+                }]])
+                .into_connection(),
+        );
+        let drive_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let processor = CreateProcessor::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            DriveFileRepository::new(drive_db),
+            UserRepository::new(user_db),
+            ApClient::new("https://local.example"),
+        )
+        .with_base_url(Url::parse("https://local.example").unwrap())
+        .with_profile_repo(UserProfileRepository::new(profile_db));
+
+        processor.process(&activity).await.unwrap();
+        drop(processor);
+
+        let log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        let insert = log
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .find(|stmt| stmt.sql.starts_with(r#"INSERT INTO "note""#))
+            .unwrap();
+        let values = format!("{:?}", insert.values);
+        assert!(values.contains("Look at this\\n\\nRE: https://local.example/notes/local1"));
+        assert!(!values.contains(r#"String(Some("local1"))"#));
+    }
+
+    #[tokio::test]
+    async fn test_quote_of_local_id_on_foreign_host_is_not_linked() {
+        let activity: CreateActivity = serde_json::from_value(json!({
+            "type": "Create",
+            "id": "https://evil.example/activities/3",
+            "actor": "https://remote.example/users/alice",
+            "published": "2025-01-01T00:00:00Z",
+            "object": {
+                "type": "Note",
+                "id": "https://remote.example/notes/3",
+                "attributedTo": "https://remote.example/users/alice",
+                "content": "<p>Leak</p>",
+                "published": "2025-01-01T00:00:00Z",
+                "quoteUrl": "https://evil.example/notes/local1",
+                "to": ["https://www.w3.org/ns/activitystreams#Public"]
+            }
+        }))
+        .unwrap();
+
+        // Note lookup, quote lookup by URI, then the insert; no lookup by ID
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new(), Vec::new()])
+                .append_query_results([[stored_note()]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_author()]])
+                .into_connection(),
+        );
+        let drive_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let processor = CreateProcessor::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            DriveFileRepository::new(drive_db),
+            UserRepository::new(user_db),
+            ApClient::new("https://local.example"),
+        )
+        .with_base_url(Url::parse("https://local.example").unwrap());
+
+        processor.process(&activity).await.unwrap();
+        drop(processor);
+
+        let log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        let insert = log
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .find(|stmt| stmt.sql.starts_with(r#"INSERT INTO "note""#))
+            .unwrap();
+        assert!(!format!("{:?}", insert.values).contains(r#"String(Some("local1"))"#));
+    }
+
+    #[tokio::test]
+    async fn test_quote_of_followers_only_local_note_is_not_linked() {
+        let activity: CreateActivity = serde_json::from_value(json!({
+            "type": "Create",
+            "id": "https://remote.example/activities/4",
+            "actor": "https://remote.example/users/alice",
+            "published": "2025-01-01T00:00:00Z",
+            "object": {
+                "type": "Note",
+                "id": "https://remote.example/notes/4",
+                "attributedTo": "https://remote.example/users/alice",
+                "content": "<p>Peek</p>",
+                "published": "2025-01-01T00:00:00Z",
+                "quoteUrl": "https://local.example/notes/local1",
+                "to": ["https://www.w3.org/ns/activitystreams#Public"]
+            }
+        }))
+        .unwrap();
+
+        let mut quoted = stored_note();
+        quoted.id = "local1".to_string();
+        quoted.user_id = "localuser".to_string();
+        quoted.user_host = None;
+        quoted.uri = None;
+        quoted.visibility = note::Visibility::Followers;
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new(), Vec::new()])
+                .append_query_results([[quoted], [stored_note()]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_author()]])
+                .into_connection(),
+        );
+        let drive_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let processor = CreateProcessor::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            DriveFileRepository::new(drive_db),
+            UserRepository::new(user_db),
+            ApClient::new("https://local.example"),
+        )
+        .with_base_url(Url::parse("https://local.example").unwrap());
+
+        processor.process(&activity).await.unwrap();
+        drop(processor);
+
+        let log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        let insert = log
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .find(|stmt| stmt.sql.starts_with(r#"INSERT INTO "note""#))
+            .unwrap();
+        assert!(!format!("{:?}", insert.values).contains(r#"String(Some("local1"))"#));
+    }
 }
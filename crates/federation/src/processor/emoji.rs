@@ -0,0 +1,138 @@
+//! Remote custom emoji import utility.
+
+use misskey_common::{AppError, AppResult, IdGenerator, reaction::DEFAULT_REACTION};
+use misskey_db::{entities::emoji, repositories::EmojiRepository};
+use sea_orm::Set;
+use serde_json::json;
+use tracing::{debug, warn};
+
+use crate::activities::EmojiTag;
+
+/// Reaction stored for a Like that does not name an emoji.
+///
+/// Matches the reaction the local API uses for favourites.
+pub const DEFAULT_LIKE_REACTION: &str = DEFAULT_REACTION;
+
+/// Utility for importing custom emojis referenced by remote activities.
+///
+/// Remote emojis are cached locally (with their origin host recorded) so that
+/// notes and reactions using them can be rendered.
+#[derive(Clone)]
+pub struct EmojiImporter {
+    emoji_repo: EmojiRepository,
+    id_gen: IdGenerator,
+}
+
+impl EmojiImporter {
+    /// Create a new emoji importer.
+    #[must_use]
+    pub const fn new(emoji_repo: EmojiRepository) -> Self {
+        Self {
+            emoji_repo,
+            id_gen: IdGenerator::new(),
+        }
+    }
+
+    /// Import all custom emojis in `tags` as remote emojis of `host`.
+    ///
+    /// Failures are logged and skipped so that a broken emoji never blocks
+    /// the activity that referenced it.
+    pub async fn import_all(&self, tags: &[EmojiTag], host: &str) -> Vec<emoji::Model> {
+        let mut imported = Vec::new();
+
+        for tag in tags {
+            match self.upsert(tag, host).await {
+                Ok(emoji) => imported.push(emoji),
+                Err(e) => {
+                    warn!(name = %tag.name, host = %host, error = %e, "Failed to import remote emoji");
+                }
+            }
+        }
+
+        imported
+    }
+
+    /// Import the custom emojis used by a reaction sent from `host`.
+    ///
+    /// Emojis are stored under the sending actor's host, which is the host
+    /// [`normalize_reaction`] records in the reaction itself.
+    pub async fn import_reaction_emojis(&self, tags: &[EmojiTag], host: &str) {
+        for tag in tags.iter().filter(|t| t.kind == "Emoji") {
+            if let Err(e) = self.upsert(tag, host).await {
+                warn!(name = %tag.name, host = %host, error = %e, "Failed to import remote emoji");
+            }
+        }
+    }
+
+    /// Insert or refresh a single remote emoji from an `Emoji` tag.
+    pub async fn upsert(&self, tag: &EmojiTag, host: &str) -> AppResult<emoji::Model> {
+        let icon = tag
+            .icon
+            .as_ref()
+            .ok_or_else(|| AppError::BadRequest(format!("Emoji tag has no icon: {}", tag.name)))?;
+
+        let name = tag.name.trim_matches(':');
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(AppError::BadRequest(format!(
+                "Invalid emoji name: {}",
+                tag.name
+            )));
+        }
+
+        let content_type = icon
+            .media_type
+            .clone()
+            .unwrap_or_else(|| "image/png".to_string());
+
+        let model = emoji::ActiveModel {
+            id: Set(self.id_gen.generate()),
+            name: Set(name.to_string()),
+            category: Set(None),
+            original_url: Set(icon.url.to_string()),
+            static_url: Set(None),
+            content_type: Set(content_type),
+            aliases: Set(json!([])),
+            host: Set(Some(host.to_string())),
+            license: Set(None),
+            is_sensitive: Set(false),
+            local_only: Set(false),
+            width: Set(None),
+            height: Set(None),
+            size: Set(None),
+            created_at: Set(chrono::Utc::now()),
+            updated_at: Set(None),
+        };
+
+        let emoji = self.emoji_repo.upsert_remote(model).await?;
+        debug!(name = %emoji.name, host = %host, "Imported remote emoji");
+
+        Ok(emoji)
+    }
+}
+
+/// Normalize reaction content received from a remote server.
+///
+/// Empty content becomes [`DEFAULT_LIKE_REACTION`]. Custom emoji shortcodes
+/// backed by an `Emoji` tag are stored in the remote `:name@host:` form, with
+/// `host` being the sending actor's host; anything else (Unicode emoji,
+/// unknown shortcodes) is kept as-is.
+pub fn normalize_reaction(content: &str, tags: Option<&[EmojiTag]>, host: Option<&str>) -> String {
+    if content.is_empty() {
+        return DEFAULT_LIKE_REACTION.to_string();
+    }
+
+    if let Some(host) = host
+        && content.starts_with(':')
+        && content.ends_with(':')
+    {
+        let tagged = tags
+            .unwrap_or_default()
+            .iter()
+            .any(|tag| tag.kind == "Emoji" && tag.name == content && tag.icon.is_some());
+        if tagged {
+            return format!(":{}@{}:", content.trim_matches(':'), host);
+        }
+    }
+
+    content.to_string()
+}
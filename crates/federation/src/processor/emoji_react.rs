@@ -5,12 +5,12 @@
 use misskey_common::{AppError, AppResult, IdGenerator};
 use misskey_db::{
     entities::{reaction, user},
-    repositories::{NoteRepository, ReactionRepository, UserRepository},
+    repositories::{EmojiRepository, NoteRepository, ReactionRepository, UserRepository},
 };
 use sea_orm::Set;
 use tracing::info;
 
-use super::ActorFetcher;
+use super::{ActorFetcher, EmojiImporter, emoji::normalize_reaction};
 use crate::{activities::EmojiReactActivity, client::ApClient};
 
 /// Processor for `EmojiReact` activities (Pleroma/Akkoma style reactions).
@@ -19,6 +19,7 @@ pub struct EmojiReactProcessor {
     actor_fetcher: ActorFetcher,
     note_repo: NoteRepository,
     reaction_repo: ReactionRepository,
+    emoji_importer: Option<EmojiImporter>,
     id_gen: IdGenerator,
 }
 
@@ -35,10 +36,18 @@ impl EmojiReactProcessor {
             actor_fetcher: ActorFetcher::new(user_repo, ap_client),
             note_repo,
             reaction_repo,
+            emoji_importer: None,
             id_gen: IdGenerator::new(),
         }
     }
 
+    /// Enable importing of custom emojis used as reactions.
+    #[must_use]
+    pub fn with_emoji_repo(mut self, emoji_repo: EmojiRepository) -> Self {
+        self.emoji_importer = Some(EmojiImporter::new(emoji_repo));
+        self
+    }
+
     /// Process an incoming `EmojiReact` activity.
     pub async fn process(&self, activity: &EmojiReactActivity) -> AppResult<reaction::Model> {
         info!(
@@ -69,7 +78,11 @@ impl EmojiReactProcessor {
         }
 
         // Normalize the reaction content
-        let reaction_content = self.normalize_reaction(&activity.content, &activity.tag);
+        let reaction_content = normalize_reaction(
+            &activity.content,
+            activity.tag.as_deref(),
+            actor.host.as_deref(),
+        );
 
         // Create reaction
         let reaction_id = self.id_gen.generate();
@@ -82,6 +95,14 @@ impl EmojiReactProcessor {
         };
 
         let reaction = self.reaction_repo.create(model).await?;
+        self.note_repo.increment_reactions_count(&note.id).await?;
+
+        // Cache custom emojis under the same host used in the stored reaction
+        if let (Some(importer), Some(tags), Some(host)) =
+            (&self.emoji_importer, &activity.tag, &actor.host)
+        {
+            importer.import_reaction_emojis(tags, host).await;
+        }
 
         info!(
             reaction_id = %reaction.id,
@@ -98,86 +119,25 @@ impl EmojiReactProcessor {
     async fn find_or_fetch_actor(&self, actor_url: &url::Url) -> AppResult<user::Model> {
         self.actor_fetcher.find_or_fetch(actor_url).await
     }
-
-    /// Normalize reaction content from `EmojiReact` activity.
-    fn normalize_reaction(
-        &self,
-        content: &str,
-        tags: &Option<Vec<crate::activities::EmojiTag>>,
-    ) -> String {
-        // If content is empty, default to heart
-        if content.is_empty() {
-            return "❤️".to_string();
-        }
-
-        // Check if it's a custom emoji shortcode
-        if content.starts_with(':') && content.ends_with(':') {
-            // Try to get the remote emoji host from tags
-            if let Some(tag_list) = tags {
-                for tag in tag_list {
-                    if tag.kind == "Emoji"
-                        && tag.name == content
-                        && let Some(ref icon) = tag.icon
-                    {
-                        // Store as remote emoji format: :emoji@host:
-                        if let Some(host) = icon.url.host_str() {
-                            return format!(":{}@{}:", content.trim_matches(':'), host);
-                        }
-                    }
-                }
-            }
-        }
-
-        // Return as-is if it's a Unicode emoji or shortcode
-        content.to_string()
-    }
 }
 
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
     use crate::activities::{EmojiIcon, EmojiTag};
+    use crate::processor::emoji::{DEFAULT_LIKE_REACTION, normalize_reaction};
     use url::Url;
 
-    /// Helper function to test reaction normalization.
-    fn normalize_reaction(content: &str, tags: &Option<Vec<EmojiTag>>) -> String {
-        // If content is empty, default to heart
-        if content.is_empty() {
-            return "❤️".to_string();
-        }
-
-        // Check if it's a custom emoji shortcode
-        if content.starts_with(':') && content.ends_with(':') {
-            // Try to get the remote emoji host from tags
-            if let Some(tag_list) = tags {
-                for tag in tag_list {
-                    if tag.kind == "Emoji"
-                        && tag.name == content
-                        && let Some(ref icon) = tag.icon
-                    {
-                        // Store as remote emoji format: :emoji@host:
-                        if let Some(host) = icon.url.host_str() {
-                            return format!(":{}@{}:", content.trim_matches(':'), host);
-                        }
-                    }
-                }
-            }
-        }
-
-        // Return as-is if it's a Unicode emoji or shortcode
-        content.to_string()
-    }
-
     #[test]
     fn test_normalize_unicode_emoji() {
-        let result = normalize_reaction("👍", &None);
+        let result = normalize_reaction("👍", None, Some("remote.example"));
         assert_eq!(result, "👍");
     }
 
     #[test]
     fn test_normalize_empty_content() {
-        let result = normalize_reaction("", &None);
-        assert_eq!(result, "❤️");
+        let result = normalize_reaction("", None, Some("remote.example"));
+        assert_eq!(result, DEFAULT_LIKE_REACTION);
     }
 
     #[test]
@@ -187,18 +147,19 @@ mod tests {
             name: ":blobcat:".to_string(),
             icon: Some(EmojiIcon {
                 kind: "Image".to_string(),
-                url: Url::parse("https://remote.example/emoji/blobcat.png").unwrap(),
+                url: Url::parse("https://cdn.example/emoji/blobcat.png").unwrap(),
                 media_type: Some("image/png".to_string()),
             }),
         }];
 
-        let result = normalize_reaction(":blobcat:", &Some(tags));
+        // The sender's host wins over the host serving the image
+        let result = normalize_reaction(":blobcat:", Some(&tags), Some("remote.example"));
         assert_eq!(result, ":blobcat@remote.example:");
     }
 
     #[test]
     fn test_normalize_custom_emoji_without_tag() {
-        let result = normalize_reaction(":blobcat:", &None);
+        let result = normalize_reaction(":blobcat:", None, Some("remote.example"));
         assert_eq!(result, ":blobcat:");
     }
 }
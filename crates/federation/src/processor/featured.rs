@@ -0,0 +1,236 @@
+//! Remote featured (pinned) notes.
+
+use std::sync::Arc;
+
+use misskey_common::{AppError, AppResult};
+use misskey_db::{
+    entities::{note, user},
+    repositories::UserProfileRepository,
+};
+use serde_json::Value;
+use tracing::{info, warn};
+use url::Url;
+
+use super::CreateProcessor;
+use crate::{
+    client::{ApClient, ObjectFetcher},
+    objects::ApNote,
+};
+
+/// Maximum number of featured notes imported per actor.
+const MAX_FEATURED_NOTES: usize = 5;
+
+/// Imports the featured collection of remote actors as their pinned notes.
+#[derive(Clone)]
+pub struct FeaturedImporter {
+    create_processor: CreateProcessor,
+    profile_repo: UserProfileRepository,
+    object_fetcher: Arc<dyn ObjectFetcher>,
+}
+
+impl FeaturedImporter {
+    /// Create a new featured importer storing notes through `create_processor`.
+    #[must_use]
+    pub fn new(
+        create_processor: CreateProcessor,
+        profile_repo: UserProfileRepository,
+        ap_client: ApClient,
+    ) -> Self {
+        Self {
+            create_processor,
+            profile_repo,
+            object_fetcher: Arc::new(ap_client),
+        }
+    }
+
+    /// Fetch the collection and its notes through `fetcher`.
+    #[must_use]
+    pub fn with_object_fetcher(mut self, fetcher: Arc<dyn ObjectFetcher>) -> Self {
+        self.object_fetcher = fetcher;
+        self
+    }
+
+    /// Import the featured notes of a remote actor and pin them.
+    ///
+    /// Only notes attributed to the actor and served by their own host are
+    /// kept. Returns the IDs of the pinned notes.
+    pub async fn import(&self, actor: &user::Model) -> AppResult<Vec<String>> {
+        let (Some(featured), Some(host), Some(actor_uri)) =
+            (&actor.featured, &actor.host, &actor.uri)
+        else {
+            return Ok(Vec::new());
+        };
+        let featured = Url::parse(featured)
+            .map_err(|e| AppError::BadRequest(format!("Invalid featured collection URL: {e}")))?;
+        if featured.host_str() != Some(host.as_str()) {
+            warn!(actor = %actor.id, url = %featured, "Featured collection is on another host");
+            return Ok(Vec::new());
+        }
+
+        let items = self
+            .object_fetcher
+            .fetch_collection_items(&featured, MAX_FEATURED_NOTES)
+            .await
+            .map_err(|e| AppError::Federation(format!("Failed to fetch featured notes: {e}")))?;
+
+        let mut note_ids = Vec::new();
+        for item in items {
+            if let Some(note) = self.import_item(item, host, actor_uri).await
+                && note.user_id == actor.id
+                && !note_ids.contains(&note.id)
+            {
+                note_ids.push(note.id);
+            }
+        }
+
+        self.profile_repo
+            .set_pinned_note_ids(&actor.id, &note_ids)
+            .await?;
+        info!(actor = %actor.id, count = note_ids.len(), "Imported featured notes");
+
+        Ok(note_ids)
+    }
+
+    /// Store a featured item given as a link or an embedded note.
+    async fn import_item(&self, item: Value, host: &str, actor_uri: &str) -> Option<note::Model> {
+        let object = match item {
+            Value::String(url) => {
+                let url = Url::parse(&url).ok()?;
+                if url.host_str() != Some(host) {
+                    return None;
+                }
+                match self.object_fetcher.fetch_object(&url).await {
+                    Ok(object) => object,
+                    Err(e) => {
+                        warn!(url = %url, error = %e, "Failed to fetch featured note");
+                        return None;
+                    }
+                }
+            }
+            object => object,
+        };
+
+        let ap_note: ApNote = match serde_json::from_value(object) {
+            Ok(ap_note) => ap_note,
+            Err(e) => {
+                warn!(error = %e, "Invalid featured note");
+                return None;
+            }
+        };
+        if ap_note.id.host_str() != Some(host) {
+            warn!(note_id = %ap_note.id, "Featured note is on another host");
+            return None;
+        }
+        if ap_note.attributed_to.as_str() != actor_uri {
+            warn!(note_id = %ap_note.id, "Featured note is attributed to another actor");
+            return None;
+        }
+
+        match self.create_processor.ingest_note(&ap_note).await {
+            Ok(note) => Some(note),
+            Err(e) => {
+                warn!(note_id = %ap_note.id, error = %e, "Failed to store featured note");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::processor::test_support::{StubFetcher, remote_author, stored_note};
+    use misskey_db::repositories::{DriveFileRepository, NoteRepository, UserRepository};
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_import_pins_only_the_actors_own_notes() {
+        let mut actor = remote_author();
+        actor.featured = Some("https://remote.example/users/alice/featured".to_string());
+        let fetcher = Arc::new(StubFetcher {
+            objects: vec![
+                json!({
+                    "type": "OrderedCollection",
+                    "id": "https://remote.example/users/alice/featured",
+                    "orderedItems": [
+                        "https://remote.example/notes/1",
+                        "https://elsewhere.example/notes/9",
+                        {
+                            "type": "Note",
+                            "id": "https://remote.example/notes/2",
+                            "attributedTo": "https://remote.example/users/mallory",
+                            "content": "<p>Not alice's</p>",
+                            "published": "2024-06-01T12:00:00Z",
+                            "to": ["https://www.w3.org/ns/activitystreams#Public"]
+                        }
+                    ]
+                }),
+                json!({
+                    "type": "Note",
+                    "id": "https://remote.example/notes/1",
+                    "attributedTo": "https://remote.example/users/alice",
+                    "content": "<p>Pinned</p>",
+                    "published": "2024-06-01T12:00:00Z",
+                    "to": ["https://www.w3.org/ns/activitystreams#Public"]
+                }),
+            ],
+            ..Default::default()
+        });
+
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[stored_note()]])
+                .into_connection(),
+        );
+        let user_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let drive_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let profile_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_exec_results([MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                }])
+                .into_connection(),
+        );
+
+        let create_processor = CreateProcessor::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            DriveFileRepository::new(drive_db),
+            UserRepository::new(user_db),
+            ApClient::new("https://local.example"),
+        );
+        let importer = FeaturedImporter::new(
+            create_processor,
+            UserProfileRepository::new(Arc::clone(&profile_db)),
+            ApClient::new("https://local.example"),
+        )
+        .with_object_fetcher(fetcher.clone());
+
+        let pinned = importer.import(&actor).await.unwrap();
+        drop(importer);
+
+        assert_eq!(pinned, ["note1"]);
+        assert_eq!(
+            *fetcher.fetched.lock().unwrap(),
+            [
+                "https://remote.example/users/alice/featured",
+                "https://remote.example/notes/1"
+            ]
+        );
+        // The note attributed to another actor is never looked up or stored
+        let note_log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert_eq!(note_log.len(), 1);
+        let log = Arc::try_unwrap(profile_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        let upsert = format!("{log:?}");
+        assert!(upsert.contains("ON CONFLICT"));
+        assert!(upsert.contains("note1"));
+    }
+}
@@ -1,14 +1,16 @@
 //! Like activity processor.
 
-use misskey_common::{AppError, AppResult, IdGenerator};
+use misskey_common::{AppError, AppResult, IdGenerator, reaction::is_valid_reaction};
 use misskey_db::{
-    entities::{reaction, user},
-    repositories::{NoteRepository, ReactionRepository, UserRepository},
+    entities::{note, reaction, user},
+    repositories::{
+        EmojiRepository, NoteRepository, ReactionRepository, UserProfileRepository, UserRepository,
+    },
 };
 use sea_orm::Set;
 use tracing::info;
 
-use super::ActorFetcher;
+use super::{ActorFetcher, DEFAULT_LIKE_REACTION, EmojiImporter, emoji::normalize_reaction};
 use crate::{activities::LikeActivity, client::ApClient};
 
 /// Processor for Like activities (reactions).
@@ -17,6 +19,8 @@ pub struct LikeProcessor {
     actor_fetcher: ActorFetcher,
     note_repo: NoteRepository,
     reaction_repo: ReactionRepository,
+    emoji_importer: Option<EmojiImporter>,
+    profile_repo: Option<UserProfileRepository>,
     id_gen: IdGenerator,
 }
 
@@ -33,10 +37,26 @@ impl LikeProcessor {
             actor_fetcher: ActorFetcher::new(user_repo, ap_client),
             note_repo,
             reaction_repo,
+            emoji_importer: None,
+            profile_repo: None,
             id_gen: IdGenerator::new(),
         }
     }
 
+    /// Enable importing of custom emojis used as reactions.
+    #[must_use]
+    pub fn with_emoji_repo(mut self, emoji_repo: EmojiRepository) -> Self {
+        self.emoji_importer = Some(EmojiImporter::new(emoji_repo));
+        self
+    }
+
+    /// Apply local note authors' default reaction to bare Likes.
+    #[must_use]
+    pub fn with_profile_repo(mut self, profile_repo: UserProfileRepository) -> Self {
+        self.profile_repo = Some(profile_repo);
+        self
+    }
+
     /// Process an incoming Like activity.
     pub async fn process(&self, activity: &LikeActivity) -> AppResult<reaction::Model> {
         info!(
@@ -66,7 +86,7 @@ impl LikeProcessor {
         }
 
         // Determine the reaction content
-        let reaction_content = self.normalize_reaction(activity);
+        let reaction_content = self.reaction_content(activity, &actor, &note).await?;
 
         // Create reaction
         let reaction_id = self.id_gen.generate();
@@ -79,6 +99,14 @@ impl LikeProcessor {
         };
 
         let reaction = self.reaction_repo.create(model).await?;
+        self.note_repo.increment_reactions_count(&note.id).await?;
+
+        // Cache custom emojis under the same host used in the stored reaction
+        if let (Some(importer), Some(tags), Some(host)) =
+            (&self.emoji_importer, &activity.tag, &actor.host)
+        {
+            importer.import_reaction_emojis(tags, host).await;
+        }
 
         info!(
             reaction_id = %reaction.id,
@@ -95,20 +123,320 @@ impl LikeProcessor {
         self.actor_fetcher.find_or_fetch(actor_url).await
     }
 
-    /// Normalize reaction content from `ActivityPub`.
-    fn normalize_reaction(&self, activity: &LikeActivity) -> String {
-        // Try Misskey-specific reaction first, then content, then default
-        if let Some(ref reaction) = activity.misskey_reaction
-            && !reaction.is_empty()
-        {
-            return reaction.clone();
+    /// Determine the reaction stored for a Like.
+    ///
+    /// Misskey sends the emoji in `_misskey_reaction`, other servers may use
+    /// `content`; a bare Like becomes the note author's default reaction.
+    async fn reaction_content(
+        &self,
+        activity: &LikeActivity,
+        actor: &user::Model,
+        note: &note::Model,
+    ) -> AppResult<String> {
+        let content = [&activity.misskey_reaction, &activity.content]
+            .into_iter()
+            .flatten()
+            .find(|c| !c.is_empty());
+        match content {
+            Some(content) => Ok(normalize_reaction(
+                content,
+                activity.tag.as_deref(),
+                actor.host.as_deref(),
+            )),
+            None => self.author_default_reaction(note).await,
         }
-        if let Some(ref content) = activity.content
-            && !content.is_empty()
-        {
-            return content.clone();
+    }
+
+    /// Get the default reaction configured by a local note's author.
+    ///
+    /// Falls back to [`DEFAULT_LIKE_REACTION`] for remote notes, unset or
+    /// illegal defaults.
+    async fn author_default_reaction(&self, note: &note::Model) -> AppResult<String> {
+        let configured = match &self.profile_repo {
+            Some(profile_repo) if note.user_host.is_none() => profile_repo
+                .find_by_user_id(&note.user_id)
+                .await?
+                .and_then(|p| p.default_reaction)
+                .filter(|r| is_valid_reaction(r)),
+            _ => None,
+        };
+        Ok(configured.unwrap_or_else(|| DEFAULT_LIKE_REACTION.to_string()))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::processor::{ParsedUndoActivity, UndoProcessor, UndoResult};
+    use misskey_db::{entities::user_profile, repositories::FollowingRepository};
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn remote_actor() -> user::Model {
+        user::Model {
+            id: "actor1".to_string(),
+            username: "bob".to_string(),
+            username_lower: "bob".to_string(),
+            host: Some("remote.example".to_string()),
+            token: None,
+            name: None,
+            description: None,
+            avatar_url: None,
+            banner_url: None,
+            followers_count: 0,
+            following_count: 0,
+            notes_count: 0,
+            is_bot: false,
+            is_cat: false,
+            is_locked: false,
+            is_suspended: false,
+            is_silenced: false,
+            is_admin: false,
+            is_moderator: false,
+            is_system: false,
+            inbox: None,
+            shared_inbox: None,
+            featured: None,
+            followers_uri: None,
+            uri: Some("https://remote.example/users/bob".to_string()),
+            last_fetched_at: None,
+            created_at: chrono::Utc::now().into(),
+            updated_at: None,
         }
-        // Default reaction (Misskey uses ❤️ for standard Like)
-        "❤️".to_string()
+    }
+
+    fn local_note() -> note::Model {
+        note::Model {
+            id: "note1".to_string(),
+            user_id: "author1".to_string(),
+            user_host: None,
+            text: Some("Hello".to_string()),
+            cw: None,
+            visibility: note::Visibility::Public,
+            reply_id: None,
+            renote_id: None,
+            thread_id: None,
+            mentions: json!([]),
+            visible_user_ids: json!([]),
+            file_ids: json!([]),
+            tags: json!([]),
+            reactions: json!({}),
+            replies_count: 0,
+            renote_count: 0,
+            reaction_count: 0,
+            is_local: true,
+            uri: Some("https://local.example/notes/note1".to_string()),
+            url: None,
+            channel_id: None,
+            created_at: chrono::Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn stored_reaction() -> reaction::Model {
+        reaction::Model {
+            id: "reaction1".to_string(),
+            user_id: "actor1".to_string(),
+            note_id: "note1".to_string(),
+            reaction: DEFAULT_LIKE_REACTION.to_string(),
+            created_at: chrono::Utc::now().into(),
+        }
+    }
+
+    fn statements(db: Arc<sea_orm::DatabaseConnection>) -> Vec<String> {
+        Arc::try_unwrap(db)
+            .ok()
+            .unwrap()
+            .into_transaction_log()
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .map(|stmt| format!("{} {:?}", stmt.sql, stmt.values))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_bare_like_creates_default_reaction_and_undo_removes_it() {
+        let like: LikeActivity = serde_json::from_value(json!({
+            "type": "Like",
+            "id": "https://remote.example/likes/1",
+            "actor": "https://remote.example/users/bob",
+            "object": "https://local.example/notes/note1"
+        }))
+        .unwrap();
+
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_actor()], [remote_actor()]])
+                .into_connection(),
+        );
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[local_note()], [local_note()]])
+                .append_exec_results([
+                    MockExecResult {
+                        last_insert_id: 0,
+                        rows_affected: 1,
+                    },
+                    MockExecResult {
+                        last_insert_id: 0,
+                        rows_affected: 1,
+                    },
+                ])
+                .into_connection(),
+        );
+        let reaction_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([
+                    Vec::<reaction::Model>::new(),
+                    vec![stored_reaction()],
+                    vec![stored_reaction()],
+                    vec![stored_reaction()],
+                ])
+                .append_exec_results([MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                }])
+                .into_connection(),
+        );
+        let following_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let processor = LikeProcessor::new(
+            UserRepository::new(Arc::clone(&user_db)),
+            NoteRepository::new(Arc::clone(&note_db)),
+            ReactionRepository::new(Arc::clone(&reaction_db)),
+            ApClient::new("https://local.example"),
+        );
+        processor.process(&like).await.unwrap();
+
+        let undo = UndoProcessor::new(
+            UserRepository::new(Arc::clone(&user_db)),
+            FollowingRepository::new(following_db),
+            ReactionRepository::new(Arc::clone(&reaction_db)),
+            NoteRepository::new(Arc::clone(&note_db)),
+        );
+        let result = undo
+            .process(&ParsedUndoActivity {
+                id: "https://remote.example/undo/1".parse().unwrap(),
+                actor: like.actor.clone(),
+                object_type: "Like".to_string(),
+                object_id: like.id.clone(),
+                object_object: Some(like.object.clone()),
+            })
+            .await
+            .unwrap();
+        assert!(matches!(result, UndoResult::Unreacted));
+        drop(processor);
+        drop(undo);
+
+        let reaction_log = statements(reaction_db);
+        let insert = reaction_log
+            .iter()
+            .find(|stmt| stmt.starts_with(r#"INSERT INTO "reaction""#))
+            .unwrap();
+        assert!(insert.contains(DEFAULT_LIKE_REACTION));
+        assert!(
+            reaction_log
+                .iter()
+                .any(|stmt| stmt.starts_with(r#"DELETE FROM "reaction""#))
+        );
+
+        let count_updates: Vec<_> = statements(note_db)
+            .into_iter()
+            .filter(|stmt| stmt.starts_with(r#"UPDATE "note""#))
+            .collect();
+        assert_eq!(count_updates.len(), 2);
+        assert!(count_updates[0].contains(r#""reaction_count" + "#));
+        assert!(count_updates[1].contains("GREATEST(reaction_count - 1, 0)"));
+    }
+
+    fn author_profile(default_reaction: &str) -> user_profile::Model {
+        user_profile::Model {
+            user_id: "author1".to_string(),
+            password: None,
+            email: None,
+            email_verified: false,
+            email_undeliverable: false,
+            two_factor_secret: None,
+            two_factor_enabled: false,
+            two_factor_pending: None,
+            two_factor_backup_codes: None,
+            auto_accept_followed: false,
+            always_mark_nsfw: false,
+            pinned_page_ids: json!([]),
+            pinned_note_ids: json!([]),
+            fields: json!([]),
+            muted_words: json!([]),
+            user_css: None,
+            birthday: None,
+            location: None,
+            lang: None,
+            pronouns: None,
+            also_known_as: None,
+            moved_to_uri: None,
+            hide_bots: false,
+            default_reaction: Some(default_reaction.to_string()),
+            receive_dm_from_followers_only: false,
+            secure_fetch_only: false,
+            disallow_quotes: false,
+            hide_online_status: false,
+            default_note_visibility: note::Visibility::Public,
+            theme: None,
+            created_at: chrono::Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bare_like_uses_author_default_reaction() {
+        let like: LikeActivity = serde_json::from_value(json!({
+            "type": "Like",
+            "id": "https://remote.example/likes/1",
+            "actor": "https://remote.example/users/bob",
+            "object": "https://local.example/notes/note1"
+        }))
+        .unwrap();
+
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_actor()]])
+                .into_connection(),
+        );
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[local_note()]])
+                .append_exec_results([MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                }])
+                .into_connection(),
+        );
+        let reaction_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<reaction::Model>::new(), vec![stored_reaction()]])
+                .into_connection(),
+        );
+        let profile_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[author_profile("🎉")]])
+                .into_connection(),
+        );
+
+        let processor = LikeProcessor::new(
+            UserRepository::new(user_db),
+            NoteRepository::new(note_db),
+            ReactionRepository::new(Arc::clone(&reaction_db)),
+            ApClient::new("https://local.example"),
+        )
+        .with_profile_repo(UserProfileRepository::new(profile_db));
+        processor.process(&like).await.unwrap();
+        drop(processor);
+
+        let insert = statements(reaction_db)
+            .into_iter()
+            .find(|stmt| stmt.starts_with(r#"INSERT INTO "reaction""#))
+            .unwrap();
+        assert!(insert.contains("🎉"));
     }
 }
@@ -7,11 +7,15 @@ mod actor_fetcher;
 mod announce;
 mod create;
 mod delete;
+mod emoji;
 mod emoji_react;
+mod featured;
 mod follow;
 mod like;
 mod move_processor;
 mod reject;
+#[cfg(test)]
+mod test_support;
 mod undo;
 mod update;
 
@@ -20,7 +24,9 @@ pub use actor_fetcher::ActorFetcher;
 pub use announce::AnnounceProcessor;
 pub use create::CreateProcessor;
 pub use delete::{DeleteProcessor, DeleteResult};
+pub use emoji::{DEFAULT_LIKE_REACTION, EmojiImporter};
 pub use emoji_react::EmojiReactProcessor;
+pub use featured::FeaturedImporter;
 pub use follow::{AcceptActivityInfo, FollowProcessResult, FollowProcessor};
 pub use like::LikeProcessor;
 pub use move_processor::{MoveProcessResult, MoveProcessor};
@@ -2,7 +2,7 @@
 
 use misskey_common::{AppError, AppResult};
 use misskey_db::{
-    entities::user_profile,
+    entities::{note, user_profile},
     repositories::{FollowingRepository, UserProfileRepository, UserRepository},
 };
 use sea_orm::Set;
@@ -205,6 +205,7 @@ impl MoveProcessor {
                 password: Set(None),
                 email: Set(None),
                 email_verified: Set(false),
+                email_undeliverable: Set(false),
                 two_factor_secret: Set(None),
                 two_factor_enabled: Set(false),
                 two_factor_pending: Set(None),
@@ -226,6 +227,10 @@ impl MoveProcessor {
                 default_reaction: Set(None),
                 receive_dm_from_followers_only: Set(false),
                 secure_fetch_only: Set(false),
+                disallow_quotes: Set(false),
+                hide_online_status: Set(false),
+                default_note_visibility: Set(note::Visibility::Public),
+                theme: Set(None),
                 created_at: Set(chrono::Utc::now().into()),
                 updated_at: Set(None),
             };
@@ -0,0 +1,98 @@
+//! Shared test doubles for processor tests.
+
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use misskey_db::entities::{note, user};
+use serde_json::{Value, json};
+use url::Url;
+
+use crate::client::{ApClientError, ObjectFetcher};
+
+/// Object fetcher serving fixed objects and recording every fetch.
+#[derive(Default)]
+pub struct StubFetcher {
+    pub objects: Vec<Value>,
+    pub fetched: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl ObjectFetcher for StubFetcher {
+    async fn fetch_object(&self, url: &Url) -> Result<Value, ApClientError> {
+        self.fetched
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(url.to_string());
+        self.objects
+            .iter()
+            .find(|object| object["id"] == url.as_str())
+            .cloned()
+            .ok_or_else(|| ApClientError::DeliveryFailed {
+                status: 404,
+                body: String::new(),
+            })
+    }
+}
+
+/// Remote author of the notes used in processor tests.
+pub fn remote_author() -> user::Model {
+    user::Model {
+        id: "author1".to_string(),
+        username: "alice".to_string(),
+        username_lower: "alice".to_string(),
+        host: Some("remote.example".to_string()),
+        token: None,
+        name: None,
+        description: None,
+        avatar_url: None,
+        banner_url: None,
+        followers_count: 0,
+        following_count: 0,
+        notes_count: 0,
+        is_bot: false,
+        is_cat: false,
+        is_locked: false,
+        is_suspended: false,
+        is_silenced: false,
+        is_admin: false,
+        is_moderator: false,
+        is_system: false,
+        inbox: None,
+        shared_inbox: None,
+        featured: None,
+        followers_uri: None,
+        uri: Some("https://remote.example/users/alice".to_string()),
+        last_fetched_at: None,
+        created_at: chrono::Utc::now().into(),
+        updated_at: None,
+    }
+}
+
+/// Note stored for [`remote_author`].
+pub fn stored_note() -> note::Model {
+    note::Model {
+        id: "note1".to_string(),
+        user_id: "author1".to_string(),
+        user_host: Some("remote.example".to_string()),
+        text: Some("Hello :blobcat:".to_string()),
+        cw: None,
+        visibility: note::Visibility::Public,
+        reply_id: None,
+        renote_id: None,
+        thread_id: None,
+        mentions: json!([]),
+        visible_user_ids: json!([]),
+        file_ids: json!([]),
+        tags: json!([]),
+        reactions: json!({}),
+        replies_count: 0,
+        renote_count: 0,
+        reaction_count: 0,
+        is_local: false,
+        uri: Some("https://remote.example/notes/1".to_string()),
+        url: None,
+        channel_id: None,
+        created_at: chrono::Utc::now().into(),
+        updated_at: None,
+    }
+}
@@ -1,10 +1,14 @@
 //! Undo activity processor.
 
 use misskey_common::{AppError, AppResult};
-use misskey_db::repositories::{
-    FollowingRepository, NoteRepository, ReactionRepository, UserRepository,
+use misskey_db::{
+    entities::user,
+    repositories::{
+        BlockingRepository, FollowRequestRepository, FollowingRepository, NoteRepository,
+        ReactionRepository, UserRepository,
+    },
 };
-use tracing::info;
+use tracing::{info, warn};
 use url::Url;
 
 /// Parsed Undo activity with resolved inner activity details.
@@ -12,24 +16,28 @@ use url::Url;
 pub struct ParsedUndoActivity {
     pub id: Url,
     pub actor: Url,
-    /// The type of activity being undone (Follow, Like, Announce).
+    /// The type of activity being undone (Follow, Like, `EmojiReact`, Announce, Block).
     pub object_type: String,
     /// The ID of the activity being undone.
     pub object_id: Url,
-    /// For Undo Follow: the followee URL.
-    /// For Undo Like: the note URL.
+    /// For Undo Follow/Block: the followee or blockee URL.
+    /// For Undo Like/EmojiReact: the note URL.
     pub object_object: Option<Url>,
 }
 
 /// Result of processing an Undo activity.
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum UndoResult {
-    /// Follow was undone.
+    /// Follow (or pending follow request) was undone.
     Unfollowed,
-    /// Like was undone.
+    /// Like or `EmojiReact` was undone.
     Unreacted,
     /// Announce was undone.
     Unrenoted,
+    /// Block was undone.
+    Unblocked,
+    /// The undone activity left nothing to reverse (e.g. it was already undone).
+    NothingToUndo,
     /// Unknown object type, ignored.
     Ignored,
 }
@@ -41,6 +49,8 @@ pub struct UndoProcessor {
     following_repo: FollowingRepository,
     reaction_repo: ReactionRepository,
     note_repo: NoteRepository,
+    follow_request_repo: Option<FollowRequestRepository>,
+    blocking_repo: Option<BlockingRepository>,
 }
 
 impl UndoProcessor {
@@ -57,9 +67,28 @@ impl UndoProcessor {
             following_repo,
             reaction_repo,
             note_repo,
+            follow_request_repo: None,
+            blocking_repo: None,
         }
     }
 
+    /// Also withdraw pending follow requests on Undo(Follow).
+    #[must_use]
+    pub fn with_follow_request_repo(
+        mut self,
+        follow_request_repo: FollowRequestRepository,
+    ) -> Self {
+        self.follow_request_repo = Some(follow_request_repo);
+        self
+    }
+
+    /// Enable handling of Undo(Block).
+    #[must_use]
+    pub fn with_blocking_repo(mut self, blocking_repo: BlockingRepository) -> Self {
+        self.blocking_repo = Some(blocking_repo);
+        self
+    }
+
     /// Process an incoming Undo activity.
     pub async fn process(&self, activity: &ParsedUndoActivity) -> AppResult<UndoResult> {
         info!(
@@ -70,8 +99,9 @@ impl UndoProcessor {
 
         match activity.object_type.as_str() {
             "Follow" => self.undo_follow(activity).await,
-            "Like" => self.undo_like(activity).await,
+            "Like" | "EmojiReact" => self.undo_like(activity).await,
             "Announce" => self.undo_announce(activity).await,
+            "Block" => self.undo_block(activity).await,
             _ => {
                 info!(object_type = %activity.object_type, "Unknown Undo object type, ignoring");
                 Ok(UndoResult::Ignored)
@@ -79,28 +109,60 @@ impl UndoProcessor {
         }
     }
 
-    /// Undo a Follow activity.
-    async fn undo_follow(&self, activity: &ParsedUndoActivity) -> AppResult<UndoResult> {
-        // Find the actor (follower)
-        let follower = self
-            .user_repo
-            .find_by_uri(activity.actor.as_str())
-            .await?
-            .ok_or_else(|| AppError::NotFound(format!("Actor not found: {}", activity.actor)))?;
+    /// Find the actor of an Undo; unknown actors have nothing to undo.
+    async fn find_actor(&self, activity: &ParsedUndoActivity) -> AppResult<Option<user::Model>> {
+        let actor = self.user_repo.find_by_uri(activity.actor.as_str()).await?;
+        if actor.is_none() {
+            info!(actor = %activity.actor, "Undo from unknown actor, nothing to undo");
+        }
+        Ok(actor)
+    }
 
-        // Get the followee URL from object.object
-        let followee_url = activity
-            .object_object
-            .as_ref()
-            .ok_or_else(|| AppError::BadRequest("Undo Follow missing object.object".to_string()))?;
+    /// Find the local user referenced by `object.object` of a Follow or Block.
+    async fn find_local_target(
+        &self,
+        activity: &ParsedUndoActivity,
+    ) -> AppResult<Option<user::Model>> {
+        let target_url = activity.object_object.as_ref().ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Undo {} missing object.object",
+                activity.object_type
+            ))
+        })?;
+        let target_id = extract_local_user_id(target_url)?;
+        let target = self.user_repo.find_by_id(&target_id).await?;
+        if target.is_none() {
+            info!(target = %target_id, "Undo target user not found, nothing to undo");
+        }
+        Ok(target)
+    }
 
-        // Extract local user ID from followee URL
-        let followee_id = extract_local_user_id(followee_url)?;
-        let followee = self
-            .user_repo
-            .find_by_id(&followee_id)
-            .await?
-            .ok_or_else(|| AppError::NotFound(format!("Followee not found: {followee_id}")))?;
+    /// Undo a Follow activity, whether it was accepted or still pending.
+    async fn undo_follow(&self, activity: &ParsedUndoActivity) -> AppResult<UndoResult> {
+        let Some(follower) = self.find_actor(activity).await? else {
+            return Ok(UndoResult::NothingToUndo);
+        };
+        let Some(followee) = self.find_local_target(activity).await? else {
+            return Ok(UndoResult::NothingToUndo);
+        };
+
+        // Withdraw a pending follow request
+        if let Some(ref follow_request_repo) = self.follow_request_repo
+            && follow_request_repo
+                .find_by_pair(&follower.id, &followee.id)
+                .await?
+                .is_some()
+        {
+            follow_request_repo
+                .delete_by_pair(&follower.id, &followee.id)
+                .await?;
+            info!(
+                follower = %follower.id,
+                followee = %followee.id,
+                "Follow request withdrawn"
+            );
+            return Ok(UndoResult::Unfollowed);
+        }
 
         // Check if following exists
         if !self
@@ -109,7 +171,7 @@ impl UndoProcessor {
             .await?
         {
             info!("Follow relationship doesn't exist, nothing to undo");
-            return Ok(UndoResult::Unfollowed);
+            return Ok(UndoResult::NothingToUndo);
         }
 
         // Delete the following relationship
@@ -134,14 +196,14 @@ impl UndoProcessor {
         Ok(UndoResult::Unfollowed)
     }
 
-    /// Undo a Like activity.
+    /// Undo a Like or `EmojiReact` activity.
+    ///
+    /// Both are stored as the actor's single reaction on the note, so either
+    /// removes whatever reaction the actor left there.
     async fn undo_like(&self, activity: &ParsedUndoActivity) -> AppResult<UndoResult> {
-        // Find the actor
-        let actor = self
-            .user_repo
-            .find_by_uri(activity.actor.as_str())
-            .await?
-            .ok_or_else(|| AppError::NotFound(format!("Actor not found: {}", activity.actor)))?;
+        let Some(actor) = self.find_actor(activity).await? else {
+            return Ok(UndoResult::NothingToUndo);
+        };
 
         // The object_id is the Like activity ID, but we need to find the note
         // In practice, we should look up the Like by its activity ID
@@ -151,57 +213,104 @@ impl UndoProcessor {
             .as_ref()
             .ok_or_else(|| AppError::BadRequest("Undo Like missing note reference".to_string()))?;
 
-        let note = self
-            .note_repo
-            .find_by_uri(note_url.as_str())
-            .await?
-            .ok_or_else(|| AppError::NotFound(format!("Note not found: {note_url}")))?;
+        let Some(note) = self.note_repo.find_by_uri(note_url.as_str()).await? else {
+            info!(note = %note_url, "Reacted note not found, nothing to undo");
+            return Ok(UndoResult::NothingToUndo);
+        };
 
         // Delete the reaction
         if self
             .reaction_repo
             .find_by_user_and_note(&actor.id, &note.id)
             .await?
-            .is_some()
+            .is_none()
         {
-            self.reaction_repo
-                .delete_by_user_and_note(&actor.id, &note.id)
-                .await?;
-
-            info!(
-                actor = %actor.id,
-                note = %note.id,
-                "Reaction removed"
-            );
+            info!("Reaction doesn't exist, nothing to undo");
+            return Ok(UndoResult::NothingToUndo);
         }
 
+        self.reaction_repo
+            .delete_by_user_and_note(&actor.id, &note.id)
+            .await?;
+        self.note_repo.decrement_reactions_count(&note.id).await?;
+
+        info!(
+            actor = %actor.id,
+            note = %note.id,
+            "Reaction removed"
+        );
+
         Ok(UndoResult::Unreacted)
     }
 
     /// Undo an Announce activity.
     async fn undo_announce(&self, activity: &ParsedUndoActivity) -> AppResult<UndoResult> {
         // Find the renote by its URI (activity ID)
-        if let Some(renote) = self
+        let Some(renote) = self
             .note_repo
             .find_by_uri(activity.object_id.as_str())
             .await?
-        {
-            // Delete the renote
-            self.note_repo.delete(&renote.id).await?;
+        else {
+            info!(announce = %activity.object_id, "Renote not found, nothing to undo");
+            return Ok(UndoResult::NothingToUndo);
+        };
 
-            // Decrement renote count on original note
-            if let Some(ref original_id) = renote.renote_id {
-                self.note_repo.decrement_renote_count(original_id).await?;
-            }
+        // Only the announcer may take their renote back
+        let Some(actor) = self.find_actor(activity).await? else {
+            return Ok(UndoResult::NothingToUndo);
+        };
+        if renote.user_id != actor.id {
+            return Err(AppError::Forbidden(
+                "Cannot undo another actor's Announce".to_string(),
+            ));
+        }
 
-            info!(
-                renote_id = %renote.id,
-                "Renote removed"
-            );
+        // Delete the renote
+        self.note_repo.delete(&renote.id).await?;
+
+        // Decrement renote count on original note
+        if let Some(ref original_id) = renote.renote_id {
+            self.note_repo.decrement_renote_count(original_id).await?;
         }
 
+        info!(
+            renote_id = %renote.id,
+            "Renote removed"
+        );
+
         Ok(UndoResult::Unrenoted)
     }
+
+    /// Undo a Block activity.
+    async fn undo_block(&self, activity: &ParsedUndoActivity) -> AppResult<UndoResult> {
+        let Some(ref blocking_repo) = self.blocking_repo else {
+            warn!("Undo Block received but blocks are not tracked, ignoring");
+            return Ok(UndoResult::Ignored);
+        };
+        let Some(blocker) = self.find_actor(activity).await? else {
+            return Ok(UndoResult::NothingToUndo);
+        };
+        let Some(blockee) = self.find_local_target(activity).await? else {
+            return Ok(UndoResult::NothingToUndo);
+        };
+
+        if !blocking_repo.is_blocking(&blocker.id, &blockee.id).await? {
+            info!("Block doesn't exist, nothing to undo");
+            return Ok(UndoResult::NothingToUndo);
+        }
+
+        blocking_repo
+            .delete_by_pair(&blocker.id, &blockee.id)
+            .await?;
+
+        info!(
+            blocker = %blocker.id,
+            blockee = %blockee.id,
+            "Unblocked"
+        );
+
+        Ok(UndoResult::Unblocked)
+    }
 }
 
 /// Extract local user ID from a URL.
@@ -214,3 +323,420 @@ fn extract_local_user_id(url: &Url) -> AppResult<String> {
         "Cannot extract user ID from URL: {url}"
     )))
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use misskey_db::entities::{blocking, follow_request, following, note, reaction};
+    use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase, MockExecResult};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn test_user(id: &str, host: Option<&str>, uri: Option<&str>) -> user::Model {
+        user::Model {
+            id: id.to_string(),
+            username: id.to_string(),
+            username_lower: id.to_string(),
+            host: host.map(ToString::to_string),
+            token: None,
+            name: None,
+            description: None,
+            avatar_url: None,
+            banner_url: None,
+            followers_count: 1,
+            following_count: 1,
+            notes_count: 0,
+            is_bot: false,
+            is_cat: false,
+            is_locked: false,
+            is_suspended: false,
+            is_silenced: false,
+            is_admin: false,
+            is_moderator: false,
+            is_system: false,
+            inbox: None,
+            shared_inbox: None,
+            featured: None,
+            followers_uri: None,
+            uri: uri.map(ToString::to_string),
+            last_fetched_at: None,
+            created_at: chrono::Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn remote_actor() -> user::Model {
+        test_user(
+            "actor1",
+            Some("remote.example"),
+            Some("https://remote.example/users/bob"),
+        )
+    }
+
+    fn local_user() -> user::Model {
+        test_user("alice", None, None)
+    }
+
+    fn test_note(id: &str, user_id: &str, renote_id: Option<&str>) -> note::Model {
+        note::Model {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            user_host: None,
+            text: None,
+            cw: None,
+            visibility: note::Visibility::Public,
+            reply_id: None,
+            renote_id: renote_id.map(ToString::to_string),
+            thread_id: None,
+            mentions: json!([]),
+            visible_user_ids: json!([]),
+            file_ids: json!([]),
+            tags: json!([]),
+            reactions: json!({}),
+            replies_count: 0,
+            renote_count: 1,
+            reaction_count: 1,
+            is_local: false,
+            uri: Some(format!("https://remote.example/notes/{id}")),
+            url: None,
+            channel_id: None,
+            created_at: chrono::Utc::now().into(),
+            updated_at: None,
+        }
+    }
+
+    fn exec_ok() -> MockExecResult {
+        MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        }
+    }
+
+    fn empty_db() -> Arc<DatabaseConnection> {
+        Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection())
+    }
+
+    fn statements(db: Arc<DatabaseConnection>) -> Vec<String> {
+        Arc::try_unwrap(db)
+            .ok()
+            .unwrap()
+            .into_transaction_log()
+            .iter()
+            .flat_map(sea_orm::Transaction::statements)
+            .map(|stmt| format!("{} {:?}", stmt.sql, stmt.values))
+            .collect()
+    }
+
+    fn undo_of(object_type: &str, object_object: Option<&str>) -> ParsedUndoActivity {
+        ParsedUndoActivity {
+            id: "https://remote.example/undo/1".parse().unwrap(),
+            actor: "https://remote.example/users/bob".parse().unwrap(),
+            object_type: object_type.to_string(),
+            object_id: "https://remote.example/activities/1".parse().unwrap(),
+            object_object: object_object.map(|url| url.parse().unwrap()),
+        }
+    }
+
+    fn processor(
+        user_db: &Arc<DatabaseConnection>,
+        following_db: &Arc<DatabaseConnection>,
+        reaction_db: &Arc<DatabaseConnection>,
+        note_db: &Arc<DatabaseConnection>,
+    ) -> UndoProcessor {
+        UndoProcessor::new(
+            UserRepository::new(Arc::clone(user_db)),
+            FollowingRepository::new(Arc::clone(following_db)),
+            ReactionRepository::new(Arc::clone(reaction_db)),
+            NoteRepository::new(Arc::clone(note_db)),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_undo_follow_removes_following_and_counts() {
+        let relationship = following::Model {
+            id: "following1".to_string(),
+            follower_id: "actor1".to_string(),
+            followee_id: "alice".to_string(),
+            follower_host: Some("remote.example".to_string()),
+            followee_host: None,
+            followee_inbox: None,
+            followee_shared_inbox: None,
+            created_at: chrono::Utc::now().into(),
+        };
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_actor()], [local_user()]])
+                .append_exec_results([exec_ok(), exec_ok()])
+                .into_connection(),
+        );
+        let following_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[relationship.clone()], [relationship]])
+                .append_exec_results([exec_ok()])
+                .into_connection(),
+        );
+        let follow_request_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<follow_request::Model>::new()])
+                .into_connection(),
+        );
+        let (reaction_db, note_db) = (empty_db(), empty_db());
+
+        let undo = processor(&user_db, &following_db, &reaction_db, &note_db)
+            .with_follow_request_repo(FollowRequestRepository::new(Arc::clone(&follow_request_db)));
+        let result = undo
+            .process(&undo_of(
+                "Follow",
+                Some("https://local.example/users/alice"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(result, UndoResult::Unfollowed);
+        drop(undo);
+
+        assert!(
+            statements(following_db)
+                .iter()
+                .any(|stmt| stmt.starts_with(r#"DELETE FROM "following""#))
+        );
+        let count_updates: Vec<_> = statements(user_db)
+            .into_iter()
+            .filter(|stmt| stmt.starts_with(r#"UPDATE "user""#))
+            .collect();
+        assert_eq!(count_updates.len(), 2);
+        assert!(count_updates[0].contains("GREATEST(following_count - 1, 0)"));
+        assert!(count_updates[1].contains("GREATEST(followers_count - 1, 0)"));
+    }
+
+    #[tokio::test]
+    async fn test_undo_follow_withdraws_pending_request() {
+        let request = follow_request::Model {
+            id: "request1".to_string(),
+            follower_id: "actor1".to_string(),
+            followee_id: "alice".to_string(),
+            follower_host: Some("remote.example".to_string()),
+            followee_host: None,
+            follower_inbox: None,
+            follower_shared_inbox: None,
+            created_at: chrono::Utc::now().into(),
+        };
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_actor()], [local_user()]])
+                .into_connection(),
+        );
+        let follow_request_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[request.clone()], [request]])
+                .append_exec_results([exec_ok()])
+                .into_connection(),
+        );
+        let (following_db, reaction_db, note_db) = (empty_db(), empty_db(), empty_db());
+
+        let undo = processor(&user_db, &following_db, &reaction_db, &note_db)
+            .with_follow_request_repo(FollowRequestRepository::new(Arc::clone(&follow_request_db)));
+        let result = undo
+            .process(&undo_of(
+                "Follow",
+                Some("https://local.example/users/alice"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(result, UndoResult::Unfollowed);
+        drop(undo);
+
+        assert!(
+            statements(follow_request_db)
+                .iter()
+                .any(|stmt| stmt.starts_with(r#"DELETE FROM "follow_request""#))
+        );
+        assert!(statements(following_db).is_empty());
+        assert!(
+            !statements(user_db)
+                .iter()
+                .any(|stmt| stmt.starts_with(r#"UPDATE "user""#))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undo_emoji_react_removes_reaction() {
+        let stored = reaction::Model {
+            id: "reaction1".to_string(),
+            user_id: "actor1".to_string(),
+            note_id: "note1".to_string(),
+            reaction: ":blobcat:".to_string(),
+            created_at: chrono::Utc::now().into(),
+        };
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_actor()]])
+                .into_connection(),
+        );
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[test_note("note1", "author1", None)]])
+                .append_exec_results([exec_ok()])
+                .into_connection(),
+        );
+        let reaction_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[stored.clone()], [stored]])
+                .append_exec_results([exec_ok()])
+                .into_connection(),
+        );
+        let following_db = empty_db();
+
+        let undo = processor(&user_db, &following_db, &reaction_db, &note_db);
+        let result = undo
+            .process(&undo_of(
+                "EmojiReact",
+                Some("https://remote.example/notes/note1"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(result, UndoResult::Unreacted);
+        drop(undo);
+
+        assert!(
+            statements(reaction_db)
+                .iter()
+                .any(|stmt| stmt.starts_with(r#"DELETE FROM "reaction""#))
+        );
+        assert!(
+            statements(note_db)
+                .iter()
+                .any(|stmt| stmt.contains("GREATEST(reaction_count - 1, 0)"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undo_announce_deletes_renote_and_decrements_count() {
+        let renote = test_note("renote1", "actor1", Some("note1"));
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_actor()]])
+                .into_connection(),
+        );
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[renote]])
+                .append_exec_results([exec_ok(), exec_ok()])
+                .into_connection(),
+        );
+        let (following_db, reaction_db) = (empty_db(), empty_db());
+
+        let undo = processor(&user_db, &following_db, &reaction_db, &note_db);
+        let result = undo.process(&undo_of("Announce", None)).await.unwrap();
+        assert_eq!(result, UndoResult::Unrenoted);
+        drop(undo);
+
+        let log = statements(note_db);
+        let delete = log
+            .iter()
+            .find(|stmt| stmt.starts_with(r#"DELETE FROM "note""#))
+            .unwrap();
+        assert!(delete.contains("renote1"));
+        let decrement = log
+            .iter()
+            .find(|stmt| stmt.contains("GREATEST(renote_count - 1, 0)"))
+            .unwrap();
+        assert!(decrement.contains("note1"));
+    }
+
+    #[tokio::test]
+    async fn test_undo_block_removes_block() {
+        let block = blocking::Model {
+            id: "block1".to_string(),
+            blocker_id: "actor1".to_string(),
+            blockee_id: "alice".to_string(),
+            created_at: chrono::Utc::now().into(),
+        };
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_actor()], [local_user()]])
+                .into_connection(),
+        );
+        let blocking_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[block.clone()], [block]])
+                .append_exec_results([exec_ok()])
+                .into_connection(),
+        );
+        let (following_db, reaction_db, note_db) = (empty_db(), empty_db(), empty_db());
+
+        let undo = processor(&user_db, &following_db, &reaction_db, &note_db)
+            .with_blocking_repo(BlockingRepository::new(Arc::clone(&blocking_db)));
+        let result = undo
+            .process(&undo_of("Block", Some("https://local.example/users/alice")))
+            .await
+            .unwrap();
+        assert_eq!(result, UndoResult::Unblocked);
+        drop(undo);
+
+        assert!(
+            statements(blocking_db)
+                .iter()
+                .any(|stmt| stmt.starts_with(r#"DELETE FROM "blocking""#))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repeated_undo_is_noop() {
+        let user_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[remote_actor()], [local_user()]])
+                .into_connection(),
+        );
+        let following_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<following::Model>::new()])
+                .into_connection(),
+        );
+        let follow_request_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<follow_request::Model>::new()])
+                .into_connection(),
+        );
+        let note_db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<note::Model>::new()])
+                .into_connection(),
+        );
+        let reaction_db = empty_db();
+
+        let undo = processor(&user_db, &following_db, &reaction_db, &note_db)
+            .with_follow_request_repo(FollowRequestRepository::new(Arc::clone(&follow_request_db)));
+        let follow = undo
+            .process(&undo_of(
+                "Follow",
+                Some("https://local.example/users/alice"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(follow, UndoResult::NothingToUndo);
+        let announce = undo.process(&undo_of("Announce", None)).await.unwrap();
+        assert_eq!(announce, UndoResult::NothingToUndo);
+        drop(undo);
+
+        let writes = |log: Vec<String>| {
+            log.iter()
+                .filter(|stmt| stmt.starts_with("DELETE") || stmt.starts_with("UPDATE"))
+                .count()
+        };
+        assert_eq!(writes(statements(user_db)), 0);
+        assert_eq!(writes(statements(following_db)), 0);
+        assert_eq!(writes(statements(follow_request_db)), 0);
+        assert_eq!(writes(statements(note_db)), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_inner_type_is_ignored() {
+        let (user_db, following_db, reaction_db, note_db) =
+            (empty_db(), empty_db(), empty_db(), empty_db());
+
+        let undo = processor(&user_db, &following_db, &reaction_db, &note_db);
+        let result = undo.process(&undo_of("Listen", None)).await.unwrap();
+        assert_eq!(result, UndoResult::Ignored);
+    }
+}
@@ -1,15 +1,16 @@
 //! Update activity processor.
 
-use misskey_common::{AppError, AppResult};
+use misskey_common::{AppError, AppResult, normalize_hashtag};
 use misskey_db::{
     entities::{note, user},
-    repositories::{NoteRepository, UserRepository},
+    repositories::{EmojiRepository, NoteRepository, UserRepository},
 };
 use sea_orm::Set;
 use serde_json::json;
 use tracing::info;
 
-use crate::activities::{UpdateActivity, UpdateObject};
+use super::EmojiImporter;
+use crate::activities::{EmojiTag, UpdateActivity, UpdateObject};
 use crate::actors::ApPerson;
 use crate::objects::ApNote;
 
@@ -20,6 +21,8 @@ pub enum UpdateResult {
     ActorUpdated,
     /// Note was updated.
     NoteUpdated,
+    /// Remote custom emoji was updated.
+    EmojiUpdated,
     /// Unknown object type.
     Ignored,
 }
@@ -29,6 +32,7 @@ pub enum UpdateResult {
 pub struct UpdateProcessor {
     user_repo: UserRepository,
     note_repo: NoteRepository,
+    emoji_importer: Option<EmojiImporter>,
 }
 
 impl UpdateProcessor {
@@ -38,9 +42,17 @@ impl UpdateProcessor {
         Self {
             user_repo,
             note_repo,
+            emoji_importer: None,
         }
     }
 
+    /// Enable handling of `Update(Emoji)` activities.
+    #[must_use]
+    pub fn with_emoji_repo(mut self, emoji_repo: EmojiRepository) -> Self {
+        self.emoji_importer = Some(EmojiImporter::new(emoji_repo));
+        self
+    }
+
     /// Process an incoming Update activity.
     pub async fn process(&self, activity: &UpdateActivity) -> AppResult<UpdateResult> {
         info!(
@@ -51,6 +63,7 @@ impl UpdateProcessor {
         match &activity.object {
             UpdateObject::Person(person) => self.update_actor_from_person(activity, person).await,
             UpdateObject::Note(ap_note) => self.update_note_from_activity(activity, ap_note).await,
+            UpdateObject::Emoji(tag) => self.update_emoji(activity, tag).await,
             UpdateObject::ObjectUrl(_url) => {
                 // Just a URL reference, we'd need to fetch the object
                 // For now, just ignore
@@ -96,6 +109,10 @@ impl UpdateProcessor {
             model.is_locked = Set(manually_approves);
         }
 
+        if let Some(ref followers) = person.followers {
+            model.followers_uri = Set(Some(followers.to_string()));
+        }
+
         model.last_fetched_at = Set(Some(chrono::Utc::now().into()));
         model.updated_at = Set(Some(chrono::Utc::now().into()));
 
@@ -109,6 +126,41 @@ impl UpdateProcessor {
         Ok(UpdateResult::ActorUpdated)
     }
 
+    /// Refresh a remote custom emoji from an embedded Emoji object.
+    ///
+    /// The emoji is recorded under the actor's host, so a remote server can
+    /// only update its own emojis.
+    async fn update_emoji(
+        &self,
+        activity: &UpdateActivity,
+        tag: &EmojiTag,
+    ) -> AppResult<UpdateResult> {
+        let Some(ref importer) = self.emoji_importer else {
+            info!("Emoji import disabled, ignoring Update(Emoji)");
+            return Ok(UpdateResult::Ignored);
+        };
+
+        if tag.kind != "Emoji" {
+            info!(kind = %tag.kind, "Update with unsupported object type, ignoring");
+            return Ok(UpdateResult::Ignored);
+        }
+
+        let host = activity
+            .actor
+            .host_str()
+            .ok_or_else(|| AppError::BadRequest("Invalid actor URL: no host".to_string()))?;
+
+        let emoji = importer.upsert(tag, host).await?;
+
+        info!(
+            name = %emoji.name,
+            host = %host,
+            "Remote emoji updated"
+        );
+
+        Ok(UpdateResult::EmojiUpdated)
+    }
+
     /// Update a note from an embedded Note object.
     async fn update_note_from_activity(
         &self,
@@ -169,11 +221,7 @@ impl UpdateProcessor {
             .map(|tags| {
                 tags.iter()
                     .filter(|t| t.kind == "Hashtag")
-                    .filter_map(|t| {
-                        t.name
-                            .as_ref()
-                            .map(|n| n.trim_start_matches('#').to_string())
-                    })
+                    .filter_map(|t| t.name.as_deref().map(normalize_hashtag))
                     .collect()
             })
             .unwrap_or_default();
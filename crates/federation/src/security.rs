@@ -9,7 +9,8 @@ use chrono::{DateTime, Duration, Utc};
 use fred::clients::Client as RedisClient;
 use fred::interfaces::KeysInterface;
 use fred::types::{Expiration, SetOptions};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
 use tracing::{debug, info, warn};
 
 /// Default maximum clock skew allowed: 5 minutes
@@ -125,10 +126,23 @@ impl ReplayProtection {
     }
 }
 
+/// Default key prefix of rate limit buckets.
+const DEFAULT_RATE_LIMIT_PREFIX: &str = "federation_rate";
+
+/// Storage of rate limit counters.
+#[derive(Clone)]
+enum RateLimitStore {
+    /// Shared across server instances.
+    Redis(Arc<RedisClient>),
+    /// Per process, counting the current window of each key.
+    Memory(Arc<Mutex<HashMap<String, (i64, u64)>>>),
+}
+
 /// Per-instance rate limiter for federation.
 #[derive(Clone)]
 pub struct FederationRateLimiter {
-    redis: Arc<RedisClient>,
+    store: RateLimitStore,
+    key_prefix: &'static str,
     window_secs: i64,
     max_activities: u64,
 }
@@ -137,11 +151,11 @@ impl FederationRateLimiter {
     /// Create a new rate limiter with default settings.
     #[must_use]
     pub const fn new(redis: Arc<RedisClient>) -> Self {
-        Self {
+        Self::with_settings(
             redis,
-            window_secs: DEFAULT_RATE_LIMIT_WINDOW_SECS,
-            max_activities: DEFAULT_RATE_LIMIT_MAX,
-        }
+            DEFAULT_RATE_LIMIT_WINDOW_SECS,
+            DEFAULT_RATE_LIMIT_MAX,
+        )
     }
 
     /// Create with custom settings.
@@ -152,36 +166,73 @@ impl FederationRateLimiter {
         max_activities: u64,
     ) -> Self {
         Self {
-            redis,
+            store: RateLimitStore::Redis(redis),
+            key_prefix: DEFAULT_RATE_LIMIT_PREFIX,
             window_secs,
             max_activities,
         }
     }
 
+    /// Create an in-process rate limiter for single-instance deployments and tests.
+    #[must_use]
+    pub fn in_memory(window_secs: i64, max_activities: u64) -> Self {
+        Self {
+            store: RateLimitStore::Memory(Arc::new(Mutex::new(HashMap::new()))),
+            key_prefix: DEFAULT_RATE_LIMIT_PREFIX,
+            window_secs,
+            max_activities,
+        }
+    }
+
+    /// Count into separate buckets, e.g. one per inbox.
+    #[must_use]
+    pub const fn with_prefix(mut self, key_prefix: &'static str) -> Self {
+        self.key_prefix = key_prefix;
+        self
+    }
+
+    /// Increment the counter of `key` in the current window.
+    async fn increment(&self, key: &str, window: i64) -> Result<u64, RateLimitError> {
+        match &self.store {
+            RateLimitStore::Redis(redis) => {
+                let redis_key = format!("{key}:{window}");
+                let count: u64 = redis
+                    .incr(redis_key.clone())
+                    .await
+                    .map_err(|e| RateLimitError::Redis(e.to_string()))?;
+
+                // Set expiry on first increment
+                if count == 1 {
+                    redis
+                        .expire::<(), _>(redis_key, self.window_secs, None)
+                        .await
+                        .map_err(|e| RateLimitError::Redis(e.to_string()))?;
+                }
+                Ok(count)
+            }
+            RateLimitStore::Memory(counters) => {
+                let mut counters = counters.lock().unwrap_or_else(PoisonError::into_inner);
+                let entry = counters.entry(key.to_string()).or_insert((window, 0));
+                if entry.0 != window {
+                    *entry = (window, 0);
+                }
+                entry.1 += 1;
+                Ok(entry.1)
+            }
+        }
+    }
+
     /// Check if an instance is within rate limits.
     /// Returns Ok(remaining) if allowed, Err if rate limited.
     pub async fn check(&self, instance_host: &str) -> Result<u64, RateLimitError> {
         let window = current_window(self.window_secs);
-        let key = format!("federation_rate:{instance_host}:{window}");
-
-        // Increment counter
-        let count: u64 = self
-            .redis
-            .incr(key.clone())
-            .await
-            .map_err(|e| RateLimitError::Redis(e.to_string()))?;
-
-        // Set expiry on first increment
-        if count == 1 {
-            self.redis
-                .expire::<(), _>(key, self.window_secs, None)
-                .await
-                .map_err(|e| RateLimitError::Redis(e.to_string()))?;
-        }
+        let key = format!("{}:{instance_host}", self.key_prefix);
+        let count = self.increment(&key, window).await?;
 
         if count > self.max_activities {
             warn!(
                 instance = %instance_host,
+                bucket = self.key_prefix,
                 count = count,
                 limit = self.max_activities,
                 "Rate limit exceeded for instance"
@@ -196,6 +247,7 @@ impl FederationRateLimiter {
         let remaining = self.max_activities.saturating_sub(count);
         debug!(
             instance = %instance_host,
+            bucket = self.key_prefix,
             count = count,
             remaining = remaining,
             "Rate limit check passed"
@@ -207,21 +259,30 @@ impl FederationRateLimiter {
     /// Get current rate limit status for an instance.
     pub async fn status(&self, instance_host: &str) -> Result<RateLimitStatus, RateLimitError> {
         let window = current_window(self.window_secs);
-        let key = format!("federation_rate:{instance_host}:{window}");
-
-        let count: Option<u64> = self
-            .redis
-            .get(key.clone())
-            .await
-            .map_err(|e| RateLimitError::Redis(e.to_string()))?;
-
-        let ttl: i64 = self
-            .redis
-            .ttl(key)
-            .await
-            .map_err(|e| RateLimitError::Redis(e.to_string()))?;
-
-        let count = count.unwrap_or(0);
+        let key = format!("{}:{instance_host}", self.key_prefix);
+
+        let (count, ttl) = match &self.store {
+            RateLimitStore::Redis(redis) => {
+                let redis_key = format!("{key}:{window}");
+                let count: Option<u64> = redis
+                    .get(redis_key.clone())
+                    .await
+                    .map_err(|e| RateLimitError::Redis(e.to_string()))?;
+                let ttl: i64 = redis
+                    .ttl(redis_key)
+                    .await
+                    .map_err(|e| RateLimitError::Redis(e.to_string()))?;
+                (count.unwrap_or(0), ttl)
+            }
+            RateLimitStore::Memory(counters) => {
+                let counters = counters.lock().unwrap_or_else(PoisonError::into_inner);
+                let count = counters
+                    .get(&key)
+                    .filter(|(counted_window, _)| *counted_window == window)
+                    .map_or(0, |(_, count)| *count);
+                (count, 0)
+            }
+        };
 
         Ok(RateLimitStatus {
             instance: instance_host.to_string(),
@@ -420,6 +481,26 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_in_memory_rate_limit_is_per_host() {
+        let shared_inbox = FederationRateLimiter::in_memory(60, 1).with_prefix("shared_inbox_rate");
+
+        assert!(shared_inbox.check("a.example").await.is_ok());
+        assert!(matches!(
+            shared_inbox.check("a.example").await,
+            Err(RateLimitError::Exceeded { .. })
+        ));
+        assert!(shared_inbox.check("b.example").await.is_ok());
+        assert_eq!(
+            shared_inbox
+                .status("a.example")
+                .await
+                .unwrap()
+                .current_count,
+            2
+        );
+    }
+
     #[test]
     fn test_current_window() {
         let window1 = current_window(60);
@@ -107,6 +107,7 @@ mod mastodon {
             kind: "Mention".to_string(),
             href: Some(test_url("/users/bob")),
             name: Some("@bob".to_string()),
+            icon: None,
         }]);
 
         let json = serde_json::to_value(&note).unwrap();
@@ -133,6 +134,7 @@ mod mastodon {
             kind: "Hashtag".to_string(),
             href: Some(test_url("/tags/rust")),
             name: Some("#rust".to_string()),
+            icon: None,
         }]);
 
         let json = serde_json::to_value(&note).unwrap();
@@ -137,6 +137,7 @@ fn test_note_with_mentions() {
         kind: "Mention".to_string(),
         href: Some(test_url("/users/bob")),
         name: Some("@bob".to_string()),
+        icon: None,
     }]);
 
     let json = serde_json::to_value(&note).unwrap();
@@ -160,11 +161,13 @@ fn test_note_with_hashtags() {
             kind: "Hashtag".to_string(),
             href: Some(test_url("/tags/rust")),
             name: Some("#rust".to_string()),
+            icon: None,
         },
         ApTag {
             kind: "Hashtag".to_string(),
             href: Some(test_url("/tags/programming")),
             name: Some("#programming".to_string()),
+            icon: None,
         },
     ]);
 
@@ -469,6 +472,7 @@ fn test_note_with_many_tags() {
             kind: "Hashtag".to_string(),
             href: Some(test_url(&format!("/tags/tag{i}"))),
             name: Some(format!("#tag{i}")),
+            icon: None,
         })
         .collect();
 
@@ -618,6 +622,7 @@ fn test_tag_with_minimal_fields() {
         kind: "Hashtag".to_string(),
         href: None,
         name: Some("#minimal".to_string()),
+        icon: None,
     };
 
     let json = serde_json::to_value(&tag).unwrap();
@@ -0,0 +1,212 @@
+//! `ActivityPub` implementation of the remote object fetcher.
+//!
+//! Fetches objects for `ap/show` and stores them with the same processors
+//! that handle inbox activities.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use misskey_common::{AppError, AppResult};
+use misskey_core::{RemoteObjectFetcher, ResolvedObject};
+use misskey_db::repositories::{
+    DriveFileRepository, EmojiRepository, NoteRepository, UserProfileRepository, UserRepository,
+};
+use misskey_federation::{
+    ActorFetcher, ApClient, ApNote, CreateProcessor, FeaturedImporter, FederationPolicy,
+    ObjectFetcher,
+};
+use serde_json::Value;
+use tracing::warn;
+use url::Url;
+
+/// Actor types stored as users.
+const ACTOR_TYPES: &[&str] = &["Person", "Service", "Application", "Group", "Organization"];
+
+/// Object types stored as notes.
+const NOTE_TYPES: &[&str] = &["Note", "Question"];
+
+/// Remote object fetcher backed by the federation client.
+#[derive(Clone)]
+pub struct ApObjectFetcher {
+    ap_client: ApClient,
+    object_fetcher: Arc<dyn ObjectFetcher>,
+    actor_fetcher: ActorFetcher,
+    create_processor: CreateProcessor,
+    profile_repo: UserProfileRepository,
+    federation_policy: FederationPolicy,
+}
+
+impl ApObjectFetcher {
+    /// Create a new fetcher.
+    #[must_use]
+    pub fn new(
+        note_repo: NoteRepository,
+        drive_file_repo: DriveFileRepository,
+        user_repo: UserRepository,
+        emoji_repo: EmojiRepository,
+        profile_repo: UserProfileRepository,
+        ap_client: ApClient,
+    ) -> Self {
+        Self {
+            actor_fetcher: ActorFetcher::new(user_repo.clone(), ap_client.clone()),
+            create_processor: CreateProcessor::new(
+                note_repo,
+                drive_file_repo,
+                user_repo,
+                ap_client.clone(),
+            )
+            .with_emoji_repo(emoji_repo),
+            profile_repo,
+            object_fetcher: Arc::new(ap_client.clone()),
+            ap_client,
+            federation_policy: FederationPolicy::open(),
+        }
+    }
+
+    /// Link fetched notes to notes on this server (`base_url`) by their ID.
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        self.create_processor = self.create_processor.with_base_url(base_url);
+        self
+    }
+
+    /// Refuse to fetch from hosts the federation policy does not allow.
+    #[must_use]
+    pub fn with_federation_policy(mut self, policy: FederationPolicy) -> Self {
+        self.create_processor = self.create_processor.with_federation_policy(policy.clone());
+        self.federation_policy = policy;
+        self
+    }
+
+    /// Fetch remote objects through `fetcher`.
+    #[must_use]
+    pub fn with_object_fetcher(mut self, fetcher: Arc<dyn ObjectFetcher>) -> Self {
+        self.create_processor = self
+            .create_processor
+            .with_object_fetcher(Arc::clone(&fetcher));
+        self.object_fetcher = fetcher;
+        self
+    }
+}
+
+#[async_trait]
+impl RemoteObjectFetcher for ApObjectFetcher {
+    async fn fetch(&self, uri: &Url) -> AppResult<ResolvedObject> {
+        let object = self.fetch_raw(uri).await?;
+
+        // Only trust objects served by the host that owns them
+        let id = object
+            .get("id")
+            .and_then(Value::as_str)
+            .and_then(|id| Url::parse(id).ok())
+            .ok_or_else(|| AppError::BadRequest("Remote object has no ID".to_string()))?;
+        if id.host_str() != uri.host_str() {
+            return Err(AppError::BadRequest(
+                "Remote object ID does not match its host".to_string(),
+            ));
+        }
+
+        match object.get("type").and_then(Value::as_str) {
+            Some(kind) if ACTOR_TYPES.contains(&kind) => {
+                let user = self.actor_fetcher.find_or_create(&object, &id).await?;
+
+                // Pinned notes are shown with the profile, so backfill them now
+                let importer = FeaturedImporter::new(
+                    self.create_processor.clone(),
+                    self.profile_repo.clone(),
+                    self.ap_client.clone(),
+                )
+                .with_object_fetcher(Arc::clone(&self.object_fetcher));
+                if let Err(e) = importer.import(&user).await {
+                    warn!(user_id = %user.id, error = %e, "Failed to import featured notes");
+                }
+
+                Ok(ResolvedObject::User(user))
+            }
+            Some(kind) if NOTE_TYPES.contains(&kind) => {
+                let ap_note: ApNote = serde_json::from_value(object)
+                    .map_err(|e| AppError::BadRequest(format!("Invalid note object: {e}")))?;
+                Ok(ResolvedObject::Note(
+                    self.create_processor.ingest_note(&ap_note).await?,
+                ))
+            }
+            kind => Err(AppError::BadRequest(format!(
+                "Unsupported object type: {}",
+                kind.unwrap_or("none")
+            ))),
+        }
+    }
+
+    async fn fetch_raw(&self, uri: &Url) -> AppResult<Value> {
+        if !self.federation_policy.is_url_allowed(uri).await? {
+            return Err(AppError::Forbidden(
+                "Federation with this host is not allowed".to_string(),
+            ));
+        }
+
+        self.object_fetcher
+            .fetch_object(uri)
+            .await
+            .map_err(|e| AppError::Federation(format!("Failed to fetch remote object: {e}")))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use misskey_federation::ApClientError;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use serde_json::json;
+
+    /// Fetcher serving a single fixed object.
+    struct FixedFetcher(Value);
+
+    #[async_trait]
+    impl ObjectFetcher for FixedFetcher {
+        async fn fetch_object(&self, _url: &Url) -> Result<Value, ApClientError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_note_attributed_to_another_host() {
+        let note_db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let fetcher = ApObjectFetcher::new(
+            NoteRepository::new(Arc::clone(&note_db)),
+            DriveFileRepository::new(Arc::new(
+                MockDatabase::new(DatabaseBackend::Postgres).into_connection(),
+            )),
+            UserRepository::new(Arc::new(
+                MockDatabase::new(DatabaseBackend::Postgres).into_connection(),
+            )),
+            EmojiRepository::new(Arc::new(
+                MockDatabase::new(DatabaseBackend::Postgres).into_connection(),
+            )),
+            UserProfileRepository::new(Arc::new(
+                MockDatabase::new(DatabaseBackend::Postgres).into_connection(),
+            )),
+            ApClient::new("https://local.example"),
+        )
+        .with_object_fetcher(Arc::new(FixedFetcher(json!({
+            "type": "Note",
+            "id": "https://evil.example/notes/1",
+            "attributedTo": "https://victim.example/users/bob",
+            "content": "<p>Spoofed</p>",
+            "published": "2024-06-01T12:00:00Z",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"]
+        }))));
+
+        let result = fetcher
+            .fetch(&Url::parse("https://evil.example/notes/1").unwrap())
+            .await;
+        drop(fetcher);
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        let log = Arc::try_unwrap(note_db)
+            .ok()
+            .unwrap()
+            .into_transaction_log();
+        assert!(log.is_empty());
+    }
+}
@@ -10,20 +10,26 @@
 //! - **Scheduler**: Periodic tasks (cleanup, aggregation)
 //! - **Shared Inbox**: Optimized batch delivery
 
+pub mod ap_resolve;
 pub mod delivery_impl;
 pub mod jobs;
+pub mod notification_debounce;
 pub mod pubsub;
 pub mod rate_limit;
 pub mod retry;
 pub mod scheduler;
 pub mod shared_inbox;
+pub mod timeline_cache;
 pub mod workers;
 
+pub use ap_resolve::ApObjectFetcher;
 pub use delivery_impl::RedisDeliveryService;
 pub use jobs::*;
+pub use notification_debounce::RedisNotificationDebouncer;
 pub use pubsub::{PubSubEvent, PubSubSseBridge, RedisPubSub, channels as pubsub_channels};
 pub use rate_limit::{InstanceRateLimiter, RateLimitConfig, RateLimitResult};
 pub use retry::{DeadLetterEntry, RetryConfig};
 pub use scheduler::{JobExecutor, ScheduledJob, SchedulerConfig, SchedulerState};
 pub use shared_inbox::{BatchDeliveryTarget, RecipientInfo};
+pub use timeline_cache::RedisTimelineCache;
 pub use workers::*;
@@ -0,0 +1,86 @@
+//! Redis-backed notification debouncer.
+//!
+//! Shares reaction aggregation windows across server instances and queue
+//! workers so that a reaction burst yields one notification cluster-wide.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use fred::clients::Client;
+use fred::interfaces::KeysInterface;
+use fred::types::{Expiration, SetOptions};
+use misskey_common::{AppError, AppResult};
+use misskey_core::services::NotificationDebouncer;
+
+/// Notification debouncer storing window owners in Redis.
+#[derive(Clone)]
+pub struct RedisNotificationDebouncer {
+    redis: Arc<Client>,
+    prefix: String,
+}
+
+impl RedisNotificationDebouncer {
+    /// Create a new Redis debouncer with the given key prefix.
+    #[must_use]
+    pub fn new(redis: Arc<Client>, prefix: &str) -> Self {
+        Self {
+            redis,
+            prefix: prefix.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationDebouncer for RedisNotificationDebouncer {
+    async fn claim(
+        &self,
+        key: &str,
+        notification_id: &str,
+        window: Duration,
+    ) -> AppResult<Option<String>> {
+        let key = format!("{}:{key}", self.prefix);
+        let window_secs = i64::try_from(window.as_secs()).unwrap_or(i64::MAX).max(1);
+
+        // SET NX opens the window atomically; whoever loses reads the owner.
+        let opened: Option<String> = self
+            .redis
+            .set(
+                key.clone(),
+                notification_id,
+                Some(Expiration::EX(window_secs)),
+                Some(SetOptions::NX),
+                false,
+            )
+            .await
+            .map_err(|e| AppError::Internal(format!("Redis error: {e}")))?;
+        if opened.is_some() {
+            return Ok(None);
+        }
+
+        let owner: Option<String> = self
+            .redis
+            .get(key)
+            .await
+            .map_err(|e| AppError::Internal(format!("Redis error: {e}")))?;
+        Ok(owner)
+    }
+
+    async fn transfer(&self, key: &str, notification_id: &str) -> AppResult<()> {
+        let key = format!("{}:{key}", self.prefix);
+
+        // SET XX KEEPTTL only replaces the owner of a window that is still open
+        let _: Option<String> = self
+            .redis
+            .set(
+                key,
+                notification_id,
+                Some(Expiration::KEEPTTL),
+                Some(SetOptions::XX),
+                false,
+            )
+            .await
+            .map_err(|e| AppError::Internal(format!("Redis error: {e}")))?;
+        Ok(())
+    }
+}
@@ -13,7 +13,9 @@ use fred::error::{Error as RedisError, ErrorKind as RedisErrorKind};
 use fred::interfaces::{ClientLike, EventInterface, PubsubInterface};
 use fred::types::config::Config as RedisConfig;
 use misskey_common::AppResult;
-use misskey_core::services::EventPublisher;
+use misskey_core::services::{
+    EventPublisher, TimelineChannel, lost_timeline_channels, timeline_channels,
+};
 use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
 use tracing::{debug, info, warn};
@@ -55,6 +57,8 @@ pub enum PubSubEvent {
     NoteDeleted { id: String, user_id: String },
     /// Note updated.
     NoteUpdated { id: String },
+    /// Note is no longer visible on this channel (visibility narrowed).
+    NoteUnavailable { id: String, user_id: String },
     /// New notification.
     Notification {
         id: String,
@@ -98,6 +102,14 @@ pub enum PubSubEvent {
     Announcement { id: String, text: String },
 }
 
+/// Redis channel carrying a timeline's events.
+const fn timeline_channel(timeline: TimelineChannel) -> &'static str {
+    match timeline {
+        TimelineChannel::Global => channels::GLOBAL_TIMELINE,
+        TimelineChannel::Local => channels::LOCAL_TIMELINE,
+    }
+}
+
 /// Redis Pub/Sub manager for event distribution.
 #[derive(Clone)]
 pub struct RedisPubSub {
@@ -215,15 +227,8 @@ impl RedisPubSub {
         self.publish(channels::NOTES, &event).await?;
 
         // Publish to appropriate timeline based on visibility
-        match visibility {
-            "public" => {
-                self.publish(channels::GLOBAL_TIMELINE, &event).await?;
-                self.publish(channels::LOCAL_TIMELINE, &event).await?;
-            }
-            "home" | "followers" => {
-                self.publish(channels::LOCAL_TIMELINE, &event).await?;
-            }
-            _ => {}
+        for timeline in timeline_channels(visibility) {
+            self.publish(timeline_channel(*timeline), &event).await?;
         }
 
         Ok(())
@@ -355,6 +360,29 @@ impl RedisPubSub {
         self.publish(channels::NOTES, &event).await
     }
 
+    /// Publish a note unavailable event after its visibility was narrowed.
+    ///
+    /// Only the timelines that lose access receive the event; the note itself
+    /// stays visible to its author and remaining audience.
+    pub async fn publish_note_unavailable(
+        &self,
+        id: &str,
+        user_id: &str,
+        old_visibility: &str,
+        new_visibility: &str,
+    ) -> Result<(), RedisError> {
+        let event = PubSubEvent::NoteUnavailable {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+        };
+
+        for timeline in lost_timeline_channels(old_visibility, new_visibility) {
+            self.publish(timeline_channel(timeline), &event).await?;
+        }
+
+        Ok(())
+    }
+
     /// Publish an unfollow event.
     pub async fn publish_unfollowed(
         &self,
@@ -471,6 +499,18 @@ impl EventPublisher for RedisPubSub {
             .map_err(|e| misskey_common::AppError::Internal(e.to_string()))
     }
 
+    async fn publish_note_unavailable(
+        &self,
+        id: &str,
+        user_id: &str,
+        old_visibility: &str,
+        new_visibility: &str,
+    ) -> AppResult<()> {
+        Self::publish_note_unavailable(self, id, user_id, old_visibility, new_visibility)
+            .await
+            .map_err(|e| misskey_common::AppError::Internal(e.to_string()))
+    }
+
     async fn publish_followed(&self, follower_id: &str, followee_id: &str) -> AppResult<()> {
         Self::publish_followed(self, follower_id, followee_id)
             .await
@@ -604,6 +644,28 @@ mod tests {
         assert_eq!(channels::USER_PREFIX, "misskey:user:");
     }
 
+    #[test]
+    fn test_narrowing_public_note_leaves_both_timelines() {
+        let lost: Vec<_> = lost_timeline_channels("public", "followers")
+            .into_iter()
+            .map(timeline_channel)
+            .collect();
+        assert_eq!(lost, [channels::GLOBAL_TIMELINE, channels::LOCAL_TIMELINE]);
+
+        let event = PubSubEvent::NoteUnavailable {
+            id: "note1".to_string(),
+            user_id: "user1".to_string(),
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains("\"type\":\"noteUnavailable\""));
+    }
+
+    #[test]
+    fn test_narrowing_non_public_note_leaves_no_timeline() {
+        assert!(lost_timeline_channels("home", "specified").is_empty());
+        assert!(lost_timeline_channels("followers", "home").is_empty());
+    }
+
     #[test]
     fn test_pubsub_event_serialization() {
         let event = PubSubEvent::NoteCreated {
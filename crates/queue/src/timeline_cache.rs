@@ -0,0 +1,82 @@
+//! Redis-backed timeline cache.
+//!
+//! Shares cached timeline pages across server instances so that a note
+//! created on one instance invalidates the page served by all of them.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use fred::clients::Client;
+use fred::interfaces::KeysInterface;
+use fred::types::Expiration;
+use misskey_common::{AppError, AppResult};
+use misskey_core::services::{CachedTimeline, TimelineCache};
+use misskey_db::entities::note;
+
+/// Timeline cache storing pages as JSON in Redis.
+#[derive(Clone)]
+pub struct RedisTimelineCache {
+    redis: Arc<Client>,
+    prefix: String,
+}
+
+impl RedisTimelineCache {
+    /// Create a new Redis timeline cache with the given key prefix.
+    #[must_use]
+    pub fn new(redis: Arc<Client>, prefix: &str) -> Self {
+        Self {
+            redis,
+            prefix: prefix.to_string(),
+        }
+    }
+
+    fn key(&self, timeline: CachedTimeline) -> String {
+        format!("{}:{}", self.prefix, timeline.key())
+    }
+}
+
+#[async_trait]
+impl TimelineCache for RedisTimelineCache {
+    async fn get(&self, timeline: CachedTimeline) -> AppResult<Option<Vec<note::Model>>> {
+        let cached: Option<String> = self
+            .redis
+            .get(self.key(timeline))
+            .await
+            .map_err(|e| AppError::Internal(format!("Redis error: {e}")))?;
+
+        cached
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(|e| AppError::Internal(format!("Invalid cached timeline: {e}")))
+    }
+
+    async fn set(
+        &self,
+        timeline: CachedTimeline,
+        notes: &[note::Model],
+        ttl: Duration,
+    ) -> AppResult<()> {
+        let json = serde_json::to_string(notes)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize timeline: {e}")))?;
+        let ttl_secs = i64::try_from(ttl.as_secs()).unwrap_or(i64::MAX).max(1);
+
+        self.redis
+            .set::<(), _, _>(
+                self.key(timeline),
+                json,
+                Some(Expiration::EX(ttl_secs)),
+                None,
+                false,
+            )
+            .await
+            .map_err(|e| AppError::Internal(format!("Redis error: {e}")))
+    }
+
+    async fn invalidate(&self, timeline: CachedTimeline) -> AppResult<()> {
+        self.redis
+            .del::<(), _>(self.key(timeline))
+            .await
+            .map_err(|e| AppError::Internal(format!("Redis error: {e}")))
+    }
+}
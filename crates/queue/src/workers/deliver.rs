@@ -4,9 +4,10 @@ use apalis::prelude::*;
 use chrono::Utc;
 use misskey_common::{calculate_digest, crypto::parse_private_key, sign_request};
 use misskey_db::repositories::UserKeypairRepository;
+use misskey_federation::FederationPolicy;
 use reqwest::Client;
 use std::collections::HashMap;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 use url::Url;
 
 use crate::jobs::DeliverJob;
@@ -17,6 +18,7 @@ pub struct DeliverContext {
     pub keypair_repo: UserKeypairRepository,
     pub http_client: Client,
     pub user_agent: String,
+    pub federation_policy: FederationPolicy,
 }
 
 impl DeliverContext {
@@ -34,8 +36,16 @@ impl DeliverContext {
                 .build()
                 .expect("Failed to create HTTP client"),
             user_agent,
+            federation_policy: FederationPolicy::open(),
         }
     }
+
+    /// Set the federation policy used to skip deliveries to disallowed hosts.
+    #[must_use]
+    pub fn with_federation_policy(mut self, policy: FederationPolicy) -> Self {
+        self.federation_policy = policy;
+        self
+    }
 }
 
 /// Worker function for delivering activities.
@@ -65,6 +75,15 @@ async fn deliver_activity(
     job: &DeliverJob,
     ctx: &DeliverContext,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Parse inbox URL
+    let inbox_url = Url::parse(&job.inbox)?;
+
+    // Silently skip hosts we don't federate with
+    if !ctx.federation_policy.is_url_allowed(&inbox_url).await? {
+        debug!(inbox = %job.inbox, "Skipping delivery to host not permitted by federation policy");
+        return Ok(());
+    }
+
     // Get user's keypair
     let keypair = ctx
         .keypair_repo
@@ -72,8 +91,6 @@ async fn deliver_activity(
         .await
         .map_err(|e| format!("Failed to get keypair: {e}"))?;
 
-    // Parse inbox URL
-    let inbox_url = Url::parse(&job.inbox)?;
     let host = inbox_url
         .host_str()
         .ok_or("Invalid inbox URL: no host")?
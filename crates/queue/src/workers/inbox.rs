@@ -4,15 +4,18 @@ use std::sync::Arc;
 
 use apalis::prelude::*;
 use misskey_core::services::delivery::DeliveryService;
+use misskey_db::entities::reaction;
 use misskey_db::repositories::{
-    DriveFileRepository, FollowRequestRepository, FollowingRepository, NoteRepository,
-    NotificationRepository, ReactionRepository, UserRepository,
+    BlockingRepository, DriveFileRepository, EmojiRepository, FollowRequestRepository,
+    FollowingRepository, NoteRepository, NoteThreadMutingRepository, NotificationRepository,
+    ReactionRepository, UserProfileRepository, UserRepository,
 };
 use misskey_federation::{
     AcceptActivity, AcceptProcessor, AnnounceActivity, AnnounceProcessor, CreateActivity,
-    CreateProcessor, DeleteActivity, DeleteProcessor, FollowActivity, FollowProcessResult,
-    FollowProcessor, HttpVerifier, LikeActivity, LikeProcessor, ParsedUndoActivity, RejectActivity,
-    RejectProcessor, UndoProcessor, UpdateActivity, UpdateProcessor, client::ApClient,
+    CreateProcessor, DeleteActivity, DeleteProcessor, EmojiReactActivity, EmojiReactProcessor,
+    FederationPolicy, FollowActivity, FollowProcessResult, FollowProcessor, HttpVerifier,
+    LikeActivity, LikeProcessor, ParsedUndoActivity, RejectActivity, RejectProcessor,
+    UndoProcessor, UpdateActivity, UpdateProcessor, client::ApClient,
 };
 use sea_orm::DatabaseConnection;
 use tracing::{debug, error, info, warn};
@@ -29,6 +32,10 @@ pub struct InboxWorkerContext {
     pub delivery: Option<DeliveryService>,
     /// Whether to require HTTP signature verification.
     pub require_signatures: bool,
+    /// Policy deciding which remote hosts are federated with.
+    pub federation_policy: FederationPolicy,
+    /// Remote accounts first seen more recently than this have their notes restricted.
+    pub min_account_age: std::time::Duration,
 }
 
 impl InboxWorkerContext {
@@ -45,6 +52,8 @@ impl InboxWorkerContext {
             base_url: Url::parse(base_url).expect("Invalid base_url"),
             delivery: None,
             require_signatures: true,
+            federation_policy: FederationPolicy::open(),
+            min_account_age: std::time::Duration::ZERO,
         }
     }
 
@@ -62,6 +71,20 @@ impl InboxWorkerContext {
         self
     }
 
+    /// Set the federation policy used to drop activities from disallowed hosts.
+    #[must_use]
+    pub fn with_federation_policy(mut self, policy: FederationPolicy) -> Self {
+        self.federation_policy = policy;
+        self
+    }
+
+    /// Set the minimum age of remote accounts whose notes are accepted unrestricted.
+    #[must_use]
+    pub const fn with_min_account_age(mut self, min_age: std::time::Duration) -> Self {
+        self.min_account_age = min_age;
+        self
+    }
+
     fn user_repo(&self) -> UserRepository {
         UserRepository::new(Arc::clone(&self.db))
     }
@@ -82,6 +105,10 @@ impl InboxWorkerContext {
         FollowRequestRepository::new(Arc::clone(&self.db))
     }
 
+    fn blocking_repo(&self) -> BlockingRepository {
+        BlockingRepository::new(Arc::clone(&self.db))
+    }
+
     fn ap_client(&self) -> ApClient {
         self.ap_client.clone()
     }
@@ -93,6 +120,14 @@ impl InboxWorkerContext {
     fn notification_repo(&self) -> NotificationRepository {
         NotificationRepository::new(Arc::clone(&self.db))
     }
+
+    fn emoji_repo(&self) -> EmojiRepository {
+        EmojiRepository::new(Arc::clone(&self.db))
+    }
+
+    fn user_profile_repo(&self) -> UserProfileRepository {
+        UserProfileRepository::new(Arc::clone(&self.db))
+    }
 }
 
 /// Worker function for processing incoming activities.
@@ -102,6 +137,24 @@ impl InboxWorkerContext {
 pub async fn inbox_worker(job: InboxJob, ctx: Data<InboxWorkerContext>) -> Result<(), Error> {
     info!("Processing incoming activity");
 
+    // Silently drop activities from hosts we don't federate with
+    if let Some(actor) = job
+        .activity
+        .get("actor")
+        .and_then(|a| a.as_str())
+        .and_then(|a| Url::parse(a).ok())
+    {
+        match ctx.federation_policy.is_url_allowed(&actor).await {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(e) => {
+                error!(error = %e, "Failed to evaluate federation policy");
+                let e: Box<dyn std::error::Error + Send + Sync> = Box::new(e);
+                return Err(Error::Failed(e.into()));
+            }
+        }
+    }
+
     // Verify HTTP signature before processing
     if ctx.require_signatures {
         match verify_signature(&job, &ctx).await {
@@ -210,6 +263,10 @@ async fn process_activity(
             info!("Processing Like activity");
             process_like(job, ctx).await?;
         }
+        Some("EmojiReact") => {
+            info!("Processing EmojiReact activity");
+            process_emoji_react(job, ctx).await?;
+        }
         Some("Announce") => {
             info!("Processing Announce activity");
             process_announce(job, ctx).await?;
@@ -237,13 +294,29 @@ async fn process_create(
     job: &InboxJob,
     ctx: &InboxWorkerContext,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let activity: CreateActivity = serde_json::from_value(job.activity.clone())?;
+    // Objects we cannot map to a note are dropped rather than retried
+    let activity: CreateActivity = match serde_json::from_value(job.activity.clone()) {
+        Ok(activity) => activity,
+        Err(e) => {
+            warn!(
+                object_type = ?job.activity.pointer("/object/type"),
+                error = %e,
+                "Ignoring Create activity with unsupported object"
+            );
+            return Ok(());
+        }
+    };
     let processor = CreateProcessor::new(
         ctx.note_repo(),
         ctx.drive_file_repo(),
         ctx.user_repo(),
         ctx.ap_client(),
-    );
+    )
+    .with_base_url(ctx.base_url.clone())
+    .with_federation_policy(ctx.federation_policy.clone())
+    .with_emoji_repo(ctx.emoji_repo())
+    .with_profile_repo(ctx.user_profile_repo())
+    .with_min_account_age(ctx.min_account_age);
     processor.process(&activity).await?;
     Ok(())
 }
@@ -361,11 +434,65 @@ async fn process_like(
         ctx.note_repo(),
         ctx.reaction_repo(),
         ctx.ap_client(),
-    );
-    processor.process(&activity).await?;
+    )
+    .with_emoji_repo(ctx.emoji_repo())
+    .with_profile_repo(ctx.user_profile_repo());
+    let reaction = processor.process(&activity).await?;
+    notify_reaction(ctx, &reaction).await;
+    Ok(())
+}
+
+async fn process_emoji_react(
+    job: &InboxJob,
+    ctx: &InboxWorkerContext,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let activity: EmojiReactActivity = serde_json::from_value(job.activity.clone())?;
+    let processor = EmojiReactProcessor::new(
+        ctx.user_repo(),
+        ctx.note_repo(),
+        ctx.reaction_repo(),
+        ctx.ap_client(),
+    )
+    .with_emoji_repo(ctx.emoji_repo());
+    let reaction = processor.process(&activity).await?;
+    notify_reaction(ctx, &reaction).await;
     Ok(())
 }
 
+/// Notify the local author of a note about a remote reaction.
+async fn notify_reaction(ctx: &InboxWorkerContext, reaction: &reaction::Model) {
+    use misskey_core::services::{NotificationService, ThreadMutingService};
+
+    let note = match ctx.note_repo().find_by_id(&reaction.note_id).await {
+        Ok(Some(note)) => note,
+        Ok(None) => return,
+        Err(e) => {
+            warn!(error = %e, "Failed to look up reacted note");
+            return;
+        }
+    };
+    if note.user_host.is_some() || note.user_id == reaction.user_id {
+        return;
+    }
+
+    let mut notification_service = NotificationService::new(ctx.notification_repo());
+    notification_service.set_thread_muting_service(ThreadMutingService::new(
+        NoteThreadMutingRepository::new(Arc::clone(&ctx.db)),
+        ctx.note_repo(),
+    ));
+    if let Err(e) = notification_service
+        .create_reaction_notification(
+            &note.user_id,
+            &reaction.user_id,
+            &note.id,
+            &reaction.reaction,
+        )
+        .await
+    {
+        warn!(error = %e, "Failed to create reaction notification");
+    }
+}
+
 async fn process_announce(
     job: &InboxJob,
     ctx: &InboxWorkerContext,
@@ -432,7 +559,9 @@ async fn process_undo(
         ctx.following_repo(),
         ctx.reaction_repo(),
         ctx.note_repo(),
-    );
+    )
+    .with_follow_request_repo(ctx.follow_request_repo())
+    .with_blocking_repo(ctx.blocking_repo());
     let result = processor.process(&parsed).await?;
     info!(?result, "Undo activity processed");
     Ok(())
@@ -443,7 +572,8 @@ async fn process_update(
     ctx: &InboxWorkerContext,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let activity: UpdateActivity = serde_json::from_value(job.activity.clone())?;
-    let processor = UpdateProcessor::new(ctx.user_repo(), ctx.note_repo());
+    let processor =
+        UpdateProcessor::new(ctx.user_repo(), ctx.note_repo()).with_emoji_repo(ctx.emoji_repo());
     let result = processor.process(&activity).await?;
     info!(?result, "Update activity processed");
     Ok(())
@@ -4,6 +4,7 @@
 
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use apalis::prelude::*;
 use axum::{
@@ -12,38 +13,46 @@ use axum::{
 };
 use fred::prelude::*;
 use misskey_api::{
-    SseBroadcaster, StreamingState, middleware::AppState, rate_limit::RateLimiterState,
-    router as api_router, streaming_handler,
+    PresenceTracker, SseBroadcaster, StreamingState, middleware::AppState,
+    rate_limit::RateLimiterState, router as api_router, streaming_handler,
 };
 use misskey_common::Config;
 use misskey_core::{
-    AccountService, AnnouncementService, AntennaService, BlockingService, ChannelService,
-    ClipService, DeliveryService, DriveService, EmojiService, FollowingService, GalleryService,
-    GroupService, InstanceService, MessagingService, MetaSettingsService, ModerationService,
-    MutingService, NoteFavoriteService, NoteService, NotificationService, OAuthService,
-    PageService, PollService, ReactionService, RegistrationApprovalService, ScheduledNoteService,
+    AccountService, AnnouncementService, AntennaService, ApResolveService, BlockingService,
+    ChannelService, ClipService, DeliveryService, DriveService, EmailBounceService, EmailService,
+    EmojiService, FollowingService, GalleryService, GroupService, InstanceService,
+    MessagingService, MetaSettingsService, ModerationService, MutingService, NoteFavoriteService,
+    NoteService, NotificationService, OAuthService, PageService, PasswordResetService, PollService,
+    ReactionService, RegistrationApprovalService, ScheduledNoteService, ThreadMutingService,
     TwoFactorService, UserListService, UserService, WebAuthnConfig, WebAuthnService,
-    WebhookService, WordFilterService,
+    WebhookService, WordFilterContentPolicy, WordFilterService,
 };
 use misskey_db::repositories::{
     AccountDeletionRepository, AnnouncementRepository, AntennaRepository, BlockingRepository,
     ChannelRepository, ClipRepository, DriveFileRepository, DriveFolderRepository, EmojiRepository,
     ExportJobRepository, FollowRequestRepository, FollowingRepository, GalleryRepository,
     GroupRepository, ImportJobRepository, InstanceRepository, MessagingRepository,
-    ModerationRepository, MutingRepository, NoteFavoriteRepository, NoteRepository,
-    NotificationRepository, OAuthRepository, PageRepository, PollRepository, PollVoteRepository,
-    ReactionRepository, ScheduledNoteRepository, SecurityKeyRepository, UserKeypairRepository,
-    UserListRepository, UserProfileRepository, UserRepository, WebhookRepository,
-    WordFilterRepository,
+    MetaSettingsRepository, ModerationRepository, MutingRepository, NoteFavoriteRepository,
+    NoteRepository, NoteThreadMutingRepository, NotificationRepository, OAuthRepository,
+    PageRepository, PasswordResetRequestRepository, PollRepository, PollVoteRepository,
+    PushSubscriptionRepository, ReactionRepository, ReactionUsageRepository,
+    ScheduledNoteRepository, SecurityKeyRepository, UserKeypairRepository, UserListRepository,
+    UserProfileRepository, UserRepository, WebhookRepository, WordFilterRepository,
 };
 use misskey_federation::{
-    ClipCollectionState, CollectionState, InboxState, NodeInfoState, UserApState, WebfingerState,
+    ApClient, ClipCollectionState, CollectionState, FederationPolicy, FederationRateLimiter,
+    InboxState, InstanceActorService, InstanceActorState, NodeInfoState, NoteApState,
+    SignatureVerificationLayer, SignatureVerificationState, UserApState, WebfingerState,
     clip_handler, clips_list_handler, followers_handler, following_handler, inbox_handler,
-    nodeinfo_2_1, outbox_handler, user_handler, user_inbox_handler, webfinger_handler,
-    well_known_nodeinfo,
+    instance_actor_handler, instance_actor_outbox_handler, instance_actor_url, nodeinfo_2_1,
+    note_handler, note_page_handler, outbox_handler, user_handler, user_inbox_handler,
+    webfinger_handler, well_known_nodeinfo,
 };
 use misskey_queue::workers::{DeliverContext, deliver_worker};
-use misskey_queue::{DeliverJob, RedisDeliveryService};
+use misskey_queue::{
+    ApObjectFetcher, DeliverJob, RedisDeliveryService, RedisNotificationDebouncer,
+    RedisTimelineCache,
+};
 use sea_orm::{ConnectOptions, Database};
 use tokio::signal;
 use tower_http::{
@@ -156,6 +165,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let import_job_repo = ImportJobRepository::new(Arc::clone(&db));
     let deletion_repo = AccountDeletionRepository::new(Arc::clone(&db));
     let reaction_repo = ReactionRepository::new(Arc::clone(&db));
+    let reaction_usage_repo = ReactionUsageRepository::new(Arc::clone(&db));
     let notification_repo = NotificationRepository::new(Arc::clone(&db));
     let blocking_repo = BlockingRepository::new(Arc::clone(&db));
     let muting_repo = MutingRepository::new(Arc::clone(&db));
@@ -165,6 +175,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let poll_vote_repo = PollVoteRepository::new(Arc::clone(&db));
     let hashtag_repo = misskey_db::repositories::HashtagRepository::new(Arc::clone(&db));
     let note_favorite_repo = NoteFavoriteRepository::new(Arc::clone(&db));
+    let note_thread_muting_repo = NoteThreadMutingRepository::new(Arc::clone(&db));
     let user_list_repo = UserListRepository::new(Arc::clone(&db));
     let moderation_repo = ModerationRepository::new(Arc::clone(&db));
     let emoji_repo = EmojiRepository::new(Arc::clone(&db));
@@ -182,15 +193,36 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let page_repo = PageRepository::new(Arc::clone(&db));
     let gallery_repo = GalleryRepository::new(Arc::clone(&db));
     let group_repo = GroupRepository::new(Arc::clone(&db));
+    let meta_settings_repo = MetaSettingsRepository::new(Arc::clone(&db));
+
+    // Federation policy (open / allowlist / blocklist), host list from instance settings
+    let federation_policy =
+        FederationPolicy::new(config.federation.mode, meta_settings_repo.clone());
 
     // Initialize services
-    let user_service = UserService::new(
+    let mut user_service = UserService::new(
         user_repo.clone(),
         user_profile_repo.clone(),
         user_keypair_repo.clone(),
         note_repo.clone(),
         &config,
     );
+    if config.federation.enabled {
+        user_service.set_delivery(delivery_service.clone(), following_repo.clone());
+    }
+
+    // Load the instance actor, creating it on first start; fetches from remote
+    // servers are signed with its key so authorized fetch works
+    let instance_actor_service = InstanceActorService::new(
+        user_repo.clone(),
+        user_keypair_repo.clone(),
+        Url::parse(&config.server.url)?,
+    );
+    let mut ap_client = ApClient::new(&config.server.url);
+    if config.federation.enabled {
+        let instance_actor = instance_actor_service.get_or_create().await?;
+        ap_client = ap_client.with_signer(instance_actor.signer()?);
+    }
 
     // Initialize services with ActivityPub delivery support
     let mut note_service = if config.federation.enabled {
@@ -206,6 +238,48 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
     // Set user list repo for antenna list membership matching
     note_service.set_user_list_repo(user_list_repo.clone());
+    // Check attachment ownership and the configured per-note limits
+    note_service.set_attachment_validation(drive_file_repo.clone(), config.notes.attachments);
+    note_service.set_quote_reach(config.notes.quote_reach);
+    note_service.set_profile_repo(user_profile_repo.clone());
+    note_service.set_poll_repo(poll_repo.clone());
+    note_service.set_note_urls(server_url.clone(), config.notes.url_template.clone());
+    if config.notes.timeline_cache_ttl_secs > 0 {
+        note_service.set_timeline_cache(
+            Arc::new(RedisTimelineCache::new(
+                fred_client.clone(),
+                &config.redis.prefix,
+            )),
+            Duration::from_secs(config.notes.timeline_cache_ttl_secs),
+        );
+    }
+
+    // Initialize ap/show resolution; remote objects are only fetched when federating
+    let mut ap_resolve_service = ApResolveService::new(
+        note_repo.clone(),
+        user_repo.clone(),
+        following_repo.clone(),
+        server_url.clone(),
+    );
+    ap_resolve_service.set_rate_limiter(FederationRateLimiter::with_settings(
+        fred_client.clone(),
+        misskey_core::ap_resolve::RESOLVE_WINDOW_SECS,
+        misskey_core::ap_resolve::MAX_RESOLVES_PER_WINDOW,
+    ));
+    if config.federation.enabled {
+        ap_resolve_service.set_fetcher(Arc::new(
+            ApObjectFetcher::new(
+                note_repo.clone(),
+                drive_file_repo.clone(),
+                user_repo.clone(),
+                emoji_repo.clone(),
+                user_profile_repo.clone(),
+                ap_client.clone(),
+            )
+            .with_base_url(Url::parse(&config.server.url)?)
+            .with_federation_policy(federation_policy.clone()),
+        ));
+    }
 
     let blocking_service = BlockingService::new(blocking_repo.clone(), following_repo.clone());
 
@@ -225,7 +299,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
     };
 
-    let reaction_service = if config.federation.enabled {
+    let mut reaction_service = if config.federation.enabled {
         ReactionService::with_delivery(
             reaction_repo.clone(),
             note_repo.clone(),
@@ -236,32 +310,58 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     } else {
         ReactionService::new(reaction_repo.clone(), note_repo.clone())
     };
-    let notification_service = NotificationService::new(notification_repo);
+    reaction_service.set_profile_repo(user_profile_repo.clone());
+    reaction_service.set_usage_repo(reaction_usage_repo);
+    let thread_muting_service =
+        ThreadMutingService::new(note_thread_muting_repo, note_repo.clone());
+    let mut notification_service = NotificationService::new(notification_repo);
+    notification_service.set_thread_muting_service(thread_muting_service.clone());
+    notification_service.set_debouncer(Arc::new(RedisNotificationDebouncer::new(
+        fred_client.clone(),
+        &config.redis.prefix,
+    )));
+    note_service.set_notification_service(notification_service.clone());
     let muting_service = MutingService::new(muting_repo);
-    let drive_service = DriveService::new(
+    let mut drive_service = DriveService::new(
         drive_file_repo.clone(),
         drive_folder_repo,
         config.server.url.clone(),
     );
+    drive_service.set_storage_config(config.storage.clone());
     let poll_service = PollService::new(poll_repo, poll_vote_repo, note_repo.clone());
     let hashtag_service = misskey_core::HashtagService::new(hashtag_repo);
     let note_favorite_service = NoteFavoriteService::new(note_favorite_repo, note_repo.clone());
     let user_list_service = UserListService::new(user_list_repo, user_repo.clone());
     let moderation_service = ModerationService::new(moderation_repo, user_repo.clone());
-    let emoji_service = EmojiService::new(emoji_repo);
+    let emoji_service = EmojiService::new(emoji_repo.clone());
     let announcement_service = AnnouncementService::new(announcement_repo);
     let messaging_service = MessagingService::new(
         messaging_repo,
         user_repo.clone(),
         user_profile_repo.clone(),
         following_repo.clone(),
-        blocking_repo,
+        blocking_repo.clone(),
     );
     let clip_service = ClipService::new(clip_repo.clone());
     let antenna_service = AntennaService::new(antenna_repo);
     let channel_service = ChannelService::new(channel_repo);
     let instance_service = InstanceService::new(instance_repo, user_repo.clone());
     let word_filter_service = WordFilterService::new(word_filter_repo);
+    note_service.set_word_filter_service(word_filter_service.clone());
+
+    // Reject or flag notes by configured phrases, and flag accounts posting in bursts
+    let policy_config = &config.notes.content_policy;
+    let mut content_policy = WordFilterContentPolicy::new(word_filter_service.clone())
+        .with_blocked_phrases(policy_config.blocked_phrases.clone())
+        .with_flagged_phrases(policy_config.flagged_phrases.clone());
+    if policy_config.burst_max_notes > 0 {
+        content_policy = content_policy.with_burst_limit(
+            note_repo.clone(),
+            policy_config.burst_max_notes,
+            policy_config.burst_window_secs,
+        );
+    }
+    note_service.set_content_policy(Arc::new(content_policy));
     let scheduled_note_service = ScheduledNoteService::new(scheduled_note_repo);
     let two_factor_service = TwoFactorService::new(user_profile_repo.clone());
 
@@ -277,6 +377,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     )
     .expect("Failed to create WebAuthn service");
 
+    // Initialize Email service (optional, based on config)
+    let email_service = if config.email.enabled {
+        let email_config = misskey_core::EmailConfig::from_config(
+            &config.email,
+            &config.federation.instance_name,
+            &config.server.url,
+        )
+        .expect("Invalid email config");
+        info!("Email delivery enabled");
+        EmailService::new(Some(email_config))
+    } else {
+        EmailService::new(None)
+    };
+
+    // Initialize password reset service (emails reset links)
+    let password_reset_service = PasswordResetService::new(
+        PasswordResetRequestRepository::new(db.clone()),
+        user_profile_repo.clone(),
+        user_repo.clone(),
+        oauth_repo.clone(),
+        email_service,
+        config.server.url.clone(),
+    );
+
+    // Initialize bounce handling for email provider webhooks
+    let email_bounce_service =
+        EmailBounceService::new(user_profile_repo.clone(), config.email.webhooks.clone());
+
     // Initialize OAuth service
     let oauth_service = OAuthService::new(oauth_repo);
 
@@ -293,12 +421,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let group_service = GroupService::new(group_repo);
 
     // Initialize Translation service (optional, based on config)
-    // For now, we set it to None. Users can configure translation in their config.
-    let translation_service: Option<misskey_core::TranslationService> = None;
+    let translation_service = if config.translation.enabled {
+        let translation_config = misskey_core::TranslationConfig::from_config(&config.translation)
+            .expect("Invalid translation config");
+        info!(provider = %config.translation.provider, "Translation enabled");
+        Some(misskey_core::TranslationService::new(translation_config))
+    } else {
+        None
+    };
 
     // Initialize Push Notification service (optional, based on config)
-    // For now, we set it to None. Users can configure VAPID keys in their config.
-    let push_notification_service: Option<misskey_core::PushNotificationService> = None;
+    let push_notification_service = if config.push.enabled {
+        let vapid_config = misskey_core::VapidConfig {
+            public_key: config.push.vapid_public_key.clone(),
+            private_key: config.push.vapid_private_key.clone(),
+            subject: config.push.vapid_subject.clone(),
+        };
+        info!("Web Push notifications enabled");
+        Some(
+            misskey_core::PushNotificationService::new(
+                PushSubscriptionRepository::new(db.clone()),
+                Some(vapid_config),
+            )
+            .expect("Failed to create push notification service"),
+        )
+    } else {
+        None
+    };
 
     // Initialize Account service
     let account_service = Some(AccountService::new(
@@ -316,32 +465,57 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ));
 
     // Initialize MetaSettings service
-    let meta_settings_service = MetaSettingsService::new(db.clone());
+    let mut meta_settings_service = MetaSettingsService::new(db.clone());
+    meta_settings_service.set_cached_repo(meta_settings_repo);
+    // Apply admin-configured defaults to newly registered users
+    user_service.set_meta_settings_service(meta_settings_service.clone());
 
     // Initialize RegistrationApproval service
     let registration_approval_service = RegistrationApprovalService::new(db.clone());
 
-    // Initialize streaming state
-    let streaming = StreamingState::new();
+    // Initialize streaming state, sharing who is online between instances
+    let presence_subscriber = fred::clients::SubscriberClient::new(
+        fred::types::config::Config::from_url(&config.redis.url)
+            .expect("Failed to parse Redis URL for presence"),
+        None,
+        None,
+        None,
+    );
+    presence_subscriber
+        .init()
+        .await
+        .expect("Failed to connect presence subscriber to Redis");
+    let presence = PresenceTracker::with_redis(
+        fred_client.clone(),
+        presence_subscriber,
+        &config.redis.prefix,
+    )
+    .await
+    .expect("Failed to subscribe to presence changes");
+    let streaming = StreamingState::new().with_presence(presence);
 
     // Initialize SSE broadcaster
     let sse_broadcaster = SseBroadcaster::new();
 
     // Initialize distributed rate limiter (uses Redis for multi-instance deployments)
-    let rate_limiter = RateLimiterState::with_redis(fred_client);
+    let rate_limiter = RateLimiterState::with_redis(fred_client.clone());
     info!("Initialized distributed API rate limiter");
 
     // Create app state
     let state = AppState {
         base_url: config.server.url.clone(),
         user_service,
+        password_reset_service,
+        email_bounce_service,
         note_service,
+        ap_resolve_service,
         following_service,
         reaction_service,
         notification_service,
         blocking_service,
         clip_service,
         muting_service,
+        thread_muting_service,
         drive_service,
         poll_service,
         hashtag_service,
@@ -387,12 +561,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .unwrap_or_default(),
         env!("CARGO_PKG_VERSION").to_string(),
         true, // open_registrations
+    )
+    .with_instance_actor(instance_actor_url(&base_url));
+    let instance_actor_state = InstanceActorState::new(
+        instance_actor_service,
+        config.federation.instance_name.clone(),
     );
     let user_ap_state = UserApState::new(
         user_repo.clone(),
         user_keypair_repo.clone(),
         base_url.clone(),
+    )
+    .with_profile_repo(user_profile_repo.clone());
+    let note_ap_state = NoteApState::new(
+        note_repo.clone(),
+        user_repo.clone(),
+        drive_file_repo.clone(),
+        base_url.clone(),
+    )
+    .with_note_url_template(config.notes.url_template.clone());
+
+    // ActivityPub clients resolving a note page URL get the note; note objects
+    // send browsers on to the note page, unless they are the page
+    let mut note_routes = Router::new().route(
+        &config.notes.url_template,
+        get(note_page_handler).with_state(note_ap_state.clone()),
     );
+    if !note_ap_state.url_config.note_page_is_object_url() {
+        note_routes = note_routes.route("/notes/{id}", get(note_handler).with_state(note_ap_state));
+    }
 
     // Create collection state for outbox/followers/following
     let collection_state = CollectionState::new(
@@ -422,8 +619,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         following_repo,
         follow_request_repo,
         reaction_repo,
+        emoji_repo,
         base_url.clone(),
-    );
+    )
+    .with_federation_policy(federation_policy.clone())
+    .with_blocking_repo(blocking_repo)
+    // The shared inbox carries relayed traffic, so it gets its own, larger budget
+    .with_rate_limiters(
+        FederationRateLimiter::with_settings(
+            fred_client.clone(),
+            config.federation.inbox_rate_limit_window_secs,
+            config.federation.shared_inbox_rate_limit,
+        )
+        .with_prefix("federation_rate:shared_inbox"),
+        FederationRateLimiter::with_settings(
+            fred_client,
+            config.federation.inbox_rate_limit_window_secs,
+            config.federation.user_inbox_rate_limit,
+        ),
+    )
+    .with_min_account_age(Duration::from_secs(config.federation.min_account_age_secs));
+
+    // Verify inbox signatures once, buffering the body; handlers reuse the result
+    let signature_layer =
+        SignatureVerificationLayer::new(SignatureVerificationState::new(ap_client.clone(), false));
 
     // Build router
     let app = Router::new()
@@ -441,10 +660,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "/nodeinfo/2.1",
             get(nodeinfo_2_1).with_state(nodeinfo_state),
         )
+        .route(
+            "/actor",
+            get(instance_actor_handler).with_state(instance_actor_state.clone()),
+        )
+        .route(
+            "/actor/outbox",
+            get(instance_actor_outbox_handler).with_state(instance_actor_state),
+        )
         .route(
             "/users/{id}",
             get(user_handler).with_state(user_ap_state),
         )
+        .merge(note_routes)
         .route(
             "/users/{username}/outbox",
             get(outbox_handler).with_state(collection_state.clone()),
@@ -469,11 +697,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // ActivityPub inbox endpoints
         .route(
             "/inbox",
-            post(inbox_handler).with_state(inbox_state.clone()),
+            post(inbox_handler)
+                .with_state(inbox_state.clone())
+                .layer(signature_layer.clone()),
         )
         .route(
             "/users/{username}/inbox",
-            post(user_inbox_handler).with_state(inbox_state),
+            post(user_inbox_handler)
+                .with_state(inbox_state)
+                .layer(signature_layer),
         )
         .nest("/api", api_router())
         .layer(middleware::from_fn_with_state(
@@ -499,7 +731,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let worker_keypair_repo = user_keypair_repo.clone();
         let user_agent = format!("misskey-rs/{}", env!("CARGO_PKG_VERSION"));
 
-        let deliver_ctx = DeliverContext::new(worker_keypair_repo, user_agent);
+        let deliver_ctx = DeliverContext::new(worker_keypair_repo, user_agent)
+            .with_federation_policy(federation_policy);
 
         // Spawn the worker in the background
         tokio::spawn(async move {